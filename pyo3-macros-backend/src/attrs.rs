@@ -6,17 +6,31 @@ pub struct FromPyWithAttribute(pub ExprPath);
 
 impl FromPyWithAttribute {
     pub fn from_meta(meta: Meta) -> Result<Self> {
-        let string_literal = match meta {
-            Meta::NameValue(MetaNameValue {
-                lit: Lit::Str(string_literal),
-                ..
-            }) => string_literal,
-            meta => {
-                bail_spanned!(meta.span() => "expected a name-value: `pyo3(from_py_with = \"func\")`")
-            }
-        };
+        expr_path_from_meta(meta, "from_py_with").map(FromPyWithAttribute)
+    }
+}
+
+/// `#[pyo3(into_py_with = "func")]` on a field of `#[derive(IntoPyObject)]`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct IntoPyWithAttribute(pub ExprPath);
 
-        let expr_path = string_literal.parse::<ExprPath>()?;
-        Ok(FromPyWithAttribute(expr_path))
+impl IntoPyWithAttribute {
+    pub fn from_meta(meta: Meta) -> Result<Self> {
+        expr_path_from_meta(meta, "into_py_with").map(IntoPyWithAttribute)
     }
 }
+
+/// Parses `name = "path::to::func"`.
+fn expr_path_from_meta(meta: Meta, name: &str) -> Result<ExprPath> {
+    let string_literal = match meta {
+        Meta::NameValue(MetaNameValue {
+            lit: Lit::Str(string_literal),
+            ..
+        }) => string_literal,
+        meta => bail_spanned!(
+            meta.span() => format!("expected a name-value: `pyo3({} = \"func\")`", name)
+        ),
+    };
+
+    string_literal.parse::<ExprPath>()
+}
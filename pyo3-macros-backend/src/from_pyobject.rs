@@ -1,4 +1,5 @@
-use crate::attrs::FromPyWithAttribute;
+use crate::attrs::{FromPyWithAttribute, IntoPyWithAttribute};
+use crate::inspect;
 use proc_macro2::TokenStream;
 use quote::quote;
 use syn::punctuated::Punctuated;
@@ -43,18 +44,30 @@ impl<'a> Enum<'a> {
         })
     }
 
+    /// Build the body of `type_input` for enums: the union of the types of all variants.
+    fn build_type_input(&self) -> TokenStream {
+        let variants = self.variants.iter().map(Container::build_type_input);
+        quote!(pyo3::inspect::TypeInfo::union_of(vec![#(#variants),*]))
+    }
+
     /// Build derivation body for enums.
     fn build(&self) -> TokenStream {
         let mut var_extracts = Vec::new();
         let mut error_names = String::new();
         for (i, var) in self.variants.iter().enumerate() {
             let struct_derive = var.build();
+            let var_name = var.path_name();
+            let annotation = match &var.annotation {
+                Some(annotation) => quote!(Some(#annotation)),
+                None => quote!(None),
+            };
             let ext = quote!(
                 let maybe_ret = || -> pyo3::PyResult<Self> {
                     #struct_derive
                 }();
-                if maybe_ret.is_ok() {
-                    return maybe_ret
+                match maybe_ret {
+                    Ok(ret) => return Ok(ret),
+                    Err(err) => errors.push((#var_name, #annotation, err)),
                 }
             );
 
@@ -69,11 +82,11 @@ impl<'a> Enum<'a> {
         } else {
             error_names
         };
+        let len = self.variants.len();
         quote!(
+            let mut errors = Vec::with_capacity(#len);
             #(#var_extracts)*
-            let type_name = obj.get_type().name()?;
-            let err_msg = format!("'{}' object cannot be converted to '{}'", type_name, #error_names);
-            Err(pyo3::exceptions::PyTypeError::new_err(err_msg))
+            Err(pyo3::derive_utils::failed_to_extract_enum(obj, #error_names, errors))
         )
     }
 }
@@ -108,7 +121,9 @@ enum ContainerType<'a> {
 struct Container<'a> {
     path: syn::Path,
     ty: ContainerType<'a>,
+    field_types: Vec<&'a syn::Type>,
     err_name: String,
+    annotation: Option<syn::LitStr>,
     is_enum_variant: bool,
 }
 
@@ -132,6 +147,17 @@ impl<'a> Container<'a> {
         if transparent {
             Self::check_transparent_len(fields)?;
         }
+        let from_item_all = attrs.contains(&ContainerAttribute::FromItemAll);
+        if from_item_all {
+            ensure_spanned!(
+                !transparent,
+                fields.span() => "`transparent` and `from_item_all` cannot be used together"
+            );
+            ensure_spanned!(
+                fields.iter().all(|field| field.ident.is_some()),
+                fields.span() => "`from_item_all` is only supported for structs and variants with named fields"
+            );
+        }
         let style = match (fields, transparent) {
             (Fields::Unnamed(_), true) => ContainerType::TupleNewtype,
             (Fields::Unnamed(unnamed), false) => match unnamed.unnamed.len() {
@@ -157,31 +183,54 @@ impl<'a> Container<'a> {
                         .ident
                         .as_ref()
                         .expect("Named fields should have identifiers");
-                    let attrs = FieldAttributes::parse_attrs(&field.attrs)?;
+                    let mut attrs = FieldAttributes::parse_attrs(&field.attrs)?;
+                    if from_item_all {
+                        match attrs.getter {
+                            Some(FieldGetter::GetAttr(_)) => bail_spanned!(
+                                ident.span() => "`attribute` cannot be used together with `from_item_all`"
+                            ),
+                            Some(FieldGetter::GetItem(_)) => {}
+                            None => attrs.getter = Some(FieldGetter::GetItem(None)),
+                        }
+                    }
                     fields.push((ident, attrs))
                 }
                 ContainerType::Struct(fields)
             }
             (Fields::Unit, _) => unreachable!(), // covered by length check above
         };
-        let err_name = attrs
-            .iter()
-            .find_map(|a| a.annotation().map(syn::LitStr::value))
+        let annotation = attrs.iter().find_map(|a| a.annotation().cloned());
+        let err_name = annotation
+            .as_ref()
+            .map(syn::LitStr::value)
             .unwrap_or_else(|| path.segments.last().unwrap().ident.to_string());
 
         let v = Container {
             path,
             ty: style,
+            field_types: fields.iter().map(|field| &field.ty).collect(),
             err_name,
+            annotation,
             is_enum_variant,
         };
         Ok(v)
     }
 
+    /// The path of the struct or variant as written in Rust, e.g. `Enum::Variant`.
+    fn path_name(&self) -> String {
+        let segments: Vec<_> = self
+            .path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect();
+        segments.join("::")
+    }
+
     fn verify_struct_container_attrs(attrs: &'a [ContainerAttribute]) -> Result<()> {
         for attr in attrs {
             match attr {
-                ContainerAttribute::Transparent => {}
+                ContainerAttribute::Transparent | ContainerAttribute::FromItemAll => {}
                 ContainerAttribute::ErrorAnnotation(annotation) => bail_spanned!(
                     annotation.span() => "annotation is not supported for structs"
                 ),
@@ -200,6 +249,27 @@ impl<'a> Container<'a> {
         }
     }
 
+    /// Build the body of `type_input` for a struct, or the type of an enum variant.
+    ///
+    /// Variants with an `annotation` use it as the Python type.
+    fn build_type_input(&self) -> TokenStream {
+        if let Some(annotation) = &self.annotation {
+            return quote!(pyo3::inspect::TypeInfo::Builtin(#annotation));
+        }
+        let field_types = &self.field_types;
+        match &self.ty {
+            ContainerType::StructNewtype(_) | ContainerType::TupleNewtype => {
+                quote!(#(<#field_types as pyo3::FromPyObject>::type_input())*)
+            }
+            ContainerType::Tuple(_) => quote! {
+                pyo3::inspect::TypeInfo::Tuple(vec![
+                    #(<#field_types as pyo3::FromPyObject>::type_input()),*
+                ])
+            },
+            ContainerType::Struct(_) => quote!(pyo3::inspect::TypeInfo::Any),
+        }
+    }
+
     fn build_newtype_struct(&self, field_ident: Option<&Ident>) -> TokenStream {
         let self_ty = &self.path;
         if let Some(ident) = field_ident {
@@ -215,7 +285,12 @@ impl<'a> Container<'a> {
         let self_ty = &self.path;
         let mut fields: Punctuated<TokenStream, syn::Token![,]> = Punctuated::new();
         for i in 0..len {
-            fields.push(quote!(s.get_item(#i).extract()?));
+            let index = i.to_string();
+            fields.push(quote!(
+                s.get_item(#i).extract().map_err(|err| {
+                    pyo3::derive_utils::failed_to_extract_field(pyo3::PyNativeType::py(obj), #index, err)
+                })?
+            ));
         }
         let msg = if self.is_enum_variant {
             quote!(format!(
@@ -239,20 +314,50 @@ impl<'a> Container<'a> {
         let self_ty = &self.path;
         let mut fields: Punctuated<TokenStream, syn::Token![,]> = Punctuated::new();
         for (ident, attrs) in tups {
-            let getter = match &attrs.getter {
-                FieldGetter::GetAttr(Some(name)) => quote!(getattr(#name)),
-                FieldGetter::GetAttr(None) => quote!(getattr(stringify!(#ident))),
-                FieldGetter::GetItem(Some(key)) => quote!(get_item(#key)),
-                FieldGetter::GetItem(None) => quote!(get_item(stringify!(#ident))),
+            let (getter, missing_error) = match &attrs.getter {
+                Some(FieldGetter::GetAttr(Some(name))) => {
+                    (quote!(getattr(#name)), quote!(PyAttributeError))
+                }
+                Some(FieldGetter::GetAttr(None)) | None => (
+                    quote!(getattr(stringify!(#ident))),
+                    quote!(PyAttributeError),
+                ),
+                Some(FieldGetter::GetItem(Some(key))) => {
+                    (quote!(get_item(#key)), quote!(PyKeyError))
+                }
+                Some(FieldGetter::GetItem(None)) => {
+                    (quote!(get_item(stringify!(#ident))), quote!(PyKeyError))
+                }
             };
 
-            let get_field = quote!(obj.#getter?);
-            let extractor = match &attrs.from_py_with {
-                None => quote!(#get_field.extract()?),
-                Some(FromPyWithAttribute(expr_path)) => quote! (#expr_path(#get_field)?),
+            let extract = match &attrs.from_py_with {
+                None => quote!(pyo3::PyAny::extract(value)),
+                Some(FromPyWithAttribute(expr_path)) => quote!(#expr_path(value)),
             };
 
-            fields.push(quote!(#ident: #extractor));
+            let field = match &attrs.default {
+                None => quote!(obj.#getter.and_then(|value| #extract)),
+                Some(default) => {
+                    let default = default.build();
+                    quote! {
+                        match obj.#getter {
+                            Ok(value) => #extract,
+                            Err(err) if err.is_instance::<pyo3::exceptions::#missing_error>(pyo3::PyNativeType::py(obj)) => Ok(#default),
+                            Err(err) => Err(err),
+                        }
+                    }
+                }
+            };
+
+            fields.push(quote!(
+                #ident: #field.map_err(|err| {
+                    pyo3::derive_utils::failed_to_extract_field(
+                        pyo3::PyNativeType::py(obj),
+                        stringify!(#ident),
+                        err,
+                    )
+                })?
+            ));
         }
         quote!(Ok(#self_ty{#fields}))
     }
@@ -266,18 +371,20 @@ impl<'a> Container<'a> {
     }
 }
 
-/// Attributes for deriving FromPyObject scoped on containers.
+/// Attributes for deriving FromPyObject and IntoPyObject scoped on containers.
 #[derive(Clone, Debug, PartialEq)]
-enum ContainerAttribute {
+pub(crate) enum ContainerAttribute {
     /// Treat the Container as a Wrapper, directly extract its fields from the input object.
     Transparent,
-    /// Change the name of an enum variant in the generated error message.
+    /// Change the name of an enum variant in the generated error message and type annotation.
     ErrorAnnotation(syn::LitStr),
+    /// Extract all fields without `item("key")` as `item`, rather than as `attribute`.
+    FromItemAll,
 }
 
 impl ContainerAttribute {
     /// Convenience method to access `ErrorAnnotation`.
-    fn annotation(&self) -> Option<&syn::LitStr> {
+    pub(crate) fn annotation(&self) -> Option<&syn::LitStr> {
         match self {
             ContainerAttribute::ErrorAnnotation(s) => Some(s),
             _ => None,
@@ -287,7 +394,7 @@ impl ContainerAttribute {
     /// Parse valid container arguments
     ///
     /// Fails if any are invalid.
-    fn parse_attrs(value: &[Attribute]) -> Result<Vec<Self>> {
+    pub(crate) fn parse_attrs(value: &[Attribute]) -> Result<Vec<Self>> {
         get_pyo3_meta_list(value)?
             .nested
             .into_iter()
@@ -297,6 +404,9 @@ impl ContainerAttribute {
                         Meta::Path(p) if p.is_ident("transparent") => {
                             return Ok(ContainerAttribute::Transparent);
                         }
+                        Meta::Path(p) if p.is_ident("from_item_all") => {
+                            return Ok(ContainerAttribute::FromItemAll);
+                        }
                         Meta::NameValue(nv) if nv.path.is_ident("annotation") => {
                             if let syn::Lit::Str(s) = &nv.lit {
                                 return Ok(ContainerAttribute::ErrorAnnotation(s.clone()));
@@ -314,25 +424,48 @@ impl ContainerAttribute {
     }
 }
 
-/// Attributes for deriving FromPyObject scoped on fields.
+/// Attributes for deriving FromPyObject and IntoPyObject scoped on fields.
+///
+/// `from_py_with` is only used by FromPyObject and `into_py_with` only by IntoPyObject, so that
+/// both can be derived for the same type.
 #[derive(Clone, Debug)]
-struct FieldAttributes {
-    getter: FieldGetter,
-    from_py_with: Option<FromPyWithAttribute>,
+pub(crate) struct FieldAttributes {
+    pub(crate) getter: Option<FieldGetter>,
+    pub(crate) from_py_with: Option<FromPyWithAttribute>,
+    pub(crate) into_py_with: Option<IntoPyWithAttribute>,
+    pub(crate) default: Option<FieldDefault>,
 }
 
 #[derive(Clone, Debug)]
-enum FieldGetter {
+pub(crate) enum FieldGetter {
     GetItem(Option<syn::Lit>),
     GetAttr(Option<syn::LitStr>),
 }
 
+/// The value of a field whose key or attribute is missing: `default` or `default = "expr"`.
+#[derive(Clone, Debug)]
+pub(crate) enum FieldDefault {
+    Default,
+    Expr(Box<syn::Expr>),
+}
+
+impl FieldDefault {
+    fn build(&self) -> TokenStream {
+        match self {
+            FieldDefault::Default => quote!(::std::default::Default::default()),
+            FieldDefault::Expr(expr) => quote!(#expr),
+        }
+    }
+}
+
 impl FieldAttributes {
     /// Extract the field attributes.
     ///
-    fn parse_attrs(attrs: &[Attribute]) -> Result<Self> {
+    pub(crate) fn parse_attrs(attrs: &[Attribute]) -> Result<Self> {
         let mut getter = None;
         let mut from_py_with = None;
+        let mut into_py_with = None;
+        let mut default = None;
 
         let list = get_pyo3_meta_list(attrs)?;
 
@@ -341,7 +474,7 @@ impl FieldAttributes {
                 syn::NestedMeta::Meta(meta) => meta,
                 syn::NestedMeta::Lit(lit) => bail_spanned!(
                     lit.span() =>
-                    "expected `attribute`, `item` or `from_py_with`, got a literal"
+                    "expected `attribute`, `item`, `from_py_with`, `into_py_with` or `default`, got a literal"
                 ),
             };
             let path = meta.path();
@@ -360,17 +493,42 @@ impl FieldAttributes {
                 getter = Some(FieldGetter::GetItem(Self::item_arg(meta)?))
             } else if path.is_ident("from_py_with") {
                 from_py_with = Some(Self::from_py_with_arg(meta)?)
+            } else if path.is_ident("into_py_with") {
+                into_py_with = Some(IntoPyWithAttribute::from_meta(meta)?)
+            } else if path.is_ident("default") {
+                default = Some(Self::default_arg(meta)?)
             } else {
-                bail_spanned!(meta.span() => "expected `attribute`, `item` or `from_py_with`")
+                bail_spanned!(
+                    meta.span() => "expected `attribute`, `item`, `from_py_with`, `into_py_with` or `default`"
+                )
             };
         }
 
         Ok(FieldAttributes {
-            getter: getter.unwrap_or(FieldGetter::GetAttr(None)),
+            getter,
             from_py_with,
+            into_py_with,
+            default,
         })
     }
 
+    fn default_arg(meta: Meta) -> syn::Result<FieldDefault> {
+        match meta {
+            Meta::Path(_) => Ok(FieldDefault::Default),
+            // Like `from_py_with`, an expression is given as a string literal
+            Meta::NameValue(syn::MetaNameValue {
+                lit: syn::Lit::Str(expr),
+                ..
+            }) => Ok(FieldDefault::Expr(Box::new(expr.parse()?))),
+            Meta::NameValue(syn::MetaNameValue { lit, .. }) => {
+                Ok(FieldDefault::Expr(Box::new(parse_quote!(#lit))))
+            }
+            Meta::List(list) => bail_spanned!(
+                list.span() => "expected `pyo3(default)` or `pyo3(default = \"expr\")`"
+            ),
+        }
+    }
+
     fn attribute_arg(meta: Meta) -> syn::Result<Option<syn::LitStr>> {
         let mut arg_list = match meta {
             Meta::List(list) => list,
@@ -475,22 +633,31 @@ pub fn build_derive_from_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
             .predicates
             .push(parse_quote!(#gen_ident: FromPyObject<#lt_param>))
     }
-    let derives = match &tokens.data {
+    let (derives, type_input) = match &tokens.data {
         syn::Data::Enum(en) => {
             let en = Enum::new(en, &tokens.ident)?;
-            en.build()
+            (en.build(), en.build_type_input())
         }
         syn::Data::Struct(st) => {
             let attrs = ContainerAttribute::parse_attrs(&tokens.attrs)?;
             Container::verify_struct_container_attrs(&attrs)?;
             let ident = &tokens.ident;
             let st = Container::new(&st.fields, parse_quote!(#ident), attrs, false)?;
-            st.build()
+            (st.build(), st.build_type_input())
         }
         syn::Data::Union(_) => bail_spanned!(
             tokens.span() => "#[derive(FromPyObject)] is not supported for unions"
         ),
     };
+    let type_input = if inspect::enabled() {
+        Some(quote! {
+            fn type_input() -> pyo3::inspect::TypeInfo {
+                #type_input
+            }
+        })
+    } else {
+        None
+    };
 
     let ident = &tokens.ident;
     Ok(quote!(
@@ -499,6 +666,8 @@ pub fn build_derive_from_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
             fn extract(obj: &#lt_param pyo3::PyAny) -> pyo3::PyResult<Self>  {
                 #derives
             }
+
+            #type_input
         }
     ))
 }
@@ -0,0 +1,438 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+//! Generation of the metadata in `pyo3::inspect`, which is used to write type stubs.
+
+use crate::method::{self, FnSpec, FnType};
+use crate::pyclass::FieldPyO3Options;
+use crate::utils;
+use proc_macro2::{Delimiter, Group, Ident, TokenStream, TokenTree};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::Parser;
+
+/// Whether the metadata is generated, which is only done with the `inspect` feature as it costs
+/// compile time.
+pub fn enabled() -> bool {
+    cfg!(feature = "inspect")
+}
+
+/// Builds the `pyo3::inspect::FunctionInfo` describing a function or method.
+///
+/// `cls` is the class of a method, which replaces `Self` in the argument and return types, as
+/// the expression is used outside of the `impl` block.
+pub fn function_info(spec: &FnSpec, cls: Option<&syn::Type>) -> TokenStream {
+    let name = match spec.tp {
+        FnType::FnNew => "__new__".to_owned(),
+        FnType::FnCall(_) => "__call__".to_owned(),
+        _ => spec.python_name.to_string(),
+    };
+    let doc = &spec.doc;
+    let kind = match spec.tp {
+        FnType::Fn(_) | FnType::FnCall(_) => quote!(Method),
+        FnType::FnNew => quote!(New),
+        FnType::FnClass => quote!(ClassMethod),
+        FnType::FnStatic if cls.is_some() => quote!(StaticMethod),
+        FnType::FnStatic => quote!(Function),
+        FnType::Getter(_) => quote!(Getter),
+        FnType::Setter(_) => quote!(Setter),
+        FnType::ClassAttribute => quote!(ClassAttribute),
+    };
+
+    let positional_only = spec.positional_only_parameters();
+    let mut positional = 0;
+    let mut parameters = Vec::new();
+    for arg in spec.args.iter().filter(|arg| !arg.py) {
+        let param_name = arg.name.unraw().to_string();
+        let (kind, annotation) = if spec.is_args(arg.name) {
+            (quote!(VarPositional), quote!(pyo3::inspect::TypeInfo::Any))
+        } else if spec.is_kwargs(arg.name) {
+            (quote!(VarKeyword), quote!(pyo3::inspect::TypeInfo::Any))
+        } else {
+            let kind = if spec.is_kw_only(arg.name) {
+                quote!(KeywordOnly)
+            } else {
+                positional += 1;
+                if positional <= positional_only {
+                    quote!(PositionalOnly)
+                } else {
+                    quote!(PositionalOrKeyword)
+                }
+            };
+            let annotation = if arg.attrs.from_py_with.is_some() {
+                // The argument type doesn't have to implement `FromPyObject` in this case
+                quote!(pyo3::inspect::TypeInfo::Any)
+            } else {
+                // `Option<&T>` is extracted like `T` in the same way as `&T`
+                let ty = erase_type(arg.optional.unwrap_or(arg.ty), cls);
+                let annotation = quote! {
+                    <<#ty as pyo3::derive_utils::ExtractExt<'_>>::Target
+                        as pyo3::FromPyObject<'_>>::type_input()
+                };
+                if arg.optional.is_some() {
+                    quote!(pyo3::inspect::TypeInfo::optional_of(#annotation))
+                } else {
+                    annotation
+                }
+            };
+            (kind, annotation)
+        };
+        let default = match spec.default_value_expr(arg.name) {
+            Some(default) => {
+                let default = method::default_value_text(default);
+                quote!(Some(#default))
+            }
+            None if arg.optional.is_some() && !spec.is_kwargs(arg.name) => quote!(Some("None")),
+            None => quote!(None),
+        };
+        parameters.push(quote! {
+            pyo3::inspect::ParameterInfo {
+                name: #param_name,
+                kind: pyo3::inspect::ParameterKind::#kind,
+                annotation: #annotation,
+                default: #default,
+            }
+        });
+    }
+
+    let return_type = match (&spec.tp, cls) {
+        (FnType::FnNew, Some(cls)) => quote!(pyo3::inspect::TypeInfo::class::<#cls>()),
+        (FnType::Setter(_), _) => quote!(pyo3::inspect::TypeInfo::None),
+        (FnType::ClassAttribute, _) => {
+            let ty = erase_type(&spec.output, cls);
+            quote!(<#ty as pyo3::IntoPy<pyo3::PyObject>>::type_output())
+        }
+        _ if spec.asyncness.is_some() => {
+            let ty = erase_type(&spec.output, cls);
+            quote!(<#ty as pyo3::callback::IntoPyCallbackOutput<pyo3::PyObject>>::type_output())
+        }
+        _ => {
+            let ty = erase_type(&spec.output, cls);
+            quote! {
+                <#ty as pyo3::callback::IntoPyCallbackOutput<*mut pyo3::ffi::PyObject>>::type_output()
+            }
+        }
+    };
+    let is_async = spec.asyncness.is_some();
+
+    quote! {
+        pyo3::inspect::FunctionInfo {
+            name: #name,
+            doc: #doc,
+            kind: pyo3::inspect::FunctionKind::#kind,
+            parameters: vec![#(#parameters),*],
+            return_type: #return_type,
+            is_async: #is_async,
+        }
+    }
+}
+
+/// Builds the `pyo3::inspect::FunctionInfo` describing a `#[classattr]` constant.
+pub fn const_info(python_name: &syn::Ident, ty: &syn::Type, cls: &syn::Type) -> TokenStream {
+    let name = python_name.to_string();
+    let ty = erase_type(ty, Some(cls));
+    quote! {
+        pyo3::inspect::FunctionInfo {
+            name: #name,
+            doc: "",
+            kind: pyo3::inspect::FunctionKind::ClassAttribute,
+            parameters: Vec::new(),
+            return_type: <#ty as pyo3::IntoPy<pyo3::PyObject>>::type_output(),
+            is_async: false,
+        }
+    }
+}
+
+/// Builds the `pyo3::inspect::AttributeInfo` describing a field exposed with `#[pyo3(get, set)]`.
+///
+/// Fields read with `get_with`, or only written with `set_with`, are annotated as `Any`, as the
+/// Python type is then given by the conversion function.
+pub fn field_info(field: &syn::Field, options: &FieldPyO3Options) -> syn::Result<TokenStream> {
+    let name = options.python_name(field).to_string();
+    let doc = utils::get_doc(&field.attrs, None, false)?;
+    let ty = &field.ty;
+    let kind = match (options.get, options.set) {
+        (true, true) => quote!(ReadWrite),
+        (true, false) => quote!(ReadOnly),
+        _ => quote!(WriteOnly),
+    };
+    let annotation = if options.get {
+        if options.get_with.is_some() {
+            quote!(pyo3::inspect::TypeInfo::Any)
+        } else {
+            quote! {
+                <#ty as pyo3::callback::IntoPyCallbackOutput<*mut pyo3::ffi::PyObject>>::type_output()
+            }
+        }
+    } else if options.set_with.is_some() {
+        quote!(pyo3::inspect::TypeInfo::Any)
+    } else {
+        quote!(<#ty as pyo3::FromPyObject<'_>>::type_input())
+    };
+    Ok(quote! {
+        pyo3::inspect::AttributeInfo {
+            name: #name,
+            doc: #doc,
+            kind: pyo3::inspect::AttributeKind::#kind,
+            annotation: #annotation,
+        }
+    })
+}
+
+/// Builds the `pyo3::inspect::AttributeInfo` describing a variant of a `#[pyclass]` enum.
+pub fn variant_info(cls: &syn::Ident, variant: &syn::Variant) -> syn::Result<TokenStream> {
+    let name = variant.ident.unraw().to_string();
+    let doc = utils::get_doc(&variant.attrs, None, false)?;
+    Ok(quote! {
+        pyo3::inspect::AttributeInfo {
+            name: #name,
+            doc: #doc,
+            kind: pyo3::inspect::AttributeKind::Class,
+            annotation: pyo3::inspect::TypeInfo::class::<#cls>(),
+        }
+    })
+}
+
+/// Generates the function returning the `pyo3::inspect::ModuleInfo` of a `#[pymodule]`, which is
+/// called by `inspect_module!`.
+///
+/// The functions, classes and submodules of the module are found by looking for
+/// `wrap_pyfunction!`, `add_class::<T>` and `wrap_pymodule!` in the body of the module function,
+/// and are only described if the `#[cfg]`s of the statements containing them hold. `pyfns` are
+/// the `#[cfg]` attributes and descriptions of the functions defined with `#[pyfn]`.
+pub fn module_info(
+    func: &syn::ItemFn,
+    modname: &syn::Ident,
+    doc: &syn::LitStr,
+    pyfns: Vec<(TokenStream, TokenStream)>,
+) -> TokenStream {
+    if !enabled() {
+        return TokenStream::new();
+    }
+
+    let mut items = ModuleItems::default();
+    items.scan_stmts(&func.block.stmts, &TokenStream::new());
+
+    let functions = pyfns
+        .into_iter()
+        .map(|(cfgs, info)| quote!(#cfgs { functions.push(#info); }))
+        .chain(items.functions.iter().map(|item| {
+            let (name, cfgs) = (&item.tokens, &item.cfgs);
+            quote!(#cfgs { functions.push(#name::info()); })
+        }));
+    let classes = items.classes.iter().map(|item| {
+        let (class, cfgs) = (&item.tokens, &item.cfgs);
+        quote! {
+            #cfgs {
+                classes.push(<#class as pyo3::class::impl_::PyClassImpl>::class_info());
+            }
+        }
+    });
+    let submodules = items.submodules.iter().map(|item| {
+        let (info, cfgs) = (&item.tokens, &item.cfgs);
+        quote!(#cfgs { submodules.push(#info()); })
+    });
+    // The `#[pyfn]`s may use types imported in the body of the module function
+    let uses = func.block.stmts.iter().filter_map(|stmt| match stmt {
+        syn::Stmt::Item(syn::Item::Use(item)) => Some(item),
+        _ => None,
+    });
+    let info_ident = inspect_module_ident(modname);
+    let name = modname.to_string();
+
+    quote! {
+        #[doc(hidden)]
+        #[allow(dead_code, unused_imports, unused_mut)]
+        pub fn #info_ident() -> pyo3::inspect::ModuleInfo {
+            #(#uses)*
+            let mut functions = Vec::new();
+            let mut classes = Vec::new();
+            let mut submodules = Vec::new();
+            #(#functions)*
+            #(#classes)*
+            #(#submodules)*
+            pyo3::inspect::ModuleInfo {
+                name: #name,
+                doc: #doc,
+                functions,
+                classes,
+                submodules,
+            }
+        }
+    }
+}
+
+/// The name of the function generated for `#[pymodule]` which returns its `ModuleInfo`.
+pub fn inspect_module_ident(name: &Ident) -> Ident {
+    // Make sure this ident matches the one of inspect_module
+    format_ident!("__pyo3_inspect_module_{}", name)
+}
+
+/// The items added to a module, in the order in which they are first added.
+#[derive(Default)]
+struct ModuleItems {
+    functions: Vec<ModuleItem>,
+    classes: Vec<ModuleItem>,
+    submodules: Vec<ModuleItem>,
+}
+
+/// An item added to a module by a statement which is only compiled if `cfgs` hold.
+struct ModuleItem {
+    /// The `__pyo3_get_function_` struct of a function, the type of a class or the
+    /// `__pyo3_inspect_module_` function of a submodule.
+    tokens: TokenStream,
+    /// The `#[cfg]` attributes of the statement and of those enclosing it.
+    cfgs: TokenStream,
+}
+
+impl ModuleItems {
+    /// Scans statements which are only compiled if `cfgs` hold.
+    fn scan_stmts(&mut self, stmts: &[syn::Stmt], cfgs: &TokenStream) {
+        for stmt in stmts {
+            let tokens: Vec<TokenTree> = stmt.to_token_stream().into_iter().collect();
+            // The outer attributes of a statement come first
+            let mut cfgs = cfgs.clone();
+            let mut start = 0;
+            while let (Some(TokenTree::Punct(pound)), Some(TokenTree::Group(attr))) =
+                (tokens.get(start), tokens.get(start + 1))
+            {
+                if pound.as_char() != '#' || attr.delimiter() != Delimiter::Bracket {
+                    break;
+                }
+                if let Some(TokenTree::Ident(ident)) = attr.stream().into_iter().next() {
+                    if ident == "cfg" {
+                        cfgs.extend(tokens[start..start + 2].iter().cloned());
+                    }
+                }
+                start += 2;
+            }
+            self.scan(&tokens[start..], &cfgs);
+        }
+    }
+
+    fn scan(&mut self, tokens: &[TokenTree], cfgs: &TokenStream) {
+        for (i, token) in tokens.iter().enumerate() {
+            match token {
+                TokenTree::Group(group) => {
+                    let block = if group.delimiter() == Delimiter::Brace {
+                        syn::Block::parse_within.parse2(group.stream()).ok()
+                    } else {
+                        None
+                    };
+                    match block {
+                        Some(stmts) => self.scan_stmts(&stmts, cfgs),
+                        None => {
+                            let tokens: Vec<TokenTree> = group.stream().into_iter().collect();
+                            self.scan(&tokens, cfgs);
+                        }
+                    }
+                }
+                TokenTree::Ident(ident) if ident == "wrap_pyfunction" => {
+                    if let Some(name) = macro_argument(&tokens[i + 1..]) {
+                        let wrapper = format_ident!("__pyo3_get_function_{}", name);
+                        push_unique(&mut self.functions, wrapper.into_token_stream(), cfgs);
+                    }
+                }
+                TokenTree::Ident(ident) if ident == "wrap_pymodule" => {
+                    if let Some(name) = macro_argument(&tokens[i + 1..]) {
+                        let info = inspect_module_ident(&name);
+                        push_unique(&mut self.submodules, info.into_token_stream(), cfgs);
+                    }
+                }
+                TokenTree::Ident(ident) if ident == "add_class" => {
+                    if let Some(class) = turbofish_argument(&tokens[i + 1..]) {
+                        push_unique(&mut self.classes, class, cfgs);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Adds an item unless it is already added under the same `#[cfg]`s.
+fn push_unique(items: &mut Vec<ModuleItem>, tokens: TokenStream, cfgs: &TokenStream) {
+    let key = (tokens.to_string(), cfgs.to_string());
+    if !items
+        .iter()
+        .any(|item| (item.tokens.to_string(), item.cfgs.to_string()) == key)
+    {
+        items.push(ModuleItem {
+            tokens,
+            cfgs: cfgs.clone(),
+        });
+    }
+}
+
+/// Returns `name` from `!(name ...)`.
+fn macro_argument(tokens: &[TokenTree]) -> Option<Ident> {
+    match (tokens.first(), tokens.get(1)) {
+        (Some(TokenTree::Punct(bang)), Some(TokenTree::Group(group))) if bang.as_char() == '!' => {
+            match group.stream().into_iter().next() {
+                Some(TokenTree::Ident(name)) => Some(name),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Returns `T` from `::<T>`.
+fn turbofish_argument(tokens: &[TokenTree]) -> Option<TokenStream> {
+    let mut tokens = tokens.iter();
+    for expected in &[':', ':', '<'] {
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == *expected => {}
+            _ => return None,
+        }
+    }
+    let mut depth = 0;
+    let mut ty = TokenStream::new();
+    for token in tokens {
+        if let TokenTree::Punct(punct) = token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if depth == 0 => return Some(ty),
+                '>' => depth -= 1,
+                _ => {}
+            }
+        }
+        ty.extend(Some(token.clone()));
+    }
+    None
+}
+
+/// Replaces `Self` with `cls` and named lifetimes with `'_` in `ty`, so that it can be used in an
+/// expression outside of the function. A missing return type (`_`) becomes `()`.
+fn erase_type(ty: &syn::Type, cls: Option<&syn::Type>) -> TokenStream {
+    if let syn::Type::Infer(_) = ty {
+        return quote!(());
+    }
+    erase_tokens(ty.to_token_stream(), cls)
+}
+
+fn erase_tokens(tokens: TokenStream, cls: Option<&syn::Type>) -> TokenStream {
+    let mut erased = TokenStream::new();
+    let mut after_apostrophe = false;
+    for token in tokens {
+        let is_apostrophe = match &token {
+            TokenTree::Punct(punct) => punct.as_char() == '\'',
+            _ => false,
+        };
+        match token {
+            TokenTree::Group(group) => {
+                let mut new_group =
+                    Group::new(group.delimiter(), erase_tokens(group.stream(), cls));
+                new_group.set_span(group.span());
+                erased.extend(Some(TokenTree::Group(new_group)));
+            }
+            TokenTree::Ident(ident) if after_apostrophe && ident != "static" => {
+                erased.extend(Some(TokenTree::Ident(Ident::new("_", ident.span()))));
+            }
+            TokenTree::Ident(ident) if ident == "Self" && cls.is_some() => {
+                erased.extend(cls.into_token_stream());
+            }
+            token => erased.extend(Some(token)),
+        }
+        after_apostrophe = is_apostrophe;
+    }
+    erased
+}
@@ -0,0 +1,349 @@
+use crate::attrs::IntoPyWithAttribute;
+use crate::from_pyobject::{ContainerAttribute, FieldAttributes, FieldGetter};
+use crate::inspect;
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{parse_quote, DataEnum, DeriveInput, Fields, Ident, Result};
+
+/// A field of a struct or enum variant, bound to a local variable by the generated `match`.
+struct Field<'a> {
+    /// The identifier of a named field.
+    ident: Option<&'a Ident>,
+    /// The local variable holding the value of the field.
+    binding: Ident,
+    ty: &'a syn::Type,
+    attrs: FieldAttributes,
+}
+
+impl<'a> Field<'a> {
+    /// The conversion of the field to a `PyObject`.
+    fn build(&self) -> TokenStream {
+        let binding = &self.binding;
+        match &self.attrs.into_py_with {
+            Some(IntoPyWithAttribute(expr_path)) => quote!(#expr_path(#binding, py)),
+            None => quote!(pyo3::IntoPy::<pyo3::PyObject>::into_py(#binding, py)),
+        }
+    }
+
+    /// The Python type of the converted field.
+    fn build_type_output(&self) -> TokenStream {
+        let ty = self.ty;
+        match &self.attrs.into_py_with {
+            Some(_) => quote!(pyo3::inspect::TypeInfo::Any),
+            None => quote!(<#ty as pyo3::IntoPy<pyo3::PyObject>>::type_output()),
+        }
+    }
+
+    /// The key given with `item`, if the field is annotated with `item`.
+    fn item(&self) -> Option<Option<&syn::Lit>> {
+        match &self.attrs.getter {
+            Some(FieldGetter::GetItem(key)) => Some(key.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The name given with `attribute`, if the field is annotated with `attribute`.
+    fn attribute(&self) -> Option<Option<&syn::LitStr>> {
+        match &self.attrs.getter {
+            Some(FieldGetter::GetAttr(name)) => Some(name.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// The key of the field in a dict: the argument of `item`, or the name of the field.
+    fn dict_key(&self) -> TokenStream {
+        let ident = self.ident;
+        match self.item() {
+            Some(Some(key)) => quote!(#key),
+            _ => quote!(stringify!(#ident)),
+        }
+    }
+
+    /// The Python type of the key of the field in a dict.
+    fn dict_key_type(&self) -> TokenStream {
+        match self.item() {
+            Some(Some(syn::Lit::Int(_))) => quote!(pyo3::inspect::TypeInfo::Builtin("int")),
+            Some(Some(syn::Lit::Str(_))) | Some(None) | None => {
+                quote!(pyo3::inspect::TypeInfo::Builtin("str"))
+            }
+            Some(Some(_)) => quote!(pyo3::inspect::TypeInfo::Any),
+        }
+    }
+
+    /// The name of the field in a namedtuple: the argument of `attribute`, or the name of the
+    /// field.
+    fn attribute_name(&self) -> TokenStream {
+        let ident = self.ident;
+        match self.attribute() {
+            Some(Some(name)) => quote!(#name),
+            _ => quote!(stringify!(#ident)),
+        }
+    }
+}
+
+/// Container Style
+///
+/// How a struct or an enum variant is converted.
+enum ContainerType {
+    /// Struct converted to a `dict`, e.g. `struct Foo { a: String }`
+    Dict,
+    /// Struct converted to a `collections.namedtuple`, because its fields use `attribute`.
+    NamedTuple,
+    /// Tuple struct converted to a `tuple`, e.g. `struct Foo(String, String)`
+    Tuple,
+    /// Newtype, e.g. `struct Foo(String)` or `#[pyo3(transparent)] struct Foo { a: String }`
+    ///
+    /// The wrapped field is converted directly.
+    Newtype,
+}
+
+/// Data container
+///
+/// Either describes a struct or an enum variant.
+struct Container<'a> {
+    path: syn::Path,
+    /// The name of the struct or enum variant, used as the name of a namedtuple.
+    name: String,
+    ty: ContainerType,
+    fields: Vec<Field<'a>>,
+    annotation: Option<syn::LitStr>,
+}
+
+impl<'a> Container<'a> {
+    /// Construct a container based on fields, identifier and attributes.
+    ///
+    /// Fails if the variant has no fields or incompatible attributes.
+    fn new(fields: &'a Fields, path: syn::Path, attrs: Vec<ContainerAttribute>) -> Result<Self> {
+        let name = &path.segments.last().unwrap().ident;
+        let span = match fields {
+            Fields::Unit => name.span(),
+            _ => fields.span(),
+        };
+        ensure_spanned!(
+            !fields.is_empty(),
+            span => "cannot derive IntoPyObject for empty structs and variants"
+        );
+        let transparent = attrs.contains(&ContainerAttribute::Transparent);
+        ensure_spanned!(
+            !transparent || fields.len() == 1,
+            fields.span() => "transparent structs and variants can only have 1 field"
+        );
+
+        let fields = fields
+            .iter()
+            .enumerate()
+            .map(|(i, field)| {
+                Ok(Field {
+                    ident: field.ident.as_ref(),
+                    binding: Ident::new(&format!("_{}", i), Span::call_site()),
+                    ty: &field.ty,
+                    attrs: FieldAttributes::parse_attrs(&field.attrs)?,
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let named = fields[0].ident.is_some();
+        let getter_span = |field: &Field| -> Span {
+            field
+                .ident
+                .map_or_else(|| field.ty.span(), |ident| ident.span())
+        };
+        let ty = if transparent || (!named && fields.len() == 1) {
+            ContainerType::Newtype
+        } else if !named {
+            if let Some(field) = fields.iter().find(|field| field.attrs.getter.is_some()) {
+                bail_spanned!(
+                    getter_span(field) => "`attribute` and `item` are not supported for tuple structs"
+                );
+            }
+            ContainerType::Tuple
+        } else if fields.iter().any(|field| field.attribute().is_some()) {
+            if let Some(field) = fields.iter().find(|field| field.item().is_some()) {
+                bail_spanned!(
+                    getter_span(field) => "`item` cannot be used together with `attribute`, which converts the struct to a namedtuple"
+                );
+            }
+            ContainerType::NamedTuple
+        } else {
+            ContainerType::Dict
+        };
+
+        let annotation = attrs.iter().find_map(|a| a.annotation().cloned());
+        let name = name.to_string();
+        Ok(Container {
+            path,
+            name,
+            ty,
+            fields,
+            annotation,
+        })
+    }
+
+    /// The pattern binding the fields of the container to local variables.
+    fn pattern(&self) -> TokenStream {
+        let path = &self.path;
+        let bindings = self.fields.iter().map(|field| &field.binding);
+        if self.fields[0].ident.is_some() {
+            let idents = self.fields.iter().map(|field| field.ident);
+            quote!(#path { #(#idents: #bindings),* })
+        } else {
+            quote!(#path(#(#bindings),*))
+        }
+    }
+
+    /// Build the conversion of the bound fields to a `PyObject`.
+    fn build(&self) -> TokenStream {
+        let values: Vec<_> = self.fields.iter().map(Field::build).collect();
+        match self.ty {
+            ContainerType::Newtype => quote!(#(#values)*),
+            ContainerType::Tuple => quote! {
+                let tuple = pyo3::types::PyTuple::new(py, vec![#(#values),*]);
+                pyo3::IntoPy::<pyo3::PyObject>::into_py(tuple, py)
+            },
+            ContainerType::Dict => {
+                let keys = self.fields.iter().map(Field::dict_key);
+                quote! {
+                    let dict = pyo3::types::PyDict::new(py);
+                    #(dict.set_item(#keys, #values).expect("failed to set item on dict");)*
+                    pyo3::IntoPy::<pyo3::PyObject>::into_py(dict, py)
+                }
+            }
+            ContainerType::NamedTuple => {
+                let name = &self.name;
+                let attributes = self.fields.iter().map(Field::attribute_name);
+                quote! {
+                    static TYPE: pyo3::once_cell::GILOnceCell<pyo3::PyObject> =
+                        pyo3::once_cell::GILOnceCell::new();
+                    let ty = TYPE.get_or_init(py, || {
+                        py.import("collections")
+                            .and_then(|collections| {
+                                collections.call1("namedtuple", (#name, vec![#(#attributes),*]))
+                            })
+                            .map(|ty| pyo3::ToPyObject::to_object(ty, py))
+                            .expect("failed to create namedtuple type")
+                    });
+                    ty.call1(py, pyo3::types::PyTuple::new(py, vec![#(#values),*]))
+                        .expect("failed to create namedtuple")
+                }
+            }
+        }
+    }
+
+    /// Build the body of `type_output` for a struct, or the type of an enum variant.
+    ///
+    /// Variants with an `annotation` use it as the Python type.
+    fn build_type_output(&self) -> TokenStream {
+        if let Some(annotation) = &self.annotation {
+            return quote!(pyo3::inspect::TypeInfo::Builtin(#annotation));
+        }
+        let types: Vec<_> = self.fields.iter().map(Field::build_type_output).collect();
+        match self.ty {
+            ContainerType::Newtype => quote!(#(#types)*),
+            ContainerType::Tuple | ContainerType::NamedTuple => {
+                quote!(pyo3::inspect::TypeInfo::Tuple(vec![#(#types),*]))
+            }
+            ContainerType::Dict => {
+                let key_types = self.fields.iter().map(Field::dict_key_type);
+                quote! {
+                    pyo3::inspect::TypeInfo::Dict(
+                        Box::new(pyo3::inspect::TypeInfo::union_of(vec![#(#key_types),*])),
+                        Box::new(pyo3::inspect::TypeInfo::union_of(vec![#(#types),*])),
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Build the `match` arms and the `type_output` of an enum.
+fn build_enum(data_enum: &DataEnum, ident: &Ident) -> Result<(TokenStream, TokenStream)> {
+    ensure_spanned!(
+        !data_enum.variants.is_empty(),
+        ident.span() => "cannot derive IntoPyObject for empty enum"
+    );
+    let variants = data_enum
+        .variants
+        .iter()
+        .map(|variant| {
+            let attrs = ContainerAttribute::parse_attrs(&variant.attrs)?;
+            let var_ident = &variant.ident;
+            Container::new(&variant.fields, parse_quote!(#ident::#var_ident), attrs)
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let arms = variants.iter().map(|variant| {
+        let pattern = variant.pattern();
+        let body = variant.build();
+        quote!(#pattern => { #body })
+    });
+    let types = variants.iter().map(Container::build_type_output);
+    Ok((
+        quote!(match self { #(#arms)* }),
+        quote!(pyo3::inspect::TypeInfo::union_of(vec![#(#types),*])),
+    ))
+}
+
+/// Derive `IntoPy<PyObject>` for enums and structs.
+///
+///   * At least one field, in case of `#[transparent]`, exactly one field
+///   * At least one variant for enums.
+///   * Fields of input structs and enums must implement `IntoPy<PyObject>` or be annotated with
+///     `into_py_with`
+///   * Derivation for structs with generic fields like `struct<T> Foo(T)`
+///     adds `T: IntoPy<PyObject>` on the derived implementation.
+pub fn build_derive_into_pyobject(tokens: &DeriveInput) -> Result<TokenStream> {
+    let ident = &tokens.ident;
+    let (body, type_output) = match &tokens.data {
+        syn::Data::Enum(en) => build_enum(en, ident)?,
+        syn::Data::Struct(st) => {
+            let attrs = ContainerAttribute::parse_attrs(&tokens.attrs)?;
+            if let Some(annotation) = attrs.iter().find_map(ContainerAttribute::annotation) {
+                bail_spanned!(annotation.span() => "annotation is not supported for structs");
+            }
+            let st = Container::new(&st.fields, parse_quote!(#ident), attrs)?;
+            let pattern = st.pattern();
+            let body = st.build();
+            (
+                quote! {
+                    let #pattern = self;
+                    #body
+                },
+                st.build_type_output(),
+            )
+        }
+        syn::Data::Union(_) => bail_spanned!(
+            tokens.span() => "#[derive(IntoPyObject)] is not supported for unions"
+        ),
+    };
+
+    let mut generics = tokens.generics.clone();
+    let where_clause = generics.make_where_clause();
+    for param in tokens.generics.type_params() {
+        let gen_ident = &param.ident;
+        where_clause
+            .predicates
+            .push(parse_quote!(#gen_ident: pyo3::IntoPy<pyo3::PyObject>))
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let type_output = if inspect::enabled() {
+        Some(quote! {
+            fn type_output() -> pyo3::inspect::TypeInfo {
+                #type_output
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(quote!(
+        #[automatically_derived]
+        impl#impl_generics pyo3::IntoPy<pyo3::PyObject> for #ident#ty_generics #where_clause {
+            fn into_py(self, py: pyo3::Python) -> pyo3::PyObject {
+                #body
+            }
+
+            #type_output
+        }
+    ))
+}
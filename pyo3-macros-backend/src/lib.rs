@@ -10,6 +10,8 @@ mod utils;
 mod attrs;
 mod defs;
 mod from_pyobject;
+mod inspect;
+mod into_pyobject;
 mod konst;
 mod method;
 mod module;
@@ -19,10 +21,16 @@ mod pyfunction;
 mod pyimpl;
 mod pymethod;
 mod pyproto;
+mod slots;
 
 pub use from_pyobject::build_derive_from_pyobject;
-pub use module::{add_fn_to_module, process_functions_in_module, py_init};
-pub use pyclass::{build_py_class, PyClassArgs};
+pub use inspect::{inspect_module_ident, module_info};
+pub use into_pyobject::build_derive_into_pyobject;
+pub use module::{
+    add_fn_to_module, module_item, process_functions_in_module, py_init, pymodule_module_impl,
+    PyModuleOptions,
+};
+pub use pyclass::{build_py_class, build_py_enum, PyClassArgs};
 pub use pyfunction::{build_py_function, PyFunctionAttr};
 pub use pyimpl::{build_py_methods, PyClassMethodsType};
 pub use pyproto::build_py_proto;
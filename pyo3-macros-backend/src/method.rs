@@ -1,7 +1,9 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::pyfunction::Argument;
-use crate::pyfunction::{parse_name_attribute, PyFunctionArgAttrs, PyFunctionAttr};
+use crate::pyfunction::{
+    parse_name_attribute, PyFunctionArgAttrs, PyFunctionAttr, PyFunctionOptions,
+};
 use crate::utils;
 use proc_macro2::TokenStream;
 use quote::ToTokens;
@@ -119,6 +121,23 @@ impl SelfType {
             }
         }
     }
+
+    /// The receiver of an `async fn`, whose future outlives the call. `&self` is only supported
+    /// for frozen classes, and is borrowed from a `Py<Self>` owned by the future.
+    pub fn async_receiver(&self, cls: &syn::Type, span: proc_macro2::Span) -> TokenStream {
+        match self {
+            SelfType::Receiver { .. } => {
+                let assert_frozen =
+                    quote_spanned! {span=> pyo3::class::impl_::assert_frozen::<#cls>(); };
+                quote! {
+                    #assert_frozen
+                    let _cell = _py.from_borrowed_ptr::<pyo3::PyCell<#cls>>(_slf);
+                    let _slf = pyo3::Py::<#cls>::from(_cell);
+                }
+            }
+            SelfType::TryFromPyCell(_) => self.receiver(cls),
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -130,9 +149,16 @@ pub struct FnSpec<'a> {
     // r# can be removed by syn::ext::IdentExt::unraw()
     pub python_name: syn::Ident,
     pub attrs: Vec<Argument>,
+    // Whether `attrs` came from `#[pyo3(signature = ...)]`, in which case default values are
+    // Rust expressions of the argument's own type.
+    pub has_signature: bool,
     pub args: Vec<FnArg<'a>>,
     pub output: syn::Type,
+    // `__text_signature__` without the function name, e.g. `($self, a, b=1)`
+    pub text_signature: Option<syn::LitStr>,
     pub doc: syn::LitStr,
+    // `async fn`s return a `pyo3::coroutine::Coroutine`
+    pub asyncness: Option<syn::Token![async]>,
 }
 
 pub fn get_return_info(output: &syn::ReturnType) -> syn::Type {
@@ -163,22 +189,34 @@ impl<'a> FnSpec<'a> {
         meth_attrs: &mut Vec<syn::Attribute>,
         allow_custom_name: bool,
     ) -> syn::Result<FnSpec<'a>> {
+        let options = PyFunctionOptions::from_attrs(meth_attrs)?;
         let MethodAttributes {
             ty: fn_type_attr,
-            args: fn_attrs,
+            args: mut fn_attrs,
             mut python_name,
         } = parse_method_attributes(meth_attrs, allow_custom_name)?;
 
         let (fn_type, skip_first_arg) = Self::parse_fn_type(sig, fn_type_attr, &mut python_name)?;
 
+        if let Some(asyncness) = &sig.asyncness {
+            match &fn_type {
+                FnType::Fn(SelfType::Receiver { mutable: true }) => bail_spanned!(
+                    sig.inputs[0].span() => "async methods cannot take `&mut self`, use `&self` on a `#[pyclass(frozen)]` or `slf: Py<Self>` instead"
+                ),
+                FnType::Fn(_) | FnType::FnStatic => {}
+                _ => bail_spanned!(
+                    asyncness.span() => "`async fn` is only supported for functions, methods and static methods"
+                ),
+            }
+        }
+
         let name = &sig.ident;
         let ty = get_return_info(&sig.output);
         let python_name = python_name.as_ref().unwrap_or(name).unraw();
 
-        let text_signature = Self::parse_text_signature(meth_attrs, &fn_type, &python_name)?;
-        let doc = utils::get_doc(&meth_attrs, text_signature, true)?;
+        let text_signature = Self::parse_text_signature(meth_attrs, &fn_type)?;
 
-        let arguments = if skip_first_arg {
+        let arguments: Vec<FnArg> = if skip_first_arg {
             sig.inputs
                 .iter_mut()
                 .skip(1)
@@ -191,27 +229,182 @@ impl<'a> FnSpec<'a> {
                 .collect::<syn::Result<_>>()?
         };
 
-        Ok(FnSpec {
+        let has_signature = options.signature.is_some();
+        if let Some(signature) = options.signature {
+            ensure_spanned!(
+                fn_attrs.is_empty(),
+                signature.span() => "`signature` cannot be used together with `#[args]`"
+            );
+            if let FnType::Getter(_) | FnType::Setter(_) | FnType::ClassAttribute = fn_type {
+                bail_spanned!(signature.span() => "`signature` not allowed with this method type");
+            }
+            signature.check_arguments(&arguments)?;
+            fn_attrs = signature.arguments;
+        }
+
+        let mut spec = FnSpec {
             tp: fn_type,
             name,
             python_name,
             attrs: fn_attrs,
+            has_signature,
             args: arguments,
             output: ty,
-            doc,
-        })
+            text_signature: None,
+            doc: syn::LitStr::new("", proc_macro2::Span::call_site()),
+            asyncness: sig.asyncness,
+        };
+        spec.check_async_args()?;
+        spec.set_text_signature_and_doc(
+            meth_attrs,
+            text_signature,
+            options.text_signature_disabled,
+        )?;
+
+        Ok(spec)
+    }
+
+    /// Async functions run after the wrapper returned, so they cannot take the GIL token.
+    pub fn check_async_args(&self) -> syn::Result<()> {
+        if self.asyncness.is_some() {
+            if let Some(arg) = self.args.iter().find(|arg| arg.py) {
+                bail_spanned!(
+                    arg.ty.span() => "async functions cannot take `Python`, use `Python::with_gil` instead"
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Wraps `call`, the call of this `async fn`, into the `pyo3::coroutine::Coroutine` returned
+    /// to Python. The `__qualname__` of methods is prefixed with the name of `cls`.
+    pub fn coroutine(&self, cls: Option<&syn::Type>, call: TokenStream) -> TokenStream {
+        let python_name = &self.python_name;
+        let qualname_prefix = match cls {
+            Some(cls) => quote!(Some(<#cls as pyo3::type_object::PyTypeInfo>::NAME)),
+            None => quote!(None),
+        };
+        quote! {
+            pyo3::callback::convert(
+                _py,
+                pyo3::coroutine::Coroutine::new(
+                    Some(stringify!(#python_name)),
+                    #qualname_prefix,
+                    async move { #call.await },
+                ),
+            )
+        }
+    }
+
+    /// Sets `text_signature` and `doc`. Functions without `#[text_signature]` get a text signature
+    /// generated from their arguments, unless disabled with `#[pyo3(text_signature = None)]`.
+    pub fn set_text_signature_and_doc(
+        &mut self,
+        attrs: &[syn::Attribute],
+        text_signature: Option<syn::LitStr>,
+        text_signature_disabled: Option<proc_macro2::Span>,
+    ) -> syn::Result<()> {
+        self.text_signature = match (text_signature, text_signature_disabled) {
+            (Some(_), Some(span)) => bail_spanned!(
+                span => "`text_signature = None` cannot be used together with `#[text_signature]`"
+            ),
+            (Some(text_signature), None) => Some(text_signature),
+            (None, Some(_)) => None,
+            // Keep a signature written by hand at the start of the docstring.
+            (None, None) if self.doc_has_text_signature(attrs)? => None,
+            (None, None) => match self.tp {
+                FnType::Fn(_) | FnType::FnClass | FnType::FnStatic | FnType::FnNew => Some(
+                    syn::LitStr::new(&self.generate_text_signature(), self.python_name.span()),
+                ),
+                _ => None,
+            },
+        };
+
+        // The text signature of `#[new]` goes on the class docstring instead.
+        let doc_text_signature = match (&self.tp, &self.text_signature) {
+            (FnType::FnNew, _) | (_, None) => None,
+            (_, Some(text_signature)) => Some(utils::text_signature_with_name(
+                &self.python_name,
+                text_signature,
+            )?),
+        };
+        self.doc = utils::get_doc(attrs, doc_text_signature, true)?;
+        Ok(())
+    }
+
+    fn doc_has_text_signature(&self, attrs: &[syn::Attribute]) -> syn::Result<bool> {
+        let doc = utils::get_doc(attrs, None, false)?.value();
+        Ok(doc.starts_with(&format!("{}(", self.python_name)) && doc.contains(")\n--\n\n"))
+    }
+
+    /// Builds the `__text_signature__` of this function from its arguments, for example
+    /// `($self, a, /, b=1, *args, c, **kwargs)`.
+    pub fn generate_text_signature(&self) -> String {
+        let mut positional = Vec::new();
+        let mut varargs = None;
+        let mut keyword_only = Vec::new();
+        let mut kwargs = None;
+
+        match self.tp {
+            FnType::Fn(_) => positional.push("$self".to_owned()),
+            FnType::FnClass => positional.push("$cls".to_owned()),
+            _ => (),
+        }
+        let num_receivers = positional.len();
+
+        for arg in self.args.iter().filter(|arg| !arg.py) {
+            let name = arg.name.unraw().to_string();
+            if self.is_args(arg.name) {
+                varargs = Some(format!("*{}", name));
+            } else if self.is_kwargs(arg.name) {
+                kwargs = Some(format!("**{}", name));
+            } else {
+                let param = match self.default_value_expr(arg.name) {
+                    Some(default) => format!("{}={}", name, default_value_text(default)),
+                    None if arg.optional.is_some() => format!("{}=None", name),
+                    None => name,
+                };
+                if self.is_kw_only(arg.name) {
+                    keyword_only.push(param);
+                } else {
+                    positional.push(param);
+                }
+            }
+        }
+
+        let positional_only = self.positional_only_parameters();
+        if positional_only > 0 {
+            positional.insert(num_receivers + positional_only, "/".to_owned());
+        }
+
+        let mut params = positional;
+        if let Some(varargs) = varargs {
+            params.push(varargs);
+        } else if !keyword_only.is_empty() {
+            params.push("*".to_owned());
+        }
+        params.extend(keyword_only);
+        params.extend(kwargs);
+
+        format!("({})", params.join(", "))
+    }
+
+    /// The number of leading positional arguments which are positional-only.
+    pub fn positional_only_parameters(&self) -> usize {
+        // Only positional arguments can precede the `/`
+        self.attrs
+            .iter()
+            .position(|arg| *arg == Argument::PosOnlyArgsSeparator)
+            .unwrap_or(0)
     }
 
     fn parse_text_signature(
         meth_attrs: &mut Vec<syn::Attribute>,
         fn_type: &FnType,
-        python_name: &syn::Ident,
     ) -> syn::Result<Option<syn::LitStr>> {
         let mut parse_erroneous_text_signature = |error_msg: &str| {
             // try to parse anyway to give better error messages
-            if let Some(text_signature) =
-                utils::parse_text_signature_attrs(meth_attrs, &python_name)?
-            {
+            if let Some(text_signature) = utils::take_text_signature_attr(meth_attrs)? {
                 bail_spanned!(text_signature.span() => error_msg)
             } else {
                 Ok(None)
@@ -220,7 +413,7 @@ impl<'a> FnSpec<'a> {
 
         let text_signature = match &fn_type {
             FnType::Fn(_) | FnType::FnClass | FnType::FnStatic => {
-                utils::parse_text_signature_attrs(&mut *meth_attrs, &python_name)?
+                utils::take_text_signature_attr(&mut *meth_attrs)?
             }
             FnType::FnNew => parse_erroneous_text_signature(
                 "text_signature not allowed on __new__; if you want to add a signature on \
@@ -325,13 +518,17 @@ impl<'a> FnSpec<'a> {
     }
 
     pub fn default_value(&self, name: &syn::Ident) -> Option<TokenStream> {
+        self.default_value_expr(name)
+            .map(|expr| expr.into_token_stream())
+    }
+
+    pub fn default_value_expr(&self, name: &syn::Ident) -> Option<&syn::Expr> {
         for s in self.attrs.iter() {
             match s {
                 Argument::Arg(path, opt) | Argument::Kwarg(path, opt) => {
                     if path.is_ident(name) {
                         if let Some(val) = opt {
-                            let i: syn::Expr = syn::parse_str(&val).unwrap();
-                            return Some(i.into_token_stream());
+                            return Some(val);
                         }
                     }
                 }
@@ -504,4 +701,38 @@ fn parse_method_name_attribute(
     })
 }
 
+/// Renders a default value for a text signature. Python can only evaluate simple literals there,
+/// so anything else is shown as `...`.
+pub fn default_value_text(expr: &syn::Expr) -> String {
+    match expr {
+        syn::Expr::Lit(syn::ExprLit { lit, .. }) => match lit {
+            syn::Lit::Int(int) => int.base10_digits().to_owned(),
+            syn::Lit::Float(float) => float.base10_digits().to_owned(),
+            syn::Lit::Bool(b) if b.value => "True".to_owned(),
+            syn::Lit::Bool(_) => "False".to_owned(),
+            syn::Lit::Str(s) if s.value().chars().all(|c| c.is_ascii_graphic() || c == ' ') => {
+                format!("{:?}", s.value())
+            }
+            _ => "...".to_owned(),
+        },
+        syn::Expr::Path(path) if path.path.is_ident("None") => "None".to_owned(),
+        syn::Expr::Unary(syn::ExprUnary {
+            op: syn::UnOp::Neg(_),
+            expr,
+            ..
+        }) => match &**expr {
+            syn::Expr::Lit(_) => {
+                let value = default_value_text(expr);
+                if value == "..." {
+                    value
+                } else {
+                    format!("-{}", value)
+                }
+            }
+            _ => "...".to_owned(),
+        },
+        _ => "...".to_owned(),
+    }
+}
+
 const IMPL_TRAIT_ERR: &str = "Python functions cannot have `impl Trait` arguments";
@@ -1,19 +1,98 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 //! Code generation for the function that initializes a python module and adds classes and function.
 
+use crate::inspect;
 use crate::method::{self, FnArg};
-use crate::pyfunction::PyFunctionAttr;
+use crate::pyfunction::{PyFunctionAttr, PyFunctionOptions};
 use crate::pymethod::{check_generic, get_arg_names, impl_arg_params};
 use crate::utils;
 use proc_macro2::{Span, TokenStream};
-use quote::{format_ident, quote};
-use syn::{spanned::Spanned, Ident, Result};
+use quote::{format_ident, quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::{spanned::Spanned, Ident, Result, Token};
+
+/// The arguments of `#[pymodule(...)]`.
+#[derive(Default)]
+pub struct PyModuleOptions {
+    /// The Python name of the module, if it differs from the name of the function.
+    pub name: Option<Ident>,
+    /// `multi_phase`: the module uses multi-phase initialization (PEP 489).
+    pub multi_phase: bool,
+    /// `state = T`: the type of the per-module state of a multi-phase module.
+    pub state: Option<syn::Type>,
+}
+
+impl Parse for PyModuleOptions {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut options = PyModuleOptions::default();
+        let mut state_key = None;
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            if input.peek(Token![=]) {
+                input.parse::<Token![=]>()?;
+                ensure_spanned!(key == "state", key.span() => "expected `state = ...`");
+                ensure_spanned!(
+                    options.state.is_none(),
+                    key.span() => "`state` may only be specified once"
+                );
+                options.state = Some(input.parse()?);
+                state_key = Some(key);
+            } else if key == "multi_phase" {
+                options.multi_phase = true;
+            } else {
+                ensure_spanned!(
+                    options.name.is_none(),
+                    key.span() => "expected `multi_phase` or `state = ...`"
+                );
+                options.name = Some(key);
+            }
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+        if let Some(state_key) = state_key {
+            ensure_spanned!(
+                options.multi_phase,
+                state_key.span() => "`state` requires `#[pymodule(multi_phase)]`"
+            );
+        }
+        Ok(options)
+    }
+}
 
 /// Generates the function that is called by the python interpreter to initialize the native
 /// module
-pub fn py_init(fnname: &Ident, name: &Ident, doc: syn::LitStr) -> TokenStream {
+pub fn py_init(
+    fnname: &syn::Path,
+    name: &Ident,
+    doc: syn::LitStr,
+    options: &PyModuleOptions,
+) -> TokenStream {
     let cb_name = Ident::new(&format!("PyInit_{}", name), Span::call_site());
 
+    let init = if options.multi_phase {
+        let state = match &options.state {
+            Some(state) => quote_spanned! { state.span() =>
+                Some(Box::new(<#state as ::std::default::Default>::default())
+                    as Box<dyn ::std::any::Any + Send>)
+            },
+            None => quote! { None },
+        };
+        quote! {
+            unsafe extern "C" fn exec(module: *mut pyo3::ffi::PyObject) -> std::os::raw::c_int {
+                pyo3::callback::handle_panic(|_py| {
+                    ModuleDef::exec_module(_py, module, #doc, #state, #fnname)
+                })
+            }
+
+            MODULE_DEF.make_multi_phase_def(exec)
+        }
+    } else {
+        quote! {
+            pyo3::callback::handle_panic(|_py| { MODULE_DEF.make_module(#doc, #fnname) })
+        }
+    };
+
     quote! {
         #[no_mangle]
         #[allow(non_snake_case)]
@@ -24,36 +103,326 @@ pub fn py_init(fnname: &Ident, name: &Ident, doc: syn::LitStr) -> TokenStream {
             const NAME: &'static str = concat!(stringify!(#name), "\0");
             static MODULE_DEF: ModuleDef = unsafe { ModuleDef::new(NAME) };
 
-            pyo3::callback::handle_panic(|_py| { MODULE_DEF.make_module(#doc, #fnname) })
+            #init
+        }
+    }
+}
+
+/// Expands `#[pymodule] mod`: registers the classes, functions and modules declared in the module
+/// or exported with `#[pymodule_export] use`, and generates the `PyInit_` function.
+pub fn pymodule_module_impl(
+    mut module: syn::ItemMod,
+    options: PyModuleOptions,
+) -> Result<TokenStream> {
+    let name = options.name.clone().unwrap_or_else(|| module.ident.clone());
+    let doc = utils::get_doc(&module.attrs, None, false)?;
+    expand_module(&mut module, &name, &doc)?;
+
+    let mod_ident = &module.ident;
+    let init: syn::Path = syn::parse_quote!(#mod_ident::__pyo3_init);
+    let expanded = py_init(&init, &name, doc, &options);
+    let info = if inspect::enabled() {
+        let info_ident = inspect::inspect_module_ident(&name);
+        Some(quote! {
+            #[doc(hidden)]
+            pub fn #info_ident() -> pyo3::inspect::ModuleInfo {
+                #mod_ident::__pyo3_module_info()
+            }
+        })
+    } else {
+        None
+    };
+    let module_item = module_item(
+        mod_ident,
+        &module.vis,
+        quote!(module.add_wrapped(pyo3::wrap_pymodule!(#name))),
+        quote!(info.submodules.push(#mod_ident::__pyo3_module_info())),
+    );
+    Ok(quote! {
+        #module
+        #expanded
+        #info
+        #module_item
+    })
+}
+
+/// Adds the functions which initialize and describe a `#[pymodule] mod` to it, after expanding the
+/// modules nested in it.
+fn expand_module(module: &mut syn::ItemMod, name: &Ident, doc: &syn::LitStr) -> Result<()> {
+    let items = match &mut module.content {
+        Some((_, items)) => items,
+        None => bail_spanned!(
+            module.span() => "`#[pymodule]` can only be used on modules with their items inline"
+        ),
+    };
+
+    // The `module_item` structs of the items to add to the module, with their `#[cfg]`s
+    let mut module_items: Vec<(TokenStream, Ident)> = Vec::new();
+    let mut extra_items: Vec<syn::Item> = Vec::new();
+    let mut init_fn: Option<Ident> = None;
+
+    for item in items.iter_mut() {
+        match item {
+            syn::Item::Struct(syn::ItemStruct { attrs, ident, .. })
+            | syn::Item::Enum(syn::ItemEnum { attrs, ident, .. }) => {
+                reject_attr(attrs, "pymodule_init")?;
+                reject_attr(attrs, "pymodule_export")?;
+                if has_attr(attrs, "pyclass") {
+                    module_items.push((cfg_attrs(attrs), module_item_ident(ident)));
+                }
+            }
+            syn::Item::Fn(func) => {
+                reject_attr(&func.attrs, "pymodule_export")?;
+                if take_attr(&mut func.attrs, "pymodule_init")?.is_some() {
+                    ensure_spanned!(
+                        init_fn.is_none(),
+                        func.sig.ident.span() => "only one `#[pymodule_init]` may be specified"
+                    );
+                    init_fn = Some(func.sig.ident.clone());
+                } else if has_attr(&func.attrs, "pyfunction") || has_attr(&func.attrs, "pymodule") {
+                    module_items.push((cfg_attrs(&func.attrs), module_item_ident(&func.sig.ident)));
+                }
+            }
+            syn::Item::Mod(submodule) => {
+                reject_attr(&submodule.attrs, "pymodule_init")?;
+                reject_attr(&submodule.attrs, "pymodule_export")?;
+                if let Some(attr) = take_attr(&mut submodule.attrs, "pymodule")? {
+                    let options = parse_attr_options(&attr)?;
+                    ensure_spanned!(
+                        !options.multi_phase,
+                        attr.span() => "`multi_phase` can only be used on the top-level module"
+                    );
+                    let name = options.name.unwrap_or_else(|| submodule.ident.clone());
+                    let doc = utils::get_doc(&submodule.attrs, None, false)?;
+                    expand_module(submodule, &name, &doc)?;
+
+                    let cfgs = cfg_attrs(&submodule.attrs);
+                    let mod_ident = &submodule.ident;
+                    let name = name.to_string();
+                    let module_item = module_item(
+                        mod_ident,
+                        &submodule.vis,
+                        quote! {
+                            let py = pyo3::PyNativeType::py(module);
+                            let submodule = pyo3::types::PyModule::new(py, #name)?;
+                            submodule.add("__doc__", #doc)?;
+                            #mod_ident::__pyo3_init(py, submodule)?;
+                            module.add_submodule(submodule)
+                        },
+                        quote!(info.submodules.push(#mod_ident::__pyo3_module_info())),
+                    );
+                    let module_item: syn::File = syn::parse2(module_item)?;
+                    extra_items.extend(
+                        module_item
+                            .items
+                            .into_iter()
+                            .map(|item| -> syn::Item { syn::parse_quote!(#cfgs #item) }),
+                    );
+                    module_items.push((cfgs, module_item_ident(mod_ident)));
+                }
+            }
+            syn::Item::Use(item_use) => {
+                reject_attr(&item_use.attrs, "pymodule_init")?;
+                if take_attr(&mut item_use.attrs, "pymodule_export")?.is_some() {
+                    // The items are only used through their `module_item` structs, which are
+                    // imported as well
+                    item_use
+                        .attrs
+                        .push(syn::parse_quote!(#[allow(unused_imports)]));
+                    let cfgs = cfg_attrs(&item_use.attrs);
+                    let mut names = Vec::new();
+                    let tree = module_item_use_tree(&item_use.tree, &mut names)?;
+                    let leading_colon = &item_use.leading_colon;
+                    extra_items.push(syn::parse_quote! {
+                        #cfgs
+                        use #leading_colon #tree;
+                    });
+                    module_items.extend(names.into_iter().map(|name| (cfgs.clone(), name)));
+                }
+            }
+            item => {
+                let attrs: &[syn::Attribute] = match item {
+                    syn::Item::Const(item) => &item.attrs,
+                    syn::Item::Static(item) => &item.attrs,
+                    syn::Item::Impl(item) => &item.attrs,
+                    syn::Item::Trait(item) => &item.attrs,
+                    syn::Item::Type(item) => &item.attrs,
+                    _ => &[],
+                };
+                reject_attr(attrs, "pymodule_init")?;
+                reject_attr(attrs, "pymodule_export")?;
+            }
+        }
+    }
+    items.extend(extra_items);
+
+    let (cfgs, module_items): (Vec<_>, Vec<_>) = module_items.into_iter().unzip();
+    let init = init_fn.map(|init_fn| quote! { #init_fn(_py, module)?; });
+    items.push(syn::parse_quote! {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        pub(crate) fn __pyo3_init(
+            _py: pyo3::Python,
+            module: &pyo3::types::PyModule,
+        ) -> pyo3::PyResult<()> {
+            #(#cfgs { #module_items::__pyo3_add_to_module(module)?; })*
+            #init
+            Ok(())
+        }
+    });
+    if !inspect::enabled() {
+        return Ok(());
+    }
+    let name = name.to_string();
+    items.push(syn::parse_quote! {
+        #[doc(hidden)]
+        #[allow(dead_code)]
+        pub(crate) fn __pyo3_module_info() -> pyo3::inspect::ModuleInfo {
+            let mut info = pyo3::inspect::ModuleInfo {
+                name: #name,
+                doc: #doc,
+                functions: Vec::new(),
+                classes: Vec::new(),
+                submodules: Vec::new(),
+            };
+            #(#cfgs { #module_items::__pyo3_module_item_info(&mut info); })*
+            info
+        }
+    });
+    Ok(())
+}
+
+/// Replaces the names which `#[pymodule_export] use` imports with their `module_item` structs,
+/// which are collected in `names`.
+fn module_item_use_tree(tree: &syn::UseTree, names: &mut Vec<Ident>) -> Result<syn::UseTree> {
+    Ok(match tree {
+        syn::UseTree::Path(path) => syn::UseTree::Path(syn::UsePath {
+            tree: Box::new(module_item_use_tree(&path.tree, names)?),
+            ..path.clone()
+        }),
+        syn::UseTree::Name(name) => {
+            let ident = module_item_ident(&name.ident);
+            names.push(ident.clone());
+            syn::UseTree::Name(syn::UseName { ident })
+        }
+        syn::UseTree::Rename(rename) => {
+            let rename_ident = module_item_ident(&rename.rename);
+            names.push(rename_ident.clone());
+            syn::UseTree::Rename(syn::UseRename {
+                ident: module_item_ident(&rename.ident),
+                as_token: rename.as_token,
+                rename: rename_ident,
+            })
         }
+        syn::UseTree::Group(group) => syn::UseTree::Group(syn::UseGroup {
+            brace_token: group.brace_token,
+            items: group
+                .items
+                .iter()
+                .map(|tree| module_item_use_tree(tree, names))
+                .collect::<Result<_>>()?,
+        }),
+        syn::UseTree::Glob(glob) => bail_spanned!(
+            glob.span() => "`#[pymodule_export]` cannot be used with glob imports"
+        ),
+    })
+}
+
+/// The `#[cfg]` attributes in `attrs`.
+fn cfg_attrs(attrs: &[syn::Attribute]) -> TokenStream {
+    let cfgs = utils::get_cfg_attributes(attrs);
+    quote!(#(#cfgs)*)
+}
+
+/// Whether `attr` is `#[name]` or `#[path::to::name]`, e.g. `#[pyo3::pyclass]`.
+fn is_attr(attr: &syn::Attribute, name: &str) -> bool {
+    match attr.path.segments.last() {
+        Some(segment) => segment.ident == name,
+        None => false,
+    }
+}
+
+fn has_attr(attrs: &[syn::Attribute], name: &str) -> bool {
+    attrs.iter().any(|attr| is_attr(attr, name))
+}
+
+/// Removes the attribute `name`, which takes no arguments, from `attrs`.
+fn take_attr(attrs: &mut Vec<syn::Attribute>, name: &str) -> Result<Option<syn::Attribute>> {
+    let index = match attrs.iter().position(|attr| is_attr(attr, name)) {
+        Some(index) => index,
+        None => return Ok(None),
+    };
+    let attr = attrs.remove(index);
+    if name != "pymodule" {
+        ensure_spanned!(
+            attr.tokens.is_empty(),
+            attr.tokens.span() => format!("`#[{}]` takes no arguments", name)
+        );
+    }
+    Ok(Some(attr))
+}
+
+fn reject_attr(attrs: &[syn::Attribute], name: &str) -> Result<()> {
+    if let Some(attr) = attrs.iter().find(|attr| is_attr(attr, name)) {
+        bail_spanned!(
+            attr.span() => match name {
+                "pymodule_init" => "`#[pymodule_init]` can only be used on functions",
+                _ => "`#[pymodule_export]` can only be used on `use` items",
+            }
+        );
+    }
+    Ok(())
+}
+
+/// Parses the arguments of a `#[pymodule(...)]` attribute in a `#[pymodule] mod`.
+fn parse_attr_options(attr: &syn::Attribute) -> Result<PyModuleOptions> {
+    if attr.tokens.is_empty() {
+        Ok(PyModuleOptions::default())
+    } else {
+        attr.parse_args()
     }
 }
 
 /// Finds and takes care of the #[pyfn(...)] in `#[pymodule]`
-pub fn process_functions_in_module(func: &mut syn::ItemFn) -> syn::Result<()> {
+///
+/// Returns the `#[cfg]` attributes of the functions along with the `pyo3::inspect::FunctionInfo`
+/// expressions describing them.
+pub fn process_functions_in_module(
+    func: &mut syn::ItemFn,
+) -> syn::Result<Vec<(TokenStream, TokenStream)>> {
     let mut stmts: Vec<syn::Stmt> = Vec::new();
+    let mut infos = Vec::new();
 
     for stmt in func.block.stmts.iter_mut() {
         if let syn::Stmt::Item(syn::Item::Fn(func)) = stmt {
             if let Some((module_name, python_name, pyfn_attrs)) =
                 extract_pyfn_attrs(&mut func.attrs)?
             {
-                let function_to_python = add_fn_to_module(func, python_name, pyfn_attrs)?;
+                let (function_to_python, info) = wrap_fn(func, python_name, pyfn_attrs)?;
                 let function_wrapper_ident = function_wrapper_ident(&func.sig.ident);
                 let item: syn::ItemFn = syn::parse_quote! {
                     fn block_wrapper() {
                         #function_to_python
-                        #module_name.add_function(#function_wrapper_ident(#module_name)?)?;
+                        #module_name.add_function(#function_wrapper_ident::wrap(#module_name)?)?;
                     }
                 };
-                stmts.extend(item.block.stmts.into_iter());
+                // The generated items and statement only exist if the function does
+                let cfgs = utils::get_cfg_attributes(&func.attrs);
+                let cfgs = quote!(#(#cfgs)*);
+                stmts.extend(
+                    item.block
+                        .stmts
+                        .into_iter()
+                        .map(|stmt| syn::parse_quote!(#cfgs #stmt)),
+                );
+                infos.push((cfgs, info));
             }
         };
         stmts.push(stmt.clone());
     }
 
     func.block.stmts = stmts;
-    Ok(())
+    Ok(infos)
 }
 
 /// Extracts the data from the #[pyfn(...)] attribute of a function
@@ -122,8 +491,91 @@ pub fn add_fn_to_module(
     python_name: Ident,
     pyfn_attrs: PyFunctionAttr,
 ) -> syn::Result<TokenStream> {
+    let (wrapper, info) = wrap_fn(func, python_name, pyfn_attrs)?;
+    let name = &func.sig.ident;
+    let vis = &func.vis;
+    let function_wrapper_ident = function_wrapper_ident(name);
+    let info = if inspect::enabled() {
+        Some(quote! {
+            #[doc(hidden)]
+            #[allow(dead_code)]
+            impl #function_wrapper_ident {
+                pub(crate) fn info() -> pyo3::inspect::FunctionInfo {
+                    #info
+                }
+            }
+        })
+    } else {
+        None
+    };
+    let module_item = module_item(
+        name,
+        vis,
+        quote!(module.add_function(pyo3::wrap_pyfunction!(#name, module)?)),
+        quote!(info.functions.push(#function_wrapper_ident::info())),
+    );
+    Ok(quote! {
+        #wrapper
+        #info
+        #module_item
+    })
+}
+
+/// Generates the struct `__pyo3_module_item_<name>` next to a `#[pyclass]`, `#[pyfunction]` or
+/// `#[pymodule]`, whose `__pyo3_add_to_module` and `__pyo3_module_item_info` run `add_to_module`
+/// and `module_item_info`, so that `#[pymodule] mod` can add the item to a module.
+///
+/// `#[pymodule] mod` finds the struct through the name of the item, including when it is
+/// imported with `#[pymodule_export] use`, which also imports the struct.
+pub fn module_item(
+    name: &Ident,
+    vis: &syn::Visibility,
+    add_to_module: TokenStream,
+    module_item_info: TokenStream,
+) -> TokenStream {
+    let ident = module_item_ident(name);
+    let module_item_info = if inspect::enabled() {
+        Some(quote! {
+            pub(crate) fn __pyo3_module_item_info(info: &mut pyo3::inspect::ModuleInfo) {
+                #module_item_info
+            }
+        })
+    } else {
+        None
+    };
+    quote! {
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        #vis struct #ident;
+
+        #[allow(dead_code)]
+        impl #ident {
+            pub(crate) fn __pyo3_add_to_module(
+                module: &pyo3::types::PyModule,
+            ) -> pyo3::PyResult<()> {
+                #add_to_module
+            }
+
+            #module_item_info
+        }
+    }
+}
+
+/// The name of the struct generated by `module_item`.
+fn module_item_ident(name: &Ident) -> Ident {
+    format_ident!("__pyo3_module_item_{}", name)
+}
+
+/// Generates the python wrapper of a function along with the `pyo3::inspect::FunctionInfo`
+/// expression describing it
+fn wrap_fn(
+    func: &mut syn::ItemFn,
+    python_name: Ident,
+    pyfn_attrs: PyFunctionAttr,
+) -> syn::Result<(TokenStream, TokenStream)> {
     check_generic(&func.sig)?;
 
+    let options = PyFunctionOptions::from_attrs(&mut func.attrs)?;
     let mut arguments = func
         .sig
         .inputs
@@ -142,24 +594,44 @@ pub fn add_fn_to_module(
             type_is_pymodule(arg.ty),
             arg.ty.span() => PASS_MODULE_ERR
         );
+        if let Some(asyncness) = &func.sig.asyncness {
+            bail_spanned!(asyncness.span() => "`async fn` cannot be used with `pass_module`");
+        }
     }
 
+    let has_signature = options.signature.is_some();
+    let fn_attrs = match options.signature {
+        Some(signature) => {
+            ensure_spanned!(
+                pyfn_attrs.arguments.is_empty(),
+                signature.span() => "`signature` cannot be used together with arguments in `#[pyfunction(...)]` or `#[pyfn(...)]`"
+            );
+            signature.check_arguments(&arguments)?;
+            signature.arguments
+        }
+        None => pyfn_attrs.arguments,
+    };
+
     let ty = method::get_return_info(&func.sig.output);
 
-    let text_signature = utils::parse_text_signature_attrs(&mut func.attrs, &python_name)?;
-    let doc = utils::get_doc(&func.attrs, text_signature, true)?;
+    let text_signature = utils::take_text_signature_attr(&mut func.attrs)?;
 
     let function_wrapper_ident = function_wrapper_ident(&func.sig.ident);
 
-    let spec = method::FnSpec {
+    let mut spec = method::FnSpec {
         tp: method::FnType::FnStatic,
         name: &function_wrapper_ident,
         python_name,
-        attrs: pyfn_attrs.arguments,
+        attrs: fn_attrs,
+        has_signature,
         args: arguments,
         output: ty,
-        doc,
+        text_signature: None,
+        doc: syn::LitStr::new("", Span::call_site()),
+        asyncness: func.sig.asyncness,
     };
+    spec.check_async_args()?;
+    spec.set_text_signature_and_doc(&func.attrs, text_signature, options.text_signature_disabled)?;
 
     let doc = &spec.doc;
     let python_name = &spec.python_name;
@@ -167,22 +639,30 @@ pub fn add_fn_to_module(
     let name = &func.sig.ident;
     let wrapper_ident = format_ident!("__pyo3_raw_{}", name);
     let wrapper = function_c_wrapper(name, &wrapper_ident, &spec, pyfn_attrs.pass_module)?;
-    Ok(quote! {
+    let info = inspect::function_info(&spec, None);
+    let wrapper = quote! {
         #wrapper
-        pub(crate) fn #function_wrapper_ident<'a>(
-            args: impl Into<pyo3::derive_utils::PyFunctionArguments<'a>>
-        ) -> pyo3::PyResult<&'a pyo3::types::PyCFunction> {
-            let name = concat!(stringify!(#python_name), "\0");
-            pyo3::types::PyCFunction::internal_new(
-                pyo3::class::methods::PyMethodDef::cfunction_with_keywords(
-                    name,
-                    pyo3::class::methods::PyCFunctionWithKeywords(#wrapper_ident),
-                    #doc,
-                ),
-                args.into(),
-            )
+        #[doc(hidden)]
+        #[allow(non_camel_case_types)]
+        pub(crate) struct #function_wrapper_ident;
+
+        impl #function_wrapper_ident {
+            pub(crate) fn wrap<'a>(
+                args: impl Into<pyo3::derive_utils::PyFunctionArguments<'a>>
+            ) -> pyo3::PyResult<&'a pyo3::types::PyCFunction> {
+                let name = concat!(stringify!(#python_name), "\0");
+                pyo3::types::PyCFunction::internal_new(
+                    pyo3::class::methods::PyMethodDef::cfunction_with_keywords(
+                        name,
+                        pyo3::class::methods::PyCFunctionWithKeywords(#wrapper_ident),
+                        #doc,
+                    ),
+                    args.into(),
+                )
+            }
         }
-    })
+    };
+    Ok((wrapper, info))
 }
 
 fn type_is_pymodule(ty: &syn::Type) -> bool {
@@ -212,7 +692,10 @@ fn function_c_wrapper(
     let names: Vec<Ident> = get_arg_names(&spec);
     let cb;
     let slf_module;
-    if pass_module {
+    if spec.asyncness.is_some() {
+        cb = spec.coroutine(None, quote! { #name(#(#names),*) });
+        slf_module = None;
+    } else if pass_module {
         cb = quote! {
             pyo3::callback::convert(_py, #name(_slf, #(#names),*))
         };
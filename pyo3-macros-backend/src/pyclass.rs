@@ -1,6 +1,8 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
-use crate::method::{FnType, SelfType};
+use crate::inspect;
+use crate::method::SelfType;
+use crate::module;
 use crate::pyimpl::PyClassMethodsType;
 use crate::pymethod::{impl_py_getter_def, impl_py_setter_def, PropertyType};
 use crate::utils;
@@ -11,6 +13,14 @@ use syn::parse::{Parse, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::{parse_quote, spanned::Spanned, Expr, Token};
 
+/// The `repr` or `str` option of the pyclass macro
+pub enum PyClassFormat {
+    /// `repr` or `str`: use the `Debug` or `Display` implementation.
+    Trait,
+    /// `repr = "..."` or `str = "..."`: format the `get` fields, e.g. `"Point(x={x}, y={y})"`.
+    FormatString(syn::LitStr),
+}
+
 /// The parsed arguments of the pyclass macro
 pub struct PyClassArgs {
     pub freelist: Option<syn::Expr>,
@@ -19,6 +29,15 @@ pub struct PyClassArgs {
     pub base: syn::TypePath,
     pub has_extends: bool,
     pub has_unsendable: bool,
+    pub has_eq: bool,
+    pub has_ord: bool,
+    pub has_hash: bool,
+    pub has_frozen: bool,
+    pub has_get_all: bool,
+    pub has_set_all: bool,
+    pub has_pickle: bool,
+    pub repr: Option<PyClassFormat>,
+    pub str: Option<PyClassFormat>,
     pub module: Option<syn::LitStr>,
 }
 
@@ -46,6 +65,15 @@ impl Default for PyClassArgs {
             base: parse_quote! { pyo3::PyAny },
             has_extends: false,
             has_unsendable: false,
+            has_eq: false,
+            has_ord: false,
+            has_hash: false,
+            has_frozen: false,
+            has_get_all: false,
+            has_set_all: false,
+            has_pickle: false,
+            repr: None,
+            str: None,
         }
     }
 }
@@ -125,7 +153,21 @@ impl PyClassArgs {
                 }
                 _ => expected!(r#"string literal (e.g., "my_mod")"#),
             },
-            _ => expected!("one of freelist/name/extends/module", left.span()),
+            "repr" => match &**right {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => Self::set_format(&mut self.repr, &key, left, lit)?,
+                _ => expected!(r#"format string (e.g., "Point(x={x})")"#),
+            },
+            "str" => match &**right {
+                syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(lit),
+                    ..
+                }) => Self::set_format(&mut self.str, &key, left, lit)?,
+                _ => expected!(r#"format string (e.g., "Point(x={x})")"#),
+            },
+            _ => expected!("one of freelist/name/extends/module/repr/str", left.span()),
         };
 
         Ok(())
@@ -145,12 +187,41 @@ impl PyClassArgs {
             "unsendable" => {
                 self.has_unsendable = true;
             }
+            "eq" => self.has_eq = true,
+            "ord" => self.has_ord = true,
+            "hash" => self.has_hash = true,
+            "frozen" => self.has_frozen = true,
+            "get_all" => self.has_get_all = true,
+            "set_all" => self.has_set_all = true,
+            "pickle" => self.has_pickle = true,
+            "repr" => {
+                ensure_spanned!(self.repr.is_none(), exp.span() => "`repr` may only be specified once");
+                self.repr = Some(PyClassFormat::Trait);
+            }
+            "str" => {
+                ensure_spanned!(self.str.is_none(), exp.span() => "`str` may only be specified once");
+                self.str = Some(PyClassFormat::Trait);
+            }
             _ => bail_spanned!(
-                exp.path.span() => "expected one of gc/weakref/subclass/dict/unsendable"
+                exp.path.span() => "expected one of gc/weakref/subclass/dict/unsendable/eq/ord/hash/frozen/get_all/set_all/repr/str/pickle"
             ),
         };
         Ok(())
     }
+
+    fn set_format(
+        format: &mut Option<PyClassFormat>,
+        key: &str,
+        left: &syn::Expr,
+        lit: &syn::LitStr,
+    ) -> syn::Result<()> {
+        ensure_spanned!(
+            format.is_none(),
+            left.span() => format!("`{}` may only be specified once", key)
+        );
+        *format = Some(PyClassFormat::FormatString(lit.clone()));
+        Ok(())
+    }
 }
 
 pub fn build_py_class(
@@ -164,53 +235,459 @@ pub fn build_py_class(
     )?;
     let doc = utils::get_doc(&class.attrs, text_signature, true)?;
     let mut descriptors = Vec::new();
+    let mut attributes = Vec::new();
 
     ensure_spanned!(
         class.generics.params.is_empty(),
         class.generics.span() => "#[pyclass] cannot have generic parameters"
     );
+    check_class_args(&class.ident, attr)?;
 
     if let syn::Fields::Named(fields) = &mut class.fields {
         for field in fields.named.iter_mut() {
-            let field_descs = parse_descriptors(field)?;
-            if !field_descs.is_empty() {
-                descriptors.push((field.clone(), field_descs));
+            let options = FieldPyO3Options::take(field, attr)?;
+            if options.get || options.set {
+                ensure_spanned!(
+                    !(options.set && attr.has_frozen),
+                    field.span() => "`#[pyo3(set)]` cannot be used in a `#[pyclass(frozen)]`"
+                );
+                attributes.push(inspect::field_info(field, &options)?);
+                descriptors.push((field.clone(), options));
             }
         }
     } else {
         bail_spanned!(class.fields.span() => "#[pyclass] can only be used with C-style structs");
     }
 
-    impl_class(&class.ident, &attr, doc, descriptors, methods_type)
+    let cls = &class.ident;
+    let mut default_slots = Vec::new();
+    let mut format_consts = Vec::new();
+    if let Some(repr) = &attr.repr {
+        default_slots.push(match repr {
+            PyClassFormat::Trait => format_slot(cls, quote!(Py_tp_repr), quote!(repr_debug)),
+            PyClassFormat::FormatString(format) => {
+                let pieces = parse_format_string(format, &descriptors)?;
+                format_consts.push(quote! {
+                    const REPR: &'static [pyo3::class::impl_::FormatPiece] = &[#(#pieces),*];
+                });
+                format_slot(cls, quote!(Py_tp_repr), quote!(repr_format))
+            }
+        });
+    }
+    if let Some(str) = &attr.str {
+        default_slots.push(match str {
+            PyClassFormat::Trait => format_slot(cls, quote!(Py_tp_str), quote!(str_display)),
+            PyClassFormat::FormatString(format) => {
+                let pieces = parse_format_string(format, &descriptors)?;
+                format_consts.push(quote! {
+                    const STR: &'static [pyo3::class::impl_::FormatPiece] = &[#(#pieces),*];
+                });
+                format_slot(cls, quote!(Py_tp_str), quote!(str_format))
+            }
+        });
+    }
+
+    let module_item = class_module_item(cls, &class.vis);
+    let class = impl_class(
+        cls,
+        &attr,
+        doc,
+        descriptors,
+        attributes,
+        default_slots,
+        Vec::new(),
+        methods_type,
+    )?;
+    let format_impl = if format_consts.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl pyo3::class::impl_::PyClassFormat for #cls {
+                #(#format_consts)*
+            }
+        }
+    };
+    Ok(quote! {
+        #class
+        #format_impl
+        #module_item
+    })
 }
 
-/// Parses `#[pyo3(get, set)]`
-fn parse_descriptors(item: &mut syn::Field) -> syn::Result<Vec<FnType>> {
-    let mut descs = Vec::new();
-    let mut new_attrs = Vec::new();
-    for attr in item.attrs.drain(..) {
-        if let Ok(syn::Meta::List(list)) = attr.parse_meta() {
-            if list.path.is_ident("pyo3") {
-                for meta in list.nested.iter() {
-                    if let syn::NestedMeta::Meta(metaitem) = meta {
-                        if metaitem.path().is_ident("get") {
-                            descs.push(FnType::Getter(SelfType::Receiver { mutable: false }));
-                        } else if metaitem.path().is_ident("set") {
-                            descs.push(FnType::Setter(SelfType::Receiver { mutable: true }));
-                        } else {
-                            bail_spanned!(metaitem.span() => "only get and set are supported");
-                        }
+pub fn build_py_enum(
+    enum_: &mut syn::ItemEnum,
+    attr: &PyClassArgs,
+    methods_type: PyClassMethodsType,
+) -> syn::Result<TokenStream> {
+    let text_signature = utils::parse_text_signature_attrs(
+        &mut enum_.attrs,
+        &get_class_python_name(&enum_.ident, attr),
+    )?;
+    let doc = utils::get_doc(&enum_.attrs, text_signature, true)?;
+
+    ensure_spanned!(
+        enum_.generics.params.is_empty(),
+        enum_.generics.span() => "#[pyclass] cannot have generic parameters"
+    );
+    ensure_spanned!(
+        !attr.has_extends,
+        enum_.ident.span() => "#[pyclass] enums cannot extend other classes"
+    );
+    ensure_spanned!(
+        !enum_.variants.is_empty(),
+        enum_.ident.span() => "#[pyclass] cannot be used on enums without any variants"
+    );
+    ensure_spanned!(
+        !(attr.has_get_all || attr.has_set_all),
+        enum_.ident.span() => "`get_all` and `set_all` cannot be used on #[pyclass] enums, which have no fields"
+    );
+    check_class_args(&enum_.ident, attr)?;
+    for variant in &enum_.variants {
+        match &variant.fields {
+            syn::Fields::Unit => {}
+            fields => {
+                bail_spanned!(fields.span() => "#[pyclass] can only be used with fieldless enums")
+            }
+        }
+    }
+
+    let attributes = enum_
+        .variants
+        .iter()
+        .map(|variant| inspect::variant_info(&enum_.ident, variant))
+        .collect::<syn::Result<_>>()?;
+    let cls = &enum_.ident;
+    let mut default_slots = Vec::new();
+    match &attr.repr {
+        Some(PyClassFormat::Trait) => {
+            default_slots.push(format_slot(cls, quote!(Py_tp_repr), quote!(repr_debug)))
+        }
+        Some(PyClassFormat::FormatString(format)) => {
+            bail_spanned!(format.span() => "#[pyclass] enums have no fields to format, use `repr` instead")
+        }
+        None => default_slots.push(format_slot(cls, quote!(Py_tp_repr), quote!(enum_repr))),
+    }
+    match &attr.str {
+        Some(PyClassFormat::Trait) => {
+            default_slots.push(format_slot(cls, quote!(Py_tp_str), quote!(str_display)))
+        }
+        Some(PyClassFormat::FormatString(format)) => {
+            bail_spanned!(format.span() => "#[pyclass] enums have no fields to format, use `str` instead")
+        }
+        None => {}
+    }
+    // `#[pyclass(eq)]` compares the variants with `PartialEq` instead, and hashes them with
+    // `#[pyclass(hash)]`
+    if !attr.has_eq {
+        default_slots.push(quote! {
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::Py_tp_richcompare,
+                pfunc: pyo3::class::impl_::enum_richcmp::<#cls> as _,
+            }
+        });
+        default_slots.push(quote! {
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::Py_tp_hash,
+                pfunc: pyo3::class::impl_::enum_hash::<#cls> as _,
+            }
+        });
+    }
+    default_slots.push(quote! {
+        pyo3::ffi::PyType_Slot {
+            slot: pyo3::ffi::Py_nb_int,
+            pfunc: pyo3::class::impl_::enum_int::<#cls> as _,
+        }
+    });
+
+    let class = impl_class(
+        cls,
+        &attr,
+        doc,
+        Vec::new(),
+        attributes,
+        default_slots,
+        enum_variant_attributes(cls, &enum_.variants),
+        methods_type,
+    )?;
+    let variants = impl_enum_variants(cls, &enum_.variants);
+    let module_item = class_module_item(cls, &enum_.vis);
+    Ok(quote! {
+        #class
+        #variants
+        #module_item
+    })
+}
+
+/// Allows `#[pymodule] mod` to add the class to a module.
+fn class_module_item(cls: &syn::Ident, vis: &syn::Visibility) -> TokenStream {
+    module::module_item(
+        cls,
+        vis,
+        quote!(module.add_class::<#cls>()),
+        quote!(info.classes.push(<#cls as pyo3::class::impl_::PyClassImpl>::class_info())),
+    )
+}
+
+/// Checks the combination of the `eq`, `ord`, `hash`, `frozen`, `set_all` and `unsendable`
+/// options.
+fn check_class_args(cls: &syn::Ident, attr: &PyClassArgs) -> syn::Result<()> {
+    ensure_spanned!(
+        !(attr.has_frozen && attr.has_unsendable),
+        cls.span() => "a #[pyclass(frozen)] can be read from any thread, so it cannot be `unsendable`"
+    );
+    ensure_spanned!(
+        !(attr.has_frozen && attr.has_set_all),
+        cls.span() => "`set_all` cannot be used in a `#[pyclass(frozen)]`"
+    );
+    ensure_spanned!(
+        attr.has_eq || !attr.has_ord,
+        cls.span() => "the `ord` option of #[pyclass] requires the `eq` option"
+    );
+    ensure_spanned!(
+        attr.has_eq || !attr.has_hash,
+        cls.span() => "the `hash` option of #[pyclass] requires the `eq` option"
+    );
+    ensure_spanned!(
+        attr.has_frozen || !attr.has_hash,
+        cls.span() => "the `hash` option of #[pyclass] requires the `frozen` option, \
+        as mutating a hashed object would corrupt the dicts and sets containing it"
+    );
+    Ok(())
+}
+
+/// The `__repr__` or `__str__` slot of a class, implemented by the function `func` of
+/// `pyo3::class::impl_`.
+fn format_slot(cls: &syn::Ident, slot: TokenStream, func: TokenStream) -> TokenStream {
+    quote! {
+        pyo3::ffi::PyType_Slot {
+            slot: pyo3::ffi::#slot,
+            pfunc: pyo3::class::impl_::#func::<#cls> as _,
+        }
+    }
+}
+
+/// Parses the format string of `repr = "..."` or `str = "..."` into
+/// `pyo3::class::impl_::FormatPiece`s. Each `{name}` must be the Python name of a field exposed
+/// with `get`, and `{{` and `}}` are literal braces.
+fn parse_format_string(
+    format: &syn::LitStr,
+    descriptors: &[(syn::Field, FieldPyO3Options)],
+) -> syn::Result<Vec<TokenStream>> {
+    let value = format.value();
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                text.push('}');
+            }
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => bail_spanned!(format.span() => "unmatched `{` in format string"),
+                    }
+                }
+                let name = name.trim();
+                ensure_spanned!(
+                    descriptors.iter().any(|(field, options)| {
+                        options.get && options.python_name(field) == name
+                    }),
+                    format.span() => format!(
+                        "`{{{}}}` in format string is not a field exposed with `get`",
+                        name
+                    )
+                );
+                if !text.is_empty() {
+                    pieces.push(quote!(pyo3::class::impl_::FormatPiece::Text(#text)));
+                    text.clear();
+                }
+                pieces.push(quote!(pyo3::class::impl_::FormatPiece::Attribute(#name)));
+            }
+            '}' => bail_spanned!(format.span() => "unmatched `}` in format string"),
+            c => text.push(c),
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(quote!(pyo3::class::impl_::FormatPiece::Text(#text)));
+    }
+    Ok(pieces)
+}
+
+/// Implements `PyClassEnum` for a fieldless enum.
+fn impl_enum_variants(
+    cls: &syn::Ident,
+    variants: &Punctuated<syn::Variant, Token![,]>,
+) -> TokenStream {
+    let idents: Vec<_> = variants.iter().map(|variant| &variant.ident).collect();
+    let names: Vec<_> = idents
+        .iter()
+        .map(|ident| ident.unraw().to_string())
+        .collect();
+
+    quote! {
+        impl pyo3::class::impl_::PyClassEnum for #cls {
+            fn variant_name(&self) -> &'static str {
+                match self {
+                    #(#cls::#idents => #names,)*
+                }
+            }
+
+            fn discriminant(&self) -> isize {
+                match self {
+                    #(#cls::#idents => #cls::#idents as isize,)*
+                }
+            }
+        }
+    }
+}
+
+/// The class attributes of the variants of a fieldless enum.
+fn enum_variant_attributes(
+    cls: &syn::Ident,
+    variants: &Punctuated<syn::Variant, Token![,]>,
+) -> Vec<TokenStream> {
+    variants
+        .iter()
+        .map(|variant| {
+            let ident = &variant.ident;
+            let name = ident.unraw().to_string();
+            quote! {
+            pyo3::class::PyMethodDefType::ClassAttribute({
+                fn __wrap(py: pyo3::Python<'_>) -> pyo3::PyObject {
+                    pyo3::IntoPy::into_py(#cls::#ident, py)
+                }
+
+                pyo3::class::PyClassAttributeDef::new(
+                    concat!(#name, "\0"),
+                    pyo3::class::methods::PyClassAttributeFactory(__wrap)
+                )
+            })
+            }
+        })
+        .collect()
+}
+
+/// The options of `#[pyo3(...)]` on a field of a `#[pyclass]` struct, combined with the
+/// `get_all` and `set_all` options of the class.
+#[derive(Default)]
+pub struct FieldPyO3Options {
+    /// Whether the field is readable from Python.
+    pub get: bool,
+    /// Whether the field is writable from Python.
+    pub set: bool,
+    /// The Python name of the field, if different from the Rust name.
+    pub name: Option<syn::Ident>,
+    /// `get_with = "func"`: converts `&FieldType` to the value returned to Python.
+    pub get_with: Option<syn::ExprPath>,
+    /// `set_with = "func"`: converts the `&PyAny` given by Python to a `FieldType`.
+    pub set_with: Option<syn::ExprPath>,
+}
+
+impl FieldPyO3Options {
+    /// Parses and removes the `#[pyo3(get, set, name = "...", get_with = "...", set_with = "...")]`
+    /// attributes of a field.
+    fn take(field: &mut syn::Field, attr: &PyClassArgs) -> syn::Result<Self> {
+        let mut options = FieldPyO3Options {
+            get: attr.has_get_all,
+            set: attr.has_set_all,
+            ..Default::default()
+        };
+        let mut new_attrs = Vec::new();
+        for field_attr in field.attrs.drain(..) {
+            if !field_attr.path.is_ident("pyo3") {
+                new_attrs.push(field_attr);
+                continue;
+            }
+            let list = match field_attr.parse_meta()? {
+                syn::Meta::List(list) => list,
+                meta => bail_spanned!(meta.span() => "expected `#[pyo3(...)]`"),
+            };
+            for meta in list.nested.iter() {
+                match meta {
+                    syn::NestedMeta::Meta(meta) => options.add_meta(meta, attr)?,
+                    syn::NestedMeta::Lit(lit) => {
+                        bail_spanned!(lit.span() => "expected one of get/set/name/get_with/set_with")
                     }
                 }
-            } else {
-                new_attrs.push(attr)
             }
+        }
+        field.attrs = new_attrs;
+
+        ensure_spanned!(
+            options.get || options.set || options.name.is_none(),
+            field.span() => "`name` requires the field to be exposed with `get` or `set`"
+        );
+        Ok(options)
+    }
+
+    fn add_meta(&mut self, meta: &syn::Meta, attr: &PyClassArgs) -> syn::Result<()> {
+        let path = meta.path();
+        if path.is_ident("get") {
+            ensure_spanned!(
+                !attr.has_get_all,
+                path.span() => "`get` is redundant with `#[pyclass(get_all)]`"
+            );
+            self.get = true;
+        } else if path.is_ident("set") {
+            ensure_spanned!(
+                !attr.has_set_all,
+                path.span() => "`set` is redundant with `#[pyclass(set_all)]`"
+            );
+            self.set = true;
+        } else if path.is_ident("name") {
+            ensure_spanned!(self.name.is_none(), path.span() => "`name` may only be specified once");
+            let lit = get_str_value(meta, "`name = \"python_name\"`")?;
+            self.name = Some(lit.parse_with(syn::Ident::parse_any).map_err(
+                |_| err_spanned!(lit.span() => "expected a single identifier in double-quotes"),
+            )?);
+        } else if path.is_ident("get_with") {
+            ensure_spanned!(
+                self.get_with.is_none(),
+                path.span() => "`get_with` may only be specified once"
+            );
+            let lit = get_str_value(meta, "`get_with = \"func\"`")?;
+            self.get_with = Some(lit.parse()?);
+            self.get = true;
+        } else if path.is_ident("set_with") {
+            ensure_spanned!(
+                self.set_with.is_none(),
+                path.span() => "`set_with` may only be specified once"
+            );
+            let lit = get_str_value(meta, "`set_with = \"func\"`")?;
+            self.set_with = Some(lit.parse()?);
+            self.set = true;
         } else {
-            new_attrs.push(attr);
+            bail_spanned!(meta.span() => "expected one of get/set/name/get_with/set_with");
         }
+        Ok(())
+    }
+
+    /// The name of the field in Python.
+    pub fn python_name(&self, field: &syn::Field) -> syn::Ident {
+        self.name
+            .clone()
+            .unwrap_or_else(|| field.ident.as_ref().unwrap().unraw())
+    }
+}
+
+/// Gets the string literal of a `key = "value"` option.
+fn get_str_value(meta: &syn::Meta, expected: &str) -> syn::Result<syn::LitStr> {
+    match meta {
+        syn::Meta::NameValue(syn::MetaNameValue {
+            lit: syn::Lit::Str(lit),
+            ..
+        }) => Ok(lit.clone()),
+        _ => bail_spanned!(meta.span() => format!("expected {}", expected)),
     }
-    item.attrs = new_attrs;
-    Ok(descs)
 }
 
 /// To allow multiple #[pymethods] block, we define inventory types.
@@ -223,14 +700,26 @@ fn impl_methods_inventory(cls: &syn::Ident) -> TokenStream {
         #[doc(hidden)]
         pub struct #inventory_cls {
             methods: Vec<pyo3::class::PyMethodDefType>,
+            slots: fn() -> &'static [pyo3::ffi::PyType_Slot],
+            info: fn() -> Vec<pyo3::inspect::FunctionInfo>,
         }
         impl pyo3::class::impl_::PyMethodsInventory for #inventory_cls {
-            fn new(methods: Vec<pyo3::class::PyMethodDefType>) -> Self {
-                Self { methods }
+            fn new(
+                methods: Vec<pyo3::class::PyMethodDefType>,
+                slots: fn() -> &'static [pyo3::ffi::PyType_Slot],
+                info: fn() -> Vec<pyo3::inspect::FunctionInfo>,
+            ) -> Self {
+                Self { methods, slots, info }
             }
             fn get(&'static self) -> &'static [pyo3::class::PyMethodDefType] {
                 &self.methods
             }
+            fn slots(&'static self) -> &'static [pyo3::ffi::PyType_Slot] {
+                (self.slots)()
+            }
+            fn info(&'static self) -> Vec<pyo3::inspect::FunctionInfo> {
+                (self.info)()
+            }
         }
 
         impl pyo3::class::impl_::HasMethodsInventory for #cls {
@@ -245,15 +734,116 @@ fn get_class_python_name<'a>(cls: &'a syn::Ident, attr: &'a PyClassArgs) -> &'a
     attr.name.as_ref().unwrap_or(cls)
 }
 
+#[allow(clippy::too_many_arguments)]
 fn impl_class(
     cls: &syn::Ident,
     attr: &PyClassArgs,
     doc: syn::LitStr,
-    descriptors: Vec<(syn::Field, Vec<FnType>)>,
+    descriptors: Vec<(syn::Field, FieldPyO3Options)>,
+    attributes: Vec<TokenStream>,
+    mut default_slots: Vec<TokenStream>,
+    mut default_methods: Vec<TokenStream>,
     methods_type: PyClassMethodsType,
 ) -> syn::Result<TokenStream> {
     let cls_name = get_class_python_name(cls, attr).to_string();
 
+    if attr.has_ord {
+        default_slots.push(quote! {
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::Py_tp_richcompare,
+                pfunc: pyo3::class::impl_::richcmp_partial_ord::<#cls> as _,
+            }
+        });
+    } else if attr.has_eq {
+        default_slots.push(quote! {
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::Py_tp_richcompare,
+                pfunc: pyo3::class::impl_::richcmp_partial_eq::<#cls> as _,
+            }
+        });
+    }
+    if attr.has_hash {
+        default_slots.push(quote! {
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::Py_tp_hash,
+                pfunc: pyo3::class::impl_::hash::<#cls> as _,
+            }
+        });
+    }
+    let default_slots = if default_slots.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl pyo3::class::impl_::PyClassDefaultSlots<#cls>
+                for pyo3::class::impl_::PyClassImplCollector<#cls>
+            {
+                fn py_class_default_slots(self) -> &'static [pyo3::ffi::PyType_Slot] {
+                    &[#(#default_slots),*]
+                }
+            }
+        }
+    };
+
+    let pickle = if attr.has_pickle {
+        default_methods.push(quote! {
+            pyo3::class::PyMethodDefType::Method(
+                pyo3::class::PyMethodDef::noargs(
+                    "__reduce__\0",
+                    pyo3::class::methods::PyCFunction(pyo3::serde::pickle_reduce::<#cls>),
+                    "\0"
+                )
+            )
+        });
+        default_methods.push(quote! {
+            pyo3::class::PyMethodDefType::Class(
+                pyo3::class::PyMethodDef::cfunction_with_keywords(
+                    "__pyo3_unpickle__\0",
+                    pyo3::class::methods::PyCFunctionWithKeywords(
+                        pyo3::serde::pickle_unpickle::<#cls>
+                    ),
+                    "\0"
+                ).flags(pyo3::ffi::METH_CLASS)
+            )
+        });
+        let (dump_state, load_state) = if attr.has_extends {
+            (quote!(dump_subclass_state), quote!(load_subclass_state))
+        } else {
+            (quote!(dump_state), quote!(load_state))
+        };
+        quote! {
+            impl pyo3::serde::PyClassPickle for #cls {
+                fn dump_state(
+                    slf: pyo3::PyRef<Self>,
+                    state: &mut Vec<u8>
+                ) -> pyo3::PyResult<()> {
+                    pyo3::serde::#dump_state(slf, state)
+                }
+                fn load_state(
+                    state: &mut &[u8]
+                ) -> pyo3::PyResult<pyo3::PyClassInitializer<Self>> {
+                    pyo3::serde::#load_state(state)
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+    let default_methods = if default_methods.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            impl pyo3::class::impl_::PyClassDefaultMethods<#cls>
+                for pyo3::class::impl_::PyClassImplCollector<#cls>
+            {
+                fn py_class_default_methods(self) -> &'static [pyo3::class::methods::PyMethodDefType] {
+                    static METHODS: &[pyo3::class::methods::PyMethodDefType] =
+                        &[#(#default_methods),*];
+                    METHODS
+                }
+            }
+        }
+    };
+
     let extra = {
         if let Some(freelist) = &attr.freelist {
             quote! {
@@ -342,8 +932,13 @@ fn impl_class(
         quote! {}
     };
 
-    let (impl_inventory, iter_py_methods) = match methods_type {
-        PyClassMethodsType::Specialization => (None, quote! { collector.py_methods().iter() }),
+    let (impl_inventory, iter_py_methods, iter_proto_slots, py_methods_info) = match methods_type {
+        PyClassMethodsType::Specialization => (
+            None,
+            quote! { collector.py_methods().iter() },
+            quote! { collector.methods_protocol_slots().iter() },
+            quote! { collector.py_methods_info() },
+        ),
         PyClassMethodsType::Inventory => (
             Some(impl_methods_inventory(&cls)),
             quote! {
@@ -351,6 +946,17 @@ fn impl_class(
                     .into_iter()
                     .flat_map(pyo3::class::impl_::PyMethodsInventory::get)
             },
+            quote! {
+                pyo3::inventory::iter::<<Self as pyo3::class::impl_::HasMethodsInventory>::Methods>
+                    .into_iter()
+                    .flat_map(pyo3::class::impl_::PyMethodsInventory::slots)
+            },
+            quote! {
+                pyo3::inventory::iter::<<Self as pyo3::class::impl_::HasMethodsInventory>::Methods>
+                    .into_iter()
+                    .flat_map(pyo3::class::impl_::PyMethodsInventory::info)
+                    .collect()
+            },
         ),
     };
 
@@ -361,10 +967,73 @@ fn impl_class(
     } else {
         quote! { 0 }
     };
-    let base_layout = if attr.has_extends {
-        quote! { <Self::BaseType as pyo3::derive_utils::PyBaseTypeUtils>::LayoutAsBase }
+    let base_layout = match (attr.has_extends, attr.has_frozen) {
+        (true, false) => {
+            quote! { <Self::BaseType as pyo3::derive_utils::PyBaseTypeUtils>::LayoutAsBase }
+        }
+        (true, true) => {
+            quote! { <Self::BaseType as pyo3::derive_utils::PyBaseTypeUtils>::FrozenLayoutAsBase }
+        }
+        (false, false) => quote! { pyo3::pycell::PyCellBase<pyo3::PyAny> },
+        (false, true) => quote! { pyo3::pycell::PyFrozenCellBase<pyo3::PyAny> },
+    };
+    let frozen = if attr.has_frozen {
+        quote! { pyo3::pyclass::boolean_struct::True }
     } else {
-        quote! { pyo3::pycell::PyCellBase<pyo3::PyAny> }
+        quote! { pyo3::pyclass::boolean_struct::False }
+    };
+    // Enforce at compile time that a class and its #[pyclass] base are either both frozen or both
+    // mutable, as they share the borrow flag
+    let base_layout_assertion = if attr.has_extends {
+        let assertion_name = format!("__assert_base_layout_{}", cls);
+        let assertion_ident = syn::Ident::new(&assertion_name, Span::call_site());
+        let assert_base = if attr.has_frozen {
+            quote! { pyo3::class::impl_::assert_frozen_base::<#cls>() }
+        } else {
+            quote! { pyo3::class::impl_::assert_mutable_base::<#cls>() }
+        };
+        quote! {
+            fn #assertion_ident() {
+                #assert_base
+            }
+        }
+    } else {
+        quote! {}
+    };
+    // Frozen classes cannot be mutably borrowed
+    let extract_mut = if attr.has_frozen {
+        quote! {}
+    } else {
+        quote! {
+            impl<'a> pyo3::derive_utils::ExtractExt<'a> for &'a mut #cls
+            {
+                type Target = pyo3::PyRefMut<'a, #cls>;
+            }
+        }
+    };
+    let base_info = if attr.has_extends {
+        quote! {
+            Some(<<&'_ #base as pyo3::derive_utils::ExtractExt<'_>>::Target
+                as pyo3::FromPyObject<'_>>::type_input())
+        }
+    } else {
+        quote! { None }
+    };
+    let class_info = if inspect::enabled() {
+        Some(quote! {
+            fn class_info() -> pyo3::inspect::ClassInfo {
+                use pyo3::class::impl_::*;
+                let collector = PyClassImplCollector::<Self>::new();
+                pyo3::inspect::ClassInfo {
+                    base: #base_info,
+                    attributes: vec![#(#attributes),*],
+                    methods: #py_methods_info,
+                    ..pyo3::inspect::ClassInfo::new::<Self>()
+                }
+            }
+        })
+    } else {
+        None
     };
     let base_nativetype = if attr.has_extends {
         quote! { <Self::BaseType as pyo3::derive_utils::PyBaseTypeUtils>::BaseNativeType }
@@ -374,11 +1043,22 @@ fn impl_class(
 
     // If #cls is not extended type, we allow Self->PyObject conversion
     let into_pyobject = if !attr.has_extends {
+        let type_output = if inspect::enabled() {
+            Some(quote! {
+                fn type_output() -> pyo3::inspect::TypeInfo {
+                    pyo3::inspect::TypeInfo::class::<Self>()
+                }
+            })
+        } else {
+            None
+        };
         quote! {
             impl pyo3::IntoPy<pyo3::PyObject> for #cls {
                 fn into_py(self, py: pyo3::Python) -> pyo3::PyObject {
                     pyo3::IntoPy::into_py(pyo3::Py::new(py, self).unwrap(), py)
                 }
+
+                #type_output
             }
         }
     } else {
@@ -421,6 +1101,7 @@ fn impl_class(
             type Dict = #dict;
             type WeakRef = #weakref;
             type BaseNativeType = #base_nativetype;
+            type Frozen = #frozen;
         }
 
         impl<'a> pyo3::derive_utils::ExtractExt<'a> for &'a #cls
@@ -428,10 +1109,7 @@ fn impl_class(
             type Target = pyo3::PyRef<'a, #cls>;
         }
 
-        impl<'a> pyo3::derive_utils::ExtractExt<'a> for &'a mut #cls
-        {
-            type Target = pyo3::PyRefMut<'a, #cls>;
-        }
+        #extract_mut
 
         #into_pyobject
 
@@ -444,6 +1122,7 @@ fn impl_class(
                 use pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<Self>::new();
                 #iter_py_methods
+                    .chain(collector.py_class_default_methods())
                     .chain(collector.py_class_descriptors())
                     .chain(collector.object_protocol_methods())
                     .chain(collector.async_protocol_methods())
@@ -458,6 +1137,11 @@ fn impl_class(
                 let collector = PyClassImplCollector::<Self>::new();
                 collector.new_impl()
             }
+            fn get_new_text_signature() -> Option<&'static str> {
+                use pyo3::class::impl_::*;
+                let collector = PyClassImplCollector::<Self>::new();
+                collector.new_text_signature()
+            }
             fn get_call() -> Option<pyo3::ffi::PyCFunctionWithKeywords> {
                 use pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<Self>::new();
@@ -468,8 +1152,10 @@ fn impl_class(
                 // Implementation which uses dtolnay specialization to load all slots.
                 use pyo3::class::impl_::*;
                 let collector = PyClassImplCollector::<Self>::new();
-                collector.object_protocol_slots()
+                collector.py_class_default_slots()
                     .iter()
+                    .chain(#iter_proto_slots)
+                    .chain(collector.object_protocol_slots())
                     .chain(collector.number_protocol_slots())
                     .chain(collector.iter_protocol_slots())
                     .chain(collector.gc_protocol_slots())
@@ -486,40 +1172,62 @@ fn impl_class(
                 let collector = PyClassImplCollector::<Self>::new();
                 collector.buffer_procs()
             }
+
+            #class_info
         }
 
+        #default_slots
+
+        #default_methods
+
+        #pickle
+
         #extra
 
         #gc_impl
+
+        #base_layout_assertion
     })
 }
 
 fn impl_descriptors(
     cls: &syn::Type,
-    descriptors: Vec<(syn::Field, Vec<FnType>)>,
+    descriptors: Vec<(syn::Field, FieldPyO3Options)>,
 ) -> syn::Result<TokenStream> {
-    let py_methods: Vec<TokenStream> = descriptors
-        .iter()
-        .flat_map(|(field, fns)| {
-            fns.iter()
-                .map(|desc| {
-                    let name = field.ident.as_ref().unwrap().unraw();
-                    let doc = utils::get_doc(&field.attrs, None, true)
-                        .unwrap_or_else(|_| syn::LitStr::new(&name.to_string(), name.span()));
-                    let property_type = PropertyType::Descriptor(&field);
-                    match desc {
-                        FnType::Getter(self_ty) => {
-                            impl_py_getter_def(cls, property_type, self_ty, &name, &doc)
-                        }
-                        FnType::Setter(self_ty) => {
-                            impl_py_setter_def(cls, property_type, self_ty, &name, &doc)
-                        }
-                        _ => unreachable!(),
-                    }
-                })
-                .collect::<Vec<syn::Result<TokenStream>>>()
-        })
-        .collect::<syn::Result<_>>()?;
+    let mut py_methods = Vec::new();
+    for (field, options) in &descriptors {
+        let name = options.python_name(field);
+        let doc = utils::get_doc(&field.attrs, None, true)
+            .unwrap_or_else(|_| syn::LitStr::new(&name.to_string(), name.span()));
+        if options.get {
+            let property_type = PropertyType::Descriptor {
+                field,
+                with: options.get_with.as_ref(),
+            };
+            let self_ty = SelfType::Receiver { mutable: false };
+            py_methods.push(impl_py_getter_def(
+                cls,
+                property_type,
+                &self_ty,
+                &name,
+                &doc,
+            )?);
+        }
+        if options.set {
+            let property_type = PropertyType::Descriptor {
+                field,
+                with: options.set_with.as_ref(),
+            };
+            let self_ty = SelfType::Receiver { mutable: true };
+            py_methods.push(impl_py_setter_def(
+                cls,
+                property_type,
+                &self_ty,
+                &name,
+                &doc,
+            )?);
+        }
+    }
 
     Ok(quote! {
         impl pyo3::class::impl_::PyClassDescriptors<#cls>
@@ -1,21 +1,24 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::attrs::FromPyWithAttribute;
+use crate::method::FnArg;
 use crate::module::add_fn_to_module;
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
+use quote::ToTokens;
 use syn::ext::IdentExt;
-use syn::parse::ParseBuffer;
+use syn::parse::{Parse, ParseBuffer, ParseStream};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
 use syn::{NestedMeta, Path};
 
 #[derive(Debug, Clone, PartialEq)]
 pub enum Argument {
+    PosOnlyArgsSeparator,
     VarArgsSeparator,
     VarArgs(syn::Path),
     KeywordArgs(syn::Path),
-    Arg(syn::Path, Option<String>),
-    Kwarg(syn::Path, Option<String>),
+    Arg(syn::Path, Option<syn::Expr>),
+    Kwarg(syn::Path, Option<syn::Expr>),
 }
 
 /// The attributes of the pyfunction macro
@@ -23,11 +26,28 @@ pub enum Argument {
 pub struct PyFunctionAttr {
     pub arguments: Vec<Argument>,
     has_kw: bool,
+    has_posonly_separator: bool,
     has_varargs: bool,
     has_kwargs: bool,
     pub pass_module: bool,
 }
 
+/// The `signature = (...)` option of `#[pyo3(...)]`, e.g.
+/// `#[pyo3(signature = (a, /, b = 1, *args, c, **kwargs))]`.
+#[derive(Debug, Clone)]
+pub struct PyFunctionSignature {
+    pub arguments: Vec<Argument>,
+    span: Span,
+}
+
+/// The options which can be passed in `#[pyo3(...)]` on a function or method.
+#[derive(Default)]
+pub struct PyFunctionOptions {
+    pub signature: Option<PyFunctionSignature>,
+    /// The span of `text_signature = None`, which disables the generated `__text_signature__`.
+    pub text_signature_disabled: Option<Span>,
+}
+
 #[derive(Clone, PartialEq, Debug)]
 pub struct PyFunctionArgAttrs {
     pub from_py_with: Option<FromPyWithAttribute>,
@@ -55,7 +75,7 @@ impl PyFunctionAttr {
             NestedMeta::Meta(syn::Meta::Path(ident)) if ident.is_ident("pass_module") => {
                 self.pass_module = true;
             }
-            NestedMeta::Meta(syn::Meta::Path(ident)) => self.add_work(item, ident)?,
+            NestedMeta::Meta(syn::Meta::Path(ident)) => self.add_work(item.span(), ident)?,
             NestedMeta::Meta(syn::Meta::NameValue(nv)) => {
                 self.add_name_value(item, nv)?;
             }
@@ -73,19 +93,17 @@ impl PyFunctionAttr {
         match lit {
             syn::Lit::Str(lits) if lits.value() == "*" => {
                 // "*"
-                self.vararg_is_ok(item)?;
-                self.has_varargs = true;
-                self.arguments.push(Argument::VarArgsSeparator);
-                Ok(())
+                self.add_varargs_separator(item.span())
             }
             _ => bail_spanned!(item.span() => "expected \"*\""),
         }
     }
 
-    fn add_work(&mut self, item: &NestedMeta, path: &Path) -> syn::Result<()> {
+    fn add_work(&mut self, span: Span, path: &Path) -> syn::Result<()> {
+        // Keyword-only arguments without a default may follow ones with a default.
         ensure_spanned!(
-            !(self.has_kw || self.has_kwargs),
-            item.span() => "positional argument or varargs(*) not allowed after keyword arguments"
+            !((self.has_kw && !self.has_varargs) || self.has_kwargs),
+            span => "positional argument or varargs(*) not allowed after keyword arguments"
         );
         if self.has_varargs {
             self.arguments.push(Argument::Kwarg(path.clone(), None));
@@ -95,29 +113,59 @@ impl PyFunctionAttr {
         Ok(())
     }
 
-    fn vararg_is_ok(&self, item: &NestedMeta) -> syn::Result<()> {
+    fn add_posonly_separator(&mut self, span: Span) -> syn::Result<()> {
+        ensure_spanned!(
+            !(self.has_posonly_separator || self.has_varargs || self.has_kwargs),
+            span => "/ is not allowed after /, varargs(*) or kwargs(**)"
+        );
+        ensure_spanned!(
+            !self.arguments.is_empty(),
+            span => "/ must be preceded by at least one positional argument"
+        );
+        self.has_posonly_separator = true;
+        self.arguments.push(Argument::PosOnlyArgsSeparator);
+        Ok(())
+    }
+
+    fn add_varargs_separator(&mut self, span: Span) -> syn::Result<()> {
+        self.vararg_is_ok(span)?;
+        self.has_varargs = true;
+        self.arguments.push(Argument::VarArgsSeparator);
+        Ok(())
+    }
+
+    fn add_varargs(&mut self, span: Span, path: &Path) -> syn::Result<()> {
+        self.vararg_is_ok(span)?;
+        self.has_varargs = true;
+        self.arguments.push(Argument::VarArgs(path.clone()));
+        Ok(())
+    }
+
+    fn add_kwargs(&mut self, span: Span, path: &Path) -> syn::Result<()> {
+        self.kw_arg_is_ok(span)?;
+        self.has_kwargs = true;
+        self.arguments.push(Argument::KeywordArgs(path.clone()));
+        Ok(())
+    }
+
+    fn vararg_is_ok(&self, span: Span) -> syn::Result<()> {
         ensure_spanned!(
             !(self.has_kwargs || self.has_varargs),
-            item.span() => "* is not allowed after varargs(*) or kwargs(**)"
+            span => "* is not allowed after varargs(*) or kwargs(**)"
         );
         Ok(())
     }
 
-    fn kw_arg_is_ok(&self, item: &NestedMeta) -> syn::Result<()> {
+    fn kw_arg_is_ok(&self, span: Span) -> syn::Result<()> {
         ensure_spanned!(
             !self.has_kwargs,
-            item.span() => "keyword argument or kwargs(**) is not allowed after kwargs(**)"
+            span => "keyword argument or kwargs(**) is not allowed after kwargs(**)"
         );
         Ok(())
     }
 
-    fn add_nv_common(
-        &mut self,
-        item: &NestedMeta,
-        name: &syn::Path,
-        value: String,
-    ) -> syn::Result<()> {
-        self.kw_arg_is_ok(item)?;
+    fn add_nv_common(&mut self, span: Span, name: &syn::Path, value: syn::Expr) -> syn::Result<()> {
+        self.kw_arg_is_ok(span)?;
         if self.has_varargs {
             // kw only
             self.arguments
@@ -135,23 +183,20 @@ impl PyFunctionAttr {
             syn::Lit::Str(litstr) => {
                 if litstr.value() == "*" {
                     // args="*"
-                    self.vararg_is_ok(item)?;
-                    self.has_varargs = true;
-                    self.arguments.push(Argument::VarArgs(nv.path.clone()));
+                    self.add_varargs(item.span(), &nv.path)?;
                 } else if litstr.value() == "**" {
                     // kwargs="**"
-                    self.kw_arg_is_ok(item)?;
-                    self.has_kwargs = true;
-                    self.arguments.push(Argument::KeywordArgs(nv.path.clone()));
+                    self.add_kwargs(item.span(), &nv.path)?;
                 } else {
-                    self.add_nv_common(item, &nv.path, litstr.value())?;
+                    self.add_nv_common(item.span(), &nv.path, litstr.parse()?)?;
                 }
             }
-            syn::Lit::Int(litint) => {
-                self.add_nv_common(item, &nv.path, format!("{}", litint))?;
-            }
-            syn::Lit::Bool(litb) => {
-                self.add_nv_common(item, &nv.path, format!("{}", litb.value))?;
+            syn::Lit::Int(_) | syn::Lit::Bool(_) => {
+                let value = syn::Expr::Lit(syn::ExprLit {
+                    attrs: Vec::new(),
+                    lit: nv.lit.clone(),
+                });
+                self.add_nv_common(item.span(), &nv.path, value)?;
             }
             _ => bail_spanned!(nv.lit.span() => "expected a string literal"),
         };
@@ -159,6 +204,163 @@ impl PyFunctionAttr {
     }
 }
 
+impl Parse for PyFunctionSignature {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let content;
+        let paren = syn::parenthesized!(content in input);
+
+        // Reuse the checks of `#[args(...)]` on the order of the entries.
+        let mut attr = PyFunctionAttr::default();
+        while !content.is_empty() {
+            if content.peek(syn::Token![/]) {
+                let slash: syn::Token![/] = content.parse()?;
+                attr.add_posonly_separator(slash.span())?;
+            } else if content.peek(syn::Token![*]) && content.peek2(syn::Token![*]) {
+                // **kwargs
+                content.parse::<syn::Token![*]>()?;
+                content.parse::<syn::Token![*]>()?;
+                let name: syn::Ident = content.parse()?;
+                attr.add_kwargs(name.span(), &name.into())?;
+            } else if content.peek(syn::Token![*]) {
+                // *args or a bare *
+                let star: syn::Token![*] = content.parse()?;
+                if content.peek(syn::Ident) {
+                    let name: syn::Ident = content.parse()?;
+                    attr.add_varargs(name.span(), &name.into())?;
+                } else {
+                    attr.add_varargs_separator(star.span())?;
+                }
+            } else {
+                let name: syn::Ident = content.parse()?;
+                if content.peek(syn::Token![=]) {
+                    content.parse::<syn::Token![=]>()?;
+                    let value: syn::Expr = content.parse()?;
+                    attr.add_nv_common(name.span(), &name.into(), value)?;
+                } else {
+                    attr.add_work(name.span(), &name.into())?;
+                }
+            }
+
+            if content.is_empty() {
+                break;
+            }
+            content.parse::<syn::Token![,]>()?;
+        }
+
+        Ok(PyFunctionSignature {
+            arguments: attr.arguments,
+            span: paren.span,
+        })
+    }
+}
+
+impl PyFunctionSignature {
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Checks that the signature names every Python-visible parameter of the function exactly
+    /// once, in the same order as the Rust function.
+    pub fn check_arguments(&self, args: &[FnArg<'_>]) -> syn::Result<()> {
+        let mut entries = self.arguments.iter().filter_map(|arg| match arg {
+            Argument::Arg(path, _)
+            | Argument::Kwarg(path, _)
+            | Argument::VarArgs(path)
+            | Argument::KeywordArgs(path) => Some(path),
+            Argument::PosOnlyArgsSeparator | Argument::VarArgsSeparator => None,
+        });
+
+        for arg in args.iter().filter(|arg| !arg.py) {
+            match entries.next() {
+                Some(path) => ensure_spanned!(
+                    path.is_ident(arg.name),
+                    path.span() => format!(
+                        "expected argument from function definition `{}` but got argument `{}`",
+                        arg.name.unraw(),
+                        path.to_token_stream()
+                    )
+                ),
+                None => bail_spanned!(
+                    self.span => format!("missing signature entry for argument `{}`", arg.name.unraw())
+                ),
+            }
+        }
+
+        if let Some(path) = entries.next() {
+            bail_spanned!(
+                path.span() => "signature entry does not have a corresponding function argument"
+            );
+        }
+        Ok(())
+    }
+}
+
+impl PyFunctionOptions {
+    /// Parses and removes the `#[pyo3(...)]` attributes of a function or method.
+    pub fn from_attrs(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Self> {
+        let mut options = PyFunctionOptions::default();
+        let mut new_attrs = Vec::new();
+
+        for attr in attrs.drain(..) {
+            if attr.path.is_ident("pyo3") {
+                let items = attr.parse_args_with(
+                    Punctuated::<PyFunctionOption, syn::Token![,]>::parse_terminated,
+                )?;
+                for item in items {
+                    match item {
+                        PyFunctionOption::Signature(signature) => {
+                            ensure_spanned!(
+                                options.signature.is_none(),
+                                signature.span => "`signature` may only be specified once"
+                            );
+                            options.signature = Some(signature);
+                        }
+                        PyFunctionOption::TextSignatureDisabled(span) => {
+                            ensure_spanned!(
+                                options.text_signature_disabled.is_none(),
+                                span => "`text_signature` may only be specified once"
+                            );
+                            options.text_signature_disabled = Some(span);
+                        }
+                    }
+                }
+            } else {
+                new_attrs.push(attr);
+            }
+        }
+        *attrs = new_attrs;
+
+        Ok(options)
+    }
+}
+
+enum PyFunctionOption {
+    Signature(PyFunctionSignature),
+    TextSignatureDisabled(Span),
+}
+
+impl Parse for PyFunctionOption {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: syn::Ident = input.parse()?;
+        if name == "signature" {
+            input.parse::<syn::Token![=]>()?;
+            Ok(PyFunctionOption::Signature(input.parse()?))
+        } else if name == "text_signature" {
+            input.parse::<syn::Token![=]>()?;
+            match input.parse()? {
+                syn::Expr::Path(value) if value.path.is_ident("None") => {
+                    Ok(PyFunctionOption::TextSignatureDisabled(name.span()))
+                }
+                value => bail_spanned!(
+                    value.span() => "expected `None`; use `#[text_signature = \"(...)\"]` to give a text signature"
+                ),
+            }
+        } else {
+            bail_spanned!(name.span() => "expected `signature` or `text_signature`")
+        }
+    }
+}
+
 pub fn parse_name_attribute(attrs: &mut Vec<syn::Attribute>) -> syn::Result<Option<syn::Ident>> {
     let mut name_attrs = Vec::new();
 
@@ -238,7 +440,7 @@ impl PyFunctionArgAttrs {
 
 #[cfg(test)]
 mod test {
-    use super::{Argument, PyFunctionAttr};
+    use super::{Argument, PyFunctionAttr, PyFunctionSignature};
     use proc_macro2::TokenStream;
     use quote::quote;
     use syn::parse_quote;
@@ -248,6 +450,11 @@ mod test {
         Ok(py_fn_attr.arguments)
     }
 
+    fn signature(input: TokenStream) -> syn::Result<Vec<Argument>> {
+        let signature: PyFunctionSignature = syn::parse2(input)?;
+        Ok(signature.arguments)
+    }
+
     #[test]
     fn test_errs() {
         assert!(items(quote! {test="1", test2}).is_err());
@@ -263,7 +470,7 @@ mod test {
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
                 Argument::Arg(parse_quote! {test2}, None),
-                Argument::Arg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test3}, Some(parse_quote! {None})),
             ]
         );
     }
@@ -274,9 +481,9 @@ mod test {
         assert!(
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
-                Argument::Arg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test2}, Some(parse_quote! {None})),
                 Argument::VarArgsSeparator,
-                Argument::Kwarg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test3}, Some(parse_quote! {None})),
             ]
         );
 
@@ -294,7 +501,7 @@ mod test {
             args == vec![
                 Argument::VarArgsSeparator,
                 Argument::Kwarg(parse_quote! {test1}, None),
-                Argument::Kwarg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test2}, Some(parse_quote! {None})),
             ]
         );
 
@@ -302,7 +509,7 @@ mod test {
         assert!(
             args == vec![
                 Argument::VarArgsSeparator,
-                Argument::Kwarg(parse_quote! {test1}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test1}, Some(parse_quote! {None})),
                 Argument::Kwarg(parse_quote! {test2}, None),
             ]
         );
@@ -315,11 +522,47 @@ mod test {
         assert!(
             args == vec![
                 Argument::Arg(parse_quote! {test1}, None),
-                Argument::Arg(parse_quote! {test2}, Some("None".to_owned())),
+                Argument::Arg(parse_quote! {test2}, Some(parse_quote! {None})),
                 Argument::VarArgs(parse_quote! {args}),
-                Argument::Kwarg(parse_quote! {test3}, Some("None".to_owned())),
+                Argument::Kwarg(parse_quote! {test3}, Some(parse_quote! {None})),
                 Argument::KeywordArgs(parse_quote! {kwargs}),
             ]
         );
     }
+
+    #[test]
+    fn test_signature() {
+        let args = signature(quote! {(a, /, b = vec![1], *args, c, d = None, **kwargs)}).unwrap();
+        assert!(
+            args == vec![
+                Argument::Arg(parse_quote! {a}, None),
+                Argument::PosOnlyArgsSeparator,
+                Argument::Arg(parse_quote! {b}, Some(parse_quote! {vec![1]})),
+                Argument::VarArgs(parse_quote! {args}),
+                Argument::Kwarg(parse_quote! {c}, None),
+                Argument::Kwarg(parse_quote! {d}, Some(parse_quote! {None})),
+                Argument::KeywordArgs(parse_quote! {kwargs}),
+            ]
+        );
+
+        let args = signature(quote! {(*, a = 1, b,)}).unwrap();
+        assert!(
+            args == vec![
+                Argument::VarArgsSeparator,
+                Argument::Kwarg(parse_quote! {a}, Some(parse_quote! {1})),
+                Argument::Kwarg(parse_quote! {b}, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_signature_errs() {
+        assert!(signature(quote! {(/)}).is_err());
+        assert!(signature(quote! {(a, /, /)}).is_err());
+        assert!(signature(quote! {(*, a, /)}).is_err());
+        assert!(signature(quote! {(a = 1, b)}).is_err());
+        assert!(signature(quote! {(**kwargs, a)}).is_err());
+        assert!(signature(quote! {(*args, *)}).is_err());
+        assert!(signature(quote! {(a b)}).is_err());
+    }
 }
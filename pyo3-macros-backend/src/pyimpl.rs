@@ -1,9 +1,13 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
+use crate::inspect;
 use crate::pymethod;
+use crate::slots::{self, GeneratedSlot};
+use crate::utils::get_cfg_attributes;
 use proc_macro2::TokenStream;
 use pymethod::GeneratedPyMethod;
 use quote::quote;
+use std::collections::HashSet;
 use syn::spanned::Spanned;
 
 /// The mechanism used to collect `#[pymethods]` into the type object
@@ -36,10 +40,22 @@ pub fn impl_methods(
     let mut new_impls = Vec::new();
     let mut call_impls = Vec::new();
     let mut methods = Vec::new();
+    let mut proto_slots = Vec::new();
+    let mut slot_fragments = Vec::new();
+    let mut implemented_fragments = HashSet::new();
+    let mut infos = Vec::new();
     for iimpl in impls.iter_mut() {
         match iimpl {
             syn::ImplItem::Method(meth) => {
-                match pymethod::gen_py_method(ty, &mut meth.sig, &mut meth.attrs)? {
+                let (method, info) = pymethod::gen_py_method(ty, &mut meth.sig, &mut meth.attrs)?;
+                match info {
+                    Some(info) if inspect::enabled() => {
+                        let attrs = get_cfg_attributes(&meth.attrs);
+                        infos.push(quote!(#(#attrs)* #info));
+                    }
+                    _ => {}
+                }
+                match method {
                     GeneratedPyMethod::Method(token_stream) => {
                         let attrs = get_cfg_attributes(&meth.attrs);
                         methods.push(quote!(#(#attrs)* #token_stream));
@@ -52,21 +68,39 @@ pub fn impl_methods(
                         let attrs = get_cfg_attributes(&meth.attrs);
                         call_impls.push(quote!(#(#attrs)* #token_stream));
                     }
+                    GeneratedPyMethod::Slot(GeneratedSlot::Slot(token_stream)) => {
+                        let attrs = get_cfg_attributes(&meth.attrs);
+                        proto_slots.push(quote!(#(#attrs)* #token_stream));
+                    }
+                    GeneratedPyMethod::Slot(GeneratedSlot::Fragment(name, token_stream)) => {
+                        let attrs = get_cfg_attributes(&meth.attrs);
+                        slot_fragments.push(quote!(#(#attrs)* #token_stream));
+                        implemented_fragments.insert(name);
+                    }
                 }
             }
             syn::ImplItem::Const(konst) => {
-                if let Some(meth) = pymethod::gen_py_const(ty, &konst.ident, &mut konst.attrs)? {
+                if let Some((meth, info)) =
+                    pymethod::gen_py_const(ty, &konst.ident, &konst.ty, &mut konst.attrs)?
+                {
                     let attrs = get_cfg_attributes(&konst.attrs);
                     methods.push(quote!(#(#attrs)* #meth));
+                    if inspect::enabled() {
+                        infos.push(quote!(#(#attrs)* #info));
+                    }
                 }
             }
             _ => (),
         }
     }
 
+    // Slots shared by several methods are emitted by each block which implements one of them,
+    // which is harmless as all of them call the same slot fragments.
+    proto_slots.extend(slots::impl_shared_slots(ty, &implemented_fragments));
+
     let methods_registration = match methods_type {
-        PyClassMethodsType::Specialization => impl_py_methods(ty, methods),
-        PyClassMethodsType::Inventory => submit_methods_inventory(ty, methods),
+        PyClassMethodsType::Specialization => impl_py_methods(ty, methods, proto_slots, infos),
+        PyClassMethodsType::Inventory => submit_methods_inventory(ty, methods, proto_slots, infos),
     };
 
     Ok(quote! {
@@ -74,11 +108,46 @@ pub fn impl_methods(
 
         #(#call_impls)*
 
+        #(#slot_fragments)*
+
         #methods_registration
     })
 }
 
-fn impl_py_methods(ty: &syn::Type, methods: Vec<TokenStream>) -> TokenStream {
+fn impl_py_methods(
+    ty: &syn::Type,
+    methods: Vec<TokenStream>,
+    proto_slots: Vec<TokenStream>,
+    infos: Vec<TokenStream>,
+) -> TokenStream {
+    let proto_slots = if proto_slots.is_empty() {
+        None
+    } else {
+        Some(quote! {
+            impl pyo3::class::impl_::PyMethodsProtocolSlots<#ty>
+                for pyo3::class::impl_::PyClassImplCollector<#ty>
+            {
+                fn methods_protocol_slots(self) -> &'static [pyo3::ffi::PyType_Slot] {
+                    const SLOTS: &[pyo3::ffi::PyType_Slot] = &[#(#proto_slots),*];
+                    SLOTS
+                }
+            }
+        })
+    };
+    let methods_info = if inspect::enabled() {
+        Some(quote! {
+            impl pyo3::class::impl_::PyMethodsInfo<#ty>
+                for pyo3::class::impl_::PyClassImplCollector<#ty>
+            {
+                fn py_methods_info(self) -> Vec<pyo3::inspect::FunctionInfo> {
+                    vec![#(#infos),*]
+                }
+            }
+        })
+    } else {
+        None
+    };
+
     quote! {
         impl pyo3::class::impl_::PyMethods<#ty>
             for pyo3::class::impl_::PyClassImplCollector<#ty>
@@ -88,11 +157,20 @@ fn impl_py_methods(ty: &syn::Type, methods: Vec<TokenStream>) -> TokenStream {
                 METHODS
             }
         }
+
+        #methods_info
+
+        #proto_slots
     }
 }
 
-fn submit_methods_inventory(ty: &syn::Type, methods: Vec<TokenStream>) -> TokenStream {
-    if methods.is_empty() {
+fn submit_methods_inventory(
+    ty: &syn::Type,
+    methods: Vec<TokenStream>,
+    proto_slots: Vec<TokenStream>,
+    infos: Vec<TokenStream>,
+) -> TokenStream {
+    if methods.is_empty() && proto_slots.is_empty() && infos.is_empty() {
         return TokenStream::default();
     }
 
@@ -100,15 +178,19 @@ fn submit_methods_inventory(ty: &syn::Type, methods: Vec<TokenStream>) -> TokenS
         pyo3::inventory::submit! {
             #![crate = pyo3] {
                 type Inventory = <#ty as pyo3::class::impl_::HasMethodsInventory>::Methods;
-                <Inventory as pyo3::class::impl_::PyMethodsInventory>::new(vec![#(#methods),*])
+                fn __slots() -> &'static [pyo3::ffi::PyType_Slot] {
+                    const SLOTS: &[pyo3::ffi::PyType_Slot] = &[#(#proto_slots),*];
+                    SLOTS
+                }
+                fn __info() -> Vec<pyo3::inspect::FunctionInfo> {
+                    vec![#(#infos),*]
+                }
+                <Inventory as pyo3::class::impl_::PyMethodsInventory>::new(
+                    vec![#(#methods),*],
+                    __slots,
+                    __info,
+                )
             }
         }
     }
 }
-
-fn get_cfg_attributes(attrs: &[syn::Attribute]) -> Vec<&syn::Attribute> {
-    attrs
-        .iter()
-        .filter(|attr| attr.path.is_ident("cfg"))
-        .collect()
-}
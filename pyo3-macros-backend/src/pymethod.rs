@@ -1,14 +1,20 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 use crate::attrs::FromPyWithAttribute;
+use crate::inspect;
 use crate::konst::ConstSpec;
 use crate::method::{FnArg, FnSpec, FnType, SelfType};
+use crate::slots::{self, GeneratedSlot};
 use crate::utils;
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned};
 use syn::{ext::IdentExt, spanned::Spanned, Result};
 
 pub enum PropertyType<'a> {
-    Descriptor(&'a syn::Field),
+    Descriptor {
+        field: &'a syn::Field,
+        /// The `get_with` or `set_with` function converting the value of the field.
+        with: Option<&'a syn::ExprPath>,
+    },
     Function(&'a FnSpec<'a>),
 }
 
@@ -16,20 +22,30 @@ pub enum GeneratedPyMethod {
     Method(TokenStream),
     New(TokenStream),
     Call(TokenStream),
+    /// A protocol method such as `__repr__`, see [crate::slots].
+    Slot(GeneratedSlot),
 }
 
+/// Generates the method definition, along with the `pyo3::inspect::FunctionInfo` expression
+/// describing the method. Protocol methods such as `__repr__` are not described.
 pub fn gen_py_method(
     cls: &syn::Type,
     sig: &mut syn::Signature,
     meth_attrs: &mut Vec<syn::Attribute>,
-) -> Result<GeneratedPyMethod> {
+) -> Result<(GeneratedPyMethod, Option<TokenStream>)> {
     check_generic(sig)?;
     let spec = FnSpec::parse(sig, &mut *meth_attrs, true)?;
 
-    Ok(match &spec.tp {
-        FnType::Fn(self_ty) => {
-            GeneratedPyMethod::Method(impl_py_method_def(cls, &spec, self_ty, None)?)
-        }
+    let method = match &spec.tp {
+        FnType::Fn(self_ty) => match slots::gen_proto_slot(cls, &spec, self_ty)? {
+            Some(slot) => {
+                if let Some(asyncness) = &spec.asyncness {
+                    bail_spanned!(asyncness.span() => "`async fn` is not supported for protocol methods");
+                }
+                GeneratedPyMethod::Slot(slot)
+            }
+            None => GeneratedPyMethod::Method(impl_py_method_def(cls, &spec, self_ty, None)?),
+        },
         FnType::FnNew => GeneratedPyMethod::New(impl_py_method_def_new(cls, &spec)?),
         FnType::FnCall(self_ty) => {
             GeneratedPyMethod::Call(impl_py_method_def_call(cls, &spec, self_ty)?)
@@ -53,7 +69,12 @@ pub fn gen_py_method(
             &spec.python_name,
             &spec.doc,
         )?),
-    })
+    };
+    let info = match method {
+        GeneratedPyMethod::Slot(_) => None,
+        _ => Some(inspect::function_info(&spec, Some(cls))),
+    };
+    Ok((method, info))
 }
 
 pub(crate) fn check_generic(sig: &syn::Signature) -> syn::Result<()> {
@@ -68,11 +89,14 @@ pub(crate) fn check_generic(sig: &syn::Signature) -> syn::Result<()> {
     Ok(())
 }
 
+/// Generates the class attribute definition of a `#[classattr]` constant, along with the
+/// `pyo3::inspect::FunctionInfo` expression describing it.
 pub fn gen_py_const(
     cls: &syn::Type,
     name: &syn::Ident,
+    ty: &syn::Type,
     attrs: &mut Vec<syn::Attribute>,
-) -> syn::Result<Option<TokenStream>> {
+) -> syn::Result<Option<(TokenStream, TokenStream)>> {
     let spec = ConstSpec::parse(name, attrs)?;
     if spec.is_class_attr {
         let wrapper = quote! {
@@ -80,7 +104,10 @@ pub fn gen_py_const(
                 pyo3::IntoPy::into_py(#cls::#name, py)
             }
         };
-        return Ok(Some(impl_py_const_class_attribute(&spec, &wrapper)));
+        return Ok(Some((
+            impl_py_const_class_attribute(&spec, &wrapper),
+            inspect::const_info(&spec.python_name, ty, cls),
+        )));
     }
     Ok(None)
 }
@@ -91,8 +118,8 @@ pub fn impl_wrap_cfunction_with_keywords(
     spec: &FnSpec<'_>,
     self_ty: &SelfType,
 ) -> Result<TokenStream> {
-    let body = impl_call(cls, &spec);
-    let slf = self_ty.receiver(cls);
+    let body = impl_call(cls, &spec, self_ty);
+    let slf = impl_receiver(cls, spec, self_ty);
     let body = impl_arg_params(&spec, Some(cls), body)?;
     Ok(quote! {
         unsafe extern "C" fn __wrap(
@@ -113,8 +140,8 @@ pub fn impl_wrap_cfunction_with_keywords(
 
 /// Generate function wrapper PyCFunction
 pub fn impl_wrap_noargs(cls: &syn::Type, spec: &FnSpec<'_>, self_ty: &SelfType) -> TokenStream {
-    let body = impl_call(cls, &spec);
-    let slf = self_ty.receiver(cls);
+    let body = impl_call(cls, &spec, self_ty);
+    let slf = impl_receiver(cls, spec, self_ty);
     assert!(spec.args.is_empty());
     quote! {
         unsafe extern "C" fn __wrap(
@@ -188,7 +215,11 @@ pub fn impl_wrap_class(cls: &syn::Type, spec: &FnSpec<'_>) -> Result<TokenStream
 pub fn impl_wrap_static(cls: &syn::Type, spec: &FnSpec<'_>) -> Result<TokenStream> {
     let name = &spec.name;
     let names: Vec<syn::Ident> = get_arg_names(&spec);
-    let cb = quote! { pyo3::callback::convert(_py, #cls::#name(#(#names),*)) };
+    let cb = if spec.asyncness.is_some() {
+        spec.coroutine(Some(cls), quote! { #cls::#name(#(#names),*) })
+    } else {
+        quote! { pyo3::callback::convert(_py, #cls::#name(#(#names),*)) }
+    };
 
     let body = impl_arg_params(spec, Some(cls), cb)?;
 
@@ -247,11 +278,16 @@ pub(crate) fn impl_wrap_getter(
     self_ty: &SelfType,
 ) -> syn::Result<TokenStream> {
     let getter_impl = match property_type {
-        PropertyType::Descriptor(field) => {
+        PropertyType::Descriptor { field, with } => {
             let name = field.ident.as_ref().unwrap();
-            quote!({
-                _slf.#name.clone()
-            })
+            match with {
+                Some(get_with) => quote!({
+                    #get_with(&_slf.#name)
+                }),
+                None => quote!({
+                    _slf.#name.clone()
+                }),
+            }
         }
         PropertyType::Function(spec) => impl_call_getter(cls, spec)?,
     };
@@ -297,13 +333,23 @@ pub(crate) fn impl_wrap_setter(
     property_type: PropertyType,
     self_ty: &SelfType,
 ) -> syn::Result<TokenStream> {
-    let setter_impl = match property_type {
-        PropertyType::Descriptor(field) => {
+    let (setter_impl, span, extract) = match property_type {
+        PropertyType::Descriptor { field, with } => {
             let name = field.ident.as_ref().unwrap();
-            quote!({ _slf.#name = _val; })
+            let extract = match with {
+                Some(set_with) => quote!(#set_with(_value)?),
+                None => quote!(pyo3::FromPyObject::extract(_value)?),
+            };
+            (quote!({ _slf.#name = _val; }), field.span(), extract)
         }
-        PropertyType::Function(spec) => impl_call_setter(cls, spec)?,
+        PropertyType::Function(spec) => (
+            impl_call_setter(cls, spec)?,
+            spec.name.span(),
+            quote!(pyo3::FromPyObject::extract(_value)?),
+        ),
     };
+    // Frozen classes cannot have setters
+    let assert_mutable = quote_spanned! {span=> pyo3::class::impl_::assert_mutable::<#cls>(); };
 
     let slf = self_ty.receiver(cls);
     Ok(quote! {
@@ -312,10 +358,11 @@ pub(crate) fn impl_wrap_setter(
             _slf: *mut pyo3::ffi::PyObject,
             _value: *mut pyo3::ffi::PyObject, _: *mut std::os::raw::c_void) -> std::os::raw::c_int
         {
+            #assert_mutable
             pyo3::callback::handle_panic(|_py| {
                 #slf
                 let _value = _py.from_borrowed_ptr::<pyo3::types::PyAny>(_value);
-                let _val = pyo3::FromPyObject::extract(_value)?;
+                let _val = #extract;
 
                 pyo3::callback::convert(_py, #setter_impl)
             })
@@ -330,10 +377,25 @@ pub fn get_arg_names(spec: &FnSpec) -> Vec<syn::Ident> {
         .collect()
 }
 
-fn impl_call(cls: &syn::Type, spec: &FnSpec<'_>) -> TokenStream {
+fn impl_receiver(cls: &syn::Type, spec: &FnSpec<'_>, self_ty: &SelfType) -> TokenStream {
+    if spec.asyncness.is_some() {
+        self_ty.async_receiver(cls, spec.name.span())
+    } else {
+        self_ty.receiver(cls)
+    }
+}
+
+fn impl_call(cls: &syn::Type, spec: &FnSpec<'_>, self_ty: &SelfType) -> TokenStream {
     let fname = &spec.name;
     let names = get_arg_names(spec);
-    quote! { pyo3::callback::convert(_py, #cls::#fname(_slf, #(#names),*)) }
+    if spec.asyncness.is_none() {
+        return quote! { pyo3::callback::convert(_py, #cls::#fname(_slf, #(#names),*)) };
+    }
+    let slf = match self_ty {
+        SelfType::Receiver { .. } => quote!(pyo3::Py::get(&_slf)),
+        SelfType::TryFromPyCell(_) => quote!(_slf),
+    };
+    spec.coroutine(Some(cls), quote! { #cls::#fname(#slf, #(#names),*) })
 }
 
 pub fn impl_arg_params(
@@ -390,6 +452,7 @@ pub fn impl_arg_params(
             _ => continue,
         }
     }
+    let positional_only_parameters = spec.positional_only_parameters();
 
     let cls_name = if let Some(cls) = self_ {
         quote! { Some(<#cls as pyo3::type_object::PyTypeInfo>::NAME) }
@@ -405,8 +468,7 @@ pub fn impl_arg_params(
                 cls_name: #cls_name,
                 func_name: stringify!(#python_name),
                 positional_parameter_names: &[#(#positional_parameter_names),*],
-                // TODO: https://github.com/PyO3/pyo3/issues/1439 - support specifying these
-                positional_only_parameters: 0,
+                positional_only_parameters: #positional_only_parameters,
                 required_positional_parameters: #required_positional_parameters,
                 keyword_only_parameters: &[#(#keyword_only_parameters),*],
                 accept_varargs: #accept_args,
@@ -474,7 +536,10 @@ fn impl_arg_param(
     *option_pos += 1;
 
     let default = match (spec.default_value(name), arg.optional.is_some()) {
-        (Some(default), true) if default.to_string() != "None" => quote! { Some(#default) },
+        // `#[args]` defaults for `Option<T>` arguments are given as values of `T`
+        (Some(default), true) if !spec.has_signature && default.to_string() != "None" => {
+            quote! { Some(#default) }
+        }
         (Some(default), _) => quote! { #default },
         (None, true) => quote! { None },
         (None, false) => quote! { panic!("Failed to extract required method argument") },
@@ -486,7 +551,7 @@ fn impl_arg_param(
         quote! {_obj.extract().map_err(#transform_error)?}
     };
 
-    return if let syn::Type::Reference(tref) = arg.optional.as_ref().unwrap_or(&ty) {
+    if let syn::Type::Reference(tref) = arg.optional.as_ref().unwrap_or(&ty) {
         let (tref, mut_) = preprocess_tref(tref, self_);
         let (target_ty, borrow_tmp) = if arg.optional.is_some() {
             // Get Option<&T> from Option<PyRef<T>>
@@ -522,35 +587,35 @@ fn impl_arg_param(
                 None => #default,
             };
         })
-    };
+    }
+}
 
-    /// Replace `Self`, remove lifetime and get mutability from the type
-    fn preprocess_tref(
-        tref: &syn::TypeReference,
-        self_: Option<&syn::Type>,
-    ) -> (syn::TypeReference, Option<syn::token::Mut>) {
-        let mut tref = tref.to_owned();
-        if let Some(syn::Type::Path(tpath)) = self_ {
-            replace_self(&mut tref, &tpath.path);
-        }
-        tref.lifetime = None;
-        let mut_ = tref.mutability;
-        (tref, mut_)
+/// Replace `Self`, remove lifetime and get mutability from the type
+pub(crate) fn preprocess_tref(
+    tref: &syn::TypeReference,
+    self_: Option<&syn::Type>,
+) -> (syn::TypeReference, Option<syn::token::Mut>) {
+    let mut tref = tref.to_owned();
+    if let Some(syn::Type::Path(tpath)) = self_ {
+        replace_self(&mut tref, &tpath.path);
     }
+    tref.lifetime = None;
+    let mut_ = tref.mutability;
+    (tref, mut_)
+}
 
-    /// Replace `Self` with the exact type name since it is used out of the impl block
-    fn replace_self(tref: &mut syn::TypeReference, self_path: &syn::Path) {
-        match &mut *tref.elem {
-            syn::Type::Reference(tref_inner) => replace_self(tref_inner, self_path),
-            syn::Type::Path(tpath) => {
-                if let Some(ident) = tpath.path.get_ident() {
-                    if ident == "Self" {
-                        tpath.path = self_path.to_owned();
-                    }
+/// Replace `Self` with the exact type name since it is used out of the impl block
+fn replace_self(tref: &mut syn::TypeReference, self_path: &syn::Path) {
+    match &mut *tref.elem {
+        syn::Type::Reference(tref_inner) => replace_self(tref_inner, self_path),
+        syn::Type::Path(tpath) => {
+            if let Some(ident) = tpath.path.get_ident() {
+                if ident == "Self" {
+                    tpath.path = self_path.to_owned();
                 }
             }
-            _ => {}
         }
+        _ => {}
     }
 }
 
@@ -597,6 +662,15 @@ pub fn impl_py_method_def(
 
 pub fn impl_py_method_def_new(cls: &syn::Type, spec: &FnSpec) -> Result<TokenStream> {
     let wrapper = impl_wrap_new(cls, &spec)?;
+    let text_signature = spec.text_signature.as_ref().map(|text_signature| {
+        quote! {
+            impl pyo3::class::impl_::PyClassNewTextSignature<#cls> for pyo3::class::impl_::PyClassImplCollector<#cls> {
+                fn new_text_signature(self) -> Option<&'static str> {
+                    Some(#text_signature)
+                }
+            }
+        }
+    });
     Ok(quote! {
         impl pyo3::class::impl_::PyClassNewImpl<#cls> for pyo3::class::impl_::PyClassImplCollector<#cls> {
             fn new_impl(self) -> Option<pyo3::ffi::newfunc> {
@@ -605,6 +679,8 @@ pub fn impl_py_method_def_new(cls: &syn::Type, spec: &FnSpec) -> Result<TokenStr
                 Some(__wrap)
             }
         }
+
+        #text_signature
     })
 }
 
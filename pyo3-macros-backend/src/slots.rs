@@ -0,0 +1,679 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Protocol methods such as `__repr__` or `__add__` in `#[pymethods]`, which are implemented by
+//! filling in the slots of the type object.
+
+use crate::attrs::FromPyWithAttribute;
+use crate::method::{FnSpec, SelfType};
+use crate::pymethod::{get_arg_names, preprocess_tref};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use std::collections::HashSet;
+use syn::spanned::Spanned;
+
+/// An argument of a slot, which is passed to the method.
+#[derive(Clone, Copy, PartialEq)]
+enum SlotArg {
+    /// A Python object.
+    Object,
+    /// A Python object which may be NULL, in which case `None` is passed.
+    NullableObject,
+    /// The `op` argument of `tp_richcompare`, which is passed as a `CompareOp`.
+    CompareOp,
+    /// An argument which is not passed to the method.
+    Ignored,
+}
+
+/// How the return value of the method is converted into the return value of the slot.
+#[derive(Clone, Copy, PartialEq)]
+enum SlotReturn {
+    /// A Python object.
+    Object,
+    /// An `IterNextOutput`, of which `Return` raises `StopIteration`.
+    IterNext,
+    /// An `IterANextOutput`, of which `Return` raises `StopAsyncIteration`.
+    AsyncIterNext,
+    /// A hash, which is never `-1`.
+    Hash,
+    /// A length, which has to fit into `Py_ssize_t`.
+    Length,
+    /// A `bool`, which is returned as a `c_int`.
+    Bool,
+    /// `()`; only used for slot fragments.
+    Unit,
+    /// `()`, after which the slot returns `self`; used for in-place operators.
+    ReturnSelf,
+}
+
+/// What happens if an argument cannot be extracted.
+#[derive(Clone, Copy, PartialEq)]
+enum ExtractErrorMode {
+    /// The error is raised.
+    Raise,
+    /// `NotImplemented` is returned, so that Python tries the other operand.
+    NotImplemented,
+}
+
+/// A protocol method which is the only implementation of its slot.
+struct SlotDef {
+    slot: &'static str,
+    args: &'static [SlotArg],
+    ret: SlotReturn,
+    extract_error_mode: ExtractErrorMode,
+    /// Whether existing attributes are looked up before calling the method, as for `__getattr__`.
+    generic_getattr: bool,
+}
+
+impl SlotDef {
+    fn new(slot: &'static str, args: &'static [SlotArg], ret: SlotReturn) -> Self {
+        SlotDef {
+            slot,
+            args,
+            ret,
+            extract_error_mode: ExtractErrorMode::Raise,
+            generic_getattr: false,
+        }
+    }
+
+    fn not_implemented_on_extract_error(mut self) -> Self {
+        self.extract_error_mode = ExtractErrorMode::NotImplemented;
+        self
+    }
+
+    /// Generates the `pyo3::ffi::PyType_Slot` for the method.
+    fn generate(
+        &self,
+        cls: &syn::Type,
+        spec: &FnSpec,
+        self_ty: &SelfType,
+    ) -> syn::Result<TokenStream> {
+        let slot = syn::Ident::new(self.slot, Span::call_site());
+        let body = impl_slot_body(
+            cls,
+            spec,
+            self_ty,
+            SlotBody {
+                args: self.args,
+                ret: self.ret,
+                extract_error_mode: self.extract_error_mode,
+                check_self_type: false,
+                generic_getattr: self.generic_getattr,
+            },
+        )?;
+        let params = raw_params(self.args);
+        let ret_ty = match self.ret {
+            SlotReturn::Object
+            | SlotReturn::IterNext
+            | SlotReturn::AsyncIterNext
+            | SlotReturn::ReturnSelf => quote! { *mut pyo3::ffi::PyObject },
+            SlotReturn::Hash => quote! { pyo3::ffi::Py_hash_t },
+            SlotReturn::Length => quote! { pyo3::ffi::Py_ssize_t },
+            SlotReturn::Bool | SlotReturn::Unit => quote! { std::os::raw::c_int },
+        };
+        Ok(quote! {{
+            unsafe extern "C" fn __wrap(
+                _slf: *mut pyo3::ffi::PyObject,
+                #(#params),*
+            ) -> #ret_ty {
+                pyo3::callback::handle_panic(|_py| {
+                    #body
+                })
+            }
+
+            pyo3::ffi::PyType_Slot {
+                slot: pyo3::ffi::#slot,
+                pfunc: __wrap as _,
+            }
+        }})
+    }
+}
+
+/// Returns the definition of the slot implemented by the protocol method `name`, unless the method
+/// shares its slot with other methods.
+fn proto_slot(name: &str) -> Option<SlotDef> {
+    use SlotArg::*;
+    const OBJECT: &[SlotArg] = &[Object];
+
+    let def = match name {
+        "__getattr__" => SlotDef {
+            generic_getattr: true,
+            ..SlotDef::new("Py_tp_getattro", OBJECT, SlotReturn::Object)
+        },
+        "__str__" => SlotDef::new("Py_tp_str", &[], SlotReturn::Object),
+        "__repr__" => SlotDef::new("Py_tp_repr", &[], SlotReturn::Object),
+        "__hash__" => SlotDef::new("Py_tp_hash", &[], SlotReturn::Hash),
+        "__richcmp__" => SlotDef::new(
+            "Py_tp_richcompare",
+            &[Object, CompareOp],
+            SlotReturn::Object,
+        )
+        .not_implemented_on_extract_error(),
+        "__bool__" => SlotDef::new("Py_nb_bool", &[], SlotReturn::Bool),
+        "__get__" => SlotDef::new(
+            "Py_tp_descr_get",
+            &[NullableObject, NullableObject],
+            SlotReturn::Object,
+        ),
+        "__iter__" => SlotDef::new("Py_tp_iter", &[], SlotReturn::Object),
+        "__next__" => SlotDef::new("Py_tp_iternext", &[], SlotReturn::IterNext),
+        "__await__" => SlotDef::new("Py_am_await", &[], SlotReturn::Object),
+        "__aiter__" => SlotDef::new("Py_am_aiter", &[], SlotReturn::Object),
+        "__anext__" => SlotDef::new("Py_am_anext", &[], SlotReturn::AsyncIterNext),
+        "__len__" => SlotDef::new("Py_mp_length", &[], SlotReturn::Length),
+        "__contains__" => SlotDef::new("Py_sq_contains", OBJECT, SlotReturn::Bool),
+        "__getitem__" => SlotDef::new("Py_mp_subscript", OBJECT, SlotReturn::Object),
+        "__neg__" => SlotDef::new("Py_nb_negative", &[], SlotReturn::Object),
+        "__pos__" => SlotDef::new("Py_nb_positive", &[], SlotReturn::Object),
+        "__abs__" => SlotDef::new("Py_nb_absolute", &[], SlotReturn::Object),
+        "__invert__" => SlotDef::new("Py_nb_invert", &[], SlotReturn::Object),
+        "__int__" => SlotDef::new("Py_nb_int", &[], SlotReturn::Object),
+        "__float__" => SlotDef::new("Py_nb_float", &[], SlotReturn::Object),
+        "__index__" => SlotDef::new("Py_nb_index", &[], SlotReturn::Object),
+        "__iadd__" => inplace("Py_nb_inplace_add", OBJECT),
+        "__isub__" => inplace("Py_nb_inplace_subtract", OBJECT),
+        "__imul__" => inplace("Py_nb_inplace_multiply", OBJECT),
+        "__imatmul__" => inplace("Py_nb_inplace_matrix_multiply", OBJECT),
+        "__itruediv__" => inplace("Py_nb_inplace_true_divide", OBJECT),
+        "__ifloordiv__" => inplace("Py_nb_inplace_floor_divide", OBJECT),
+        "__imod__" => inplace("Py_nb_inplace_remainder", OBJECT),
+        "__ipow__" => inplace("Py_nb_inplace_power", &[Object, Ignored]),
+        "__ilshift__" => inplace("Py_nb_inplace_lshift", OBJECT),
+        "__irshift__" => inplace("Py_nb_inplace_rshift", OBJECT),
+        "__iand__" => inplace("Py_nb_inplace_and", OBJECT),
+        "__ixor__" => inplace("Py_nb_inplace_xor", OBJECT),
+        "__ior__" => inplace("Py_nb_inplace_or", OBJECT),
+        _ => return None,
+    };
+    Some(def)
+}
+
+fn inplace(slot: &'static str, args: &'static [SlotArg]) -> SlotDef {
+    SlotDef::new(slot, args, SlotReturn::ReturnSelf).not_implemented_on_extract_error()
+}
+
+/// A protocol method which shares its slot with another method, such as `__add__` and `__radd__`.
+///
+/// The method implements `pyo3::class::impl_::PyClass<name>SlotFragment`, and the slot calls the
+/// fragments of both methods (see [impl_shared_slots]).
+struct SlotFragmentDef {
+    args: &'static [SlotArg],
+    ret: SlotReturn,
+    extract_error_mode: ExtractErrorMode,
+}
+
+impl SlotFragmentDef {
+    /// Generates the implementation of the slot fragment trait for the method `name`.
+    fn generate(
+        &self,
+        name: &str,
+        cls: &syn::Type,
+        spec: &FnSpec,
+        self_ty: &SelfType,
+    ) -> syn::Result<TokenStream> {
+        let trait_name =
+            syn::Ident::new(&format!("PyClass{}SlotFragment", name), Span::call_site());
+        let method = syn::Ident::new(name, Span::call_site());
+        let binary_operator = self.extract_error_mode == ExtractErrorMode::NotImplemented;
+        let body = impl_slot_body(
+            cls,
+            spec,
+            self_ty,
+            SlotBody {
+                args: self.args,
+                ret: self.ret,
+                extract_error_mode: self.extract_error_mode,
+                // The slot of a binary operator is called for both operands
+                check_self_type: binary_operator,
+                generic_getattr: false,
+            },
+        )?;
+        let params = raw_params(self.args);
+        let ret_ty = match self.ret {
+            SlotReturn::Unit => quote! { () },
+            _ => quote! { *mut pyo3::ffi::PyObject },
+        };
+        Ok(quote! {
+            impl pyo3::class::impl_::#trait_name<#cls>
+                for pyo3::class::impl_::PyClassImplCollector<#cls>
+            {
+                #[inline]
+                unsafe fn #method(
+                    self,
+                    _py: pyo3::Python,
+                    _slf: *mut pyo3::ffi::PyObject,
+                    #(#params),*
+                ) -> pyo3::PyResult<#ret_ty> {
+                    #body
+                }
+            }
+        })
+    }
+}
+
+fn slot_fragment(name: &str) -> Option<SlotFragmentDef> {
+    use SlotArg::*;
+
+    let (args, ret, extract_error_mode): (&'static [SlotArg], _, _) = match name {
+        "__add__" | "__radd__" | "__sub__" | "__rsub__" | "__mul__" | "__rmul__" | "__matmul__"
+        | "__rmatmul__" | "__truediv__" | "__rtruediv__" | "__floordiv__" | "__rfloordiv__"
+        | "__mod__" | "__rmod__" | "__divmod__" | "__rdivmod__" | "__lshift__" | "__rlshift__"
+        | "__rshift__" | "__rrshift__" | "__and__" | "__rand__" | "__xor__" | "__rxor__"
+        | "__or__" | "__ror__" => (
+            &[Object],
+            SlotReturn::Object,
+            ExtractErrorMode::NotImplemented,
+        ),
+        "__pow__" | "__rpow__" => (
+            &[Object, Object],
+            SlotReturn::Object,
+            ExtractErrorMode::NotImplemented,
+        ),
+        "__setattr__" | "__setitem__" | "__set__" => {
+            (&[Object, Object], SlotReturn::Unit, ExtractErrorMode::Raise)
+        }
+        "__delattr__" | "__delitem__" | "__delete__" => {
+            (&[Object], SlotReturn::Unit, ExtractErrorMode::Raise)
+        }
+        _ => return None,
+    };
+    Some(SlotFragmentDef {
+        args,
+        ret,
+        extract_error_mode,
+    })
+}
+
+/// How the slot calls the fragments of the methods which share it.
+#[derive(Clone, Copy)]
+enum SharedSlot {
+    /// `binaryfunc` of a binary operator, which calls the reflected method of the right operand
+    /// if the left operand returns `NotImplemented`.
+    Binary,
+    /// `ternaryfunc` of `pow()`, which works like `Binary`.
+    Ternary,
+    /// A setter which deletes if the value is NULL, such as `setattrofunc`.
+    SetDel,
+}
+
+const SHARED_SLOTS: &[(&str, &str, &str, SharedSlot)] = &[
+    ("__add__", "__radd__", "Py_nb_add", SharedSlot::Binary),
+    ("__sub__", "__rsub__", "Py_nb_subtract", SharedSlot::Binary),
+    ("__mul__", "__rmul__", "Py_nb_multiply", SharedSlot::Binary),
+    (
+        "__matmul__",
+        "__rmatmul__",
+        "Py_nb_matrix_multiply",
+        SharedSlot::Binary,
+    ),
+    (
+        "__truediv__",
+        "__rtruediv__",
+        "Py_nb_true_divide",
+        SharedSlot::Binary,
+    ),
+    (
+        "__floordiv__",
+        "__rfloordiv__",
+        "Py_nb_floor_divide",
+        SharedSlot::Binary,
+    ),
+    ("__mod__", "__rmod__", "Py_nb_remainder", SharedSlot::Binary),
+    (
+        "__divmod__",
+        "__rdivmod__",
+        "Py_nb_divmod",
+        SharedSlot::Binary,
+    ),
+    (
+        "__lshift__",
+        "__rlshift__",
+        "Py_nb_lshift",
+        SharedSlot::Binary,
+    ),
+    (
+        "__rshift__",
+        "__rrshift__",
+        "Py_nb_rshift",
+        SharedSlot::Binary,
+    ),
+    ("__and__", "__rand__", "Py_nb_and", SharedSlot::Binary),
+    ("__xor__", "__rxor__", "Py_nb_xor", SharedSlot::Binary),
+    ("__or__", "__ror__", "Py_nb_or", SharedSlot::Binary),
+    ("__pow__", "__rpow__", "Py_nb_power", SharedSlot::Ternary),
+    (
+        "__setattr__",
+        "__delattr__",
+        "Py_tp_setattro",
+        SharedSlot::SetDel,
+    ),
+    (
+        "__setitem__",
+        "__delitem__",
+        "Py_mp_ass_subscript",
+        SharedSlot::SetDel,
+    ),
+    (
+        "__set__",
+        "__delete__",
+        "Py_tp_descr_set",
+        SharedSlot::SetDel,
+    ),
+];
+
+/// The method generated for a protocol method in `#[pymethods]`.
+pub enum GeneratedSlot {
+    /// A `pyo3::ffi::PyType_Slot`.
+    Slot(TokenStream),
+    /// The implementation of a slot fragment, see [SlotFragmentDef].
+    Fragment(String, TokenStream),
+}
+
+/// Generates the slot for the protocol method `spec`, if it is one.
+pub fn gen_proto_slot(
+    cls: &syn::Type,
+    spec: &FnSpec,
+    self_ty: &SelfType,
+) -> syn::Result<Option<GeneratedSlot>> {
+    let name = spec.python_name.to_string();
+    if let Some(def) = proto_slot(&name) {
+        Ok(Some(GeneratedSlot::Slot(def.generate(cls, spec, self_ty)?)))
+    } else if let Some(def) = slot_fragment(&name) {
+        let fragment = def.generate(&name, cls, spec, self_ty)?;
+        Ok(Some(GeneratedSlot::Fragment(name, fragment)))
+    } else if let Some(protocol) = pyproto_only(&name) {
+        bail_spanned!(
+            spec.python_name.span() => format!(
+                "`{}` is not supported in `#[pymethods]`; use #[pyproto] for `{}` instead",
+                name, protocol
+            )
+        )
+    } else {
+        Ok(None)
+    }
+}
+
+/// Returns the `#[pyproto]` trait of the protocol method `name`, if it can only be implemented
+/// there. Otherwise it would become an ordinary method, leaving its slot empty.
+fn pyproto_only(name: &str) -> Option<&'static str> {
+    match name {
+        "__traverse__" | "__clear__" => Some("PyGCProtocol"),
+        "__getbuffer__" | "__releasebuffer__" => Some("PyBufferProtocol"),
+        _ => None,
+    }
+}
+
+/// Generates the slots which are shared by the implemented slot fragments.
+pub fn impl_shared_slots(cls: &syn::Type, fragments: &HashSet<String>) -> Vec<TokenStream> {
+    SHARED_SLOTS
+        .iter()
+        .filter(|(first, second, _, _)| fragments.contains(*first) || fragments.contains(*second))
+        .map(|&(first, second, slot, kind)| {
+            let first = syn::Ident::new(first, Span::call_site());
+            let second = syn::Ident::new(second, Span::call_site());
+            let slot = syn::Ident::new(slot, Span::call_site());
+            let wrapper = match kind {
+                SharedSlot::Binary => quote! {
+                    unsafe extern "C" fn __wrap(
+                        _slf: *mut pyo3::ffi::PyObject,
+                        _other: *mut pyo3::ffi::PyObject,
+                    ) -> *mut pyo3::ffi::PyObject {
+                        pyo3::callback::handle_panic(|_py| {
+                            use pyo3::class::impl_::*;
+                            let collector = PyClassImplCollector::<#cls>::new();
+                            let lhs_result = collector.#first(_py, _slf, _other)?;
+                            if lhs_result == pyo3::ffi::Py_NotImplemented() {
+                                pyo3::ffi::Py_DECREF(lhs_result);
+                                collector.#second(_py, _other, _slf)
+                            } else {
+                                Ok(lhs_result)
+                            }
+                        })
+                    }
+                },
+                SharedSlot::Ternary => quote! {
+                    unsafe extern "C" fn __wrap(
+                        _slf: *mut pyo3::ffi::PyObject,
+                        _other: *mut pyo3::ffi::PyObject,
+                        _modulo: *mut pyo3::ffi::PyObject,
+                    ) -> *mut pyo3::ffi::PyObject {
+                        pyo3::callback::handle_panic(|_py| {
+                            use pyo3::class::impl_::*;
+                            let collector = PyClassImplCollector::<#cls>::new();
+                            let lhs_result = collector.#first(_py, _slf, _other, _modulo)?;
+                            if lhs_result == pyo3::ffi::Py_NotImplemented() {
+                                pyo3::ffi::Py_DECREF(lhs_result);
+                                collector.#second(_py, _other, _slf, _modulo)
+                            } else {
+                                Ok(lhs_result)
+                            }
+                        })
+                    }
+                },
+                SharedSlot::SetDel => quote! {
+                    unsafe extern "C" fn __wrap(
+                        _slf: *mut pyo3::ffi::PyObject,
+                        _key: *mut pyo3::ffi::PyObject,
+                        _value: *mut pyo3::ffi::PyObject,
+                    ) -> std::os::raw::c_int {
+                        pyo3::callback::handle_panic(|_py| {
+                            use pyo3::class::impl_::*;
+                            let collector = PyClassImplCollector::<#cls>::new();
+                            if _value.is_null() {
+                                collector.#second(_py, _slf, _key)?;
+                            } else {
+                                collector.#first(_py, _slf, _key, _value)?;
+                            }
+                            Ok(0)
+                        })
+                    }
+                },
+            };
+            quote! {{
+                #wrapper
+
+                pyo3::ffi::PyType_Slot {
+                    slot: pyo3::ffi::#slot,
+                    pfunc: __wrap as _,
+                }
+            }}
+        })
+        .collect()
+}
+
+/// Parameters of the slot after `_slf`, named `_arg0`, `_arg1`, ...
+fn raw_params(args: &[SlotArg]) -> Vec<TokenStream> {
+    args.iter()
+        .enumerate()
+        .map(|(i, arg)| {
+            let name = raw_arg_name(i);
+            match arg {
+                SlotArg::CompareOp => quote! { #name: std::os::raw::c_int },
+                _ => quote! { #name: *mut pyo3::ffi::PyObject },
+            }
+        })
+        .collect()
+}
+
+fn raw_arg_name(index: usize) -> syn::Ident {
+    syn::Ident::new(&format!("_arg{}", index), Span::call_site())
+}
+
+struct SlotBody {
+    args: &'static [SlotArg],
+    ret: SlotReturn,
+    extract_error_mode: ExtractErrorMode,
+    /// Whether `NotImplemented` is returned if `_slf` is not an instance of the class.
+    check_self_type: bool,
+    generic_getattr: bool,
+}
+
+/// Generates the body of a slot or slot fragment, which evaluates to a `PyResult` of the return
+/// value.
+fn impl_slot_body(
+    cls: &syn::Type,
+    spec: &FnSpec,
+    self_ty: &SelfType,
+    body: SlotBody,
+) -> syn::Result<TokenStream> {
+    let python_name = &spec.python_name;
+    ensure_spanned!(
+        spec.attrs.is_empty(),
+        python_name.span() => format!(
+            "`{}` cannot be used with `#[args]` or `#[pyo3(signature)]`",
+            python_name
+        )
+    );
+
+    let slot_args: Vec<usize> = body
+        .args
+        .iter()
+        .enumerate()
+        .filter(|(_, arg)| **arg != SlotArg::Ignored)
+        .map(|(i, _)| i)
+        .collect();
+    let num_args = spec.args.iter().filter(|arg| !arg.py).count();
+    ensure_spanned!(
+        num_args == slot_args.len(),
+        spec.name.span() => format!(
+            "`{}` expects {} argument{} after the receiver, found {}",
+            python_name,
+            slot_args.len(),
+            if slot_args.len() == 1 { "" } else { "s" },
+            num_args
+        )
+    );
+
+    let not_implemented = quote! {
+        return Ok(pyo3::IntoPyPointer::into_ptr(_py.NotImplemented()))
+    };
+
+    let mut statements = Vec::new();
+    if body.ret == SlotReturn::ReturnSelf {
+        statements.push(quote! { let _raw_slf = _slf; });
+    }
+    if body.check_self_type {
+        statements.push(quote! {
+            let _any = _py.from_borrowed_ptr::<pyo3::PyAny>(_slf);
+            if !<#cls as pyo3::type_object::PyTypeInfo>::is_type_of(_any) {
+                #not_implemented;
+            }
+        });
+    }
+    if body.generic_getattr {
+        // Behave like Python's `__getattr__` (as opposed to `__getattribute__`), which is only
+        // called if there is no existing attribute
+        statements.push(quote! {
+            let _existing = pyo3::ffi::PyObject_GenericGetAttr(_slf, _arg0);
+            if !_existing.is_null() {
+                return Ok(_existing);
+            }
+            let _err = pyo3::PyErr::fetch(_py);
+            if !_err.is_instance::<pyo3::exceptions::PyAttributeError>(_py) {
+                return Err(_err);
+            }
+        });
+    }
+    statements.push(self_ty.receiver(cls));
+
+    let names = get_arg_names(spec);
+    let mut slot_args = slot_args.into_iter();
+    for (arg, name) in spec.args.iter().zip(&names) {
+        if arg.py {
+            statements.push(quote_spanned! { arg.ty.span() => let #name = _py; });
+            continue;
+        }
+        let index = slot_args.next().unwrap();
+        let raw = raw_arg_name(index);
+        let obj = match body.args[index] {
+            SlotArg::CompareOp => {
+                statements.push(quote! {
+                    let #name = pyo3::class::basic::CompareOp::from_raw(#raw).ok_or_else(|| {
+                        pyo3::exceptions::PyValueError::new_err("invalid comparison operator")
+                    })?;
+                });
+                continue;
+            }
+            SlotArg::NullableObject => quote! {
+                _py.from_borrowed_ptr::<pyo3::PyAny>(
+                    if #raw.is_null() { pyo3::ffi::Py_None() } else { #raw }
+                )
+            },
+            _ => quote! { _py.from_borrowed_ptr::<pyo3::PyAny>(#raw) },
+        };
+
+        let arg_name = arg.name;
+        let on_error = match body.extract_error_mode {
+            ExtractErrorMode::Raise => quote! {
+                return Err(pyo3::derive_utils::argument_extraction_error(
+                    _py,
+                    stringify!(#arg_name),
+                    e,
+                ))
+            },
+            ExtractErrorMode::NotImplemented => quote! {{
+                let _ = e;
+                #not_implemented
+            }},
+        };
+        let extract = match &arg.attrs.from_py_with {
+            Some(FromPyWithAttribute(expr_path)) => quote! { #expr_path(_obj) },
+            None => quote! { _obj.extract() },
+        };
+        let extract = quote! {
+            match { let _obj = #obj; #extract } {
+                Ok(value) => value,
+                Err(e) => #on_error,
+            }
+        };
+
+        statements.push(match arg.ty {
+            syn::Type::Reference(tref) => {
+                let (tref, mut_) = preprocess_tref(tref, Some(cls));
+                let tmp = syn::Ident::new(&format!("_tmp_{}", name), Span::call_site());
+                quote_spanned! { arg.ty.span() =>
+                    let #mut_ #tmp: <#tref as pyo3::derive_utils::ExtractExt>::Target = #extract;
+                    let #name = &#mut_ *#tmp;
+                }
+            }
+            _ => quote_spanned! { arg.ty.span() => let #name = #extract; },
+        });
+    }
+
+    let fname = spec.name;
+    statements.push(quote! { let _result = #cls::#fname(_slf, #(#names),*); });
+
+    statements.push(match body.ret {
+        SlotReturn::Object => quote! {
+            pyo3::callback::convert::<_, *mut pyo3::ffi::PyObject>(_py, _result)
+        },
+        SlotReturn::IterNext => quote! {
+            let _next: pyo3::class::iter::PyIterNextOutput = pyo3::callback::convert(_py, _result)?;
+            pyo3::callback::convert(_py, _next)
+        },
+        SlotReturn::AsyncIterNext => quote! {
+            let _next: pyo3::class::pyasync::PyIterANextOutput =
+                pyo3::callback::convert(_py, _result)?;
+            pyo3::callback::convert(_py, _next)
+        },
+        SlotReturn::Hash => quote! {
+            let _hash: pyo3::callback::HashCallbackOutput = pyo3::callback::convert(_py, _result)?;
+            pyo3::callback::convert(_py, _hash)
+        },
+        SlotReturn::Length => quote! {
+            pyo3::callback::convert::<_, pyo3::ffi::Py_ssize_t>(_py, _result)
+        },
+        SlotReturn::Bool => quote! {
+            pyo3::callback::convert::<_, std::os::raw::c_int>(_py, _result)
+        },
+        SlotReturn::Unit => quote! {
+            pyo3::callback::convert::<_, ()>(_py, _result)
+        },
+        SlotReturn::ReturnSelf => quote! {
+            pyo3::callback::convert::<_, ()>(_py, _result)?;
+            pyo3::ffi::Py_INCREF(_raw_slf);
+            Ok(_raw_slf)
+        },
+    });
+
+    Ok(quote! { #(#statements)* })
+}
@@ -56,20 +56,10 @@ pub fn is_text_signature_attr(attr: &syn::Attribute) -> bool {
     attr.path.is_ident("text_signature")
 }
 
-fn parse_text_signature_attr(
-    attr: &syn::Attribute,
-    python_name: &syn::Ident,
-) -> syn::Result<Option<syn::LitStr>> {
+fn parse_text_signature_attr(attr: &syn::Attribute) -> syn::Result<Option<syn::LitStr>> {
     if !is_text_signature_attr(attr) {
         return Ok(None);
     }
-    let python_name_str = python_name.to_string();
-    let python_name_str = python_name_str
-        .rsplit('.')
-        .next()
-        .map(str::trim)
-        .filter(|v| !v.is_empty())
-        .ok_or_else(|| err_spanned!(python_name.span() => "failed to parse python name"))?;
     match attr.parse_meta()? {
         syn::Meta::NameValue(syn::MetaNameValue {
             lit: syn::Lit::Str(lit),
@@ -80,10 +70,7 @@ fn parse_text_signature_attr(
                 value.starts_with('(') && value.ends_with(')'),
                 lit.span() => "text_signature must start with \"(\" and end with \")\""
             );
-            Ok(Some(syn::LitStr::new(
-                &(python_name_str.to_owned() + &value),
-                lit.span(),
-            )))
+            Ok(Some(lit))
         }
         meta => bail_spanned!(
             meta.span() => "text_signature must be of the form #[text_signature = \"\"]"
@@ -91,14 +78,14 @@ fn parse_text_signature_attr(
     }
 }
 
-pub fn parse_text_signature_attrs(
+/// Extracts the `#[text_signature = "(...)"]` attribute, returning its value without the name.
+pub fn take_text_signature_attr(
     attrs: &mut Vec<syn::Attribute>,
-    python_name: &syn::Ident,
 ) -> syn::Result<Option<syn::LitStr>> {
     let mut text_signature = None;
     let mut attrs_out = Vec::with_capacity(attrs.len());
     for attr in attrs.drain(..) {
-        if let Some(value) = parse_text_signature_attr(&attr, python_name)? {
+        if let Some(value) = parse_text_signature_attr(&attr)? {
             ensure_spanned!(
                 text_signature.is_none(),
                 attr.span() => "text_signature attribute already specified previously"
@@ -112,6 +99,34 @@ pub fn parse_text_signature_attrs(
     Ok(text_signature)
 }
 
+/// Prepends the Python name to a text signature such as `(a, b)`, which is the form CPython
+/// expects at the start of a docstring.
+pub fn text_signature_with_name(
+    python_name: &syn::Ident,
+    text_signature: &syn::LitStr,
+) -> syn::Result<syn::LitStr> {
+    let python_name_str = python_name.to_string();
+    let python_name_str = python_name_str
+        .rsplit('.')
+        .next()
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .ok_or_else(|| err_spanned!(python_name.span() => "failed to parse python name"))?;
+    Ok(syn::LitStr::new(
+        &(python_name_str.to_owned() + &text_signature.value()),
+        text_signature.span(),
+    ))
+}
+
+pub fn parse_text_signature_attrs(
+    attrs: &mut Vec<syn::Attribute>,
+    python_name: &syn::Ident,
+) -> syn::Result<Option<syn::LitStr>> {
+    take_text_signature_attr(attrs)?
+        .map(|text_signature| text_signature_with_name(python_name, &text_signature))
+        .transpose()
+}
+
 // FIXME(althonos): not sure the docstring formatting is on par here.
 pub fn get_doc(
     attrs: &[syn::Attribute],
@@ -160,3 +175,10 @@ pub fn get_doc(
 
     Ok(syn::LitStr::new(&doc, span))
 }
+
+pub fn get_cfg_attributes(attrs: &[syn::Attribute]) -> Vec<&syn::Attribute> {
+    attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("cfg"))
+        .collect()
+}
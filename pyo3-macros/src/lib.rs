@@ -6,39 +6,72 @@ extern crate proc_macro;
 
 use proc_macro::TokenStream;
 use pyo3_macros_backend::{
-    build_derive_from_pyobject, build_py_class, build_py_function, build_py_methods,
-    build_py_proto, get_doc, process_functions_in_module, py_init, PyClassArgs, PyClassMethodsType,
-    PyFunctionAttr,
+    build_derive_from_pyobject, build_derive_into_pyobject, build_py_class, build_py_enum,
+    build_py_function, build_py_methods, build_py_proto, get_doc, inspect_module_ident,
+    module_info, module_item, process_functions_in_module, py_init, pymodule_module_impl,
+    PyClassArgs, PyClassMethodsType, PyFunctionAttr, PyModuleOptions,
 };
 use quote::quote;
 use syn::parse_macro_input;
 
 /// Internally, this proc macro create a new c function called `PyInit_{my_module}`
 /// that then calls the init function you provided
+///
+/// With `#[pymodule(multi_phase)]`, the module uses multi-phase initialization instead, and
+/// `#[pymodule(multi_phase, state = MyState)]` gives every module object a `MyState`.
+///
+/// On a `mod`, the `#[pyclass]`es, `#[pyfunction]`s and `#[pymodule] mod`s declared in it, and
+/// the items imported with `#[pymodule_export] use`, are added to the module, before calling the
+/// function marked `#[pymodule_init]`, if any.
 #[proc_macro_attribute]
 pub fn pymodule(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let mut ast = parse_macro_input!(input as syn::ItemFn);
-
-    let modname = if attr.is_empty() {
-        ast.sig.ident.clone()
-    } else {
-        parse_macro_input!(attr as syn::Ident)
+    let options = parse_macro_input!(attr as PyModuleOptions);
+    let mut ast = match parse_macro_input!(input as syn::Item) {
+        syn::Item::Fn(ast) => ast,
+        syn::Item::Mod(module) => {
+            return pymodule_module_impl(module, options)
+                .unwrap_or_else(|e| e.to_compile_error())
+                .into()
+        }
+        item => {
+            return syn::Error::new_spanned(
+                item,
+                "`#[pymodule]` can only be used on functions and modules",
+            )
+            .to_compile_error()
+            .into()
+        }
     };
+    let modname = options
+        .name
+        .clone()
+        .unwrap_or_else(|| ast.sig.ident.clone());
 
-    if let Err(err) = process_functions_in_module(&mut ast) {
-        return err.to_compile_error().into();
-    }
+    let pyfns = match process_functions_in_module(&mut ast) {
+        Ok(pyfns) => pyfns,
+        Err(err) => return err.to_compile_error().into(),
+    };
 
     let doc = match get_doc(&ast.attrs, None, false) {
         Ok(doc) => doc,
         Err(err) => return err.to_compile_error().into(),
     };
 
-    let expanded = py_init(&ast.sig.ident, &modname, doc);
+    let info = module_info(&ast, &modname, &doc, pyfns);
+    let expanded = py_init(&ast.sig.ident.clone().into(), &modname, doc, &options);
+    let info_ident = inspect_module_ident(&modname);
+    let module_item = module_item(
+        &ast.sig.ident,
+        &ast.vis,
+        quote!(module.add_wrapped(pyo3::wrap_pymodule!(#modname))),
+        quote!(info.submodules.push(#info_ident())),
+    );
 
     quote!(
         #ast
         #expanded
+        #info
+        #module_item
     )
     .into()
 }
@@ -99,15 +132,32 @@ pub fn derive_from_py_object(item: TokenStream) -> TokenStream {
     .into()
 }
 
+#[proc_macro_derive(IntoPyObject, attributes(pyo3))]
+pub fn derive_into_py_object(item: TokenStream) -> TokenStream {
+    let ast = parse_macro_input!(item as syn::DeriveInput);
+    let expanded = build_derive_into_pyobject(&ast).unwrap_or_else(|e| e.to_compile_error());
+    quote!(
+        #expanded
+    )
+    .into()
+}
+
 fn pyclass_impl(
     attr: TokenStream,
     input: TokenStream,
     methods_type: PyClassMethodsType,
 ) -> TokenStream {
-    let mut ast = parse_macro_input!(input as syn::ItemStruct);
+    let mut ast = parse_macro_input!(input as syn::Item);
     let args = parse_macro_input!(attr as PyClassArgs);
-    let expanded =
-        build_py_class(&mut ast, &args, methods_type).unwrap_or_else(|e| e.to_compile_error());
+    let expanded = match &mut ast {
+        syn::Item::Struct(struct_) => build_py_class(struct_, &args, methods_type),
+        syn::Item::Enum(enum_) => build_py_enum(enum_, &args, methods_type),
+        unsupported => Err(syn::Error::new_spanned(
+            unsupported,
+            "#[pyclass] only supports structs and enums",
+        )),
+    }
+    .unwrap_or_else(|e| e.to_compile_error());
 
     quote!(
         #ast
@@ -0,0 +1,283 @@
+//! Interoperability with Python's [`asyncio`](https://docs.python.org/3/library/asyncio.html):
+//! Rust futures as Python awaitables, and Python awaitables as Rust futures.
+//!
+//! [`Awaitable`] wraps a Rust future so that it can be returned to Python, where it is awaited
+//! as an `asyncio.Future`. By default the Rust future is polled by the running event loop itself,
+//! and [`Awaitable::into_py_future_with`] hands it to any other executor instead (see
+//! [`tokio`](self::tokio) for the Tokio adapter). [`into_future`] goes the other way, and runs
+//! a Python coroutine or other awaitable as an `asyncio.Task` which a Rust future waits for.
+//!
+//! Cancellation propagates in both directions: cancelling the `asyncio.Future` of an
+//! [`Awaitable`] drops its Rust future, and dropping the future returned by [`into_future`]
+//! before it completes cancels its task.
+//!
+//! # Example
+//! ```rust
+//! use pyo3::asyncio::{self, Awaitable};
+//! use pyo3::prelude::*;
+//! use pyo3::types::PyAny;
+//! use pyo3::wrap_pyfunction;
+//!
+//! /// Awaits two Python awaitables one after the other.
+//! #[pyfunction]
+//! fn await_both(first: &PyAny, second: &PyAny) -> PyResult<Awaitable> {
+//!     let first = asyncio::into_future(first)?;
+//!     let second = asyncio::into_future(second)?;
+//!     Ok(Awaitable::new(async move {
+//!         let first = first.await?;
+//!         let second = second.await?;
+//!         Ok((first, second))
+//!     }))
+//! }
+//!
+//! Python::with_gil(|py| -> PyResult<()> {
+//!     let example = PyModule::from_code(py, r#"
+//! import asyncio
+//!
+//! async def value(x):
+//!     await asyncio.sleep(0)
+//!     return x
+//!
+//! async def main(await_both):
+//!     return await await_both(value(1), value(2))
+//! "#, "example.py", "example")?;
+//!
+//!     let main = example.call1("main", (wrap_pyfunction!(await_both)(py)?,))?;
+//!     let event_loop = py.import("asyncio")?.call_method0("new_event_loop")?;
+//!     let result = event_loop.call_method1("run_until_complete", (main,))?;
+//!     event_loop.call_method0("close")?;
+//!     assert_eq!(result.extract::<(i32, i32)>()?, (1, 2));
+//!     Ok(())
+//! })
+//! .unwrap();
+//! ```
+
+use crate::callback::IntoPyCallbackOutput;
+use crate::exceptions::asyncio::CancelledError;
+use crate::inspect::TypeInfo;
+use crate::panic::PanicException;
+use crate::types::{PyAny, PyCFunction};
+use crate::{ffi, IntoPy, IntoPyPointer, PyNativeType, PyObject, PyResult, Python};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+mod driver;
+mod task;
+#[cfg(feature = "tokio")]
+pub mod tokio;
+mod waker;
+
+pub use self::task::{into_future, into_future_with_loop, TaskFuture};
+pub(crate) use self::waker::{arc_waker, Wake};
+
+/// A boxed future which can be sent to another thread, as passed to the `spawn` function of
+/// [`Awaitable::into_py_future_with`].
+pub type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send + 'static>>;
+
+/// Returns the running event loop of the current thread.
+///
+/// This fails with `RuntimeError` when no event loop is running, i.e. outside of a coroutine
+/// or callback of an event loop.
+pub fn get_running_loop(py: Python) -> PyResult<&PyAny> {
+    let asyncio = py.import("asyncio")?;
+    // `get_running_loop` was added in Python 3.7
+    match asyncio.getattr("get_running_loop") {
+        Ok(get_running_loop) => get_running_loop.call0(),
+        Err(_) => asyncio.call_method0("get_event_loop"),
+    }
+}
+
+/// A Rust future which can be awaited in Python.
+///
+/// It is converted to an `asyncio.Future` of the running event loop when it is returned from a
+/// `#[pyfunction]` or `#[pymethods]` method, or by [`Awaitable::into_py_future`]. Such functions
+/// must therefore be called while the event loop is running, typically from a coroutine. The value
+/// of the Rust future is converted to a Python object, and errors are raised from `await`.
+///
+/// If the `asyncio.Future` is cancelled, the Rust future is dropped without being polled again.
+/// Conversely, the `asyncio.Future` is cancelled when the Rust future fails with
+/// [`CancelledError`], or when it is dropped before completing, e.g. because the executor which
+/// polls it is shut down.
+pub struct Awaitable {
+    future: BoxFuture<PyResult<PyObject>>,
+}
+
+impl Awaitable {
+    /// Wraps `future`, whose value is converted to a Python object when it completes.
+    pub fn new<F, T>(future: F) -> Self
+    where
+        F: Future<Output = PyResult<T>> + Send + 'static,
+        T: IntoPy<PyObject>,
+    {
+        Awaitable {
+            future: Box::pin(async move {
+                let value = future.await?;
+                Ok(Python::with_gil(|py| value.into_py(py)))
+            }),
+        }
+    }
+
+    /// Converts this to an `asyncio.Future` of the running event loop, which polls the Rust future
+    /// on the thread of the event loop.
+    ///
+    /// The Rust future is polled with the GIL held, so it should not block: long computations
+    /// belong on another executor, see [`Awaitable::into_py_future_with`].
+    pub fn into_py_future(self, py: Python) -> PyResult<&PyAny> {
+        let event_loop = get_running_loop(py)?;
+        self.into_py_future_with(event_loop, |future| driver::spawn(event_loop, future))
+    }
+
+    /// Converts this to an `asyncio.Future` of `event_loop`, and passes the Rust future to `spawn`
+    /// to run it on any executor.
+    ///
+    /// The future passed to `spawn` resolves the `asyncio.Future` when it completes, so it only
+    /// has to be polled to completion or dropped.
+    pub fn into_py_future_with<S>(self, event_loop: &PyAny, spawn: S) -> PyResult<&PyAny>
+    where
+        S: FnOnce(BoxFuture<()>),
+    {
+        let py = event_loop.py();
+        let py_future = event_loop.call_method0("create_future")?;
+        let cancellation = Arc::new(Cancellation::default());
+        let on_done = {
+            let cancellation = cancellation.clone();
+            PyCFunction::new_closure(py, move |args, _| -> PyResult<()> {
+                if args.get_item(0).call_method0("cancelled")?.is_true()? {
+                    cancellation.cancel();
+                }
+                Ok(())
+            })?
+        };
+        py_future.call_method1("add_done_callback", (on_done,))?;
+        spawn(Box::pin(Spawned {
+            future: self.future,
+            py_future: Some(py_future.into()),
+            event_loop: event_loop.into(),
+            cancellation,
+        }));
+        Ok(py_future)
+    }
+}
+
+impl IntoPyCallbackOutput<*mut ffi::PyObject> for Awaitable {
+    fn convert(self, py: Python) -> PyResult<*mut ffi::PyObject> {
+        Ok(self.into_py_future(py)?.into_ptr())
+    }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Class {
+            module: Some("asyncio"),
+            name: "Future",
+        }
+    }
+}
+
+/// Converts `future` to an `asyncio.Future` of the running event loop, which polls it on the
+/// thread of the event loop.
+///
+/// This is a shorthand for `Awaitable::new(future).into_py_future(py)`.
+pub fn future_into_py<F, T>(py: Python, future: F) -> PyResult<&PyAny>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: IntoPy<PyObject>,
+{
+    Awaitable::new(future).into_py_future(py)
+}
+
+/// Tells the Rust future of an [`Awaitable`] that its `asyncio.Future` was cancelled.
+#[derive(Default)]
+struct Cancellation {
+    cancelled: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Cancellation {
+    fn cancel(&self) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let waker = self.waker.lock().take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+}
+
+/// The future spawned by [`Awaitable::into_py_future_with`], which resolves the `asyncio.Future`.
+struct Spawned {
+    future: BoxFuture<PyResult<PyObject>>,
+    /// The `asyncio.Future`, until it is resolved.
+    py_future: Option<PyObject>,
+    event_loop: PyObject,
+    cancellation: Arc<Cancellation>,
+}
+
+impl Future for Spawned {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let this = &mut *self;
+        *this.cancellation.waker.lock() = Some(cx.waker().clone());
+        if this.cancellation.is_cancelled() {
+            this.py_future = None;
+            return Poll::Ready(());
+        }
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| this.future.as_mut().poll(cx))) {
+            Ok(Poll::Pending) => return Poll::Pending,
+            Ok(Poll::Ready(result)) => result,
+            Err(payload) => Err(PanicException::from_panic_payload(payload)),
+        };
+        if let Some(py_future) = this.py_future.take() {
+            let event_loop = &this.event_loop;
+            Python::with_gil(|py| {
+                // This only fails when the event loop is closed, and then nobody is waiting.
+                let _ = resolve(event_loop.as_ref(py), py_future, result);
+            });
+        }
+        Poll::Ready(())
+    }
+}
+
+impl Drop for Spawned {
+    fn drop(&mut self) {
+        if let Some(py_future) = self.py_future.take() {
+            let event_loop = &self.event_loop;
+            Python::with_gil(|py| {
+                let cancelled = CancelledError::new_err("the Rust future was dropped");
+                let _ = resolve(event_loop.as_ref(py), py_future, Err(cancelled));
+            });
+        }
+    }
+}
+
+/// Sets the result of `py_future` on the thread of `event_loop`, unless it is done already.
+///
+/// A `CancelledError` cancels `py_future`, and other errors are set as its exception.
+fn resolve(event_loop: &PyAny, py_future: PyObject, result: PyResult<PyObject>) -> PyResult<()> {
+    let py = event_loop.py();
+    let (method, arg) = match result {
+        Ok(value) => ("set_result", Some(value)),
+        Err(err) if err.is_instance::<CancelledError>(py) => ("cancel", None),
+        Err(err) => ("set_exception", Some(err.into_instance(py).into_py(py))),
+    };
+    let callback = PyCFunction::new_closure(py, move |args, _| -> PyResult<()> {
+        let py = args.py();
+        let py_future = py_future.as_ref(py);
+        if !py_future.call_method0("done")?.is_true()? {
+            match &arg {
+                Some(arg) => py_future.call_method1(method, (arg.clone_ref(py),))?,
+                None => py_future.call_method0(method)?,
+            };
+        }
+        Ok(())
+    })?;
+    event_loop.call_method1("call_soon_threadsafe", (callback,))?;
+    Ok(())
+}
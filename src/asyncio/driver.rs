@@ -0,0 +1,69 @@
+//! A minimal executor which polls futures on the thread of an `asyncio` event loop.
+
+use super::waker::{arc_waker, Wake};
+use super::BoxFuture;
+use crate::types::{PyAny, PyCFunction};
+use crate::{PyObject, PyResult, Python};
+use parking_lot::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::Context;
+
+/// Polls `future` from callbacks of `event_loop`, starting with the next iteration of the loop.
+pub(super) fn spawn(event_loop: &PyAny, future: BoxFuture<()>) {
+    let task = Arc::new(Task {
+        future: Mutex::new(Some(future)),
+        event_loop: event_loop.into(),
+        scheduled: AtomicBool::new(false),
+    });
+    schedule(&task);
+}
+
+struct Task {
+    /// The future, until it completes.
+    future: Mutex<Option<BoxFuture<()>>>,
+    event_loop: PyObject,
+    /// Whether a callback polling the future is already waiting on the event loop.
+    scheduled: AtomicBool,
+}
+
+impl Wake for Task {
+    fn wake(self: &Arc<Self>) {
+        schedule(self);
+    }
+}
+
+/// Asks the event loop to poll the future of `task`. This is what waking the task does, so
+/// it may be called from any thread.
+fn schedule(task: &Arc<Task>) {
+    if task.scheduled.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    Python::with_gil(|py| {
+        // If the event loop is closed, the future is dropped together with its last waker.
+        let _ = call_soon_threadsafe(py, task);
+    })
+}
+
+fn call_soon_threadsafe(py: Python, task: &Arc<Task>) -> PyResult<()> {
+    let callback = {
+        let task = task.clone();
+        PyCFunction::new_closure(py, move |_, _| run(&task))?
+    };
+    task.event_loop
+        .call_method1(py, "call_soon_threadsafe", (callback,))?;
+    Ok(())
+}
+
+/// Polls the future of `task`, on the thread of the event loop.
+fn run(task: &Arc<Task>) {
+    task.scheduled.store(false, Ordering::SeqCst);
+    let waker = arc_waker(task.clone());
+    let mut cx = Context::from_waker(&waker);
+    let mut future = task.future.lock();
+    if let Some(f) = future.as_mut() {
+        if f.as_mut().poll(&mut cx).is_ready() {
+            *future = None;
+        }
+    }
+}
@@ -0,0 +1,149 @@
+//! Python awaitables as Rust futures.
+
+use super::get_running_loop;
+use crate::types::{IntoPyDict, PyAny, PyCFunction};
+use crate::{PyNativeType, PyObject, PyResult, Python};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// Runs `awaitable`, such as a coroutine, as a task of the running event loop, and returns a
+/// Rust future which completes with the result of the task.
+///
+/// See [`into_future_with_loop`] for details.
+pub fn into_future(awaitable: &PyAny) -> PyResult<TaskFuture> {
+    into_future_with_loop(get_running_loop(awaitable.py())?, awaitable)
+}
+
+/// Runs `awaitable`, such as a coroutine, as a task of `event_loop`, and returns a Rust future
+/// which completes with the result of the task.
+///
+/// The task is created on the thread of the event loop, so the returned future may be awaited
+/// on any thread and executor, as long as the event loop is running. Exceptions raised by the
+/// task, including `CancelledError` when the task is cancelled, are returned as errors.
+///
+/// Dropping the returned future before it completes cancels the task.
+pub fn into_future_with_loop(event_loop: &PyAny, awaitable: &PyAny) -> PyResult<TaskFuture> {
+    let py = event_loop.py();
+    let state = Arc::new(Mutex::new(TaskState::default()));
+    let start = {
+        let state = state.clone();
+        let event_loop: PyObject = event_loop.into();
+        let awaitable: PyObject = awaitable.into();
+        PyCFunction::new_closure(py, move |args, _| {
+            let py = args.py();
+            if let Err(err) = start_task(&state, event_loop.as_ref(py), awaitable.as_ref(py)) {
+                complete(&state, Err(err));
+            }
+        })?
+    };
+    event_loop.call_method1("call_soon_threadsafe", (start,))?;
+    Ok(TaskFuture {
+        state,
+        event_loop: event_loop.into(),
+    })
+}
+
+/// A Rust future which completes with the result of an `asyncio.Task`, see [`into_future`].
+pub struct TaskFuture {
+    state: Arc<Mutex<TaskState>>,
+    event_loop: PyObject,
+}
+
+#[derive(Default)]
+struct TaskState {
+    /// The task, once it is created.
+    task: Option<PyObject>,
+    /// The result of the task, until it is returned by `poll`.
+    result: Option<PyResult<PyObject>>,
+    /// Whether the task is done.
+    done: bool,
+    /// Whether the `TaskFuture` was dropped.
+    dropped: bool,
+    waker: Option<Waker>,
+}
+
+fn start_task(
+    state: &Arc<Mutex<TaskState>>,
+    event_loop: &PyAny,
+    awaitable: &PyAny,
+) -> PyResult<()> {
+    let py = event_loop.py();
+    let kwargs = [("loop", event_loop)].into_py_dict(py);
+    let task = py
+        .import("asyncio")?
+        .call_method("ensure_future", (awaitable,), Some(kwargs))?;
+    let on_done = {
+        let state = state.clone();
+        PyCFunction::new_closure(py, move |args, _| {
+            let result = args.get_item(0).call_method0("result");
+            complete(&state, result.map(PyObject::from));
+        })?
+    };
+    task.call_method1("add_done_callback", (on_done,))?;
+    let dropped = {
+        let mut state = state.lock();
+        state.task = Some(task.into());
+        state.dropped
+    };
+    if dropped {
+        task.call_method0("cancel")?;
+    }
+    Ok(())
+}
+
+fn complete(state: &Mutex<TaskState>, result: PyResult<PyObject>) {
+    let waker = {
+        let mut state = state.lock();
+        state.result = Some(result);
+        state.done = true;
+        state.waker.take()
+    };
+    if let Some(waker) = waker {
+        waker.wake();
+    }
+}
+
+fn cancel_task(py: Python, event_loop: &PyObject, task: PyObject) -> PyResult<()> {
+    let cancel = task.getattr(py, "cancel")?;
+    event_loop.call_method1(py, "call_soon_threadsafe", (cancel,))?;
+    Ok(())
+}
+
+impl Future for TaskFuture {
+    type Output = PyResult<PyObject>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Self::Output> {
+        let mut state = self.state.lock();
+        match state.result.take() {
+            Some(result) => Poll::Ready(result),
+            None => {
+                state.waker = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl Drop for TaskFuture {
+    fn drop(&mut self) {
+        let task = {
+            let mut state = self.state.lock();
+            state.dropped = true;
+            if state.done {
+                return;
+            }
+            state.task.take()
+        };
+        // If the task is not created yet, `start_task` cancels it right away.
+        if let Some(task) = task {
+            let event_loop = &self.event_loop;
+            Python::with_gil(|py| {
+                // This only fails when the event loop is closed, and then the task never runs.
+                let _ = cancel_task(py, event_loop, task);
+            });
+        }
+    }
+}
@@ -0,0 +1,65 @@
+//! Running the Rust futures of [`Awaitable`](super::Awaitable)s on a [Tokio](https://tokio.rs/)
+//! runtime.
+//!
+//! This module is only available with the `tokio` feature.
+
+use super::{get_running_loop, Awaitable};
+use crate::{IntoPy, PyAny, PyObject, PyResult, Python};
+use ::tokio::runtime::Handle;
+use std::future::Future;
+
+/// Converts `future` to an `asyncio.Future` of the running event loop, and spawns it on the
+/// Tokio runtime of `handle`.
+///
+/// Unlike [`future_into_py`](super::future_into_py), the future is polled on the threads of the
+/// runtime, without holding the GIL. The `asyncio.Future` is cancelled if the runtime is shut
+/// down before the future completes.
+///
+/// # Example
+/// ```rust
+/// use pyo3::prelude::*;
+/// use pyo3::wrap_pyfunction;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// static CALLS: AtomicUsize = AtomicUsize::new(0);
+///
+/// fn runtime() -> &'static tokio::runtime::Runtime {
+///     static RUNTIME: pyo3::once_cell::GILOnceCell<tokio::runtime::Runtime> =
+///         pyo3::once_cell::GILOnceCell::new();
+///     let py = unsafe { Python::assume_gil_acquired() };
+///     RUNTIME.get_or_init(py, || {
+///         tokio::runtime::Builder::new_multi_thread().build().unwrap()
+///     })
+/// }
+///
+/// #[pyfunction]
+/// fn count(py: Python) -> PyResult<&PyAny> {
+///     pyo3::asyncio::tokio::future_into_py(py, runtime().handle(), async {
+///         Ok(CALLS.fetch_add(1, Ordering::SeqCst) + 1)
+///     })
+/// }
+///
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let example = PyModule::from_code(py, r#"
+/// async def main(count):
+///     return [await count(), await count()]
+/// "#, "example.py", "example")?;
+///
+///     let main = example.call1("main", (wrap_pyfunction!(count)(py)?,))?;
+///     let event_loop = py.import("asyncio")?.call_method0("new_event_loop")?;
+///     let result = event_loop.call_method1("run_until_complete", (main,))?;
+///     event_loop.call_method0("close")?;
+///     assert_eq!(result.extract::<Vec<usize>>()?, [1, 2]);
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+pub fn future_into_py<'p, F, T>(py: Python<'p>, handle: &Handle, future: F) -> PyResult<&'p PyAny>
+where
+    F: Future<Output = PyResult<T>> + Send + 'static,
+    T: IntoPy<PyObject>,
+{
+    Awaitable::new(future).into_py_future_with(get_running_loop(py)?, |future| {
+        handle.spawn(future);
+    })
+}
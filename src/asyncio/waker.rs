@@ -0,0 +1,46 @@
+//! Wakers backed by an `Arc`, for the executors of this module.
+
+use std::mem::ManuallyDrop;
+use std::sync::Arc;
+use std::task::{RawWaker, RawWakerVTable, Waker};
+
+/// What a [`Waker`] created by [`arc_waker`] does when it is woken.
+///
+/// This is `std::task::Wake`, which needs a newer compiler than PyO3 supports.
+pub(crate) trait Wake: Send + Sync + 'static {
+    fn wake(self: &Arc<Self>);
+}
+
+/// Creates a [`Waker`] which holds a reference to `inner`, and calls [`Wake::wake`] on it.
+pub(crate) fn arc_waker<W: Wake>(inner: Arc<W>) -> Waker {
+    unsafe { Waker::from_raw(raw_waker(inner)) }
+}
+
+fn raw_waker<W: Wake>(inner: Arc<W>) -> RawWaker {
+    RawWaker::new(
+        Arc::into_raw(inner) as *const (),
+        &RawWakerVTable::new(
+            clone_waker::<W>,
+            wake::<W>,
+            wake_by_ref::<W>,
+            drop_waker::<W>,
+        ),
+    )
+}
+
+unsafe fn clone_waker<W: Wake>(ptr: *const ()) -> RawWaker {
+    let inner = ManuallyDrop::new(Arc::from_raw(ptr as *const W));
+    raw_waker(Arc::clone(&inner))
+}
+
+unsafe fn wake<W: Wake>(ptr: *const ()) {
+    Arc::from_raw(ptr as *const W).wake();
+}
+
+unsafe fn wake_by_ref<W: Wake>(ptr: *const ()) {
+    ManuallyDrop::new(Arc::from_raw(ptr as *const W)).wake();
+}
+
+unsafe fn drop_waker<W: Wake>(ptr: *const ()) {
+    drop(Arc::from_raw(ptr as *const W));
+}
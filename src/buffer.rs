@@ -59,6 +59,28 @@ impl ElementType {
             _ => ElementType::Unknown,
         }
     }
+
+    /// Returns the native `struct` module style format string for this element type, or `None`
+    /// if there is no such format (e.g. for `ElementType::Unknown`).
+    pub fn to_format(self) -> Option<&'static CStr> {
+        use self::ElementType::*;
+        let format: &'static [u8] = match self {
+            SignedInteger { bytes: 1 } => b"b\0",
+            SignedInteger { bytes: 2 } => b"h\0",
+            SignedInteger { bytes: 4 } => b"i\0",
+            SignedInteger { bytes: 8 } => b"q\0",
+            UnsignedInteger { bytes: 1 } => b"B\0",
+            UnsignedInteger { bytes: 2 } => b"H\0",
+            UnsignedInteger { bytes: 4 } => b"I\0",
+            UnsignedInteger { bytes: 8 } => b"Q\0",
+            Bool => b"?\0",
+            Float { bytes: 2 } => b"e\0",
+            Float { bytes: 4 } => b"f\0",
+            Float { bytes: 8 } => b"d\0",
+            _ => return None,
+        };
+        CStr::from_bytes_with_nul(format).ok()
+    }
 }
 
 fn native_element_type_from_type_char(type_char: u8) -> ElementType {
@@ -145,6 +167,13 @@ pub unsafe trait Element: Copy {
     /// Gets whether the element specified in the format string is potentially compatible.
     /// Alignment and size are checked separately from this function.
     fn is_compatible_format(format: &CStr) -> bool;
+
+    /// Gets the element type to use when exporting a buffer of this element.
+    ///
+    /// The default implementation returns `ElementType::Unknown`, which cannot be exported.
+    fn element_type() -> ElementType {
+        ElementType::Unknown
+    }
 }
 
 fn validate(b: &ffi::Py_buffer) -> PyResult<()> {
@@ -545,6 +574,11 @@ impl<T: Element> PyBuffer<T> {
         }
     }
 
+    /// Gets the raw `Py_buffer`, which stays at the same address as long as `self` exists.
+    pub(crate) fn raw(&self) -> &ffi::Py_buffer {
+        &self.0
+    }
+
     pub fn release(self, _py: Python) {
         // First move self into a ManuallyDrop, so that PyBuffer::drop will
         // never be called. (It would acquire the GIL and call PyBuffer_Release
@@ -603,7 +637,11 @@ macro_rules! impl_element(
                 if slice.len() > 1 && !is_matching_endian(slice[0]) {
                     return false;
                 }
-                ElementType::from_format(format) == ElementType::$f { bytes: mem::size_of::<$t>() }
+                ElementType::from_format(format) == Self::element_type()
+            }
+
+            fn element_type() -> ElementType {
+                ElementType::$f { bytes: mem::size_of::<$t>() }
             }
         }
     }
@@ -624,7 +662,7 @@ impl_element!(f64, Float);
 
 #[cfg(test)]
 mod test {
-    use super::PyBuffer;
+    use super::{Element, ElementType, PyBuffer};
     use crate::ffi;
     use crate::Python;
 
@@ -699,4 +737,22 @@ mod test {
 
         assert_eq!(buffer.to_vec(py).unwrap(), [10.0, 11.0, 12.0, 13.0]);
     }
+
+    #[test]
+    fn test_element_type_format_roundtrip() {
+        fn check<T: Element>(expected: &str) {
+            let format = T::element_type().to_format().unwrap();
+            assert_eq!(format.to_str().unwrap(), expected);
+            assert!(T::is_compatible_format(format));
+            assert!(ElementType::from_format(format) == T::element_type());
+        }
+
+        check::<u8>("B");
+        check::<i16>("h");
+        check::<u32>("I");
+        check::<i64>("q");
+        check::<f32>("f");
+        check::<f64>("d");
+        assert!(ElementType::Unknown.to_format().is_none());
+    }
 }
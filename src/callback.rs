@@ -5,6 +5,7 @@
 use crate::err::{PyErr, PyResult};
 use crate::exceptions::PyOverflowError;
 use crate::ffi::{self, Py_hash_t};
+use crate::inspect::TypeInfo;
 use crate::IntoPyPointer;
 use crate::{IntoPy, PyObject, Python};
 use std::isize;
@@ -36,6 +37,11 @@ impl PyCallbackOutput for () {
 /// Convert the result of callback function into the appropriate return value.
 pub trait IntoPyCallbackOutput<Target> {
     fn convert(self, py: Python) -> PyResult<Target>;
+
+    /// The Python type of the converted value, see [IntoPy::type_output].
+    fn type_output() -> TypeInfo {
+        TypeInfo::Any
+    }
 }
 
 impl<T, E, U> IntoPyCallbackOutput<U> for Result<T, E>
@@ -46,6 +52,10 @@ where
     fn convert(self, py: Python) -> PyResult<U> {
         self.map_err(Into::into).and_then(|t| t.convert(py))
     }
+
+    fn type_output() -> TypeInfo {
+        <T as IntoPyCallbackOutput<U>>::type_output()
+    }
 }
 
 impl<T> IntoPyCallbackOutput<*mut ffi::PyObject> for T
@@ -55,6 +65,10 @@ where
     fn convert(self, py: Python) -> PyResult<*mut ffi::PyObject> {
         Ok(self.into_py(py).into_ptr())
     }
+
+    fn type_output() -> TypeInfo {
+        <T as IntoPy<PyObject>>::type_output()
+    }
 }
 
 impl IntoPyCallbackOutput<Self> for *mut ffi::PyObject {
@@ -113,6 +127,10 @@ where
     fn convert(self, py: Python) -> PyResult<PyObject> {
         Ok(self.into_py(py))
     }
+
+    fn type_output() -> TypeInfo {
+        <T as IntoPy<PyObject>>::type_output()
+    }
 }
 
 pub trait WrappingCastTo<T> {
@@ -242,18 +260,7 @@ where
     let result =
         match std::panic::catch_unwind(move || -> crate::PyResult<_> { body(*unwind_safe_py) }) {
             Ok(result) => result,
-            Err(e) => {
-                // Try to format the error in the same way panic does
-                if let Some(string) = e.downcast_ref::<String>() {
-                    Err(crate::panic::PanicException::new_err((string.clone(),)))
-                } else if let Some(s) = e.downcast_ref::<&str>() {
-                    Err(crate::panic::PanicException::new_err((s.to_string(),)))
-                } else {
-                    Err(crate::panic::PanicException::new_err((
-                        "panic from Rust code",
-                    )))
-                }
-            }
+            Err(payload) => Err(crate::panic::PanicException::from_panic_payload(payload)),
         };
 
     result.unwrap_or_else(|e| {
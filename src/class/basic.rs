@@ -23,6 +23,22 @@ pub enum CompareOp {
     Ge = ffi::Py_GE as isize,
 }
 
+impl CompareOp {
+    /// Converts the `op` argument of `tp_richcompare`.
+    #[doc(hidden)]
+    pub fn from_raw(op: c_int) -> Option<Self> {
+        match op {
+            ffi::Py_LT => Some(CompareOp::Lt),
+            ffi::Py_LE => Some(CompareOp::Le),
+            ffi::Py_EQ => Some(CompareOp::Eq),
+            ffi::Py_NE => Some(CompareOp::Ne),
+            ffi::Py_GT => Some(CompareOp::Gt),
+            ffi::Py_GE => Some(CompareOp::Ge),
+            _ => None,
+        }
+    }
+}
+
 /// Basic Python class customization
 #[allow(unused_variables)]
 pub trait PyObjectProtocol<'p>: PyClass {
@@ -174,19 +190,11 @@ where
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<crate::PyCell<T>>(slf);
         let arg = extract_or_return_not_implemented!(py, arg);
-        let op = match op {
-            ffi::Py_LT => CompareOp::Lt,
-            ffi::Py_LE => CompareOp::Le,
-            ffi::Py_EQ => CompareOp::Eq,
-            ffi::Py_NE => CompareOp::Ne,
-            ffi::Py_GT => CompareOp::Gt,
-            ffi::Py_GE => CompareOp::Ge,
-            _ => {
-                return Err(exceptions::PyValueError::new_err(
-                    "tp_richcompare called with invalid comparison operator",
-                ))
-            }
-        };
+        let op = CompareOp::from_raw(op).ok_or_else(|| {
+            exceptions::PyValueError::new_err(
+                "tp_richcompare called with invalid comparison operator",
+            )
+        })?;
 
         slf.try_borrow()?.__richcmp__(arg, op).convert(py)
     })
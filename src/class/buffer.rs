@@ -5,6 +5,7 @@
 //! For more information check [buffer protocol](https://docs.python.org/3/c-api/buffer.html)
 //! c-api
 use crate::callback::IntoPyCallbackOutput;
+use crate::pyclass::boolean_struct::False;
 use crate::{ffi, PyCell, PyClass, PyRefMut};
 use std::os::raw::c_int;
 
@@ -41,6 +42,7 @@ pub unsafe extern "C" fn getbuffer<T>(
 ) -> c_int
 where
     T: for<'p> PyBufferGetBufferProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
@@ -52,6 +54,7 @@ where
 pub unsafe extern "C" fn releasebuffer<T>(slf: *mut ffi::PyObject, arg1: *mut ffi::Py_buffer)
 where
     T: for<'p> PyBufferReleaseBufferProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<crate::PyCell<T>>(slf);
@@ -3,6 +3,7 @@
 //! Python GC support
 //!
 
+use crate::pyclass::boolean_struct::False;
 use crate::{ffi, AsPyPointer, PyCell, PyClass, Python};
 use std::os::raw::{c_int, c_void};
 
@@ -51,6 +52,7 @@ where
 pub unsafe extern "C" fn clear<T>(slf: *mut ffi::PyObject) -> c_int
 where
     T: for<'p> PyGCClearProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     let pool = crate::GILPool::new();
     let slf = pool.python().from_borrowed_ptr::<PyCell<T>>(slf);
@@ -1,7 +1,26 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
-use crate::{derive_utils::PyBaseTypeUtils, ffi, PyMethodDefType, PyNativeType};
-use std::{marker::PhantomData, thread};
+use crate::{
+    callback::HashCallbackOutput,
+    class::basic::CompareOp,
+    derive_utils::PyBaseTypeUtils,
+    exceptions::PyNotImplementedError,
+    ffi,
+    inspect::{ClassInfo, FunctionInfo},
+    pyclass::boolean_struct::{False, True},
+    type_object::{PyBorrowFlagLayout, PyFrozenLayout},
+    types::PyString,
+    IntoPy, IntoPyPointer, PyAny, PyCell, PyClass, PyErr, PyMethodDefType, PyNativeType, PyResult,
+    PyTryFrom, Python,
+};
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    os::raw::c_int,
+    thread,
+};
 
 /// This type is used as a "dummy" type on which dtolnay specializations are
 /// applied to apply implementations from `#[pymethods]` & `#[pyproto]`
@@ -45,6 +64,10 @@ pub trait PyClassImpl: Sized {
     fn get_new() -> Option<ffi::newfunc> {
         None
     }
+    /// The text signature generated for `#[new]`, such as `(a, b=None)`.
+    fn get_new_text_signature() -> Option<&'static str> {
+        None
+    }
     fn get_call() -> Option<ffi::PyCFunctionWithKeywords> {
         None
     }
@@ -52,6 +75,13 @@ pub trait PyClassImpl: Sized {
     fn get_buffer() -> Option<&'static PyBufferProcs> {
         None
     }
+    /// Describes the class for type stubs, see [crate::inspect].
+    fn class_info() -> ClassInfo
+    where
+        Self: PyClass,
+    {
+        ClassInfo::new::<Self>()
+    }
 }
 
 // Traits describing known special methods.
@@ -66,6 +96,16 @@ impl<T> PyClassNewImpl<T> for &'_ PyClassImplCollector<T> {
     }
 }
 
+pub trait PyClassNewTextSignature<T> {
+    fn new_text_signature(self) -> Option<&'static str>;
+}
+
+impl<T> PyClassNewTextSignature<T> for &'_ PyClassImplCollector<T> {
+    fn new_text_signature(self) -> Option<&'static str> {
+        None
+    }
+}
+
 pub trait PyClassCallImpl<T> {
     fn call_impl(self) -> Option<ffi::PyCFunctionWithKeywords>;
 }
@@ -100,10 +140,20 @@ macro_rules! methods_trait {
 #[cfg(all(feature = "macros", feature = "multiple-pymethods"))]
 pub trait PyMethodsInventory: inventory::Collect {
     /// Create a new instance
-    fn new(methods: Vec<PyMethodDefType>) -> Self;
+    fn new(
+        methods: Vec<PyMethodDefType>,
+        slots: fn() -> &'static [ffi::PyType_Slot],
+        info: fn() -> Vec<FunctionInfo>,
+    ) -> Self;
 
     /// Returns the methods for a single `#[pymethods] impl` block
     fn get(&'static self) -> &'static [PyMethodDefType];
+
+    /// Returns the slots of the protocol methods for a single `#[pymethods] impl` block
+    fn slots(&'static self) -> &'static [ffi::PyType_Slot];
+
+    /// Returns the descriptions of the methods for a single `#[pymethods] impl` block
+    fn info(&'static self) -> Vec<FunctionInfo>;
 }
 
 /// Implemented for `#[pyclass]` in our proc macro code.
@@ -116,10 +166,26 @@ pub trait HasMethodsInventory {
 // Methods from #[pyo3(get, set)] on struct fields.
 methods_trait!(PyClassDescriptors, py_class_descriptors);
 
+// Methods and class attributes generated by #[pyclass] itself, e.g. the variants of an enum.
+methods_trait!(PyClassDefaultMethods, py_class_default_methods);
+
 // Methods from #[pymethods] if not using inventory.
 #[cfg(not(feature = "multiple-pymethods"))]
 methods_trait!(PyMethods, py_methods);
 
+// Descriptions of the methods from #[pymethods] for type stubs, if not using inventory.
+#[cfg(not(feature = "multiple-pymethods"))]
+pub trait PyMethodsInfo<T> {
+    fn py_methods_info(self) -> Vec<FunctionInfo>;
+}
+
+#[cfg(not(feature = "multiple-pymethods"))]
+impl<T> PyMethodsInfo<T> for &'_ PyClassImplCollector<T> {
+    fn py_methods_info(self) -> Vec<FunctionInfo> {
+        Vec::new()
+    }
+}
+
 // All traits describing slots, as well as the fallback implementations for unimplemented protos
 //
 // Protos which are implemented use dtolnay specialization to implement for PyClassImplCollector<T>.
@@ -140,6 +206,10 @@ macro_rules! slots_trait {
     };
 }
 
+// Slots generated by #[pyclass] itself. These are visited before the slots from #[pyproto], so
+// that the latter take precedence.
+slots_trait!(PyClassDefaultSlots, py_class_default_slots);
+
 slots_trait!(PyObjectProtocolSlots, object_protocol_slots);
 slots_trait!(PyDescrProtocolSlots, descr_protocol_slots);
 slots_trait!(PyGCProtocolSlots, gc_protocol_slots);
@@ -150,6 +220,10 @@ slots_trait!(PyAsyncProtocolSlots, async_protocol_slots);
 slots_trait!(PySequenceProtocolSlots, sequence_protocol_slots);
 slots_trait!(PyBufferProtocolSlots, buffer_protocol_slots);
 
+// Slots for protocol methods such as `__repr__` from #[pymethods] if not using inventory.
+#[cfg(not(feature = "multiple-pymethods"))]
+slots_trait!(PyMethodsProtocolSlots, methods_protocol_slots);
+
 methods_trait!(PyObjectProtocolMethods, object_protocol_methods);
 methods_trait!(PyAsyncProtocolMethods, async_protocol_methods);
 methods_trait!(PyContextProtocolMethods, context_protocol_methods);
@@ -176,6 +250,389 @@ impl<T> PyBufferProtocolProcs<T> for &'_ PyClassImplCollector<T> {
     }
 }
 
+// Protocol methods in #[pymethods] which share a slot, such as `__add__` and `__radd__`.
+//
+// Each method is a "fragment" of the slot. #[pymethods] implements the fragments it defines for
+// PyClassImplCollector<T> and emits the slot, which calls the fragments through dtolnay
+// specialization so that the fallbacks below are used for the fragments which are not defined.
+
+macro_rules! slot_fragment_trait {
+    (
+        $trait_name:ident,
+        unsafe fn $name:ident($py:ident, $slf:ident, $($arg:ident),*) -> $ret:ty $default:block
+    ) => {
+        #[allow(non_camel_case_types)]
+        pub trait $trait_name<T>: Sized {
+            #[allow(unused_variables)]
+            #[inline]
+            unsafe fn $name(
+                self,
+                $py: Python,
+                $slf: *mut ffi::PyObject,
+                $($arg: *mut ffi::PyObject),*
+            ) -> $ret $default
+        }
+
+        impl<T> $trait_name<T> for &'_ PyClassImplCollector<T> {}
+    };
+}
+
+macro_rules! binary_slot_fragment_traits {
+    ($($trait_name:ident: $name:ident),* $(,)?) => {
+        $(
+            slot_fragment_trait! {
+                $trait_name,
+                unsafe fn $name(py, slf, other) -> PyResult<*mut ffi::PyObject> {
+                    Ok(py.NotImplemented().into_ptr())
+                }
+            }
+        )*
+    };
+}
+
+binary_slot_fragment_traits! {
+    PyClass__add__SlotFragment: __add__,
+    PyClass__radd__SlotFragment: __radd__,
+    PyClass__sub__SlotFragment: __sub__,
+    PyClass__rsub__SlotFragment: __rsub__,
+    PyClass__mul__SlotFragment: __mul__,
+    PyClass__rmul__SlotFragment: __rmul__,
+    PyClass__matmul__SlotFragment: __matmul__,
+    PyClass__rmatmul__SlotFragment: __rmatmul__,
+    PyClass__truediv__SlotFragment: __truediv__,
+    PyClass__rtruediv__SlotFragment: __rtruediv__,
+    PyClass__floordiv__SlotFragment: __floordiv__,
+    PyClass__rfloordiv__SlotFragment: __rfloordiv__,
+    PyClass__mod__SlotFragment: __mod__,
+    PyClass__rmod__SlotFragment: __rmod__,
+    PyClass__divmod__SlotFragment: __divmod__,
+    PyClass__rdivmod__SlotFragment: __rdivmod__,
+    PyClass__lshift__SlotFragment: __lshift__,
+    PyClass__rlshift__SlotFragment: __rlshift__,
+    PyClass__rshift__SlotFragment: __rshift__,
+    PyClass__rrshift__SlotFragment: __rrshift__,
+    PyClass__and__SlotFragment: __and__,
+    PyClass__rand__SlotFragment: __rand__,
+    PyClass__xor__SlotFragment: __xor__,
+    PyClass__rxor__SlotFragment: __rxor__,
+    PyClass__or__SlotFragment: __or__,
+    PyClass__ror__SlotFragment: __ror__,
+}
+
+slot_fragment_trait! {
+    PyClass__pow__SlotFragment,
+    unsafe fn __pow__(py, slf, other, modulo) -> PyResult<*mut ffi::PyObject> {
+        Ok(py.NotImplemented().into_ptr())
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__rpow__SlotFragment,
+    unsafe fn __rpow__(py, slf, other, modulo) -> PyResult<*mut ffi::PyObject> {
+        Ok(py.NotImplemented().into_ptr())
+    }
+}
+
+// Without `__setattr__` or `__delattr__`, attributes are set and deleted as usual.
+
+slot_fragment_trait! {
+    PyClass__setattr__SlotFragment,
+    unsafe fn __setattr__(py, slf, attr, value) -> PyResult<()> {
+        generic_setattr(py, slf, attr, value)
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__delattr__SlotFragment,
+    unsafe fn __delattr__(py, slf, attr) -> PyResult<()> {
+        generic_setattr(py, slf, attr, std::ptr::null_mut())
+    }
+}
+
+unsafe fn generic_setattr(
+    py: Python,
+    slf: *mut ffi::PyObject,
+    attr: *mut ffi::PyObject,
+    value: *mut ffi::PyObject,
+) -> PyResult<()> {
+    if ffi::PyObject_GenericSetAttr(slf, attr, value) == -1 {
+        Err(PyErr::fetch(py))
+    } else {
+        Ok(())
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__setitem__SlotFragment,
+    unsafe fn __setitem__(py, slf, key, value) -> PyResult<()> {
+        Err(PyNotImplementedError::new_err("Subscript assignment not supported"))
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__delitem__SlotFragment,
+    unsafe fn __delitem__(py, slf, key) -> PyResult<()> {
+        Err(PyNotImplementedError::new_err("Subscript deletion not supported"))
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__set__SlotFragment,
+    unsafe fn __set__(py, slf, instance, value) -> PyResult<()> {
+        Err(PyNotImplementedError::new_err("Descriptor assignment not supported"))
+    }
+}
+
+slot_fragment_trait! {
+    PyClass__delete__SlotFragment,
+    unsafe fn __delete__(py, slf, instance) -> PyResult<()> {
+        Err(PyNotImplementedError::new_err("Descriptor deletion not supported"))
+    }
+}
+
+// Mutability of #[pyclass]
+
+/// Fails to compile if `T` is a `#[pyclass(frozen)]`, which cannot have setters.
+#[doc(hidden)]
+#[inline]
+pub fn assert_mutable<T: PyClass<Frozen = False>>() {}
+
+/// Fails to compile if `T` is not a `#[pyclass(frozen)]`, as `async fn`s with `&self` borrow it
+/// without a runtime check.
+#[doc(hidden)]
+#[inline]
+pub fn assert_frozen<T: PyClass<Frozen = True>>() {}
+
+/// Fails to compile if the mutable class `T` extends a frozen class, which stores no borrow flag.
+#[doc(hidden)]
+pub fn assert_mutable_base<T>()
+where
+    T: PyClass<Frozen = False>,
+    T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
+{
+}
+
+/// Fails to compile if the frozen class `T` extends a mutable class, as borrowing `T` would
+/// ignore the borrow flag of the base class.
+#[doc(hidden)]
+pub fn assert_frozen_base<T>()
+where
+    T: PyClass<Frozen = True>,
+    T::BaseLayout: PyFrozenLayout<T::BaseType>,
+{
+}
+
+// Slots from #[pyclass(eq, ord, hash)]
+
+/// `__richcmp__` of a `#[pyclass(eq)]`, which uses `PartialEq` and supports only `==` and `!=`.
+#[doc(hidden)]
+pub unsafe extern "C" fn richcmp_partial_eq<T: PyClass + PartialEq>(
+    slf: *mut ffi::PyObject,
+    other: *mut ffi::PyObject,
+    op: c_int,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let other = <PyCell<T> as PyTryFrom>::try_from(py.from_borrowed_ptr::<PyAny>(other));
+        let result = match (CompareOp::from_raw(op), other) {
+            (Some(CompareOp::Eq), Ok(other)) => {
+                (*slf.try_borrow()? == *other.try_borrow()?).into_py(py)
+            }
+            (Some(CompareOp::Ne), Ok(other)) => {
+                (*slf.try_borrow()? != *other.try_borrow()?).into_py(py)
+            }
+            _ => py.NotImplemented(),
+        };
+        crate::PyResult::Ok(result)
+    })
+}
+
+/// `__richcmp__` of a `#[pyclass(eq, ord)]`, which uses `PartialEq` and `PartialOrd`.
+#[doc(hidden)]
+pub unsafe extern "C" fn richcmp_partial_ord<T: PyClass + PartialOrd>(
+    slf: *mut ffi::PyObject,
+    other: *mut ffi::PyObject,
+    op: c_int,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let other = <PyCell<T> as PyTryFrom>::try_from(py.from_borrowed_ptr::<PyAny>(other));
+        let result = match (CompareOp::from_raw(op), other) {
+            (Some(op), Ok(other)) => {
+                let (slf, other) = (slf.try_borrow()?, other.try_borrow()?);
+                let (slf, other) = (&*slf, &*other);
+                match op {
+                    CompareOp::Lt => slf < other,
+                    CompareOp::Le => slf <= other,
+                    CompareOp::Eq => slf == other,
+                    CompareOp::Ne => slf != other,
+                    CompareOp::Gt => slf > other,
+                    CompareOp::Ge => slf >= other,
+                }
+                .into_py(py)
+            }
+            _ => py.NotImplemented(),
+        };
+        crate::PyResult::Ok(result)
+    })
+}
+
+/// `__hash__` of a `#[pyclass(hash)]`, which uses `Hash`.
+#[doc(hidden)]
+pub unsafe extern "C" fn hash<T: PyClass + Hash>(slf: *mut ffi::PyObject) -> ffi::Py_hash_t {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let mut hasher = DefaultHasher::new();
+        slf.try_borrow()?.hash(&mut hasher);
+        crate::callback::convert::<_, HashCallbackOutput>(py, hasher.finish())
+    })
+}
+
+// Slots from #[pyclass(repr, str)]
+
+/// `__repr__` of a `#[pyclass(repr)]`, which uses `Debug`.
+#[doc(hidden)]
+pub unsafe extern "C" fn repr_debug<T: PyClass + Debug>(
+    slf: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let repr = format!("{:?}", &*slf.try_borrow()?);
+        crate::PyResult::Ok(repr.into_py(py))
+    })
+}
+
+/// `__str__` of a `#[pyclass(str)]`, which uses `Display`.
+#[doc(hidden)]
+pub unsafe extern "C" fn str_display<T: PyClass + Display>(
+    slf: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let string = slf.try_borrow()?.to_string();
+        crate::PyResult::Ok(string.into_py(py))
+    })
+}
+
+/// A piece of the format string given to `#[pyclass(repr = "...")]` or `#[pyclass(str = "...")]`.
+#[doc(hidden)]
+pub enum FormatPiece {
+    /// Text which is copied as is.
+    Text(&'static str),
+    /// `{name}`: the attribute `name` of the object, formatted with `repr()` or `str()`.
+    Attribute(&'static str),
+}
+
+/// Implemented by `#[pyclass]` for the format strings of `repr = "..."` and `str = "..."`.
+#[doc(hidden)]
+pub trait PyClassFormat: PyClass {
+    const REPR: &'static [FormatPiece] = &[];
+    const STR: &'static [FormatPiece] = &[];
+}
+
+fn format_attributes(
+    slf: &PyAny,
+    pieces: &[FormatPiece],
+    convert: fn(&PyAny) -> PyResult<&PyString>,
+) -> PyResult<String> {
+    let mut result = String::new();
+    for piece in pieces {
+        match piece {
+            FormatPiece::Text(text) => result.push_str(text),
+            FormatPiece::Attribute(name) => {
+                result.push_str(convert(slf.getattr(*name)?)?.to_str()?);
+            }
+        }
+    }
+    Ok(result)
+}
+
+/// `__repr__` of a `#[pyclass(repr = "...")]`.
+#[doc(hidden)]
+pub unsafe extern "C" fn repr_format<T: PyClassFormat>(
+    slf: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyAny>(slf);
+        format_attributes(slf, T::REPR, PyAny::repr)
+    })
+}
+
+/// `__str__` of a `#[pyclass(str = "...")]`.
+#[doc(hidden)]
+pub unsafe extern "C" fn str_format<T: PyClassFormat>(
+    slf: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyAny>(slf);
+        format_attributes(slf, T::STR, PyAny::str)
+    })
+}
+
+// Fieldless enums
+
+/// Implemented by `#[pyclass]` for fieldless enums.
+#[doc(hidden)]
+pub trait PyClassEnum: PyClass {
+    /// The name of the variant `self`.
+    fn variant_name(&self) -> &'static str;
+    /// The discriminant of the variant `self`.
+    fn discriminant(&self) -> isize;
+}
+
+/// `__repr__` of a `#[pyclass]` enum, e.g. `Color.Red`.
+#[doc(hidden)]
+pub unsafe extern "C" fn enum_repr<T: PyClassEnum>(slf: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let repr = format!("{}.{}", T::NAME, slf.try_borrow()?.variant_name());
+        crate::PyResult::Ok(repr.into_py(py))
+    })
+}
+
+/// `__richcmp__` of a `#[pyclass]` enum: variants are equal if their discriminants are.
+#[doc(hidden)]
+pub unsafe extern "C" fn enum_richcmp<T: PyClassEnum>(
+    slf: *mut ffi::PyObject,
+    other: *mut ffi::PyObject,
+    op: c_int,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let other = <PyCell<T> as PyTryFrom>::try_from(py.from_borrowed_ptr::<PyAny>(other));
+        let result = match (op, other) {
+            (ffi::Py_EQ, Ok(other)) => {
+                (slf.try_borrow()?.discriminant() == other.try_borrow()?.discriminant()).into_py(py)
+            }
+            (ffi::Py_NE, Ok(other)) => {
+                (slf.try_borrow()?.discriminant() != other.try_borrow()?.discriminant()).into_py(py)
+            }
+            _ => py.NotImplemented(),
+        };
+        crate::PyResult::Ok(result)
+    })
+}
+
+/// `__hash__` of a `#[pyclass]` enum, which hashes the discriminant like an `int`, so that the
+/// variants can be used as dict keys and set members.
+#[doc(hidden)]
+pub unsafe extern "C" fn enum_hash<T: PyClassEnum>(slf: *mut ffi::PyObject) -> ffi::Py_hash_t {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let discriminant = slf.try_borrow()?.discriminant();
+        crate::callback::convert::<_, HashCallbackOutput>(py, discriminant)
+    })
+}
+
+/// `__int__` of a `#[pyclass]` enum, which returns the discriminant.
+#[doc(hidden)]
+pub unsafe extern "C" fn enum_int<T: PyClassEnum>(slf: *mut ffi::PyObject) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        crate::PyResult::Ok(slf.try_borrow()?.discriminant().into_py(py))
+    })
+}
+
 // Thread checkers
 
 #[doc(hidden)]
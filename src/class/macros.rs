@@ -58,13 +58,17 @@ macro_rules! py_len_func {
 macro_rules! py_binary_func {
     // Use call_ref! by default
     ($name:ident, $trait:ident, $class:ident :: $f:ident, $return:ty, $call:ident) => {
+        py_binary_func!($name, $trait, $class::$f, $return, $call, $crate::PyClass);
+    };
+    // Mutable borrows require `T: PyClass<Frozen = False>`
+    ($name:ident, $trait:ident, $class:ident :: $f:ident, $return:ty, $call:ident, $bound:path) => {
         #[doc(hidden)]
         pub unsafe extern "C" fn $name<T>(
             slf: *mut ffi::PyObject,
             arg: *mut ffi::PyObject,
         ) -> $return
         where
-            T: for<'p> $trait<'p>,
+            T: for<'p> $trait<'p> + $bound,
         {
             $crate::callback_body!(py, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
@@ -158,6 +162,7 @@ macro_rules! py_binary_self_func {
         ) -> *mut $crate::ffi::PyObject
         where
             T: for<'p> $trait<'p>,
+            T: $crate::PyClass<Frozen = $crate::pyclass::boolean_struct::False>,
         {
             $crate::callback_body!(py, {
                 let slf_ = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
@@ -176,13 +181,17 @@ macro_rules! py_ssizearg_func {
         py_ssizearg_func!($name, $trait, $class::$f, call_ref);
     };
     ($name:ident, $trait:ident, $class:ident :: $f:ident, $call:ident) => {
+        py_ssizearg_func!($name, $trait, $class::$f, $call, $crate::PyClass);
+    };
+    // Mutable borrows require `T: PyClass<Frozen = False>`
+    ($name:ident, $trait:ident, $class:ident :: $f:ident, $call:ident, $bound:path) => {
         #[doc(hidden)]
         pub unsafe extern "C" fn $name<T>(
             slf: *mut ffi::PyObject,
             arg: $crate::ffi::Py_ssize_t,
         ) -> *mut $crate::ffi::PyObject
         where
-            T: for<'p> $trait<'p>,
+            T: for<'p> $trait<'p> + $bound,
         {
             $crate::callback_body!(py, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
@@ -234,6 +243,7 @@ macro_rules! py_func_set {
         ) -> std::os::raw::c_int
         where
             T: for<'p> $trait_name<'p>,
+            T: $crate::PyClass<Frozen = $crate::pyclass::boolean_struct::False>,
         {
             $crate::callback_body!(py, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
@@ -263,6 +273,7 @@ macro_rules! py_func_del {
         ) -> std::os::raw::c_int
         where
             T: for<'p> $trait_name<'p>,
+            T: $crate::PyClass<Frozen = $crate::pyclass::boolean_struct::False>,
         {
             $crate::callback_body!(py, {
                 if value.is_null() {
@@ -291,6 +302,7 @@ macro_rules! py_func_set_del {
         ) -> std::os::raw::c_int
         where
             T: for<'p> $trait1<'p> + for<'p> $trait2<'p>,
+            T: $crate::PyClass<Frozen = $crate::pyclass::boolean_struct::False>,
         {
             $crate::callback_body!(py, {
                 let slf = py.from_borrowed_ptr::<$crate::PyCell<T>>(slf);
@@ -4,6 +4,7 @@
 //! Trait and support implementation for implementing number protocol
 use crate::callback::IntoPyCallbackOutput;
 use crate::err::PyErr;
+use crate::pyclass::boolean_struct::False;
 use crate::{ffi, FromPyObject, PyClass, PyObject};
 
 /// Number interface
@@ -732,6 +733,7 @@ pub unsafe extern "C" fn ipow<T>(
 ) -> *mut ffi::PyObject
 where
     T: for<'p> PyNumberIPowProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     // NOTE: Somehow __ipow__ causes SIGSEGV in Python < 3.8 when we extract,
     // so we ignore it. It's the same as what CPython does.
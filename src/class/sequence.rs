@@ -6,6 +6,7 @@
 use crate::callback::IntoPyCallbackOutput;
 use crate::conversion::{FromPyObject, IntoPy};
 use crate::err::PyErr;
+use crate::pyclass::boolean_struct::False;
 use crate::{exceptions, ffi, PyAny, PyCell, PyClass, PyObject};
 use std::os::raw::c_int;
 
@@ -141,6 +142,7 @@ pub unsafe extern "C" fn setitem<T>(
 ) -> c_int
 where
     T: for<'p> PySequenceSetItemProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
@@ -167,6 +169,7 @@ pub unsafe extern "C" fn delitem<T>(
 ) -> c_int
 where
     T: for<'p> PySequenceDelItemProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
@@ -190,6 +193,7 @@ pub unsafe extern "C" fn setdelitem<T>(
 ) -> c_int
 where
     T: for<'p> PySequenceSetItemProtocol<'p> + for<'p> PySequenceDelItemProtocol<'p>,
+    T: PyClass<Frozen = False>,
 {
     crate::callback_body!(py, {
         let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
@@ -216,11 +220,13 @@ py_binary_func!(
     PySequenceInplaceConcatProtocol,
     Self::__inplace_concat__,
     *mut ffi::PyObject,
-    call_mut
+    call_mut,
+    crate::PyClass<Frozen = False>
 );
 py_ssizearg_func!(
     inplace_repeat,
     PySequenceInplaceRepeatProtocol,
     Self::__inplace_repeat__,
-    call_mut
+    call_mut,
+    crate::PyClass<Frozen = False>
 );
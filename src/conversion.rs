@@ -2,6 +2,7 @@
 
 //! Conversions between various states of Rust and Python types and their wrappers.
 use crate::err::{self, PyDowncastError, PyResult};
+use crate::inspect::TypeInfo;
 use crate::type_object::PyTypeInfo;
 use crate::types::PyTuple;
 use crate::{
@@ -140,6 +141,14 @@ where
 pub trait IntoPy<T>: Sized {
     /// Performs the conversion.
     fn into_py(self, py: Python) -> T;
+
+    /// The Python type of the converted value, used for return types in type stubs.
+    ///
+    /// The default is `typing.Any`; implementations should override this where the type is known.
+    /// See the [`inspect`](crate::inspect) module for details.
+    fn type_output() -> TypeInfo {
+        TypeInfo::Any
+    }
 }
 
 /// `FromPyObject` is implemented by various types that can be extracted from
@@ -170,6 +179,14 @@ pub trait IntoPy<T>: Sized {
 pub trait FromPyObject<'source>: Sized {
     /// Extracts `Self` from the source `PyObject`.
     fn extract(ob: &'source PyAny) -> PyResult<Self>;
+
+    /// The Python type of the values accepted by `extract`, used for parameters in type stubs.
+    ///
+    /// The default is `typing.Any`; implementations should override this where the type is known.
+    /// See the [`inspect`](crate::inspect) module for details.
+    fn type_input() -> TypeInfo {
+        TypeInfo::Any
+    }
 }
 
 /// Identity conversion: allows using existing `PyObject` instances where
@@ -200,6 +217,10 @@ where
     fn into_py(self, py: Python) -> PyObject {
         self.map_or_else(|| py.None(), |val| val.into_py(py))
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::optional_of(T::type_output())
+    }
 }
 
 /// `()` is converted to Python `None`.
@@ -213,6 +234,10 @@ impl IntoPy<PyObject> for () {
     fn into_py(self, py: Python) -> PyObject {
         py.None()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::None
+    }
 }
 
 impl<T> IntoPy<PyObject> for &'_ T
@@ -232,6 +257,10 @@ where
     fn extract(obj: &'a PyAny) -> PyResult<Self> {
         PyTryFrom::try_from(obj).map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for T
@@ -242,6 +271,10 @@ where
         let cell: &PyCell<Self> = PyTryFrom::try_from(obj)?;
         Ok(unsafe { cell.try_borrow_unguarded()?.clone() })
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for PyRef<'a, T>
@@ -252,16 +285,24 @@ where
         let cell: &PyCell<T> = PyTryFrom::try_from(obj)?;
         cell.try_borrow().map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for PyRefMut<'a, T>
 where
-    T: PyClass,
+    T: PyClass<Frozen = crate::pyclass::boolean_struct::False>,
 {
     fn extract(obj: &'a PyAny) -> PyResult<Self> {
         let cell: &PyCell<T> = PyTryFrom::try_from(obj)?;
         cell.try_borrow_mut().map_err(Into::into)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T> FromPyObject<'a> for Option<T>
@@ -275,6 +316,10 @@ where
             T::extract(obj).map(Some)
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::optional_of(T::type_input())
+    }
 }
 
 /// Trait implemented by Python object types that allow a checked downcast.
@@ -0,0 +1,650 @@
+//! Conversions between [chrono](https://docs.rs/chrono)'s date and time types and the types of
+//! Python's `datetime` module.
+//!
+//! | Rust                       | Python               |
+//! | -------------------------- | -------------------- |
+//! | `chrono::Duration`         | `datetime.timedelta` |
+//! | `chrono::NaiveDate`        | `datetime.date`      |
+//! | `chrono::NaiveTime`        | `datetime.time`      |
+//! | `chrono::NaiveDateTime`    | `datetime.datetime` without `tzinfo` |
+//! | `chrono::DateTime<Tz>`     | `datetime.datetime` with a fixed-offset `datetime.timezone` |
+//! | `chrono::FixedOffset`, `chrono::Utc` | `datetime.timezone` |
+//!
+//! Python's `datetime` only has microsecond precision, so nanoseconds are truncated when
+//! converting to Python. Python cannot represent leap seconds either, so a chrono leap second
+//! is converted to the last microsecond of the 59th second of the minute.
+//!
+//! chrono's naive types have no equivalent of the `fold` attribute, which tells apart the two
+//! occurrences of a local time repeated when the UTC offset changes, so extracting a
+//! `NaiveTime` or `NaiveDateTime` with `fold=1` fails with a `ValueError`. A `DateTime` takes
+//! its offset from `utcoffset()`, which already accounts for `fold`.
+//!
+//! Converting a value which is out of the range supported by Python (e.g. a year before 1 or
+//! after 9999) to a Python object panics.
+
+use crate::exceptions::{PyTypeError, PyValueError};
+use crate::inspect::TypeInfo;
+use crate::types::{
+    timezone_from_offset, PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PyTime,
+    PyTimeAccess, PyTzInfo,
+};
+use crate::{FromPyObject, IntoPy, PyAny, PyNativeType, PyObject, PyResult, Python, ToPyObject};
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, Offset,
+    TimeZone, Timelike, Utc,
+};
+
+fn datetime_type(name: &'static str) -> TypeInfo {
+    TypeInfo::Class {
+        module: Some("datetime"),
+        name,
+    }
+}
+
+/// Converts the nanoseconds of a chrono time to microseconds, saturating a leap second to the
+/// last microsecond of the second.
+fn micros(nanos: u32) -> u32 {
+    std::cmp::min(nanos, 999_999_999) / 1000
+}
+
+fn py_time<'p>(py: Python<'p>, time: &NaiveTime) -> PyResult<&'p PyTime> {
+    PyTime::new(
+        py,
+        time.hour() as u8,
+        time.minute() as u8,
+        time.second() as u8,
+        micros(time.nanosecond()),
+        None,
+    )
+}
+
+fn py_datetime<'p>(
+    py: Python<'p>,
+    datetime: &NaiveDateTime,
+    tzinfo: Option<&PyObject>,
+) -> PyResult<&'p PyDateTime> {
+    PyDateTime::new(
+        py,
+        datetime.year(),
+        datetime.month() as u8,
+        datetime.day() as u8,
+        datetime.hour() as u8,
+        datetime.minute() as u8,
+        datetime.second() as u8,
+        micros(datetime.nanosecond()),
+        tzinfo,
+    )
+}
+
+fn naive_date(date: &impl PyDateAccess) -> PyResult<NaiveDate> {
+    NaiveDate::from_ymd_opt(
+        date.get_year(),
+        u32::from(date.get_month()),
+        u32::from(date.get_day()),
+    )
+    .ok_or_else(|| PyValueError::new_err("invalid or out-of-range date"))
+}
+
+/// Fails for a time with `fold=1`, which a naive chrono type cannot represent.
+fn check_fold(time: &impl PyTimeAccess) -> PyResult<()> {
+    #[cfg(not(PyPy))]
+    {
+        if time.get_fold() {
+            return Err(PyValueError::new_err(
+                "naive times with fold=1 cannot be represented by chrono",
+            ));
+        }
+    }
+    #[cfg(PyPy)]
+    let _ = time;
+    Ok(())
+}
+
+fn naive_time(time: &impl PyTimeAccess) -> PyResult<NaiveTime> {
+    NaiveTime::from_hms_micro_opt(
+        u32::from(time.get_hour()),
+        u32::from(time.get_minute()),
+        u32::from(time.get_second()),
+        time.get_microsecond(),
+    )
+    .ok_or_else(|| PyValueError::new_err("invalid or out-of-range time"))
+}
+
+fn fixed_offset(offset: &PyAny) -> PyResult<FixedOffset> {
+    let offset: &PyDelta = offset.downcast()?;
+    if offset.get_microseconds() != 0 {
+        return Err(PyValueError::new_err(
+            "UTC offsets with sub-second precision are not supported",
+        ));
+    }
+    let seconds = offset.get_days() * 86400 + offset.get_seconds();
+    FixedOffset::east_opt(seconds).ok_or_else(|| PyValueError::new_err("UTC offset out of range"))
+}
+
+impl ToPyObject for Duration {
+    fn to_object(&self, py: Python) -> PyObject {
+        let days = self.num_days();
+        let rest = *self - Duration::days(days);
+        let seconds = rest.num_seconds();
+        // Less than a second remains, so this cannot overflow
+        let micros = (rest - Duration::seconds(seconds))
+            .num_microseconds()
+            .unwrap();
+        // Days outside of the range of `i32` are also out of the range of `timedelta`; clamp
+        // them and let Python raise the error.
+        let days = days.max(i32::MIN.into()).min(i32::MAX.into()) as i32;
+        PyDelta::new(py, days, seconds as i32, micros as i32, true)
+            .expect("failed to construct timedelta")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for Duration {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("timedelta")
+    }
+}
+
+impl FromPyObject<'_> for Duration {
+    fn extract(ob: &PyAny) -> PyResult<Duration> {
+        let delta: &PyDelta = ob.downcast()?;
+        Ok(Duration::days(delta.get_days().into())
+            + Duration::seconds(delta.get_seconds().into())
+            + Duration::microseconds(delta.get_microseconds().into()))
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("timedelta")
+    }
+}
+
+impl ToPyObject for NaiveDate {
+    fn to_object(&self, py: Python) -> PyObject {
+        PyDate::new(py, self.year(), self.month() as u8, self.day() as u8)
+            .expect("failed to construct date")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for NaiveDate {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("date")
+    }
+}
+
+impl FromPyObject<'_> for NaiveDate {
+    fn extract(ob: &PyAny) -> PyResult<NaiveDate> {
+        naive_date(ob.downcast::<PyDate>()?)
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("date")
+    }
+}
+
+impl ToPyObject for NaiveTime {
+    fn to_object(&self, py: Python) -> PyObject {
+        py_time(py, self).expect("failed to construct time").into()
+    }
+}
+
+impl IntoPy<PyObject> for NaiveTime {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("time")
+    }
+}
+
+impl FromPyObject<'_> for NaiveTime {
+    fn extract(ob: &PyAny) -> PyResult<NaiveTime> {
+        let time: &PyTime = ob.downcast()?;
+        check_fold(time)?;
+        naive_time(time)
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("time")
+    }
+}
+
+impl ToPyObject for NaiveDateTime {
+    fn to_object(&self, py: Python) -> PyObject {
+        py_datetime(py, self, None)
+            .expect("failed to construct datetime")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for NaiveDateTime {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+impl FromPyObject<'_> for NaiveDateTime {
+    fn extract(ob: &PyAny) -> PyResult<NaiveDateTime> {
+        let datetime: &PyDateTime = ob.downcast()?;
+        if !datetime.getattr("tzinfo")?.is_none() {
+            return Err(PyTypeError::new_err(
+                "expected a naive datetime, got a timezone-aware datetime",
+            ));
+        }
+        check_fold(datetime)?;
+        Ok(naive_date(datetime)?.and_time(naive_time(datetime)?))
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+impl<Tz: TimeZone> ToPyObject for DateTime<Tz> {
+    fn to_object(&self, py: Python) -> PyObject {
+        let tzinfo = self.offset().fix().to_object(py);
+        py_datetime(py, &self.naive_local(), Some(&tzinfo))
+            .expect("failed to construct datetime")
+            .into()
+    }
+}
+
+impl<Tz: TimeZone> IntoPy<PyObject> for DateTime<Tz> {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+/// Extracts a timezone-aware `datetime.datetime`, using the offset returned by its
+/// `utcoffset()` method.
+impl FromPyObject<'_> for DateTime<FixedOffset> {
+    fn extract(ob: &PyAny) -> PyResult<DateTime<FixedOffset>> {
+        let datetime: &PyDateTime = ob.downcast()?;
+        let offset = datetime.call_method0("utcoffset")?;
+        if offset.is_none() {
+            return Err(PyTypeError::new_err(
+                "expected a timezone-aware datetime, got a naive datetime",
+            ));
+        }
+        let local = naive_date(datetime)?.and_time(naive_time(datetime)?);
+        fixed_offset(offset)?
+            .from_local_datetime(&local)
+            .single()
+            .ok_or_else(|| PyValueError::new_err("invalid or out-of-range datetime"))
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+/// Extracts a timezone-aware `datetime.datetime`, converting it to UTC.
+impl FromPyObject<'_> for DateTime<Utc> {
+    fn extract(ob: &PyAny) -> PyResult<DateTime<Utc>> {
+        DateTime::<FixedOffset>::extract(ob).map(|datetime| datetime.with_timezone(&Utc))
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+impl ToPyObject for FixedOffset {
+    fn to_object(&self, py: Python) -> PyObject {
+        let offset = PyDelta::new(py, 0, self.local_minus_utc(), 0, true)
+            .expect("failed to construct timedelta");
+        timezone_from_offset(py, offset)
+            .expect("failed to construct timezone")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for FixedOffset {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("timezone")
+    }
+}
+
+/// Extracts a `datetime.tzinfo` whose `utcoffset(None)` is a fixed offset, such as a
+/// `datetime.timezone`.
+impl FromPyObject<'_> for FixedOffset {
+    fn extract(ob: &PyAny) -> PyResult<FixedOffset> {
+        let tzinfo: &PyTzInfo = ob.downcast()?;
+        let offset = tzinfo.call_method1("utcoffset", (ob.py().None(),))?;
+        if offset.is_none() {
+            return Err(PyValueError::new_err("tzinfo has no fixed UTC offset"));
+        }
+        fixed_offset(offset)
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("tzinfo")
+    }
+}
+
+impl ToPyObject for Utc {
+    fn to_object(&self, py: Python) -> PyObject {
+        self.fix().to_object(py)
+    }
+}
+
+impl IntoPy<PyObject> for Utc {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("timezone")
+    }
+}
+
+impl FromPyObject<'_> for Utc {
+    fn extract(ob: &PyAny) -> PyResult<Utc> {
+        if FixedOffset::extract(ob)?.local_minus_utc() == 0 {
+            Ok(Utc)
+        } else {
+            Err(PyValueError::new_err("expected a UTC offset of zero"))
+        }
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("tzinfo")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::class::basic::CompareOp;
+    use crate::types::PyDict;
+
+    fn eval<'p>(py: Python<'p>, code: &str) -> &'p PyAny {
+        let locals = PyDict::new(py);
+        locals
+            .set_item("datetime", py.import("datetime").unwrap())
+            .unwrap();
+        py.eval(code, None, Some(locals)).unwrap()
+    }
+
+    fn check_roundtrip<T>(py: Python, value: T, python: &str)
+    where
+        T: ToPyObject + for<'a> FromPyObject<'a> + PartialEq + std::fmt::Debug,
+    {
+        let expected = eval(py, python);
+        let actual = value.to_object(py);
+        assert!(
+            expected
+                .rich_compare(actual.as_ref(py), CompareOp::Eq)
+                .and_then(PyAny::is_true)
+                .unwrap(),
+            "{} != {}",
+            actual,
+            python
+        );
+        assert_eq!(expected.extract::<T>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_duration() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_roundtrip(
+            py,
+            Duration::days(3) + Duration::seconds(7) + Duration::microseconds(250),
+            "datetime.timedelta(days=3, seconds=7, microseconds=250)",
+        );
+        check_roundtrip(
+            py,
+            Duration::microseconds(-1),
+            "datetime.timedelta(microseconds=-1)",
+        );
+        check_roundtrip(
+            py,
+            -Duration::days(999_999_999),
+            "datetime.timedelta(days=-999999999)",
+        );
+    }
+
+    #[test]
+    fn test_duration_overflow() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let result = std::panic::catch_unwind(|| Duration::days(1_000_000_000).to_object(py));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_date() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_roundtrip(
+            py,
+            NaiveDate::from_ymd_opt(2021, 2, 28).unwrap(),
+            "datetime.date(2021, 2, 28)",
+        );
+        check_roundtrip(
+            py,
+            NaiveDate::from_ymd_opt(1, 1, 1).unwrap(),
+            "datetime.date.min",
+        );
+    }
+
+    #[test]
+    fn test_time() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_roundtrip(
+            py,
+            NaiveTime::from_hms_micro_opt(13, 5, 32, 123_456).unwrap(),
+            "datetime.time(13, 5, 32, 123456)",
+        );
+        // Nanoseconds are truncated
+        let time = NaiveTime::from_hms_nano_opt(13, 5, 32, 123_456_789).unwrap();
+        assert_eq!(
+            time.to_object(py).extract::<NaiveTime>(py).unwrap(),
+            NaiveTime::from_hms_micro_opt(13, 5, 32, 123_456).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_leap_second() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let time = NaiveTime::from_hms_micro_opt(23, 59, 59, 1_500_000)
+            .unwrap()
+            .to_object(py);
+        assert_eq!(
+            time.extract::<NaiveTime>(py).unwrap(),
+            NaiveTime::from_hms_micro_opt(23, 59, 59, 999_999).unwrap()
+        );
+        let datetime = NaiveDate::from_ymd_opt(2016, 12, 31)
+            .unwrap()
+            .and_hms_milli_opt(23, 59, 59, 1_000)
+            .unwrap()
+            .to_object(py);
+        assert_eq!(
+            datetime.extract::<NaiveDateTime>(py).unwrap(),
+            NaiveDate::from_ymd_opt(2016, 12, 31)
+                .unwrap()
+                .and_hms_micro_opt(23, 59, 59, 999_999)
+                .unwrap()
+        );
+        #[cfg(not(PyPy))]
+        {
+            let datetime: &PyDateTime = datetime.as_ref(py).downcast().unwrap();
+            assert!(!datetime.get_fold());
+        }
+    }
+
+    #[test]
+    #[cfg(not(PyPy))]
+    fn test_fold() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let time = eval(py, "datetime.time(1, 30, fold=1)");
+        let err = time.extract::<NaiveTime>().unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+        let datetime = eval(py, "datetime.datetime(2021, 11, 7, 1, 30, fold=1)");
+        let err = datetime.extract::<NaiveDateTime>().unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+
+        // aware datetimes take the offset chosen by `fold` from `utcoffset()`
+        let aware = eval(
+            py,
+            "datetime.datetime(2021, 11, 7, 1, 30, fold=1, tzinfo=datetime.timezone.utc)",
+        );
+        assert_eq!(
+            aware.extract::<DateTime<Utc>>().unwrap(),
+            Utc.from_utc_datetime(
+                &NaiveDate::from_ymd_opt(2021, 11, 7)
+                    .unwrap()
+                    .and_hms_opt(1, 30, 0)
+                    .unwrap()
+            )
+        );
+        let globals = PyDict::new(py);
+        py.run(
+            r#"
+import datetime
+
+class Eastern(datetime.tzinfo):
+    def utcoffset(self, dt):
+        return datetime.timedelta(hours=-5 if dt.fold else -4)
+
+first = datetime.datetime(2021, 11, 7, 1, 30, tzinfo=Eastern())
+second = datetime.datetime(2021, 11, 7, 1, 30, fold=1, tzinfo=Eastern())
+"#,
+            Some(globals),
+            None,
+        )
+        .unwrap();
+        let first: DateTime<FixedOffset> = globals.get_item("first").unwrap().extract().unwrap();
+        let second: DateTime<FixedOffset> = globals.get_item("second").unwrap().extract().unwrap();
+        assert_eq!(first.offset().local_minus_utc(), -4 * 3600);
+        assert_eq!(second.offset().local_minus_utc(), -5 * 3600);
+        assert_eq!(second - first, Duration::hours(1));
+
+        // the same times with fold=0 round-trip
+        check_roundtrip(
+            py,
+            NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+            "datetime.time(1, 30, fold=0)",
+        );
+        let datetime = NaiveDate::from_ymd_opt(2021, 11, 7)
+            .unwrap()
+            .and_hms_opt(1, 30, 0)
+            .unwrap();
+        check_roundtrip(
+            py,
+            datetime,
+            "datetime.datetime(2021, 11, 7, 1, 30, fold=0)",
+        );
+        let datetime = datetime.to_object(py);
+        let datetime: &PyDateTime = datetime.as_ref(py).downcast().unwrap();
+        assert!(!datetime.get_fold());
+    }
+
+    #[test]
+    fn test_naive_datetime() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        check_roundtrip(
+            py,
+            NaiveDate::from_ymd_opt(2021, 3, 4)
+                .unwrap()
+                .and_hms_micro_opt(5, 6, 7, 8)
+                .unwrap(),
+            "datetime.datetime(2021, 3, 4, 5, 6, 7, 8)",
+        );
+
+        let aware = eval(
+            py,
+            "datetime.datetime(2021, 3, 4, tzinfo=datetime.timezone.utc)",
+        );
+        let err = aware.extract::<NaiveDateTime>().unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+
+    #[test]
+    fn test_datetime_fixed_offset() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let offset = FixedOffset::east_opt(-5 * 3600 - 30 * 60).unwrap();
+        let datetime = offset
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2021, 3, 4)
+                    .unwrap()
+                    .and_hms_micro_opt(5, 6, 7, 8)
+                    .unwrap(),
+            )
+            .unwrap();
+        check_roundtrip(
+            py,
+            datetime,
+            "datetime.datetime(2021, 3, 4, 5, 6, 7, 8, \
+             tzinfo=datetime.timezone(-datetime.timedelta(hours=5, minutes=30)))",
+        );
+        check_roundtrip(
+            py,
+            offset,
+            "datetime.timezone(-datetime.timedelta(hours=5, minutes=30))",
+        );
+
+        let naive = eval(py, "datetime.datetime(2021, 3, 4)");
+        let err = naive.extract::<DateTime<FixedOffset>>().unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+
+    #[test]
+    fn test_datetime_utc() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let datetime = Utc
+            .from_local_datetime(
+                &NaiveDate::from_ymd_opt(2021, 3, 4)
+                    .unwrap()
+                    .and_hms_opt(5, 6, 7)
+                    .unwrap(),
+            )
+            .unwrap();
+        check_roundtrip(
+            py,
+            datetime,
+            "datetime.datetime(2021, 3, 4, 5, 6, 7, tzinfo=datetime.timezone.utc)",
+        );
+        check_roundtrip(py, Utc, "datetime.timezone.utc");
+
+        // Other offsets are converted to UTC
+        let other = eval(
+            py,
+            "datetime.datetime(2021, 3, 4, 7, 6, 7, \
+             tzinfo=datetime.timezone(datetime.timedelta(hours=2)))",
+        );
+        assert_eq!(other.extract::<DateTime<Utc>>().unwrap(), datetime);
+
+        let offset = FixedOffset::east_opt(3600).unwrap().to_object(py);
+        let err = offset.extract::<Utc>(py).unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+    }
+}
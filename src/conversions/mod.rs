@@ -1,5 +1,9 @@
-//! This module contains conversions between non-String Rust object and their string representation
-//! in Python
+//! This module contains conversions between Rust types and Python types which are not part of
+//! `pyo3::types`, e.g. non-String Rust objects and their string representation in Python.
 
+#[cfg(all(feature = "chrono", not(Py_LIMITED_API)))]
+mod chrono;
 mod osstr;
 mod path;
+#[cfg(not(Py_LIMITED_API))]
+mod time;
@@ -1,3 +1,4 @@
+use crate::inspect::TypeInfo;
 use crate::types::PyString;
 #[cfg(windows)]
 use crate::PyErr;
@@ -101,6 +102,10 @@ impl FromPyObject<'_> for OsString {
             Ok(os_string)
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl IntoPy<PyObject> for &'_ OsStr {
@@ -108,6 +113,10 @@ impl IntoPy<PyObject> for &'_ OsStr {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl ToPyObject for Cow<'_, OsStr> {
@@ -122,6 +131,10 @@ impl IntoPy<PyObject> for Cow<'_, OsStr> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl ToPyObject for OsString {
@@ -135,6 +148,10 @@ impl IntoPy<PyObject> for OsString {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 #[cfg(test)]
@@ -1,3 +1,4 @@
+use crate::inspect::TypeInfo;
 use crate::{FromPyObject, IntoPy, PyAny, PyObject, PyResult, Python, ToPyObject};
 use std::borrow::Cow;
 use std::ffi::OsString;
@@ -15,6 +16,10 @@ impl FromPyObject<'_> for PathBuf {
     fn extract(ob: &PyAny) -> PyResult<Self> {
         Ok(PathBuf::from(OsString::extract(ob)?))
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a Path {
@@ -22,6 +27,10 @@ impl<'a> IntoPy<PyObject> for &'a Path {
     fn into_py(self, py: Python) -> PyObject {
         self.as_os_str().to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl<'a> ToPyObject for Cow<'a, Path> {
@@ -36,6 +45,10 @@ impl<'a> IntoPy<PyObject> for Cow<'a, Path> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl ToPyObject for PathBuf {
@@ -49,6 +62,10 @@ impl IntoPy<PyObject> for PathBuf {
     fn into_py(self, py: Python) -> PyObject {
         self.into_os_string().to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 #[cfg(test)]
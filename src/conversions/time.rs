@@ -0,0 +1,263 @@
+//! Conversions of `std::time::Duration` to and from `datetime.timedelta`, and of
+//! `std::time::SystemTime` to and from a timezone-aware `datetime.datetime`.
+//!
+//! Sub-microsecond precision is lost when converting to Python. As `ToPyObject` cannot fail,
+//! converting a value outside of the range of `timedelta` or `datetime` panics.
+
+use crate::exceptions::{PyOverflowError, PyTypeError, PyValueError};
+use crate::inspect::TypeInfo;
+use crate::types::{timezone_from_offset, PyDateTime, PyDelta, PyDeltaAccess};
+use crate::{FromPyObject, IntoPy, PyAny, PyNativeType, PyObject, PyResult, Python, ToPyObject};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+fn datetime_type(name: &'static str) -> TypeInfo {
+    TypeInfo::Class {
+        module: Some("datetime"),
+        name,
+    }
+}
+
+fn py_delta(py: Python, duration: Duration) -> PyResult<&PyDelta> {
+    let days = duration.as_secs() / SECONDS_PER_DAY;
+    if days > i32::MAX as u64 {
+        return Err(PyOverflowError::new_err(
+            "Duration is too large to convert to timedelta",
+        ));
+    }
+    let seconds = duration.as_secs() % SECONDS_PER_DAY;
+    let micros = duration.subsec_micros();
+    PyDelta::new(py, days as i32, seconds as i32, micros as i32, false)
+}
+
+/// Returns `datetime.datetime(1970, 1, 1, tzinfo=datetime.timezone.utc)`.
+fn unix_epoch(py: Python) -> PyResult<&PyDateTime> {
+    let utc = timezone_from_offset(py, PyDelta::new(py, 0, 0, 0, false)?)?;
+    PyDateTime::new(py, 1970, 1, 1, 0, 0, 0, 0, Some(&utc.into()))
+}
+
+impl ToPyObject for Duration {
+    fn to_object(&self, py: Python) -> PyObject {
+        py_delta(py, *self)
+            .expect("failed to convert Duration to timedelta")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for Duration {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("timedelta")
+    }
+}
+
+/// Extracts a `datetime.timedelta` or a number of seconds.
+impl FromPyObject<'_> for Duration {
+    fn extract(ob: &PyAny) -> PyResult<Duration> {
+        if let Ok(delta) = ob.downcast::<PyDelta>() {
+            // `timedelta` normalizes seconds and microseconds to be positive
+            let days = delta.get_days();
+            if days < 0 {
+                return Err(PyValueError::new_err(
+                    "negative timedelta cannot be converted to Duration",
+                ));
+            }
+            let seconds = days as u64 * SECONDS_PER_DAY + delta.get_seconds() as u64;
+            return Ok(Duration::new(
+                seconds,
+                delta.get_microseconds() as u32 * 1000,
+            ));
+        }
+
+        let seconds: f64 = ob.extract()?;
+        if seconds.is_nan() {
+            Err(PyValueError::new_err("cannot convert NaN to Duration"))
+        } else if seconds < 0.0 {
+            Err(PyValueError::new_err(
+                "negative number of seconds cannot be converted to Duration",
+            ))
+        } else if seconds >= u64::MAX as f64 {
+            Err(PyOverflowError::new_err(
+                "number of seconds is too large to convert to Duration",
+            ))
+        } else {
+            Ok(Duration::from_secs_f64(seconds))
+        }
+    }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::union_of(vec![datetime_type("timedelta"), TypeInfo::Builtin("float")])
+    }
+}
+
+impl ToPyObject for SystemTime {
+    fn to_object(&self, py: Python) -> PyObject {
+        let datetime = match self.duration_since(UNIX_EPOCH) {
+            Ok(after) => py_delta(py, after)
+                .and_then(|delta| unix_epoch(py)?.call_method1("__add__", (delta,))),
+            Err(before) => py_delta(py, before.duration())
+                .and_then(|delta| unix_epoch(py)?.call_method1("__sub__", (delta,))),
+        };
+        datetime
+            .expect("failed to convert SystemTime to datetime")
+            .into()
+    }
+}
+
+impl IntoPy<PyObject> for SystemTime {
+    fn into_py(self, py: Python) -> PyObject {
+        self.to_object(py)
+    }
+
+    fn type_output() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+/// Extracts a timezone-aware `datetime.datetime`.
+impl FromPyObject<'_> for SystemTime {
+    fn extract(ob: &PyAny) -> PyResult<SystemTime> {
+        let datetime: &PyDateTime = ob.downcast()?;
+        if datetime.call_method0("utcoffset")?.is_none() {
+            return Err(PyTypeError::new_err(
+                "expected a timezone-aware datetime, got a naive datetime",
+            ));
+        }
+        let py = ob.py();
+        let delta: &PyDelta = datetime
+            .call_method1("__sub__", (unix_epoch(py)?,))?
+            .downcast()?;
+
+        // `timedelta` normalizes seconds and microseconds to be positive
+        let seconds =
+            i64::from(delta.get_days()) * SECONDS_PER_DAY as i64 + i64::from(delta.get_seconds());
+        let micros = Duration::from_micros(delta.get_microseconds() as u64);
+        let time = if seconds >= 0 {
+            UNIX_EPOCH.checked_add(Duration::from_secs(seconds as u64) + micros)
+        } else {
+            UNIX_EPOCH.checked_sub(Duration::from_secs(seconds.wrapping_neg() as u64) - micros)
+        };
+        time.ok_or_else(|| PyOverflowError::new_err("datetime is out of the range of SystemTime"))
+    }
+
+    fn type_input() -> TypeInfo {
+        datetime_type("datetime")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::types::PyDict;
+    use std::cmp::Ordering;
+
+    fn eval<'p>(py: Python<'p>, code: &str) -> &'p PyAny {
+        let locals = PyDict::new(py);
+        locals
+            .set_item("datetime", py.import("datetime").unwrap())
+            .unwrap();
+        py.eval(code, None, Some(locals)).unwrap()
+    }
+
+    #[test]
+    fn test_duration() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let duration = Duration::new(3 * SECONDS_PER_DAY + 7, 250_999);
+        let delta = duration.to_object(py);
+        let expected = eval(
+            py,
+            "datetime.timedelta(days=3, seconds=7, microseconds=250)",
+        );
+        assert_eq!(delta.as_ref(py).compare(expected).unwrap(), Ordering::Equal);
+        assert_eq!(
+            expected.extract::<Duration>().unwrap(),
+            Duration::new(3 * SECONDS_PER_DAY + 7, 250_000)
+        );
+    }
+
+    #[test]
+    fn test_duration_from_seconds() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert_eq!(
+            eval(py, "1.5").extract::<Duration>().unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(
+            eval(py, "10").extract::<Duration>().unwrap(),
+            Duration::from_secs(10)
+        );
+
+        let err = eval(py, "-1.0").extract::<Duration>().unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+        let err = eval(py, "float('nan')").extract::<Duration>().unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+        let err = eval(py, "1e30").extract::<Duration>().unwrap_err();
+        assert!(err.is_instance::<PyOverflowError>(py));
+        let err = eval(py, "'1.0'").extract::<Duration>().unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+
+    #[test]
+    fn test_duration_errors() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let err = eval(py, "datetime.timedelta(microseconds=-1)")
+            .extract::<Duration>()
+            .unwrap_err();
+        assert!(err.is_instance::<PyValueError>(py));
+
+        let err = py_delta(py, Duration::from_secs(u64::MAX)).unwrap_err();
+        assert!(err.is_instance::<PyOverflowError>(py));
+        let err = py_delta(py, Duration::from_secs(1_000_000_000 * SECONDS_PER_DAY)).unwrap_err();
+        assert!(err.is_instance::<PyOverflowError>(py));
+    }
+
+    #[test]
+    fn test_system_time() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cases = [
+            (
+                UNIX_EPOCH + Duration::new(1_600_000_000, 123_456_000),
+                "datetime.datetime(2020, 9, 13, 12, 26, 40, 123456, tzinfo=datetime.timezone.utc)",
+            ),
+            (
+                UNIX_EPOCH - Duration::new(86_400, 500_000_000),
+                "datetime.datetime(1969, 12, 30, 23, 59, 59, 500000, tzinfo=datetime.timezone.utc)",
+            ),
+        ];
+        for &(time, python) in cases.iter() {
+            let expected = eval(py, python);
+            let actual = time.to_object(py);
+            assert_eq!(
+                actual.as_ref(py).compare(expected).unwrap(),
+                Ordering::Equal
+            );
+            let tzinfo = actual.as_ref(py).getattr("tzinfo").unwrap();
+            assert_eq!(
+                tzinfo.compare(eval(py, "datetime.timezone.utc")).unwrap(),
+                Ordering::Equal
+            );
+            assert_eq!(expected.extract::<SystemTime>().unwrap(), time);
+        }
+
+        // Other offsets describe the same instant
+        let time = eval(
+            py,
+            "datetime.datetime(2020, 9, 13, 14, 26, 40, 123456, \
+             tzinfo=datetime.timezone(datetime.timedelta(hours=2)))",
+        );
+        assert_eq!(time.extract::<SystemTime>().unwrap(), cases[0].0);
+
+        let err = eval(py, "datetime.datetime(2020, 9, 13)")
+            .extract::<SystemTime>()
+            .unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+}
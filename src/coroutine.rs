@@ -0,0 +1,219 @@
+//! Python coroutines backed by Rust futures, as returned by `async fn`s in `#[pyfunction]` and
+//! `#[pymethods]`.
+
+use crate::asyncio::{arc_waker, get_running_loop, BoxFuture, Wake};
+use crate::callback::IntoPyCallbackOutput;
+use crate::class::iter::{IterNextOutput, PyIterNextOutput};
+use crate::exceptions::{PyAttributeError, PyRuntimeError};
+use crate::generator::thrown_exception;
+use crate::panic::PanicException;
+use crate::proc_macro::{pyclass, pymethods};
+use crate::types::{PyAny, PyCFunction};
+use crate::{PyNativeType, PyObject, PyRef, PyResult, Python};
+use parking_lot::Mutex;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// A Python coroutine which runs a Rust future.
+///
+/// This is what an `async fn` in `#[pyfunction]` or `#[pymethods]` returns to Python. Like the
+/// coroutine of an `async def`, it does nothing until it is awaited, and supports `send`,
+/// `throw` and `close`. The value of the future is converted to a Python object, and errors are
+/// raised from `await`.
+///
+/// The future is polled, with the GIL held, every time the coroutine is resumed. While it is
+/// pending, a coroutine run by an `asyncio` event loop suspends until the future is woken, from
+/// any thread. Without a running event loop, it yields `None` and has to be resumed by whatever
+/// runs it.
+///
+/// Waking the future acquires the GIL, so it must not be woken while holding a lock which
+/// polling the future also takes.
+///
+/// Throwing an exception into the coroutine, or closing it, drops the future.
+///
+/// # Example
+/// ```rust
+/// use pyo3::coroutine::Coroutine;
+/// use pyo3::prelude::*;
+///
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let coroutine = Py::new(py, Coroutine::new(Some("answer"), None, async { 42 }))?;
+///     let asyncio = py.import("asyncio")?;
+///     let event_loop = asyncio.call_method0("new_event_loop")?;
+///     let result = event_loop.call_method1("run_until_complete", (coroutine,))?;
+///     event_loop.call_method0("close")?;
+///     assert_eq!(result.extract::<i32>()?, 42);
+///     Ok(())
+/// })
+/// .unwrap();
+/// ```
+#[pyclass]
+pub struct Coroutine {
+    name: Option<&'static str>,
+    qualname_prefix: Option<&'static str>,
+    /// The future, until it completes or the coroutine is closed.
+    future: Option<BoxFuture<PyResult<PyObject>>>,
+}
+
+impl Coroutine {
+    /// Wraps `future`, whose output is converted to a Python object when it completes.
+    ///
+    /// `name` is the `__name__` of the coroutine, and its `__qualname__` is prefixed with
+    /// `qualname_prefix`, e.g. the name of the class of a method.
+    pub fn new<F, R>(
+        name: Option<&'static str>,
+        qualname_prefix: Option<&'static str>,
+        future: F,
+    ) -> Self
+    where
+        F: Future<Output = R> + Send + 'static,
+        R: IntoPyCallbackOutput<PyObject>,
+    {
+        Coroutine {
+            name,
+            qualname_prefix,
+            future: Some(Box::pin(async move {
+                let output = future.await;
+                Python::with_gil(|py| output.convert(py))
+            })),
+        }
+    }
+
+    /// Polls the future, and returns what the coroutine yields while it is pending.
+    fn resume(&mut self, py: Python) -> PyResult<PyIterNextOutput> {
+        let future = match self.future.as_mut() {
+            Some(future) => future,
+            None => {
+                return Err(PyRuntimeError::new_err(
+                    "cannot reuse already awaited coroutine",
+                ))
+            }
+        };
+        let asyncio_waker = Arc::new(AsyncioWaker::default());
+        let waker = arc_waker(asyncio_waker.clone());
+        let mut cx = Context::from_waker(&waker);
+        let result = match panic::catch_unwind(AssertUnwindSafe(|| future.as_mut().poll(&mut cx))) {
+            Ok(Poll::Pending) => return asyncio_waker.suspend(py).map(IterNextOutput::Yield),
+            Ok(Poll::Ready(result)) => result,
+            Err(payload) => Err(PanicException::from_panic_payload(payload)),
+        };
+        self.future = None;
+        result.map(IterNextOutput::Return)
+    }
+}
+
+#[pymethods]
+impl Coroutine {
+    #[getter(__name__)]
+    fn name(&self) -> PyResult<&'static str> {
+        self.name
+            .ok_or_else(|| PyAttributeError::new_err("coroutine has no __name__"))
+    }
+
+    #[getter(__qualname__)]
+    fn qualname(&self) -> PyResult<String> {
+        let name = self.name()?;
+        Ok(match self.qualname_prefix {
+            Some(prefix) => format!("{}.{}", prefix, name),
+            None => name.to_owned(),
+        })
+    }
+
+    fn __await__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<PyIterNextOutput> {
+        self.resume(py)
+    }
+
+    /// Resumes the coroutine. The value is ignored, as futures cannot receive values.
+    #[allow(unused_variables)]
+    fn send(&mut self, py: Python, value: &PyAny) -> PyResult<PyIterNextOutput> {
+        self.resume(py)
+    }
+
+    /// Drops the future, and raises the exception given as in `generator.throw`.
+    fn throw(
+        &mut self,
+        typ: &PyAny,
+        val: Option<&PyAny>,
+        tb: Option<&PyAny>,
+    ) -> PyResult<PyIterNextOutput> {
+        self.future = None;
+        Err(thrown_exception(typ, val, tb)?)
+    }
+
+    /// Drops the future.
+    fn close(&mut self) {
+        self.future = None;
+    }
+}
+
+/// The waker of one poll of a [`Coroutine`], which wakes up the `asyncio.Future` the coroutine
+/// suspends on.
+#[derive(Default)]
+struct AsyncioWaker(Mutex<WakerState>);
+
+#[derive(Default)]
+struct WakerState {
+    woken: bool,
+    /// The event loop and the `asyncio.Future` the coroutine is suspended on, until it is woken.
+    future: Option<(PyObject, PyObject)>,
+}
+
+impl AsyncioWaker {
+    /// Returns the value the coroutine yields after its future returned `Poll::Pending`.
+    fn suspend(&self, py: Python) -> PyResult<PyObject> {
+        let event_loop = match get_running_loop(py) {
+            Ok(event_loop) if event_loop.call_method0("is_running")?.is_true()? => event_loop,
+            // A bare `yield`, which asyncio tasks also take as a request to be resumed soon.
+            _ => return Ok(py.None()),
+        };
+        let future = event_loop.call_method0("create_future")?;
+        // What `asyncio.Future.__await__` sets before yielding the future to the task.
+        future.setattr("_asyncio_future_blocking", true)?;
+        let mut state = self.0.lock();
+        if state.woken {
+            return Ok(py.None());
+        }
+        state.future = Some((event_loop.into(), future.into()));
+        Ok(future.into())
+    }
+}
+
+impl Wake for AsyncioWaker {
+    fn wake(self: &Arc<Self>) {
+        let future = {
+            let mut state = self.0.lock();
+            state.woken = true;
+            state.future.take()
+        };
+        if let Some((event_loop, future)) = future {
+            Python::with_gil(|py| {
+                // A closed event loop no longer runs the coroutine, so there is nothing to wake.
+                let _ = release(event_loop.as_ref(py), future);
+            });
+        }
+    }
+}
+
+/// Sets the result of `future` on the thread of `event_loop`, unless it was cancelled.
+fn release(event_loop: &PyAny, future: PyObject) -> PyResult<()> {
+    let callback = PyCFunction::new_closure(event_loop.py(), move |args, _| -> PyResult<()> {
+        let py = args.py();
+        let future = future.as_ref(py);
+        if !future.call_method0("done")?.is_true()? {
+            future.call_method1("set_result", (py.None(),))?;
+        }
+        Ok(())
+    })?;
+    event_loop.call_method1("call_soon_threadsafe", (callback,))?;
+    Ok(())
+}
@@ -10,8 +10,13 @@ use crate::exceptions::PyTypeError;
 use crate::instance::PyNativeType;
 use crate::pyclass::PyClass;
 use crate::types::{PyAny, PyDict, PyModule, PyString, PyTuple};
-use crate::{ffi, GILPool, PyCell, Python};
+use crate::{ffi, AsPyPointer, GILPool, PyCell, PyObject, Python};
+use std::any::Any;
 use std::cell::UnsafeCell;
+use std::ffi::CStr;
+use std::mem;
+use std::os::raw::{c_int, c_void};
+use std::panic::AssertUnwindSafe;
 
 #[derive(Debug)]
 pub struct KeywordOnlyParameterDescription {
@@ -281,9 +286,77 @@ pub fn argument_extraction_error(py: Python, arg_name: &str, error: PyErr) -> Py
     }
 }
 
+/// Describes an error for the message of an error wrapping it: `TypeError`s by their message,
+/// so that the paths of nested fields read naturally, and other errors with their type.
+fn describe_error(py: Python, error: &PyErr) -> String {
+    let reason = error
+        .instance(py)
+        .str()
+        .map(|reason| reason.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    if error.is_instance::<PyTypeError>(py) {
+        reason
+    } else {
+        let type_name = error.ptype(py).name().unwrap_or("<unknown>");
+        format!("{}: {}", type_name, reason)
+    }
+}
+
+/// Add the name of a field to an error which occurred during `#[derive(FromPyObject)]`.
+///
+/// The result is a `TypeError` whose `__cause__` is the original error.
+#[doc(hidden)]
+pub fn failed_to_extract_field(py: Python, field_name: &str, error: PyErr) -> PyErr {
+    let err = PyTypeError::new_err(format!(
+        "field '{}': {}",
+        field_name,
+        describe_error(py, &error)
+    ));
+    err.set_cause(py, Some(error));
+    err
+}
+
+/// Combine the errors of all variants tried by `#[derive(FromPyObject)]` for an enum.
+///
+/// `union_name` describes the expected type, and `variants` hold the path of each variant, its
+/// `annotation` and its error. The first variant's error is the `__cause__` of the result.
+#[doc(hidden)]
+pub fn failed_to_extract_enum(
+    obj: &PyAny,
+    union_name: &str,
+    variants: Vec<(&str, Option<&str>, PyErr)>,
+) -> PyErr {
+    let py = obj.py();
+    let type_name = obj.get_type().name().unwrap_or("<unknown>");
+    let mut msg = format!(
+        "'{}' object cannot be converted to '{}'",
+        type_name, union_name
+    );
+    let mut cause = None;
+    for (name, annotation, error) in variants {
+        msg.push_str("\n- variant ");
+        msg.push_str(name);
+        if let Some(annotation) = annotation {
+            msg.push_str(&format!(" ({})", annotation));
+        }
+        msg.push_str(": ");
+        msg.push_str(&describe_error(py, &error));
+        if cause.is_none() {
+            cause = Some(error);
+        }
+    }
+    let err = PyTypeError::new_err(msg);
+    err.set_cause(py, cause);
+    err
+}
+
 /// `Sync` wrapper of `ffi::PyModuleDef`.
 #[doc(hidden)]
-pub struct ModuleDef(UnsafeCell<ffi::PyModuleDef>);
+pub struct ModuleDef {
+    def: UnsafeCell<ffi::PyModuleDef>,
+    /// The `Py_mod_exec` slot of multi-phase modules, and the terminating null slot.
+    slots: UnsafeCell<[ffi::PyModuleDef_Slot; 2]>,
+}
 
 unsafe impl Sync for ModuleDef {}
 
@@ -296,7 +369,14 @@ impl ModuleDef {
         #[allow(deprecated)]
         let mut init = ffi::PyModuleDef_INIT;
         init.m_name = name.as_ptr() as *const _;
-        ModuleDef(UnsafeCell::new(init))
+        const NULL_SLOT: ffi::PyModuleDef_Slot = ffi::PyModuleDef_Slot {
+            slot: 0,
+            value: std::ptr::null_mut(),
+        };
+        ModuleDef {
+            def: UnsafeCell::new(init),
+            slots: UnsafeCell::new([NULL_SLOT, NULL_SLOT]),
+        }
     }
     /// Builds a module using user given initializer. Used for `#[pymodule]`.
     ///
@@ -313,7 +393,7 @@ impl ModuleDef {
         #[cfg(not(Py_3_7))]
         ffi::PyEval_InitThreads();
 
-        let module = ffi::PyModule_Create(self.0.get());
+        let module = ffi::PyModule_Create(self.def.get());
         let pool = GILPool::new();
         let py = pool.python();
         if module.is_null() {
@@ -324,6 +404,128 @@ impl ModuleDef {
         initializer(py, module)?;
         Ok(crate::IntoPyPointer::into_ptr(module))
     }
+
+    /// Returns the initialized definition of a multi-phase module, which Python executes with
+    /// `exec`. Used for `#[pymodule(multi_phase)]`.
+    ///
+    /// # Safety
+    /// The caller must have GIL.
+    pub unsafe fn make_multi_phase_def(
+        &'static self,
+        exec: unsafe extern "C" fn(*mut ffi::PyObject) -> c_int,
+    ) -> *mut ffi::PyObject {
+        #[cfg(py_sys_config = "WITH_THREAD")]
+        #[cfg(not(Py_3_7))]
+        ffi::PyEval_InitThreads();
+
+        let slots = &mut *self.slots.get();
+        slots[0] = ffi::PyModuleDef_Slot {
+            slot: ffi::Py_mod_exec,
+            value: exec as *mut c_void,
+        };
+        let def = &mut *self.def.get();
+        def.m_slots = slots.as_mut_ptr();
+        def.m_size = mem::size_of::<*mut ModuleState>() as ffi::Py_ssize_t;
+        def.m_free = Some(free_module_state);
+        ffi::PyModuleDef_Init(def)
+    }
+
+    /// Executes a multi-phase module: allocates its state, then runs the user given initializer.
+    ///
+    /// # Safety
+    /// `module` must be a module created from a definition returned by
+    /// [ModuleDef::make_multi_phase_def].
+    pub unsafe fn exec_module(
+        py: Python,
+        module: *mut ffi::PyObject,
+        doc: &str,
+        state: Option<Box<dyn Any + Send>>,
+        initializer: impl Fn(Python, &PyModule) -> PyResult<()>,
+    ) -> PyResult<c_int> {
+        let module = py.from_borrowed_ptr::<PyModule>(module);
+        let slot = ffi::PyModule_GetState(module.as_ptr()) as *mut *mut ModuleState;
+        if slot.is_null() {
+            return Err(PyErr::fetch(py));
+        }
+        let old = mem::replace(&mut *slot, Box::into_raw(Box::new(ModuleState { state })));
+        if !old.is_null() {
+            drop(Box::from_raw(old));
+        }
+        module.add("__doc__", doc)?;
+        initializer(py, module)?;
+        Ok(0)
+    }
+}
+
+/// The state of a multi-phase module, to which the module state allocated by Python points.
+pub(crate) struct ModuleState {
+    /// The user given state of `#[pymodule(state = ...)]`.
+    pub(crate) state: Option<Box<dyn Any + Send>>,
+}
+
+impl ModuleState {
+    /// Returns the state of `module`, if it is a multi-phase module defined by
+    /// [ModuleDef::make_multi_phase_def] which was executed.
+    pub(crate) fn of(module: &PyModule) -> Option<&ModuleState> {
+        unsafe {
+            let def = ffi::PyModule_GetDef(module.as_ptr());
+            // Comparing the addresses is fine, as `free_module_state` is not generic.
+            let free = free_module_state as ffi::freefunc as usize;
+            if def.is_null() || (*def).m_free.map(|f| f as usize) != Some(free) {
+                return None;
+            }
+            let slot = ffi::PyModule_GetState(module.as_ptr()) as *mut *mut ModuleState;
+            if slot.is_null() {
+                return None;
+            }
+            (*slot).as_ref()
+        }
+    }
+}
+
+/// The `m_free` function of multi-phase modules, which drops their state.
+unsafe extern "C" fn free_module_state(module: *mut c_void) {
+    let slot = ffi::PyModule_GetState(module as *mut ffi::PyObject) as *mut *mut ModuleState;
+    if slot.is_null() || (*slot).is_null() {
+        return;
+    }
+    let state = AssertUnwindSafe(mem::replace(&mut *slot, std::ptr::null_mut()));
+    crate::callback::handle_panic(move |_py| {
+        drop(Box::from_raw(state.0));
+        Ok(())
+    })
+}
+
+/// Creates the module of a `#[pymodule]` from the result of its `PyInit_` function, which is
+/// either the module itself or the definition of a multi-phase module. Used for
+/// [wrap_pymodule!](crate::wrap_pymodule).
+///
+/// # Safety
+/// `init` must be the result of the `PyInit_` function of a module.
+pub unsafe fn module_from_init(py: Python, init: *mut ffi::PyObject) -> PyObject {
+    if ffi::PyObject_TypeCheck(init, &mut ffi::PyModuleDef_Type) == 0 {
+        return PyObject::from_owned_ptr(py, init);
+    }
+    let def = init as *mut ffi::PyModuleDef;
+    module_from_def(py, def).unwrap_or_else(|err| {
+        err.restore(py);
+        crate::err::panic_after_error(py)
+    })
+}
+
+/// Creates and executes a multi-phase module, like the import system does.
+unsafe fn module_from_def(py: Python, def: *mut ffi::PyModuleDef) -> PyResult<PyObject> {
+    let name = CStr::from_ptr((*def).m_name).to_str()?;
+    let spec = py
+        .import("importlib.machinery")?
+        .getattr("ModuleSpec")?
+        .call1((name, py.None()))?;
+    let module =
+        PyObject::from_owned_ptr_or_err(py, ffi::PyModule_FromDefAndSpec(def, spec.as_ptr()))?;
+    if ffi::PyModule_ExecDef(module.as_ptr(), def) != 0 {
+        return Err(PyErr::fetch(py));
+    }
+    Ok(module)
 }
 
 /// Utilities for basetype
@@ -332,6 +534,8 @@ pub trait PyBaseTypeUtils: Sized {
     type Dict;
     type WeakRef;
     type LayoutAsBase;
+    /// The base layout of a `#[pyclass(frozen)]` extending this type.
+    type FrozenLayoutAsBase;
     type BaseNativeType;
     type ThreadChecker: PyClassThreadChecker<Self>;
 }
@@ -340,6 +544,7 @@ impl<T: PyClass> PyBaseTypeUtils for T {
     type Dict = T::Dict;
     type WeakRef = T::WeakRef;
     type LayoutAsBase = crate::pycell::PyCellInner<T>;
+    type FrozenLayoutAsBase = crate::pycell::PyCellInner<T>;
     type BaseNativeType = T::BaseNativeType;
     type ThreadChecker = T::ThreadChecker;
 }
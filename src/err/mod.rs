@@ -8,7 +8,7 @@ use crate::{
     ffi,
 };
 use crate::{
-    AsPyPointer, FromPyPointer, IntoPy, Py, PyAny, PyNativeType, PyObject, Python,
+    AsPyPointer, FromPyPointer, IntoPy, IntoPyPointer, Py, PyAny, PyNativeType, PyObject, Python,
     ToBorrowedObject, ToPyObject,
 };
 use std::borrow::Cow;
@@ -345,6 +345,31 @@ impl PyErr {
         out
     }
 
+    /// Returns the cause (either an exception instance, or `None`) set for this exception, as
+    /// accessible from Python through `__cause__`.
+    pub fn cause(&self, py: Python) -> Option<PyErr> {
+        let ptr = unsafe { ffi::PyException_GetCause(self.pvalue(py).as_ptr()) };
+        let obj = unsafe { py.from_owned_ptr_or_opt::<PyAny>(ptr) };
+        obj.map(PyErr::from_instance)
+    }
+
+    /// Sets the cause associated with the exception, pass `None` to clear it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use pyo3::{Python, exceptions::{PyKeyError, PyTypeError}};
+    /// Python::with_gil(|py| {
+    ///     let err = PyTypeError::new_err("failed to extract field");
+    ///     err.set_cause(py, Some(PyKeyError::new_err("name")));
+    ///     assert!(err.cause(py).unwrap().is_instance::<PyKeyError>(py));
+    /// });
+    /// ```
+    pub fn set_cause(&self, py: Python, cause: Option<PyErr>) {
+        let cause = cause.map_or(std::ptr::null_mut(), |err| err.into_instance(py).into_ptr());
+        // `PyException_SetCause` steals the reference to the cause
+        unsafe { ffi::PyException_SetCause(self.pvalue(py).as_ptr(), cause) }
+    }
+
     /// Writes the error back to the Python interpreter's global state.
     /// This is the opposite of `PyErr::fetch()`.
     #[inline]
@@ -4,9 +4,9 @@ use std::os::raw::c_int;
 opaque_struct!(PyWeakReference);
 
 extern "C" {
-    static mut _PyWeakref_RefType: PyTypeObject;
-    static mut _PyWeakref_ProxyType: PyTypeObject;
-    static mut _PyWeakref_CallableProxyType: PyTypeObject;
+    pub static mut _PyWeakref_RefType: PyTypeObject;
+    pub static mut _PyWeakref_ProxyType: PyTypeObject;
+    pub static mut _PyWeakref_CallableProxyType: PyTypeObject;
 
     #[cfg(PyPy)]
     #[link_name = "PyPyWeakref_CheckRef"]
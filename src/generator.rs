@@ -0,0 +1,247 @@
+//! Python generators backed by Rust state machines.
+//!
+//! [`PyIterProtocol`](crate::class::iter::PyIterProtocol) is enough for plain iterators, but
+//! `yield from` and `await` also send values into the iterator, throw exceptions into it, close it,
+//! and read the value it returns from `StopIteration.value`. A [`Generator`] handles all of these,
+//! and [`PyGenerator`] exposes it to Python as an object which behaves like a generator of a
+//! `def` with `yield` statements.
+//!
+//! # Example
+//! ```rust
+//! use pyo3::class::iter::IterNextOutput;
+//! use pyo3::generator::{Generator, PyGenerator};
+//! use pyo3::prelude::*;
+//!
+//! /// Sums the values sent to it, and returns the sum once it reaches `limit`.
+//! struct Sum {
+//!     total: i64,
+//!     limit: i64,
+//! }
+//!
+//! impl Generator for Sum {
+//!     type Yield = i64;
+//!     type Return = i64;
+//!
+//!     fn resume(&mut self, _py: Python, value: &PyAny) -> PyResult<IterNextOutput<i64, i64>> {
+//!         if !value.is_none() {
+//!             self.total += value.extract::<i64>()?;
+//!         }
+//!         if self.total >= self.limit {
+//!             Ok(IterNextOutput::Return(self.total))
+//!         } else {
+//!             Ok(IterNextOutput::Yield(self.total))
+//!         }
+//!     }
+//! }
+//!
+//! #[pyclass]
+//! struct Adder {}
+//!
+//! #[pymethods]
+//! impl Adder {
+//!     fn sum(&self, limit: i64) -> PyGenerator {
+//!         PyGenerator::new(Sum { total: 0, limit })
+//!     }
+//! }
+//!
+//! Python::with_gil(|py| {
+//!     let adder = Py::new(py, Adder {}).unwrap();
+//!     pyo3::py_run!(py, adder, r#"
+//! def delegate(adder):
+//!     return (yield from adder.sum(5))
+//!
+//! generator = delegate(adder)
+//! assert generator.send(None) == 0
+//! assert generator.send(2) == 2
+//! try:
+//!     generator.send(3)
+//! except StopIteration as e:
+//!     assert e.value == 5
+//! else:
+//!     assert False
+//! "#);
+//! });
+//! ```
+
+use crate::callback::IntoPyCallbackOutput;
+use crate::class::iter::{IterNextOutput, PyIterNextOutput};
+use crate::exceptions::{
+    PyBaseException, PyGeneratorExit, PyRuntimeError, PyStopIteration, PyTypeError,
+};
+use crate::proc_macro::{pyclass, pymethods};
+use crate::{IntoPy, PyAny, PyErr, PyObject, PyRef, PyResult, Python};
+
+/// A Rust state machine which is resumed like a Python generator, see [`PyGenerator`].
+///
+/// Each resumption either yields a value, returns a value which finishes the generator, or fails
+/// with an error which also finishes it.
+pub trait Generator: Send + 'static {
+    /// The type of the values yielded to Python.
+    type Yield: IntoPy<PyObject>;
+    /// The type of the value returned in `StopIteration.value` when the generator finishes.
+    type Return: IntoPy<PyObject>;
+
+    /// Resumes the generator with the value given to `send`, which is `None` for `__next__`.
+    fn resume(
+        &mut self,
+        py: Python,
+        value: &PyAny,
+    ) -> PyResult<IterNextOutput<Self::Yield, Self::Return>>;
+
+    /// Resumes the generator with the exception given to `throw`, or with `GeneratorExit` when the
+    /// generator is closed.
+    ///
+    /// By default the generator does not handle the exception, i.e. it finishes by raising `err`.
+    fn throw(
+        &mut self,
+        py: Python,
+        err: PyErr,
+    ) -> PyResult<IterNextOutput<Self::Yield, Self::Return>> {
+        let _ = py;
+        Err(err)
+    }
+}
+
+/// [`Generator`] with the values converted to Python objects, so that [`PyGenerator`] can hold
+/// any generator.
+trait ErasedGenerator: Send {
+    fn resume(&mut self, py: Python, value: &PyAny) -> PyResult<PyIterNextOutput>;
+    fn throw(&mut self, py: Python, err: PyErr) -> PyResult<PyIterNextOutput>;
+}
+
+impl<G: Generator> ErasedGenerator for G {
+    fn resume(&mut self, py: Python, value: &PyAny) -> PyResult<PyIterNextOutput> {
+        Generator::resume(self, py, value).convert(py)
+    }
+
+    fn throw(&mut self, py: Python, err: PyErr) -> PyResult<PyIterNextOutput> {
+        Generator::throw(self, py, err).convert(py)
+    }
+}
+
+/// A Python generator which runs a [`Generator`].
+///
+/// It supports the same protocol as the generators of Python functions with `yield` statements:
+/// `__next__`, `send`, `throw` and `close`, so it can be delegated to with `yield from`, or be
+/// returned by `__await__` to implement an awaitable object. The value returned by the
+/// [`Generator`] is raised as `StopIteration(value)`.
+///
+/// The [`Generator`] is dropped when it finishes or the generator is closed. Resuming a finished
+/// generator raises `StopIteration`.
+#[pyclass]
+pub struct PyGenerator {
+    /// The generator, until it finishes.
+    generator: Option<Box<dyn ErasedGenerator>>,
+    started: bool,
+}
+
+impl PyGenerator {
+    /// Wraps `generator`, which is first resumed by the first call of `__next__` or `send`.
+    pub fn new<G: Generator>(generator: G) -> Self {
+        PyGenerator {
+            generator: Some(Box::new(generator)),
+            started: false,
+        }
+    }
+
+    /// Drops the generator once it returned or failed.
+    fn finish_unless_yielded(
+        &mut self,
+        result: PyResult<PyIterNextOutput>,
+    ) -> PyResult<PyIterNextOutput> {
+        if let Ok(IterNextOutput::Yield(_)) = result {
+            return result;
+        }
+        self.generator = None;
+        result
+    }
+}
+
+#[pymethods]
+impl PyGenerator {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(&mut self, py: Python) -> PyResult<PyIterNextOutput> {
+        self.send(py, py.None().into_ref(py))
+    }
+
+    /// Resumes the generator with `value`.
+    fn send(&mut self, py: Python, value: &PyAny) -> PyResult<PyIterNextOutput> {
+        let generator = match self.generator.as_mut() {
+            Some(generator) => generator,
+            None => return Err(PyStopIteration::new_err(())),
+        };
+        if !self.started && !value.is_none() {
+            return Err(PyTypeError::new_err(
+                "can't send non-None value to a just-started generator",
+            ));
+        }
+        self.started = true;
+        let result = generator.resume(py, value);
+        self.finish_unless_yielded(result)
+    }
+
+    /// Resumes the generator with the exception given as in `generator.throw`.
+    fn throw(
+        &mut self,
+        py: Python,
+        typ: &PyAny,
+        val: Option<&PyAny>,
+        tb: Option<&PyAny>,
+    ) -> PyResult<PyIterNextOutput> {
+        let err = thrown_exception(typ, val, tb)?;
+        let generator = match self.generator.as_mut() {
+            Some(generator) => generator,
+            None => return Err(err),
+        };
+        self.started = true;
+        let result = generator.throw(py, err);
+        self.finish_unless_yielded(result)
+    }
+
+    /// Resumes the generator with `GeneratorExit`, which it has to raise or return for.
+    fn close(&mut self, py: Python) -> PyResult<()> {
+        let mut generator = match self.generator.take() {
+            Some(generator) => generator,
+            None => return Ok(()),
+        };
+        if !self.started {
+            return Ok(());
+        }
+        match generator.throw(py, PyGeneratorExit::new_err(())) {
+            Ok(IterNextOutput::Yield(_)) => {
+                Err(PyRuntimeError::new_err("generator ignored GeneratorExit"))
+            }
+            Ok(IterNextOutput::Return(_)) => Ok(()),
+            Err(err)
+                if err.is_instance::<PyGeneratorExit>(py)
+                    || err.is_instance::<PyStopIteration>(py) =>
+            {
+                Ok(())
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// Builds the exception thrown by `throw(typ, val, tb)`, where `typ` is an exception type or
+/// instance, and `val` the instance or the argument of `typ`.
+pub(crate) fn thrown_exception(
+    typ: &PyAny,
+    val: Option<&PyAny>,
+    tb: Option<&PyAny>,
+) -> PyResult<PyErr> {
+    let mut exception = match val {
+        Some(val) if val.is_instance::<PyBaseException>()? => val,
+        Some(val) if !val.is_none() => typ.call1((val,))?,
+        _ => typ,
+    };
+    if let Some(tb) = tb.filter(|tb| !tb.is_none()) {
+        if exception.is_instance::<PyBaseException>()? {
+            exception = exception.call_method1("with_traceback", (tb,))?;
+        }
+    }
+    Ok(PyErr::from_instance(exception))
+}
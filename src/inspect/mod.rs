@@ -0,0 +1,199 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Metadata about the functions, classes and modules exposed to Python, used to generate
+//! type stubs (`.pyi` files).
+//!
+//! `#[pymodule]`, `#[pyclass]`, `#[pyfunction]` and `#[pymethods]` record the names,
+//! parameters, defaults and types of everything they expose. The Rust types of parameters and
+//! return values are mapped to Python types with [`FromPyObject::type_input`] and
+//! [`IntoPy::type_output`], which custom conversions can override to state their annotation.
+//!
+//! The macros only record this metadata when the `inspect` feature is enabled. The metadata of a
+//! `#[pymodule]` is then obtained with `inspect_module!` and turned into a stub with
+//! `module_stub` or `write_stubs`:
+//!
+//! ```
+//! # #[cfg(feature = "inspect")] {
+//! use pyo3::prelude::*;
+//! use pyo3::{inspect, inspect_module, wrap_pyfunction};
+//!
+//! #[pyfunction]
+//! fn add(a: i64, b: Option<i64>) -> i64 {
+//!     a + b.unwrap_or(1)
+//! }
+//!
+//! #[pymodule]
+//! fn my_module(_py: Python, m: &PyModule) -> PyResult<()> {
+//!     m.add_function(wrap_pyfunction!(add, m)?)?;
+//!     Ok(())
+//! }
+//!
+//! let stub = inspect::module_stub(&inspect_module!(my_module));
+//! assert!(stub.contains("def add(a: int, b: typing.Optional[int] = None) -> int: ..."));
+//! # }
+//! ```
+//!
+//! The contents of a module are found by looking for `wrap_pyfunction!`, `wrap_pymodule!`,
+//! `add_class::<T>()` and `#[pyfn]` in the body of the `#[pymodule]` function. Items which are
+//! added to the module in any other way, e.g. in a helper function, are not included.
+//!
+//! [`FromPyObject::type_input`]: crate::FromPyObject::type_input
+//! [`IntoPy::type_output`]: crate::IntoPy::type_output
+
+#[cfg(feature = "inspect")]
+mod stub;
+pub mod types;
+
+#[cfg(feature = "inspect")]
+pub use self::stub::{module_stub, write_stubs};
+pub use self::types::TypeInfo;
+
+use crate::PyClass;
+
+/// The kind of a [`FunctionInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FunctionKind {
+    /// A module-level function.
+    Function,
+    /// An instance method, including `__call__`.
+    Method,
+    /// A method marked with `#[classmethod]`.
+    ClassMethod,
+    /// A method marked with `#[staticmethod]`.
+    StaticMethod,
+    /// The constructor, `__new__`.
+    New,
+    /// A property getter.
+    Getter,
+    /// A property setter.
+    Setter,
+    /// A class attribute created by a method marked with `#[classattr]`.
+    ClassAttribute,
+}
+
+/// The kind of a [`ParameterInfo`], as in Python's `inspect.Parameter.kind`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParameterKind {
+    /// A parameter before `/`.
+    PositionalOnly,
+    /// A regular parameter.
+    PositionalOrKeyword,
+    /// `*args`.
+    VarPositional,
+    /// A parameter after `*` or `*args`.
+    KeywordOnly,
+    /// `**kwargs`.
+    VarKeyword,
+}
+
+/// A parameter of a [`FunctionInfo`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParameterInfo {
+    /// The name of the parameter.
+    pub name: &'static str,
+    /// How arguments are bound to the parameter.
+    pub kind: ParameterKind,
+    /// The type of the values accepted by the parameter.
+    pub annotation: TypeInfo,
+    /// The default value as Python source, e.g. `None` or `1`, or `...` if the default cannot be
+    /// written in Python.
+    pub default: Option<&'static str>,
+}
+
+/// A function or method exposed to Python.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionInfo {
+    /// The Python name of the function.
+    pub name: &'static str,
+    /// The docstring, which may start with the text signature and may be nul-terminated.
+    pub doc: &'static str,
+    /// The kind of the function.
+    pub kind: FunctionKind,
+    /// The parameters, not including `self` or `cls`.
+    pub parameters: Vec<ParameterInfo>,
+    /// The type of the value returned to Python, which is awaited first if `is_async`.
+    pub return_type: TypeInfo,
+    /// Whether this is an `async fn`, i.e. returns a coroutine.
+    pub is_async: bool,
+}
+
+/// The kind of an [`AttributeInfo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AttributeKind {
+    /// An instance attribute which can only be read, i.e. `#[pyo3(get)]`.
+    ReadOnly,
+    /// An instance attribute which can be read and written, i.e. `#[pyo3(get, set)]`.
+    ReadWrite,
+    /// An instance attribute which can only be written, i.e. `#[pyo3(set)]`.
+    WriteOnly,
+    /// A class attribute, such as a `#[classattr]` constant or a variant of a `#[pyclass]` enum.
+    Class,
+}
+
+/// An attribute of a [`ClassInfo`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeInfo {
+    /// The name of the attribute.
+    pub name: &'static str,
+    /// The docstring, which may be nul-terminated.
+    pub doc: &'static str,
+    /// How the attribute is accessed.
+    pub kind: AttributeKind,
+    /// The type of the attribute.
+    pub annotation: TypeInfo,
+}
+
+/// A class exposed to Python.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassInfo {
+    /// The Python name of the class.
+    pub name: &'static str,
+    /// The module given with `#[pyclass(module = "...")]`.
+    pub module: Option<&'static str>,
+    /// The docstring, which may start with the text signature and may be nul-terminated.
+    pub doc: &'static str,
+    /// The base class given with `#[pyclass(extends = ...)]`.
+    pub base: Option<TypeInfo>,
+    /// The fields, class attributes and enum variants.
+    pub attributes: Vec<AttributeInfo>,
+    /// The methods, including properties implemented with `#[getter]` and `#[setter]`.
+    pub methods: Vec<FunctionInfo>,
+}
+
+impl ClassInfo {
+    /// The description of a class which only has a name, module and docstring.
+    pub fn new<T: PyClass>() -> Self {
+        ClassInfo {
+            name: T::NAME,
+            module: T::MODULE,
+            doc: T::DESCRIPTION,
+            base: None,
+            attributes: Vec::new(),
+            methods: Vec::new(),
+        }
+    }
+}
+
+/// A module exposed to Python.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModuleInfo {
+    /// The name of the module.
+    pub name: &'static str,
+    /// The docstring of the module.
+    pub doc: &'static str,
+    /// The functions added to the module.
+    pub functions: Vec<FunctionInfo>,
+    /// The classes added to the module.
+    pub classes: Vec<ClassInfo>,
+    /// The submodules added to the module.
+    pub submodules: Vec<ModuleInfo>,
+}
+
+/// Returns the description of the class `T`.
+///
+/// For `#[pyclass]` this includes the fields exposed with `#[pyo3(get, set)]` and the methods
+/// from `#[pymethods]`.
+#[cfg(feature = "inspect")]
+pub fn class_info<T: PyClass>() -> ClassInfo {
+    T::class_info()
+}
@@ -0,0 +1,406 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! Rendering of [`ModuleInfo`] as a type stub.
+
+use super::{
+    AttributeInfo, AttributeKind, ClassInfo, FunctionInfo, FunctionKind, ModuleInfo, ParameterKind,
+    TypeInfo,
+};
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::path::Path;
+use std::{fs, io};
+
+/// Renders the contents of the type stub (`.pyi` file) of `module`.
+///
+/// Submodules are imported with `from . import submodule`, which matches the layout created by
+/// [`write_stubs`].
+pub fn module_stub(module: &ModuleInfo) -> String {
+    let mut imports = BTreeSet::new();
+    let mut body = String::new();
+    let mut stub = StubWriter {
+        module: module.name,
+        imports: &mut imports,
+        out: &mut body,
+    };
+    for class in &module.classes {
+        stub.class(class);
+    }
+    for function in &module.functions {
+        stub.function(function, "");
+    }
+
+    let mut out = String::new();
+    let doc = clean_doc(module.doc);
+    if !doc.is_empty() {
+        write_docstring(&mut out, doc, "");
+        out.push('\n');
+    }
+    for import in &imports {
+        writeln!(out, "import {}", import).unwrap();
+    }
+    for submodule in &module.submodules {
+        writeln!(out, "from . import {0} as {0}", submodule.name).unwrap();
+    }
+    out.push_str(&body);
+    out
+}
+
+/// Writes the type stubs of `module` into the directory `dir`.
+///
+/// A module without submodules is written to `<dir>/<name>.pyi`. A module with submodules is
+/// written as a package, i.e. to `<dir>/<name>/__init__.pyi`, with the stubs of the submodules
+/// next to it.
+pub fn write_stubs(module: &ModuleInfo, dir: impl AsRef<Path>) -> io::Result<()> {
+    let dir = dir.as_ref();
+    if module.submodules.is_empty() {
+        return fs::write(
+            dir.join(format!("{}.pyi", module.name)),
+            module_stub(module),
+        );
+    }
+    let package = dir.join(module.name);
+    fs::create_dir_all(&package)?;
+    fs::write(package.join("__init__.pyi"), module_stub(module))?;
+    for submodule in &module.submodules {
+        write_stubs(submodule, &package)?;
+    }
+    Ok(())
+}
+
+struct StubWriter<'a> {
+    module: &'static str,
+    imports: &'a mut BTreeSet<&'static str>,
+    out: &'a mut String,
+}
+
+impl StubWriter<'_> {
+    fn annotation(&mut self, type_info: &TypeInfo) -> String {
+        let module = self.module;
+        let imports = &mut *self.imports;
+        type_info.for_each_module(&mut |import| {
+            if import != module {
+                imports.insert(import);
+            }
+        });
+        type_info.display_in(module).to_string()
+    }
+
+    fn class(&mut self, class: &ClassInfo) {
+        self.out.push('\n');
+        write!(self.out, "class {}", class.name).unwrap();
+        match &class.base {
+            Some(TypeInfo::Any) | None => {}
+            Some(base) => {
+                let base = self.annotation(base);
+                write!(self.out, "({})", base).unwrap();
+            }
+        }
+        self.out.push_str(":\n");
+
+        let start = self.out.len();
+        let doc = clean_doc(class.doc);
+        if !doc.is_empty() {
+            write_docstring(self.out, doc, "    ");
+        }
+        for attribute in &class.attributes {
+            self.attribute(attribute);
+        }
+        for method in &class.methods {
+            match method.kind {
+                FunctionKind::Setter => {}
+                FunctionKind::ClassAttribute => {
+                    let annotation = self.annotation(&method.return_type);
+                    writeln!(
+                        self.out,
+                        "    {}: typing.ClassVar[{}]",
+                        method.name, annotation
+                    )
+                    .unwrap();
+                    self.imports.insert("typing");
+                }
+                _ => self.function(method, "    "),
+            }
+        }
+        for setter in class
+            .methods
+            .iter()
+            .filter(|method| method.kind == FunctionKind::Setter)
+        {
+            let has_getter =
+                class.methods.iter().any(|method| {
+                    method.kind == FunctionKind::Getter && method.name == setter.name
+                }) || class.attributes.iter().any(|attribute| {
+                    attribute.kind == AttributeKind::ReadOnly && attribute.name == setter.name
+                });
+            if has_getter {
+                self.function(setter, "    ");
+            } else if let Some(value) = setter.parameters.first() {
+                let annotation = self.annotation(&value.annotation);
+                writeln!(self.out, "    {}: {}", setter.name, annotation).unwrap();
+            }
+        }
+        if self.out.len() == start {
+            self.out.push_str("    ...\n");
+        }
+    }
+
+    fn attribute(&mut self, attribute: &AttributeInfo) {
+        let annotation = self.annotation(&attribute.annotation);
+        match attribute.kind {
+            AttributeKind::ReadOnly => {
+                writeln!(
+                    self.out,
+                    "    @property\n    def {}(self) -> {}: ...",
+                    attribute.name, annotation
+                )
+                .unwrap();
+            }
+            AttributeKind::ReadWrite | AttributeKind::WriteOnly => {
+                writeln!(self.out, "    {}: {}", attribute.name, annotation).unwrap();
+            }
+            AttributeKind::Class => {
+                writeln!(
+                    self.out,
+                    "    {}: typing.ClassVar[{}]",
+                    attribute.name, annotation
+                )
+                .unwrap();
+                self.imports.insert("typing");
+            }
+        }
+    }
+
+    fn function(&mut self, function: &FunctionInfo, indent: &str) {
+        if indent.is_empty() {
+            self.out.push('\n');
+        }
+        let (decorator, receiver) = match function.kind {
+            FunctionKind::Function => (None, None),
+            FunctionKind::Method => (None, Some("self")),
+            FunctionKind::ClassMethod => (Some("@classmethod".to_owned()), Some("cls")),
+            FunctionKind::StaticMethod => (Some("@staticmethod".to_owned()), None),
+            FunctionKind::New => (None, Some("cls")),
+            FunctionKind::Getter => (Some("@property".to_owned()), Some("self")),
+            FunctionKind::Setter => (Some(format!("@{}.setter", function.name)), Some("self")),
+            FunctionKind::ClassAttribute => unreachable!("class attributes are not functions"),
+        };
+        if let Some(decorator) = decorator {
+            writeln!(self.out, "{}{}", indent, decorator).unwrap();
+        }
+
+        let mut params: Vec<String> = receiver.into_iter().map(str::to_owned).collect();
+        let mut positional_only = false;
+        let mut keyword_only = false;
+        for param in &function.parameters {
+            if positional_only && param.kind != ParameterKind::PositionalOnly {
+                params.push("/".to_owned());
+                positional_only = false;
+            }
+            let param = match param.kind {
+                ParameterKind::VarPositional => {
+                    keyword_only = true;
+                    format!("*{}", param.name)
+                }
+                ParameterKind::VarKeyword => format!("**{}", param.name),
+                kind => {
+                    if kind == ParameterKind::PositionalOnly {
+                        positional_only = true;
+                    } else if kind == ParameterKind::KeywordOnly && !keyword_only {
+                        params.push("*".to_owned());
+                        keyword_only = true;
+                    }
+                    let annotation = self.annotation(&param.annotation);
+                    match param.default {
+                        Some(default) => format!("{}: {} = {}", param.name, annotation, default),
+                        None => format!("{}: {}", param.name, annotation),
+                    }
+                }
+            };
+            params.push(param);
+        }
+        if positional_only {
+            params.push("/".to_owned());
+        }
+
+        let name = match function.kind {
+            FunctionKind::New => "__new__",
+            _ => function.name,
+        };
+        let return_type = self.annotation(&function.return_type);
+        write!(
+            self.out,
+            "{}{}def {}({}) -> {}:",
+            indent,
+            if function.is_async { "async " } else { "" },
+            name,
+            params.join(", "),
+            return_type
+        )
+        .unwrap();
+
+        let doc = clean_doc(function.doc);
+        if doc.is_empty() {
+            self.out.push_str(" ...\n");
+        } else {
+            self.out.push('\n');
+            write_docstring(self.out, doc, &format!("{}    ", indent));
+        }
+    }
+}
+
+/// Strips the nul terminator and the text signature from a docstring.
+fn clean_doc(doc: &str) -> &str {
+    let doc = doc.trim_end_matches('\0');
+    let doc = match doc.find("\n--\n\n") {
+        Some(end) if !doc[..end].contains('\n') => &doc[end + 5..],
+        _ => doc,
+    };
+    doc.trim()
+}
+
+fn write_docstring(out: &mut String, doc: &str, indent: &str) {
+    let doc = doc.replace('\\', "\\\\").replace("\"\"\"", "\\\"\\\"\\\"");
+    write!(out, "{}\"\"\"", indent).unwrap();
+    let mut lines = doc.lines();
+    out.push_str(lines.next().unwrap_or_default());
+    let mut multiline = false;
+    for line in lines {
+        out.push('\n');
+        if !line.is_empty() {
+            out.push_str(indent);
+            out.push_str(line);
+        }
+        multiline = true;
+    }
+    if multiline {
+        write!(out, "\n{}", indent).unwrap();
+    }
+    out.push_str("\"\"\"\n");
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::inspect::ParameterInfo;
+
+    fn param(
+        name: &'static str,
+        kind: ParameterKind,
+        annotation: TypeInfo,
+        default: Option<&'static str>,
+    ) -> ParameterInfo {
+        ParameterInfo {
+            name,
+            kind,
+            annotation,
+            default,
+        }
+    }
+
+    #[test]
+    fn test_function_stub() {
+        let function = FunctionInfo {
+            name: "f",
+            doc: "f(a, /, b=1, *, c)\n--\n\nDoes things.\0",
+            kind: FunctionKind::Function,
+            parameters: vec![
+                param(
+                    "a",
+                    ParameterKind::PositionalOnly,
+                    TypeInfo::Builtin("int"),
+                    None,
+                ),
+                param(
+                    "b",
+                    ParameterKind::PositionalOrKeyword,
+                    TypeInfo::Builtin("int"),
+                    Some("1"),
+                ),
+                param("c", ParameterKind::KeywordOnly, TypeInfo::Any, None),
+                param("kwargs", ParameterKind::VarKeyword, TypeInfo::Any, None),
+            ],
+            return_type: TypeInfo::None,
+            is_async: false,
+        };
+        let module = ModuleInfo {
+            name: "m",
+            doc: "",
+            functions: vec![function],
+            classes: Vec::new(),
+            submodules: Vec::new(),
+        };
+        assert_eq!(
+            module_stub(&module),
+            "import typing\n\
+             \n\
+             def f(a: int, /, b: int = 1, *, c: typing.Any, **kwargs) -> None:\n    \
+                 \"\"\"Does things.\"\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_class_stub() {
+        let class = ClassInfo {
+            name: "C",
+            module: Some("m"),
+            doc: "\0",
+            base: Some(TypeInfo::Class {
+                module: Some("other"),
+                name: "Base",
+            }),
+            attributes: vec![AttributeInfo {
+                name: "x",
+                doc: "",
+                kind: AttributeKind::ReadOnly,
+                annotation: TypeInfo::Builtin("float"),
+            }],
+            methods: vec![
+                FunctionInfo {
+                    name: "x",
+                    doc: "",
+                    kind: FunctionKind::Setter,
+                    parameters: vec![param(
+                        "value",
+                        ParameterKind::PositionalOrKeyword,
+                        TypeInfo::Builtin("float"),
+                        None,
+                    )],
+                    return_type: TypeInfo::None,
+                    is_async: false,
+                },
+                FunctionInfo {
+                    name: "__new__",
+                    doc: "",
+                    kind: FunctionKind::New,
+                    parameters: Vec::new(),
+                    return_type: TypeInfo::Class {
+                        module: Some("m"),
+                        name: "C",
+                    },
+                    is_async: false,
+                },
+            ],
+        };
+        let module = ModuleInfo {
+            name: "m",
+            doc: "The module.\n\nMore words.",
+            functions: Vec::new(),
+            classes: vec![class],
+            submodules: Vec::new(),
+        };
+        assert_eq!(
+            module_stub(&module),
+            "\"\"\"The module.\n\nMore words.\n\"\"\"\n\
+             \n\
+             import other\n\
+             \n\
+             class C(other.Base):\n    \
+                 @property\n    \
+                 def x(self) -> float: ...\n    \
+                 def __new__(cls) -> C: ...\n    \
+                 @x.setter\n    \
+                 def x(self, value: float) -> None: ...\n"
+        );
+    }
+}
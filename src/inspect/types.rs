@@ -0,0 +1,311 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+//! The Python types that Rust types are mapped to in type stubs.
+
+use crate::type_object::PyTypeInfo;
+use std::fmt::{self, Display, Formatter};
+
+/// The Python type of a value converted to or from Rust.
+///
+/// This is returned by [`FromPyObject::type_input`](crate::FromPyObject::type_input) and
+/// [`IntoPy::type_output`](crate::IntoPy::type_output), and rendered as a type annotation
+/// using the `typing` module, e.g. `typing.Optional[typing.List[int]]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TypeInfo {
+    /// The type is unknown or cannot be expressed: `typing.Any`.
+    Any,
+    /// `None`.
+    None,
+    /// A builtin type such as `int` or `str`, which is rendered as is.
+    Builtin(&'static str),
+    /// A class, such as a `#[pyclass]`.
+    ///
+    /// When `module` is given and is not the module the annotation is rendered for, the name
+    /// is qualified with the module.
+    Class {
+        module: Option<&'static str>,
+        name: &'static str,
+    },
+    /// `typing.Optional[T]`.
+    Optional(Box<TypeInfo>),
+    /// `typing.Union[T1, T2, ...]`.
+    Union(Vec<TypeInfo>),
+    /// `typing.Tuple[T1, T2, ...]`.
+    Tuple(Vec<TypeInfo>),
+    /// `typing.Tuple[T, ...]`, a tuple of any length.
+    UnsizedTuple(Box<TypeInfo>),
+    /// `typing.List[T]`.
+    List(Box<TypeInfo>),
+    /// `typing.Sequence[T]`.
+    Sequence(Box<TypeInfo>),
+    /// `typing.Set[T]`.
+    Set(Box<TypeInfo>),
+    /// `typing.FrozenSet[T]`.
+    FrozenSet(Box<TypeInfo>),
+    /// `typing.Dict[K, V]`.
+    Dict(Box<TypeInfo>, Box<TypeInfo>),
+    /// `typing.Mapping[K, V]`.
+    Mapping(Box<TypeInfo>, Box<TypeInfo>),
+}
+
+impl TypeInfo {
+    /// The class of the Python type `T`, e.g. a `#[pyclass]`.
+    pub fn class<T: PyTypeInfo>() -> Self {
+        TypeInfo::Class {
+            module: T::MODULE,
+            name: T::NAME,
+        }
+    }
+
+    /// `typing.Optional[T]`.
+    pub fn optional_of(t: TypeInfo) -> Self {
+        match t {
+            TypeInfo::Any | TypeInfo::None | TypeInfo::Optional(_) => t,
+            t => TypeInfo::Optional(Box::new(t)),
+        }
+    }
+
+    /// `typing.Union[...]` of the given types.
+    ///
+    /// Nested unions are flattened and duplicates are removed; if any of the types is
+    /// `Any`, so is the union.
+    pub fn union_of(types: impl IntoIterator<Item = TypeInfo>) -> Self {
+        let mut members: Vec<TypeInfo> = Vec::new();
+        for t in types {
+            let nested = match t {
+                TypeInfo::Any => return TypeInfo::Any,
+                TypeInfo::Union(nested) => nested,
+                t => vec![t],
+            };
+            for t in nested {
+                if !members.contains(&t) {
+                    members.push(t);
+                }
+            }
+        }
+        match members.len() {
+            0 => TypeInfo::Any,
+            1 => members.pop().unwrap(),
+            _ => TypeInfo::Union(members),
+        }
+    }
+
+    /// `typing.Tuple[T, ...]`.
+    pub fn unsized_tuple_of(t: TypeInfo) -> Self {
+        TypeInfo::UnsizedTuple(Box::new(t))
+    }
+
+    /// `typing.List[T]`.
+    pub fn list_of(t: TypeInfo) -> Self {
+        TypeInfo::List(Box::new(t))
+    }
+
+    /// `typing.Sequence[T]`.
+    pub fn sequence_of(t: TypeInfo) -> Self {
+        TypeInfo::Sequence(Box::new(t))
+    }
+
+    /// `typing.Set[T]`.
+    pub fn set_of(t: TypeInfo) -> Self {
+        TypeInfo::Set(Box::new(t))
+    }
+
+    /// `typing.FrozenSet[T]`.
+    pub fn frozen_set_of(t: TypeInfo) -> Self {
+        TypeInfo::FrozenSet(Box::new(t))
+    }
+
+    /// `typing.Dict[K, V]`.
+    pub fn dict_of(k: TypeInfo, v: TypeInfo) -> Self {
+        TypeInfo::Dict(Box::new(k), Box::new(v))
+    }
+
+    /// `typing.Mapping[K, V]`.
+    pub fn mapping_of(k: TypeInfo, v: TypeInfo) -> Self {
+        TypeInfo::Mapping(Box::new(k), Box::new(v))
+    }
+
+    /// Returns an object which renders this type as seen from inside the module `module`,
+    /// i.e. without qualifying classes of that module.
+    pub fn display_in<'a>(&'a self, module: &'a str) -> impl Display + 'a {
+        DisplayIn {
+            type_info: self,
+            module: Some(module),
+        }
+    }
+
+    /// Calls `f` with every module that has to be imported to use this type in an annotation.
+    #[cfg(feature = "inspect")]
+    pub(crate) fn for_each_module(&self, f: &mut impl FnMut(&'static str)) {
+        match self {
+            TypeInfo::Any
+            | TypeInfo::Optional(_)
+            | TypeInfo::Union(_)
+            | TypeInfo::Tuple(_)
+            | TypeInfo::UnsizedTuple(_)
+            | TypeInfo::List(_)
+            | TypeInfo::Sequence(_)
+            | TypeInfo::Set(_)
+            | TypeInfo::FrozenSet(_)
+            | TypeInfo::Dict(_, _)
+            | TypeInfo::Mapping(_, _) => f("typing"),
+            TypeInfo::None | TypeInfo::Builtin(_) => {}
+            TypeInfo::Class { module, .. } => {
+                if let Some(module) = module {
+                    if *module != "builtins" {
+                        f(module)
+                    }
+                }
+            }
+        }
+        match self {
+            TypeInfo::Optional(t)
+            | TypeInfo::UnsizedTuple(t)
+            | TypeInfo::List(t)
+            | TypeInfo::Sequence(t)
+            | TypeInfo::Set(t)
+            | TypeInfo::FrozenSet(t) => t.for_each_module(f),
+            TypeInfo::Union(ts) | TypeInfo::Tuple(ts) => {
+                ts.iter().for_each(|t| t.for_each_module(f))
+            }
+            TypeInfo::Dict(k, v) | TypeInfo::Mapping(k, v) => {
+                k.for_each_module(f);
+                v.for_each_module(f);
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Display for TypeInfo {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        DisplayIn {
+            type_info: self,
+            module: None,
+        }
+        .fmt(f)
+    }
+}
+
+struct DisplayIn<'a> {
+    type_info: &'a TypeInfo,
+    module: Option<&'a str>,
+}
+
+impl DisplayIn<'_> {
+    fn inner<'a>(&'a self, type_info: &'a TypeInfo) -> DisplayIn<'a> {
+        DisplayIn {
+            type_info,
+            module: self.module,
+        }
+    }
+
+    fn fmt_generic(&self, f: &mut Formatter, name: &str, args: &[&TypeInfo]) -> fmt::Result {
+        write!(f, "typing.{}[", name)?;
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            self.inner(arg).fmt(f)?;
+        }
+        f.write_str("]")
+    }
+}
+
+impl Display for DisplayIn<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.type_info {
+            TypeInfo::Any => f.write_str("typing.Any"),
+            TypeInfo::None => f.write_str("None"),
+            TypeInfo::Builtin(name) => f.write_str(name),
+            TypeInfo::Class { module, name } => match module {
+                Some(module) if *module != "builtins" && Some(*module) != self.module => {
+                    write!(f, "{}.{}", module, name)
+                }
+                _ => f.write_str(name),
+            },
+            TypeInfo::Optional(t) => self.fmt_generic(f, "Optional", &[t]),
+            TypeInfo::Union(ts) => {
+                let ts: Vec<_> = ts.iter().collect();
+                self.fmt_generic(f, "Union", &ts)
+            }
+            TypeInfo::Tuple(ts) if ts.is_empty() => f.write_str("typing.Tuple[()]"),
+            TypeInfo::Tuple(ts) => {
+                let ts: Vec<_> = ts.iter().collect();
+                self.fmt_generic(f, "Tuple", &ts)
+            }
+            TypeInfo::UnsizedTuple(t) => {
+                f.write_str("typing.Tuple[")?;
+                self.inner(t).fmt(f)?;
+                f.write_str(", ...]")
+            }
+            TypeInfo::List(t) => self.fmt_generic(f, "List", &[t]),
+            TypeInfo::Sequence(t) => self.fmt_generic(f, "Sequence", &[t]),
+            TypeInfo::Set(t) => self.fmt_generic(f, "Set", &[t]),
+            TypeInfo::FrozenSet(t) => self.fmt_generic(f, "FrozenSet", &[t]),
+            TypeInfo::Dict(k, v) => self.fmt_generic(f, "Dict", &[k, v]),
+            TypeInfo::Mapping(k, v) => self.fmt_generic(f, "Mapping", &[k, v]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::TypeInfo;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(TypeInfo::Any.to_string(), "typing.Any");
+        assert_eq!(TypeInfo::None.to_string(), "None");
+        assert_eq!(
+            TypeInfo::optional_of(TypeInfo::list_of(TypeInfo::Builtin("int"))).to_string(),
+            "typing.Optional[typing.List[int]]"
+        );
+        assert_eq!(
+            TypeInfo::dict_of(TypeInfo::Builtin("str"), TypeInfo::Any).to_string(),
+            "typing.Dict[str, typing.Any]"
+        );
+        assert_eq!(TypeInfo::Tuple(vec![]).to_string(), "typing.Tuple[()]");
+        assert_eq!(
+            TypeInfo::Tuple(vec![TypeInfo::Builtin("int"), TypeInfo::Builtin("str")]).to_string(),
+            "typing.Tuple[int, str]"
+        );
+        assert_eq!(
+            TypeInfo::unsized_tuple_of(TypeInfo::Builtin("float")).to_string(),
+            "typing.Tuple[float, ...]"
+        );
+    }
+
+    #[test]
+    fn test_class_module() {
+        let class = TypeInfo::Class {
+            module: Some("my_module"),
+            name: "MyClass",
+        };
+        assert_eq!(class.to_string(), "my_module.MyClass");
+        assert_eq!(class.display_in("my_module").to_string(), "MyClass");
+        assert_eq!(class.display_in("other").to_string(), "my_module.MyClass");
+
+        let builtin = TypeInfo::Class {
+            module: Some("builtins"),
+            name: "int",
+        };
+        assert_eq!(builtin.to_string(), "int");
+    }
+
+    #[test]
+    fn test_union_of() {
+        let int = TypeInfo::Builtin("int");
+        let str_ = TypeInfo::Builtin("str");
+        assert_eq!(TypeInfo::union_of(vec![int.clone()]), int);
+        assert_eq!(
+            TypeInfo::union_of(vec![
+                int.clone(),
+                TypeInfo::union_of(vec![str_.clone(), int.clone()])
+            ]),
+            TypeInfo::Union(vec![int.clone(), str_])
+        );
+        assert_eq!(TypeInfo::union_of(vec![int, TypeInfo::Any]), TypeInfo::Any);
+        assert_eq!(TypeInfo::optional_of(TypeInfo::Any), TypeInfo::Any);
+    }
+}
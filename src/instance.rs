@@ -2,9 +2,10 @@
 use crate::conversion::{PyTryFrom, ToBorrowedObject};
 use crate::err::{PyDowncastError, PyErr, PyResult};
 use crate::gil;
+use crate::inspect::TypeInfo;
 use crate::pycell::{PyBorrowError, PyBorrowMutError, PyCell};
-use crate::type_object::PyBorrowFlagLayout;
-use crate::types::{PyDict, PyTuple};
+use crate::pyclass::boolean_struct::{False, True};
+use crate::types::{PyDict, PyTuple, PyWeakref};
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, IntoPyPointer, PyAny, PyClass, PyClassInitializer,
     PyRef, PyRefMut, PyTypeInfo, Python, ToPyObject,
@@ -59,10 +60,7 @@ where
     T: PyClass,
 {
     /// Create a new instance `Py<T>` of a `#[pyclass]` on the Python heap.
-    pub fn new(py: Python, value: impl Into<PyClassInitializer<T>>) -> PyResult<Py<T>>
-    where
-        T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
-    {
+    pub fn new(py: Python, value: impl Into<PyClassInitializer<T>>) -> PyResult<Py<T>> {
         let initializer = value.into();
         let obj = initializer.create_cell(py)?;
         let ob = unsafe { Py::from_owned_ptr(py, obj as _) };
@@ -156,7 +154,10 @@ where
     /// # Panics
     /// Panics if the value is currently mutably borrowed. For a non-panicking variant, use
     /// [`try_borrow_mut`](#method.try_borrow_mut).
-    pub fn borrow_mut<'py>(&'py self, py: Python<'py>) -> PyRefMut<'py, T> {
+    pub fn borrow_mut<'py>(&'py self, py: Python<'py>) -> PyRefMut<'py, T>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.as_ref(py).borrow_mut()
     }
 
@@ -181,9 +182,41 @@ where
     pub fn try_borrow_mut<'py>(
         &'py self,
         py: Python<'py>,
-    ) -> Result<PyRefMut<'py, T>, PyBorrowMutError> {
+    ) -> Result<PyRefMut<'py, T>, PyBorrowMutError>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.as_ref(py).try_borrow_mut()
     }
+
+    /// Borrows the value of a `#[pyclass(frozen)]` without a runtime check or the GIL, because it
+    /// can never be mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::sync::Arc;
+    /// # use pyo3::prelude::*;
+    ///
+    /// #[pyclass(frozen)]
+    /// struct FrozenCounter {
+    ///     value: usize,
+    /// }
+    ///
+    /// let counter = Python::with_gil(|py| Py::new(py, FrozenCounter { value: 3 })).unwrap();
+    /// let counter = Arc::new(counter);
+    /// let other = Arc::clone(&counter);
+    /// let value = std::thread::spawn(move || other.get().value).join().unwrap();
+    /// assert_eq!(value, 3);
+    /// ```
+    pub fn get(&self) -> &T
+    where
+        T: PyClass<Frozen = True> + Sync,
+    {
+        let cell: *const PyCell<T> = self.as_ptr() as _;
+        // Safety: the object is kept alive by `self`, and the value is never mutated
+        unsafe { (*cell).get() }
+    }
 }
 
 impl<T> Py<T> {
@@ -199,6 +232,16 @@ impl<T> Py<T> {
         unsafe { Py::from_borrowed_ptr(py, self.0.as_ptr()) }
     }
 
+    /// Creates a weak reference to this object.
+    ///
+    /// Returns a `TypeError` if the object does not support weak references, for example a
+    /// `#[pyclass]` declared without the `weakref` option.
+    ///
+    /// This is equivalent to the Python expression `weakref.ref(self)`.
+    pub fn downgrade<'py>(&self, py: Python<'py>) -> PyResult<&'py PyWeakref> {
+        PyWeakref::new(unsafe { py.from_borrowed_ptr(self.as_ptr()) }, None)
+    }
+
     /// Returns whether the object is considered to be None.
     ///
     /// This is equivalent to the Python expression `self is None`.
@@ -562,6 +605,10 @@ where
                 .map(|val| Py::from_borrowed_ptr(ob.py(), val.as_ptr()))
         }
     }
+
+    fn type_input() -> TypeInfo {
+        <&'a T::AsRefTarget>::type_input()
+    }
 }
 
 /// Py<T> can be used as an error when T is an Error.
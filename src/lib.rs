@@ -174,9 +174,14 @@ pub use {
 #[cfg(all(feature = "macros", feature = "multiple-pymethods"))]
 pub use inventory; // Re-exported for `#[pyclass]` and `#[pymethods]` with `multiple-pymethods`.
 
+// So that the code generated by `#[pyclass]` and `#[pymethods]` also works in this crate.
+#[cfg(feature = "macros")]
+extern crate self as pyo3;
+
 #[macro_use]
 mod internal_tricks;
 
+pub mod asyncio;
 // The CPython stable ABI does not include PyBuffer.
 #[cfg(not(Py_LIMITED_API))]
 pub mod buffer;
@@ -185,6 +190,8 @@ pub mod callback;
 pub mod class;
 pub mod conversion;
 mod conversions;
+#[cfg(feature = "macros")]
+pub mod coroutine;
 #[macro_use]
 #[doc(hidden)]
 pub mod derive_utils;
@@ -195,7 +202,10 @@ pub mod exceptions;
 #[allow(clippy::missing_safety_doc)]
 pub mod ffi;
 pub mod freelist;
+#[cfg(feature = "macros")]
+pub mod generator;
 mod gil;
+pub mod inspect;
 mod instance;
 #[cfg(not(Py_LIMITED_API))]
 pub mod marshal;
@@ -235,7 +245,7 @@ pub mod proc_macro {
 #[macro_export]
 macro_rules! wrap_pyfunction {
     ($function_name: ident) => {{
-        &pyo3::paste::expr! { [<__pyo3_get_function_ $function_name>] }
+        &pyo3::paste::expr! { [<__pyo3_get_function_ $function_name>]::wrap }
     }};
 
     ($function_name: ident, $arg: expr) => {
@@ -274,16 +284,32 @@ macro_rules! raw_pycfunction {
 
 /// Returns a function that takes a [Python] instance and returns a Python module.
 ///
-/// Use this together with `#[pymodule]` and [types::PyModule::add_wrapped].
+/// Use this together with `#[pymodule]` and [types::PyModule::add_wrapped]. Every call creates a
+/// new module object from a `#[pymodule(multi_phase)]`.
 #[macro_export]
 macro_rules! wrap_pymodule {
     ($module_name:ident) => {{
         pyo3::paste::expr! {
-            &|py| unsafe { pyo3::PyObject::from_owned_ptr(py, [<PyInit_ $module_name>]()) }
+            &|py| unsafe {
+                pyo3::derive_utils::module_from_init(py, [<PyInit_ $module_name>]())
+            }
         }
     }};
 }
 
+/// Returns the [inspect::ModuleInfo] describing a `#[pymodule]`, which can be used to generate
+/// its type stub.
+///
+/// Like [wrap_pymodule], this takes the Python name of the module. See the [inspect] module for
+/// an example.
+#[cfg(feature = "inspect")]
+#[macro_export]
+macro_rules! inspect_module {
+    ($module_name:ident) => {{
+        pyo3::paste::expr! { [<__pyo3_inspect_module_ $module_name>]() }
+    }};
+}
+
 /// A convenient macro to execute a Python code snippet, with some local variables set.
 ///
 /// # Example
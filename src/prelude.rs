@@ -20,4 +20,7 @@ pub use crate::{FromPyObject, IntoPy, IntoPyPointer, PyTryFrom, PyTryInto, ToPyO
 // PyModule is only part of the prelude because we need it for the pymodule function
 pub use crate::types::{PyAny, PyModule};
 #[cfg(feature = "macros")]
-pub use {crate::proc_macro::*, pyo3_macros::FromPyObject};
+pub use {
+    crate::proc_macro::*,
+    pyo3_macros::{FromPyObject, IntoPyObject},
+};
@@ -2,10 +2,12 @@
 use crate::class::impl_::PyClassThreadChecker;
 use crate::conversion::{AsPyPointer, FromPyPointer, ToPyObject};
 use crate::exceptions::PyRuntimeError;
+use crate::inspect::TypeInfo;
+use crate::pyclass::boolean_struct::{private::Boolean, False, True};
 use crate::pyclass::PyClass;
 use crate::pyclass_init::PyClassInitializer;
 use crate::pyclass_slots::{PyClassDict, PyClassWeakRef};
-use crate::type_object::{PyBorrowFlagLayout, PyLayout, PySizedLayout, PyTypeInfo};
+use crate::type_object::{PyBorrowFlagLayout, PyFrozenLayout, PyLayout, PySizedLayout, PyTypeInfo};
 use crate::types::PyAny;
 use crate::{ffi, IntoPy, PyErr, PyNativeType, PyObject, PyResult, Python};
 use std::cell::{Cell, UnsafeCell};
@@ -45,6 +47,35 @@ where
 {
 }
 
+/// Base layout of a `#[pyclass(frozen)]`, which needs no borrow flag.
+#[doc(hidden)]
+#[repr(C)]
+pub struct PyFrozenCellBase<T: PyTypeInfo> {
+    ob_base: T::Layout,
+}
+
+unsafe impl<T> PyLayout<T> for PyFrozenCellBase<T>
+where
+    T: PyTypeInfo + PyNativeType,
+    T::Layout: PySizedLayout<T>,
+{
+    const IS_NATIVE_TYPE: bool = true;
+}
+
+impl<T> PySizedLayout<T> for PyFrozenCellBase<T>
+where
+    T: PyTypeInfo + PyNativeType,
+    T::Layout: PySizedLayout<T>,
+{
+}
+
+unsafe impl<T> PyFrozenLayout<T> for PyFrozenCellBase<T>
+where
+    T: PyTypeInfo + PyNativeType,
+    T::Layout: PySizedLayout<T>,
+{
+}
+
 /// Inner type of `PyCell` without dict slots and reference counter.
 /// This struct has two usages:
 /// 1. As an inner type of `PyRef` and `PyRefMut`.
@@ -78,17 +109,21 @@ unsafe impl<T: PyClass> PyLayout<T> for PyCellInner<T> {
 
 // These impls ensures `PyCellInner` can be a base type.
 impl<T: PyClass> PySizedLayout<T> for PyCellInner<T> {}
-unsafe impl<T: PyClass> PyBorrowFlagLayout<T> for PyCellInner<T> {}
+unsafe impl<T: PyClass<Frozen = False>> PyBorrowFlagLayout<T> for PyCellInner<T> {}
+unsafe impl<T: PyClass<Frozen = True>> PyFrozenLayout<T> for PyCellInner<T> {}
 
 impl<T: PyClass> PyCellInner<T> {
     fn get_ptr(&self) -> *mut T {
         self.value.get()
     }
+    // The borrow flag must not be accessed for frozen classes, which don't store it.
     fn get_borrow_flag(&self) -> BorrowFlag {
+        debug_assert!(!T::Frozen::VALUE);
         let base = (&self.ob_base) as *const _ as *const PyCellBase<T::BaseNativeType>;
         unsafe { (*base).borrow_flag.get() }
     }
     fn set_borrow_flag(&self, flag: BorrowFlag) {
+        debug_assert!(!T::Frozen::VALUE);
         let base = (&self.ob_base) as *const _ as *const PyCellBase<T::BaseNativeType>;
         unsafe { (*base).borrow_flag.set(flag) }
     }
@@ -203,10 +238,7 @@ impl<T: PyClass> PyCell<T> {
     /// In cases where the value in the cell does not need to be accessed immediately after
     /// creation, consider [`Py::new`](../instance/struct.Py.html#method.new) as a more efficient
     /// alternative.
-    pub fn new(py: Python, value: impl Into<PyClassInitializer<T>>) -> PyResult<&Self>
-    where
-        T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
-    {
+    pub fn new(py: Python, value: impl Into<PyClassInitializer<T>>) -> PyResult<&Self> {
         unsafe {
             let initializer = value.into();
             let self_ = initializer.create_cell(py)?;
@@ -230,7 +262,10 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// Panics if the value is currently mutably borrowed. For a non-panicking variant, use
     /// [`try_borrow_mut`](#method.try_borrow_mut).
-    pub fn borrow_mut(&self) -> PyRefMut<'_, T> {
+    pub fn borrow_mut(&self) -> PyRefMut<'_, T>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.try_borrow_mut().expect("Already borrowed")
     }
 
@@ -260,6 +295,9 @@ impl<T: PyClass> PyCell<T> {
     /// ```
     pub fn try_borrow(&self) -> Result<PyRef<'_, T>, PyBorrowError> {
         self.thread_checker.ensure();
+        if T::Frozen::VALUE {
+            return Ok(PyRef { inner: &self.inner });
+        }
         let flag = self.inner.get_borrow_flag();
         if flag == BorrowFlag::HAS_MUTABLE_BORROW {
             Err(PyBorrowError { _private: () })
@@ -290,7 +328,10 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// assert!(c.try_borrow_mut().is_ok());
     /// ```
-    pub fn try_borrow_mut(&self) -> Result<PyRefMut<'_, T>, PyBorrowMutError> {
+    pub fn try_borrow_mut(&self) -> Result<PyRefMut<'_, T>, PyBorrowMutError>
+    where
+        T: PyClass<Frozen = False>,
+    {
         self.thread_checker.ensure();
         if self.inner.get_borrow_flag() != BorrowFlag::UNUSED {
             Err(PyBorrowMutError { _private: () })
@@ -331,20 +372,46 @@ impl<T: PyClass> PyCell<T> {
     /// ```
     pub unsafe fn try_borrow_unguarded(&self) -> Result<&T, PyBorrowError> {
         self.thread_checker.ensure();
-        if self.inner.get_borrow_flag() == BorrowFlag::HAS_MUTABLE_BORROW {
+        if !T::Frozen::VALUE && self.inner.get_borrow_flag() == BorrowFlag::HAS_MUTABLE_BORROW {
             Err(PyBorrowError { _private: () })
         } else {
             Ok(&*self.inner.value.get())
         }
     }
 
+    /// Borrows the value of a `#[pyclass(frozen)]`, which needs no runtime check as it can never
+    /// be mutably borrowed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use pyo3::prelude::*;
+    /// #[pyclass(frozen)]
+    /// struct FrozenCounter {
+    ///     value: usize,
+    /// }
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let c = PyCell::new(py, FrozenCounter { value: 3 }).unwrap();
+    /// assert_eq!(c.get().value, 3);
+    /// ```
+    pub fn get(&self) -> &T
+    where
+        T: PyClass<Frozen = True> + Sync,
+    {
+        unsafe { &*self.inner.get_ptr() }
+    }
+
     /// Replaces the wrapped value with a new one, returning the old value,
     ///
     /// # Panics
     ///
     /// Panics if the value is currently borrowed.
     #[inline]
-    pub fn replace(&self, t: T) -> T {
+    pub fn replace(&self, t: T) -> T
+    where
+        T: PyClass<Frozen = False>,
+    {
         std::mem::replace(&mut *self.borrow_mut(), t)
     }
 
@@ -353,7 +420,10 @@ impl<T: PyClass> PyCell<T> {
     /// # Panics
     ///
     /// Panics if the value is currently borrowed.
-    pub fn replace_with<F: FnOnce(&mut T) -> T>(&self, f: F) -> T {
+    pub fn replace_with<F: FnOnce(&mut T) -> T>(&self, f: F) -> T
+    where
+        T: PyClass<Frozen = False>,
+    {
         let mut_borrow = &mut *self.borrow_mut();
         let replacement = f(mut_borrow);
         std::mem::replace(mut_borrow, replacement)
@@ -365,25 +435,28 @@ impl<T: PyClass> PyCell<T> {
     ///
     /// Panics if the value in either `PyCell` is currently borrowed.
     #[inline]
-    pub fn swap(&self, other: &Self) {
+    pub fn swap(&self, other: &Self)
+    where
+        T: PyClass<Frozen = False>,
+    {
         std::mem::swap(&mut *self.borrow_mut(), &mut *other.borrow_mut())
     }
 
     /// Allocates a new PyCell given a type object `subtype`. Used by our `tp_new` implementation.
-    /// Requires `T::BaseLayout: PyBorrowFlagLayout<T::BaseType>` to ensure `self` has a borrow flag.
+    ///
+    /// `#[pyclass]` ensures that `T::BaseLayout` is a `PyBorrowFlagLayout` unless `T` is frozen.
     pub(crate) unsafe fn internal_new(
         py: Python,
         subtype: *mut ffi::PyTypeObject,
-    ) -> PyResult<*mut Self>
-    where
-        T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
-    {
+    ) -> PyResult<*mut Self> {
         let base = T::new(py, subtype);
         if base.is_null() {
             return Err(PyErr::fetch(py));
         }
-        let base = base as *mut PyCellBase<T::BaseNativeType>;
-        (*base).borrow_flag = Cell::new(BorrowFlag::UNUSED);
+        if !T::Frozen::VALUE {
+            let base = base as *mut PyCellBase<T::BaseNativeType>;
+            (*base).borrow_flag = Cell::new(BorrowFlag::UNUSED);
+        }
         let self_ = base as *mut Self;
         (*self_).dict = T::Dict::new();
         (*self_).weakref = T::WeakRef::new();
@@ -574,6 +647,9 @@ impl<'p, T: PyClass> Deref for PyRef<'p, T> {
 
 impl<'p, T: PyClass> Drop for PyRef<'p, T> {
     fn drop(&mut self) {
+        if T::Frozen::VALUE {
+            return;
+        }
         let flag = self.inner.get_borrow_flag();
         self.inner.set_borrow_flag(flag.decrement())
     }
@@ -583,6 +659,10 @@ impl<T: PyClass> IntoPy<PyObject> for PyRef<'_, T> {
     fn into_py(self, py: Python<'_>) -> PyObject {
         unsafe { PyObject::from_borrowed_ptr(py, self.inner.as_ptr()) }
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T: PyClass> std::convert::TryFrom<&'a PyCell<T>> for crate::PyRef<'a, T> {
@@ -681,6 +761,10 @@ impl<T: PyClass> IntoPy<PyObject> for PyRefMut<'_, T> {
     fn into_py(self, py: Python) -> PyObject {
         unsafe { PyObject::from_borrowed_ptr(py, self.inner.as_ptr()) }
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::class::<T>()
+    }
 }
 
 impl<'a, T: PyClass> AsPyPointer for PyRefMut<'a, T> {
@@ -689,7 +773,9 @@ impl<'a, T: PyClass> AsPyPointer for PyRefMut<'a, T> {
     }
 }
 
-impl<'a, T: PyClass> std::convert::TryFrom<&'a PyCell<T>> for crate::PyRefMut<'a, T> {
+impl<'a, T: PyClass<Frozen = False>> std::convert::TryFrom<&'a PyCell<T>>
+    for crate::PyRefMut<'a, T>
+{
     type Error = PyBorrowMutError;
     fn try_from(cell: &'a crate::PyCell<T>) -> Result<Self, Self::Error> {
         cell.try_borrow_mut()
@@ -146,6 +146,35 @@ pub trait PyClass:
     /// The closest native ancestor. This is `PyAny` by default, and when you declare
     /// `#[pyclass(extends=PyDict)]`, it's `PyDict`.
     type BaseNativeType: PyTypeInfo + PyNativeType;
+    /// [`True`](boolean_struct::True) if this class has `#[pyclass(frozen)]`, so that it cannot
+    /// be mutably borrowed and stores no borrow flag, otherwise [`False`](boolean_struct::False).
+    type Frozen: boolean_struct::private::Boolean;
+}
+
+/// Type-level booleans for [`PyClass::Frozen`], so that methods can require e.g.
+/// `T: PyClass<Frozen = False>`.
+pub mod boolean_struct {
+    #[doc(hidden)]
+    pub mod private {
+        /// Implemented only by [`True`](super::True) and [`False`](super::False).
+        pub trait Boolean {
+            const VALUE: bool;
+        }
+    }
+
+    /// Type-level `true`.
+    pub struct True;
+
+    /// Type-level `false`.
+    pub struct False;
+
+    impl private::Boolean for True {
+        const VALUE: bool = true;
+    }
+
+    impl private::Boolean for False {
+        const VALUE: bool = false;
+    }
 }
 
 /// For collecting slot items.
@@ -158,15 +187,38 @@ impl TypeSlots {
     }
 }
 
-fn tp_doc<T: PyClass>() -> PyResult<Option<*mut c_void>> {
+/// Returns the null-terminated class docstring. If `#[new]` has a generated text signature and
+/// the class has none of its own, it is prepended in the form CPython expects.
+fn tp_doc<T: PyClass>() -> PyResult<Option<&'static [u8]>> {
+    if let Some(text_signature) = T::get_new_text_signature() {
+        if !has_text_signature(T::NAME, T::DESCRIPTION) {
+            let doc = format!(
+                "{}{}\n--\n\n{}",
+                T::NAME,
+                text_signature,
+                T::DESCRIPTION.trim_end_matches('\0')
+            );
+            return Ok(Some(leak_cstring(doc)?));
+        }
+    }
     Ok(match T::DESCRIPTION {
         "\0" => None,
-        s if s.as_bytes().ends_with(b"\0") => Some(s.as_ptr() as _),
+        s if s.as_bytes().ends_with(b"\0") => Some(s.as_bytes()),
         // If the description is not null-terminated, create CString and leak it
-        s => Some(CString::new(s)?.into_raw() as _),
+        s => Some(leak_cstring(s)?),
     })
 }
 
+fn leak_cstring(s: impl Into<Vec<u8>>) -> PyResult<&'static [u8]> {
+    let s = CString::new(s)?;
+    Ok(Box::leak(s.into_boxed_c_str()).to_bytes_with_nul())
+}
+
+/// Whether `doc` starts with a text signature for `name`, such as `name(a, b)\n--\n\n`.
+fn has_text_signature(name: &str, doc: &str) -> bool {
+    doc.starts_with(name) && doc[name.len()..].starts_with('(') && doc.contains(")\n--\n\n")
+}
+
 fn get_type_name<T: PyTypeInfo>(module_name: Option<&str>) -> PyResult<*mut c_char> {
     Ok(match module_name {
         Some(module_name) => CString::new(format!("{}.{}", module_name, T::NAME))?.into_raw(),
@@ -189,8 +241,9 @@ where
 
     slots.push(ffi::Py_tp_base, T::BaseType::type_object_raw(py) as _);
     slots.push(ffi::Py_tp_dealloc, tp_dealloc::<T> as _);
-    if let Some(doc) = tp_doc::<T>()? {
-        slots.push(ffi::Py_tp_doc, doc);
+    let doc = tp_doc::<T>()?;
+    if let Some(doc) = doc {
+        slots.push(ffi::Py_tp_doc, doc.as_ptr() as _);
     }
 
     slots.push(ffi::Py_tp_new, T::get_new().unwrap_or(fallback_new) as _);
@@ -238,14 +291,14 @@ where
     if type_object.is_null() {
         Err(PyErr::fetch(py))
     } else {
-        tp_init_additional::<T>(type_object as _);
+        tp_init_additional::<T>(type_object as _, doc);
         Ok(type_object as _)
     }
 }
 
 /// Additional type initializations necessary before Python 3.10
 #[cfg(all(not(Py_LIMITED_API), not(Py_3_10)))]
-fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
+fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject, doc: Option<&[u8]>) {
     // Just patch the type objects for the things there's no
     // PyType_FromSpec API for... there's no reason this should work,
     // except for that it does and we have tests.
@@ -253,15 +306,15 @@ fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
     // Running this causes PyPy to segfault.
     #[cfg(all(not(PyPy), not(Py_3_10)))]
     {
-        if T::DESCRIPTION != "\0" {
+        if let Some(doc) = doc {
             unsafe {
                 // Until CPython 3.10, tp_doc was treated specially for
                 // heap-types, and it removed the text_signature value from it.
                 // We go in after the fact and replace tp_doc with something
                 // that _does_ include the text_signature value!
                 ffi::PyObject_Free((*type_object).tp_doc as _);
-                let data = ffi::PyObject_Malloc(T::DESCRIPTION.len());
-                data.copy_from(T::DESCRIPTION.as_ptr() as _, T::DESCRIPTION.len());
+                let data = ffi::PyObject_Malloc(doc.len());
+                data.copy_from(doc.as_ptr() as _, doc.len());
                 (*type_object).tp_doc = data as _;
             }
         }
@@ -297,7 +350,7 @@ fn tp_init_additional<T: PyClass>(type_object: *mut ffi::PyTypeObject) {
 }
 
 #[cfg(any(Py_LIMITED_API, Py_3_10))]
-fn tp_init_additional<T: PyClass>(_type_object: *mut ffi::PyTypeObject) {}
+fn tp_init_additional<T: PyClass>(_type_object: *mut ffi::PyTypeObject, _doc: Option<&[u8]>) {}
 
 fn py_class_flags<T: PyClass + PyTypeInfo>(has_gc_methods: bool) -> c_uint {
     let mut flags = if has_gc_methods || T::FLAGS & type_flags::GC != 0 {
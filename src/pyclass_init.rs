@@ -1,6 +1,6 @@
 //! Initialization utilities for `#[pyclass]`.
 use crate::callback::IntoPyCallbackOutput;
-use crate::type_object::{PyBorrowFlagLayout, PyLayout, PySizedLayout, PyTypeInfo};
+use crate::type_object::{PyLayout, PySizedLayout, PyTypeInfo};
 use crate::{PyCell, PyClass, PyResult, Python};
 use std::marker::PhantomData;
 
@@ -120,7 +120,6 @@ impl<T: PyClass> PyClassInitializer<T> {
     pub fn create_cell(self, py: Python) -> PyResult<*mut PyCell<T>>
     where
         T: PyClass,
-        T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
     {
         unsafe { self.create_cell_from_subtype(py, T::type_object_raw(py)) }
     }
@@ -138,7 +137,6 @@ impl<T: PyClass> PyClassInitializer<T> {
     ) -> PyResult<*mut PyCell<T>>
     where
         T: PyClass,
-        T::BaseLayout: PyBorrowFlagLayout<T::BaseType>,
     {
         let cell = PyCell::internal_new(py, subtype)?;
         self.init_class(&mut *cell);
@@ -1,14 +1,33 @@
-use crate::type_object::PyBorrowFlagLayout;
-use crate::{Py, PyClass, PyClassInitializer, PyTypeInfo, Python};
-use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
+//! Support for [serde](https://serde.rs/): conversions between Rust values and Python objects,
+//! (de)serialization of `Py<T>`, and pickling of `#[pyclass(pickle)]` instances.
+
+use crate::exceptions::{PyTypeError, PyValueError};
+use crate::pycell::PyCellInner;
+use crate::type_object::PyTypeObject;
+use crate::types::{PyBytes, PyTuple};
+use crate::{
+    ffi, AsPyPointer, Py, PyCell, PyClass, PyClassInitializer, PyErr, PyRef, PyResult, PyTypeInfo,
+    Python, ToPyObject,
+};
+use serde::de::DeserializeOwned;
+use serde::{de, ser, Deserialize, Serialize};
+
+mod compact;
+mod deserializer;
+mod error;
+mod serializer;
+
+pub use self::deserializer::{from_object, Deserializer};
+pub use self::error::Error;
+pub use self::serializer::{to_object, SerializeMap, SerializeSeq, Serializer};
 
 impl<T> Serialize for Py<T>
 where
     T: Serialize + PyClass,
 {
-    fn serialize<S>(&self, serializer: S) -> Result<<S as Serializer>::Ok, <S as Serializer>::Error>
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
-        S: Serializer,
+        S: ser::Serializer,
     {
         Python::with_gil(|py| {
             self.try_borrow(py)
@@ -21,11 +40,10 @@ where
 impl<'de, T> Deserialize<'de> for Py<T>
 where
     T: Into<PyClassInitializer<T>> + PyClass + Deserialize<'de>,
-    <T as PyTypeInfo>::BaseLayout: PyBorrowFlagLayout<<T as PyTypeInfo>::BaseType>,
 {
     fn deserialize<D>(deserializer: D) -> Result<Py<T>, D::Error>
     where
-        D: Deserializer<'de>,
+        D: de::Deserializer<'de>,
     {
         let deserialized = T::deserialize(deserializer)?;
 
@@ -34,3 +52,164 @@ where
         })
     }
 }
+
+// Pickling of #[pyclass(pickle)]
+
+/// Implemented by `#[pyclass(pickle)]` to store the state of an instance and of its `#[pyclass]`
+/// base classes, in a compact binary format.
+#[doc(hidden)]
+pub trait PyClassPickle: PyClass {
+    /// Appends the state of `slf` to `state`.
+    fn dump_state(slf: PyRef<Self>, state: &mut Vec<u8>) -> PyResult<()>;
+    /// Reads the state written by `dump_state`, and advances `state` past it.
+    fn load_state(state: &mut &[u8]) -> PyResult<PyClassInitializer<Self>>;
+}
+
+fn pickle_error(e: compact::Error) -> PyErr {
+    PyValueError::new_err(format!("failed to pickle: {}", e))
+}
+
+fn unpickle_error(e: compact::Error) -> PyErr {
+    PyValueError::new_err(format!("failed to unpickle: {}", e))
+}
+
+/// The version of the pickled state, stored in its header. It must change whenever the layout of
+/// the header or of the state written by `PyClassPickle` changes.
+const PICKLE_FORMAT_VERSION: u32 = 1;
+
+/// The qualified name of the class, stored in the header of its pickled state.
+fn pickle_class_name<T: PyClass>() -> String {
+    match T::MODULE {
+        Some(module) => format!("{}.{}", module, T::NAME),
+        None => T::NAME.to_string(),
+    }
+}
+
+/// Writes the header of the state of a `T`: the format version followed by the class name.
+fn dump_header<T: PyClass>(state: &mut Vec<u8>) -> PyResult<()> {
+    compact::serialize_into(state, &PICKLE_FORMAT_VERSION).map_err(pickle_error)?;
+    compact::serialize_into(state, &pickle_class_name::<T>()).map_err(pickle_error)
+}
+
+/// Reads the header written by `dump_header`, and checks that the state was written by this
+/// version of the format for the class `T`.
+fn load_header<T: PyClass>(state: &mut &[u8]) -> PyResult<()> {
+    let version: u32 = compact::deserialize_from(state).map_err(unpickle_error)?;
+    if version != PICKLE_FORMAT_VERSION {
+        return Err(PyValueError::new_err(format!(
+            "failed to unpickle: unsupported state format version {} (expected {})",
+            version, PICKLE_FORMAT_VERSION
+        )));
+    }
+    let name: String = compact::deserialize_from(state).map_err(unpickle_error)?;
+    let expected = pickle_class_name::<T>();
+    if name != expected {
+        return Err(PyValueError::new_err(format!(
+            "failed to unpickle: the state of '{}' cannot be loaded as '{}'",
+            name, expected
+        )));
+    }
+    Ok(())
+}
+
+/// `PyClassPickle::dump_state` of a class which has no `#[pyclass]` base.
+#[doc(hidden)]
+pub fn dump_state<T>(slf: PyRef<T>, state: &mut Vec<u8>) -> PyResult<()>
+where
+    T: PyClass + Serialize,
+{
+    compact::serialize_into(state, &*slf).map_err(pickle_error)
+}
+
+/// `PyClassPickle::load_state` of a class which has no `#[pyclass]` base.
+#[doc(hidden)]
+pub fn load_state<T>(state: &mut &[u8]) -> PyResult<PyClassInitializer<T>>
+where
+    T: PyClass + DeserializeOwned + Into<PyClassInitializer<T>>,
+{
+    let value: T = compact::deserialize_from(state).map_err(unpickle_error)?;
+    Ok(value.into())
+}
+
+/// `PyClassPickle::dump_state` of a class with `extends = Base`, which stores the state of the
+/// class followed by the state of `Base`.
+#[doc(hidden)]
+pub fn dump_subclass_state<T, U>(slf: PyRef<T>, state: &mut Vec<u8>) -> PyResult<()>
+where
+    T: PyClass + PyTypeInfo<BaseType = U, BaseLayout = PyCellInner<U>> + Serialize,
+    U: PyClassPickle,
+{
+    compact::serialize_into(state, &*slf).map_err(pickle_error)?;
+    U::dump_state(slf.into_super(), state)
+}
+
+/// `PyClassPickle::load_state` of a class with `extends = Base`.
+#[doc(hidden)]
+pub fn load_subclass_state<T, U>(state: &mut &[u8]) -> PyResult<PyClassInitializer<T>>
+where
+    T: PyClass<BaseType = U> + DeserializeOwned,
+    U: PyClassPickle + PyTypeInfo<Initializer = PyClassInitializer<U>>,
+{
+    let value: T = compact::deserialize_from(state).map_err(unpickle_error)?;
+    Ok(PyClassInitializer::new(value, U::load_state(state)?))
+}
+
+/// `__reduce__` of a `#[pyclass(pickle)]`, which returns `(cls.__pyo3_unpickle__, (state,))`,
+/// followed by the `__dict__` of the instance if it has one. The state starts with a header
+/// holding the format version and the class name.
+#[doc(hidden)]
+pub unsafe extern "C" fn pickle_reduce<T: PyClassPickle>(
+    slf: *mut ffi::PyObject,
+    _args: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let slf = py.from_borrowed_ptr::<PyCell<T>>(slf);
+        let cls = T::type_object(py);
+        // Subclasses defined in Python may have state which is not in `T`
+        if slf.get_type().as_ptr() != cls.as_ptr() {
+            return Err(PyTypeError::new_err(format!(
+                "cannot pickle '{}' object, only instances of '{}' itself can be pickled",
+                slf.get_type().name()?,
+                T::NAME
+            )));
+        }
+        let mut state = Vec::new();
+        dump_header::<T>(&mut state)?;
+        T::dump_state(slf.try_borrow()?, &mut state)?;
+        let unpickle = cls.getattr("__pyo3_unpickle__")?;
+        let args = (PyBytes::new(py, &state),);
+        let reduced = match slf.getattr("__dict__") {
+            Ok(dict) => (unpickle, args, dict).to_object(py),
+            Err(_) => (unpickle, args).to_object(py),
+        };
+        crate::PyResult::Ok(reduced)
+    })
+}
+
+/// The class method `__pyo3_unpickle__(state)` of a `#[pyclass(pickle)]`.
+#[doc(hidden)]
+pub unsafe extern "C" fn pickle_unpickle<T: PyClassPickle>(
+    _cls: *mut ffi::PyObject,
+    args: *mut ffi::PyObject,
+    _kwargs: *mut ffi::PyObject,
+) -> *mut ffi::PyObject {
+    crate::callback_body!(py, {
+        let args = py.from_borrowed_ptr::<PyTuple>(args);
+        let mut state: &[u8] = match args.as_slice() {
+            [state] => state.downcast::<PyBytes>()?.as_bytes(),
+            _ => {
+                return Err(PyTypeError::new_err(
+                    "__pyo3_unpickle__() takes exactly one argument",
+                ))
+            }
+        };
+        load_header::<T>(&mut state)?;
+        let init = T::load_state(&mut state)?;
+        if !state.is_empty() {
+            return Err(PyValueError::new_err(
+                "failed to unpickle: unexpected data after the state",
+            ));
+        }
+        crate::PyResult::Ok(Py::new(py, init)?)
+    })
+}
@@ -0,0 +1,698 @@
+//! A compact binary serde format, used to store the state of pickled `#[pyclass]` instances.
+//!
+//! Integers, lengths and enum variant indices are LEB128 varints (zigzag-encoded for signed
+//! integers), floats are little-endian and structs and tuples are stored as their fields in order.
+//! Like `bincode`, the format is not self-describing: the deserializing type must be the
+//! serialized type, and `deserialize_any` is not supported.
+
+use serde::de::value::U32Deserializer;
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::{ser, Deserialize, Serialize};
+use std::fmt;
+
+/// An error while encoding or decoding the compact format.
+#[derive(Debug)]
+pub struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Error(msg.to_string())
+    }
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Appends the encoding of `value` to `out`.
+pub fn serialize_into<T: Serialize + ?Sized>(out: &mut Vec<u8>, value: &T) -> Result<()> {
+    value.serialize(&mut Serializer { out })
+}
+
+/// Decodes a `T` from the start of `input`, and advances `input` past it.
+pub fn deserialize_from<'de, T: Deserialize<'de>>(input: &mut &'de [u8]) -> Result<T> {
+    let mut deserializer = Deserializer { input: *input };
+    let value = T::deserialize(&mut deserializer)?;
+    *input = deserializer.input;
+    Ok(value)
+}
+
+struct Serializer<'a> {
+    out: &'a mut Vec<u8>,
+}
+
+impl Serializer<'_> {
+    fn write_varint(&mut self, mut value: u128) {
+        while value >= 0x80 {
+            self.out.push(value as u8 | 0x80);
+            value >>= 7;
+        }
+        self.out.push(value as u8);
+    }
+
+    fn write_signed(&mut self, value: i128) {
+        self.write_varint(((value << 1) ^ (value >> 127)) as u128);
+    }
+
+    fn write_len(&mut self, len: Option<usize>) -> Result<()> {
+        match len {
+            Some(len) => {
+                self.write_varint(len as u128);
+                Ok(())
+            }
+            None => Err(Error(
+                "sequences and maps must have a known length".to_string(),
+            )),
+        }
+    }
+}
+
+impl<'a, 'b> ser::Serializer for &'a mut Serializer<'b> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Self;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    fn serialize_bool(self, v: bool) -> Result<()> {
+        self.out.push(v as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<()> {
+        self.write_signed(v.into());
+        Ok(())
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<()> {
+        self.write_signed(v);
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<()> {
+        self.write_varint(v.into());
+        Ok(())
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<()> {
+        self.write_varint(v);
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<()> {
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<()> {
+        self.out.extend_from_slice(&v.to_le_bytes());
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<()> {
+        self.write_varint(u128::from(v as u32));
+        Ok(())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<()> {
+        self.serialize_bytes(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<()> {
+        self.write_varint(v.len() as u128);
+        self.out.extend_from_slice(v);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        self.out.push(0);
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<()> {
+        self.out.push(1);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<()> {
+        self.write_varint(variant_index.into());
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.write_varint(variant_index.into());
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self> {
+        self.write_len(len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self> {
+        self.write_varint(variant_index.into());
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+macro_rules! impl_compound {
+    ($trait:ident, $serialize:ident) => {
+        impl<'a, 'b> ser::$trait for &'a mut Serializer<'b> {
+            type Ok = ();
+            type Error = Error;
+
+            fn $serialize<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    };
+    ($trait:ident) => {
+        impl<'a, 'b> ser::$trait for &'a mut Serializer<'b> {
+            type Ok = ();
+            type Error = Error;
+
+            fn serialize_field<T: Serialize + ?Sized>(
+                &mut self,
+                _key: &'static str,
+                value: &T,
+            ) -> Result<()> {
+                value.serialize(&mut **self)
+            }
+
+            fn end(self) -> Result<()> {
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_compound!(SerializeSeq, serialize_element);
+impl_compound!(SerializeTuple, serialize_element);
+impl_compound!(SerializeTupleStruct, serialize_field);
+impl_compound!(SerializeTupleVariant, serialize_field);
+impl_compound!(SerializeStruct);
+impl_compound!(SerializeStructVariant);
+
+impl<'a, 'b> ser::SerializeMap for &'a mut Serializer<'b> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<()> {
+        Ok(())
+    }
+}
+
+struct Deserializer<'de> {
+    input: &'de [u8],
+}
+
+impl<'de> Deserializer<'de> {
+    fn read_bytes(&mut self, len: usize) -> Result<&'de [u8]> {
+        if self.input.len() < len {
+            return Err(Error("unexpected end of input".to_string()));
+        }
+        let (bytes, rest) = self.input.split_at(len);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn read_varint(&mut self) -> Result<u128> {
+        let mut value = 0u128;
+        for shift in (0..128).step_by(7) {
+            let byte = self.read_bytes(1)?[0];
+            value |= u128::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error("invalid varint".to_string()))
+    }
+
+    fn read_signed(&mut self) -> Result<i128> {
+        let value = self.read_varint()?;
+        Ok((value >> 1) as i128 ^ -((value & 1) as i128))
+    }
+
+    fn read_len(&mut self) -> Result<usize> {
+        self.read_unsigned()
+    }
+
+    fn read_unsigned<T: std::convert::TryFrom<u128>>(&mut self) -> Result<T> {
+        T::try_from(self.read_varint()?).map_err(|_| Error("integer out of range".to_string()))
+    }
+
+    fn read_integer<T: std::convert::TryFrom<i128>>(&mut self) -> Result<T> {
+        T::try_from(self.read_signed()?).map_err(|_| Error("integer out of range".to_string()))
+    }
+}
+
+macro_rules! deserialize_integer {
+    ($deserialize:ident, $visit:ident, $read:ident) => {
+        fn $deserialize<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+            visitor.$visit(self.$read()?)
+        }
+    };
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error(
+            "the pickle format is not self-describing, and cannot be deserialized as any type"
+                .to_string(),
+        ))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.read_bytes(1)?[0] {
+            0 => visitor.visit_bool(false),
+            1 => visitor.visit_bool(true),
+            _ => Err(Error("invalid bool".to_string())),
+        }
+    }
+
+    deserialize_integer!(deserialize_i8, visit_i8, read_integer);
+    deserialize_integer!(deserialize_i16, visit_i16, read_integer);
+    deserialize_integer!(deserialize_i32, visit_i32, read_integer);
+    deserialize_integer!(deserialize_i64, visit_i64, read_integer);
+    deserialize_integer!(deserialize_i128, visit_i128, read_signed);
+    deserialize_integer!(deserialize_u8, visit_u8, read_unsigned);
+    deserialize_integer!(deserialize_u16, visit_u16, read_unsigned);
+    deserialize_integer!(deserialize_u32, visit_u32, read_unsigned);
+    deserialize_integer!(deserialize_u64, visit_u64, read_unsigned);
+    deserialize_integer!(deserialize_u128, visit_u128, read_varint);
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(self.read_bytes(4)?);
+        visitor.visit_f32(f32::from_le_bytes(bytes))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(self.read_bytes(8)?);
+        visitor.visit_f64(f64::from_le_bytes(bytes))
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let code: u32 = self.read_unsigned()?;
+        match std::char::from_u32(code) {
+            Some(c) => visitor.visit_char(c),
+            None => Err(Error("invalid char".to_string())),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        match std::str::from_utf8(self.read_bytes(len)?) {
+            Ok(s) => visitor.visit_borrowed_str(s),
+            Err(e) => Err(Error(e.to_string())),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_borrowed_bytes(self.read_bytes(len)?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.read_bytes(1)?[0] {
+            0 => visitor.visit_none(),
+            1 => visitor.visit_some(self),
+            _ => Err(Error("invalid option".to_string())),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let len = self.read_len()?;
+        visitor.visit_map(Access { de: self, len })
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let len = fields.len();
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_enum(self)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_u32(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value> {
+        Err(Error(
+            "the pickle format is not self-describing, and cannot skip values".to_string(),
+        ))
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+/// Reads the `len` elements of a sequence, or entries of a map.
+struct Access<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    len: usize,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for Access<'a, 'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de, 'a> de::MapAccess<'de> for Access<'a, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.len == 0 {
+            return Ok(None);
+        }
+        self.len -= 1;
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.len)
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let index: u32 = self.read_unsigned()?;
+        let deserializer: U32Deserializer<Error> = index.into_deserializer();
+        let variant = seed.deserialize(deserializer)?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, len: usize, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(Access { de: self, len })
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let len = fields.len();
+        visitor.visit_seq(Access { de: self, len })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{deserialize_from, serialize_into};
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum Shape {
+        Empty,
+        Circle(f64),
+        Rect { w: u16, h: u16 },
+        Path(Vec<(i8, i8)>),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Everything {
+        unit: (),
+        flag: bool,
+        small: i8,
+        big: i128,
+        unsigned: u64,
+        float: f32,
+        ch: char,
+        text: String,
+        bytes: Vec<u8>,
+        missing: Option<u32>,
+        present: Option<String>,
+        shapes: Vec<Shape>,
+        map: BTreeMap<String, i64>,
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let value = Everything {
+            unit: (),
+            flag: true,
+            small: -128,
+            big: i128::min_value(),
+            unsigned: u64::max_value(),
+            float: -1.5,
+            ch: 'é',
+            text: "hello".into(),
+            bytes: vec![0, 255],
+            missing: None,
+            present: Some(String::new()),
+            shapes: vec![
+                Shape::Empty,
+                Shape::Circle(2.0),
+                Shape::Rect { w: 3, h: 300 },
+                Shape::Path(vec![(-1, 1), (0, 0)]),
+            ],
+            map: vec![("a".to_string(), -300), ("b".to_string(), 0)]
+                .into_iter()
+                .collect(),
+        };
+        let mut out = Vec::new();
+        serialize_into(&mut out, &value).unwrap();
+        serialize_into(&mut out, &7u8).unwrap();
+        let mut input = &out[..];
+        assert_eq!(deserialize_from::<Everything>(&mut input).unwrap(), value);
+        assert_eq!(deserialize_from::<u8>(&mut input).unwrap(), 7);
+        assert!(input.is_empty());
+    }
+
+    #[test]
+    fn test_varints() {
+        let mut out = Vec::new();
+        serialize_into(&mut out, &(0u32, 127u32, 128u32, -1i32, 1i32)).unwrap();
+        assert_eq!(out, [0x00, 0x7f, 0x80, 0x01, 0x01, 0x02]);
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        let mut out = Vec::new();
+        serialize_into(&mut out, &300u32).unwrap();
+        assert!(deserialize_from::<u8>(&mut &out[..]).is_err());
+        assert!(deserialize_from::<u32>(&mut &out[..1]).is_err());
+        assert!(deserialize_from::<String>(&mut &[2, b'a'][..]).is_err());
+        assert!(deserialize_from::<String>(&mut &[1, 0xff][..]).is_err());
+        assert!(deserialize_from::<bool>(&mut &[2][..]).is_err());
+        assert!(deserialize_from::<Shape>(&mut &[4][..]).is_err());
+        // Length prefixes larger than the input must not allocate
+        assert!(deserialize_from::<Vec<u64>>(&mut &[0xff, 0xff, 0xff, 0xff, 0x0f][..]).is_err());
+    }
+}
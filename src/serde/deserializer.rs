@@ -0,0 +1,315 @@
+use super::Error;
+use crate::types::{
+    PyBool, PyByteArray, PyBytes, PyDict, PyFloat, PyFrozenSet, PyIterator, PyList, PyLong,
+    PyMapping, PySet, PyString, PyTuple,
+};
+use crate::{PyAny, PyNativeType, PyResult};
+use serde::de::{self, DeserializeSeed, Unexpected, Visitor};
+use serde::Deserialize;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Converts a Python object to a Rust value with serde.
+///
+/// This accepts the objects produced by [`to_object`](super::to_object), and more generally
+/// any `dict` or other mapping where serde expects a map or struct, any `list`, `tuple`, `set`
+/// or other iterable where it expects a sequence, and `bytes` or `bytearray` for byte buffers.
+/// Enums are read from the name of a unit variant, or from a `dict` with the name of the
+/// variant as its only key, unless they use another serde representation.
+///
+/// Errors give the path to the value which could not be converted.
+///
+/// # Example
+/// ```rust
+/// use pyo3::prelude::*;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct User {
+///     name: String,
+///     groups: Vec<String>,
+/// }
+///
+/// Python::with_gil(|py| {
+///     let obj = py.eval("{'name': 'alice', 'groups': ('admin',)}", None, None).unwrap();
+///     let user: User = pyo3::serde::from_object(obj).unwrap();
+///     assert_eq!(user.groups, ["admin"]);
+///
+///     let obj = py.eval("{'name': 'bob', 'groups': [None]}", None, None).unwrap();
+///     let err = pyo3::serde::from_object::<User>(obj).err().unwrap();
+///     assert_eq!(
+///         err.to_string(),
+///         "TypeError: invalid type: unit value, expected a string at `groups[0]`"
+///     );
+/// });
+/// ```
+pub fn from_object<'de, T>(obj: &'de PyAny) -> PyResult<T>
+where
+    T: Deserialize<'de>,
+{
+    Ok(T::deserialize(Deserializer::new(obj))?)
+}
+
+/// The serde `Deserializer` behind [`from_object`].
+#[derive(Clone, Copy)]
+pub struct Deserializer<'de> {
+    obj: &'de PyAny,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Creates a deserializer reading from `obj`.
+    pub fn new(obj: &'de PyAny) -> Self {
+        Deserializer { obj }
+    }
+
+    fn invalid_type(&self, exp: &dyn de::Expected) -> Error {
+        let type_name = self
+            .obj
+            .get_type()
+            .name()
+            .map(|name| format!("object of type '{}'", name))
+            .unwrap_or_else(|_| "object".to_string());
+        de::Error::invalid_type(Unexpected::Other(&type_name), exp)
+    }
+
+    fn deserialize_int<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if let Ok(v) = self.obj.extract::<i64>() {
+            visitor.visit_i64(v)
+        } else if let Ok(v) = self.obj.extract::<u64>() {
+            visitor.visit_u64(v)
+        } else if let Ok(v) = self.obj.extract::<i128>() {
+            visitor.visit_i128(v)
+        } else {
+            visitor.visit_u128(self.obj.extract()?)
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let obj = self.obj;
+        if obj.is_none() {
+            visitor.visit_unit()
+        } else if let Ok(b) = obj.downcast::<PyBool>() {
+            visitor.visit_bool(b.is_true())
+        } else if obj.is_instance::<PyLong>()? {
+            self.deserialize_int(visitor)
+        } else if let Ok(f) = obj.downcast::<PyFloat>() {
+            visitor.visit_f64(f.value())
+        } else if let Ok(s) = obj.downcast::<PyString>() {
+            visitor.visit_borrowed_str(s.to_str()?)
+        } else if let Ok(b) = obj.downcast::<PyBytes>() {
+            visitor.visit_borrowed_bytes(b.as_bytes())
+        } else if let Ok(b) = obj.downcast::<PyByteArray>() {
+            visitor.visit_byte_buf(b.to_vec())
+        } else if let Ok(dict) = obj.downcast::<PyDict>() {
+            visitor.visit_map(MapAccess::new(PyIterator::from_object(
+                obj.py(),
+                dict.items(),
+            )?))
+        } else if obj.is_instance::<PyList>()?
+            || obj.is_instance::<PyTuple>()?
+            || obj.is_instance::<PySet>()?
+            || obj.is_instance::<PyFrozenSet>()?
+        {
+            visitor.visit_seq(SeqAccess::new(obj.iter()?))
+        } else if let Ok(mapping) = obj.downcast::<PyMapping>() {
+            visitor.visit_map(MapAccess::new(PyIterator::from_object(
+                obj.py(),
+                mapping.items()?,
+            )?))
+        } else if let Ok(iter) = obj.iter() {
+            visitor.visit_seq(SeqAccess::new(iter))
+        } else {
+            Err(self.invalid_type(&visitor))
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // `bytes` are sequences of integers when serde expects a sequence, e.g. for `Vec<u8>`
+        if self.obj.is_instance::<PyBytes>()? || self.obj.is_instance::<PyByteArray>()? {
+            visitor.visit_seq(SeqAccess::new(self.obj.iter()?))
+        } else {
+            self.deserialize_any(visitor)
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.obj.is_none() {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        if let Ok(variant) = self.obj.downcast::<PyString>() {
+            return visitor.visit_enum(EnumAccess {
+                variant,
+                value: None,
+            });
+        }
+        if let Ok(dict) = self.obj.downcast::<PyDict>() {
+            if let Some((variant, value)) = dict.iter().next() {
+                if dict.len() == 1 {
+                    return visitor.visit_enum(EnumAccess {
+                        variant,
+                        value: Some(value),
+                    });
+                }
+            }
+            return Err(de::Error::invalid_length(
+                dict.len(),
+                &"a dict with the name of the variant as its only key",
+            ));
+        }
+        Err(self.invalid_type(&visitor))
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf unit
+        unit_struct tuple tuple_struct map struct identifier
+    }
+}
+
+/// Reads the elements of a Python iterable.
+struct SeqAccess<'de> {
+    iter: &'de PyIterator,
+    index: usize,
+}
+
+impl<'de> SeqAccess<'de> {
+    fn new(iter: &'de PyIterator) -> Self {
+        SeqAccess { iter, index: 0 }
+    }
+}
+
+impl<'de> de::SeqAccess<'de> for SeqAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        let item = match self.iter.next() {
+            Some(item) => item.map_err(|e| Error::from(e).at_index(self.index))?,
+            None => return Ok(None),
+        };
+        let value = seed
+            .deserialize(Deserializer::new(item))
+            .map_err(|e| e.at_index(self.index))?;
+        self.index += 1;
+        Ok(Some(value))
+    }
+}
+
+/// Reads the `(key, value)` pairs of `dict.items()` or `mapping.items()`.
+struct MapAccess<'de> {
+    items: &'de PyIterator,
+    entry: Option<(&'de PyAny, &'de PyAny)>,
+}
+
+impl<'de> MapAccess<'de> {
+    fn new(items: &'de PyIterator) -> Self {
+        MapAccess { items, entry: None }
+    }
+}
+
+impl<'de> de::MapAccess<'de> for MapAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let (key, value) = match self.items.next() {
+            Some(item) => item?.extract::<(&PyAny, &PyAny)>()?,
+            None => return Ok(None),
+        };
+        self.entry = Some((key, value));
+        seed.deserialize(Deserializer::new(key))
+            .map(Some)
+            .map_err(|e| e.at_key(key))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        let (key, value) = self
+            .entry
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        seed.deserialize(Deserializer::new(value))
+            .map_err(|e| e.at_key(key))
+    }
+}
+
+/// Reads the name of a variant, and its data if it is not a unit variant.
+struct EnumAccess<'de> {
+    variant: &'de PyAny,
+    value: Option<&'de PyAny>,
+}
+
+impl<'de> de::EnumAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self)> {
+        let variant = seed.deserialize(Deserializer::new(self.variant))?;
+        Ok((variant, self))
+    }
+}
+
+impl<'de> EnumAccess<'de> {
+    /// The data of a variant which is not a unit variant.
+    fn value(&self, exp: &dyn de::Expected) -> Result<Deserializer<'de>> {
+        match self.value {
+            Some(value) => Ok(Deserializer::new(value)),
+            None => Err(de::Error::invalid_type(Unexpected::UnitVariant, exp)),
+        }
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for EnumAccess<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        match self.value {
+            Some(value) if !value.is_none() => Err(de::Error::invalid_type(
+                Unexpected::Other("variant with data"),
+                &"unit variant",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value> {
+        seed.deserialize(self.value(&"newtype variant")?)
+            .map_err(|e| e.at_key(self.variant))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        de::Deserializer::deserialize_seq(self.value(&"tuple variant")?, visitor)
+            .map_err(|e| e.at_key(self.variant))
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        de::Deserializer::deserialize_map(self.value(&"struct variant")?, visitor)
+            .map_err(|e| e.at_key(self.variant))
+    }
+}
@@ -0,0 +1,134 @@
+use crate::exceptions::{PyTypeError, PyValueError};
+use crate::types::PyString;
+use crate::{PyAny, PyErr, Python};
+use serde::{de, ser};
+use std::fmt;
+
+/// An error of [`Serializer`](super::Serializer) or [`Deserializer`](super::Deserializer),
+/// together with the path to the value which caused it, such as `users[1].name`.
+///
+/// It converts to a `PyErr` of the same type as the original Python exception. Values of the
+/// wrong type raise `TypeError`, and other errors reported by serde raise `ValueError`.
+#[derive(Debug)]
+pub struct Error {
+    kind: ErrorKind,
+    /// The segments of the path, such as `[1]` or `.name`, innermost first.
+    path: Vec<String>,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Py(PyErr),
+    Type(String),
+    Value(String),
+}
+
+impl Error {
+    /// The path from the outermost value to the value which caused the error, or `None` if the
+    /// error was caused by the outermost value itself.
+    pub fn path(&self) -> Option<String> {
+        if self.path.is_empty() {
+            return None;
+        }
+        let mut path: String = self.path.iter().rev().map(String::as_str).collect();
+        if path.starts_with('.') {
+            path.remove(0);
+        }
+        Some(path)
+    }
+
+    /// Adds the index of an element of a sequence to the path.
+    pub(super) fn at_index(mut self, index: usize) -> Self {
+        self.path.push(format!("[{}]", index));
+        self
+    }
+
+    /// Adds the name of a struct field to the path.
+    pub(super) fn at_field(mut self, field: &str) -> Self {
+        self.path.push(format!(".{}", field));
+        self
+    }
+
+    /// Adds the key of a dict to the path: strings are written like struct fields, and other
+    /// keys with their `repr()`.
+    pub(super) fn at_key(mut self, key: &PyAny) -> Self {
+        let segment = if let Ok(key) = key.downcast::<PyString>() {
+            format!(".{}", key.to_string_lossy())
+        } else {
+            match key.repr() {
+                Ok(repr) => format!("[{}]", repr.to_string_lossy()),
+                Err(_) => "[?]".to_string(),
+            }
+        };
+        self.path.push(segment);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::Py(err) => write!(f, "{}", err)?,
+            ErrorKind::Type(msg) | ErrorKind::Value(msg) => f.write_str(msg)?,
+        }
+        if let Some(path) = self.path() {
+            write!(f, " at `{}`", path)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ErrorKind::Value(msg.to_string()).into()
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ErrorKind::Value(msg.to_string()).into()
+    }
+
+    fn invalid_type(unexp: de::Unexpected, exp: &dyn de::Expected) -> Self {
+        ErrorKind::Type(format!("invalid type: {}, expected {}", unexp, exp)).into()
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            path: Vec::new(),
+        }
+    }
+}
+
+impl From<PyErr> for Error {
+    fn from(err: PyErr) -> Self {
+        ErrorKind::Py(err).into()
+    }
+}
+
+impl From<Error> for PyErr {
+    fn from(err: Error) -> PyErr {
+        let path = err.path();
+        let with_path = |msg: String| match &path {
+            Some(path) => format!("{} at `{}`", msg, path),
+            None => msg,
+        };
+        match err.kind {
+            ErrorKind::Py(err) if path.is_none() => err,
+            ErrorKind::Py(err) => Python::with_gil(|py| {
+                let msg = match err.instance(py).str() {
+                    Ok(msg) => msg.to_string_lossy().into_owned(),
+                    Err(_) => String::new(),
+                };
+                PyErr::from_type(err.ptype(py), with_path(msg))
+            }),
+            ErrorKind::Type(msg) => PyTypeError::new_err(with_path(msg)),
+            ErrorKind::Value(msg) => PyValueError::new_err(with_path(msg)),
+        }
+    }
+}
@@ -0,0 +1,433 @@
+use super::Error;
+use crate::types::{PyBytes, PyDict, PyList, PyString, PyTuple};
+use crate::{IntoPy, PyObject, PyResult, Python};
+use serde::{ser, Serialize};
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Converts a Rust value to a Python object with serde.
+///
+/// Structs and maps become `dict`s, sequences become `list`s, tuples and tuple structs become
+/// `tuple`s, and `None`, `()` and unit structs become `None`. Enums use serde's default
+/// representation: unit variants become the `str` name of the variant, and other variants a
+/// `dict` with the name of the variant as its only key. The `#[serde(tag = "...")]` and
+/// `#[serde(untagged)]` representations are supported as well.
+///
+/// # Example
+/// ```rust
+/// use pyo3::prelude::*;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct User {
+///     name: String,
+///     groups: Vec<String>,
+/// }
+///
+/// Python::with_gil(|py| -> PyResult<()> {
+///     let user = User { name: "alice".into(), groups: vec!["admin".into()] };
+///     let obj = pyo3::serde::to_object(py, &user)?;
+///     assert_eq!(obj.as_ref(py).repr()?.to_str()?, "{'name': 'alice', 'groups': ['admin']}");
+///     Ok(())
+/// }).unwrap();
+/// ```
+pub fn to_object<T>(py: Python, value: &T) -> PyResult<PyObject>
+where
+    T: Serialize + ?Sized,
+{
+    Ok(value.serialize(Serializer::new(py))?)
+}
+
+/// The serde `Serializer` behind [`to_object`].
+#[derive(Clone, Copy)]
+pub struct Serializer<'py> {
+    py: Python<'py>,
+}
+
+impl<'py> Serializer<'py> {
+    /// Creates a serializer producing Python objects.
+    pub fn new(py: Python<'py>) -> Self {
+        Serializer { py }
+    }
+
+    fn serialize_element<T>(self, value: &T) -> Result<PyObject>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    /// Creates `{variant: value}`, the representation of a variant with data.
+    fn variant(self, variant: &'static str, value: PyObject) -> Result<PyObject> {
+        let dict = PyDict::new(self.py);
+        dict.set_item(variant, value)?;
+        Ok(dict.into())
+    }
+}
+
+impl<'py> ser::Serializer for Serializer<'py> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    type SerializeSeq = SerializeSeq<'py>;
+    type SerializeTuple = SerializeSeq<'py>;
+    type SerializeTupleStruct = SerializeSeq<'py>;
+    type SerializeTupleVariant = SerializeSeq<'py>;
+    type SerializeMap = SerializeMap<'py>;
+    type SerializeStruct = SerializeMap<'py>;
+    type SerializeStructVariant = SerializeMap<'py>;
+
+    fn serialize_bool(self, v: bool) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_char(self, v: char) -> Result<PyObject> {
+        Ok(v.into_py(self.py))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<PyObject> {
+        Ok(PyString::new(self.py, v).into())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<PyObject> {
+        Ok(PyBytes::new(self.py, v).into())
+    }
+
+    fn serialize_none(self) -> Result<PyObject> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<PyObject> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<PyObject> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<PyObject> {
+        Ok(self.py.None())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<PyObject> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<PyObject> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<PyObject> {
+        let value = self
+            .serialize_element(value)
+            .map_err(|e| e.at_field(variant))?;
+        self.variant(variant, value)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq<'py>> {
+        Ok(SerializeSeq::new(self, len.unwrap_or(0), SeqKind::List))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeq<'py>> {
+        Ok(SerializeSeq::new(self, len, SeqKind::Tuple))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SerializeSeq<'py>> {
+        Ok(SerializeSeq::new(self, len, SeqKind::Tuple))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq<'py>> {
+        Ok(SerializeSeq::new(self, len, SeqKind::Variant(variant)))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap<'py>> {
+        Ok(SerializeMap::new(self, None))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<SerializeMap<'py>> {
+        Ok(SerializeMap::new(self, None))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeMap<'py>> {
+        Ok(SerializeMap::new(self, Some(variant)))
+    }
+}
+
+enum SeqKind {
+    List,
+    Tuple,
+    /// A tuple variant, stored as `{variant: tuple}`.
+    Variant(&'static str),
+}
+
+/// Collects the elements of a sequence, tuple or tuple variant.
+pub struct SerializeSeq<'py> {
+    serializer: Serializer<'py>,
+    items: Vec<PyObject>,
+    kind: SeqKind,
+}
+
+impl<'py> SerializeSeq<'py> {
+    fn new(serializer: Serializer<'py>, len: usize, kind: SeqKind) -> Self {
+        SerializeSeq {
+            serializer,
+            items: Vec::with_capacity(len),
+            kind,
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let index = self.items.len();
+        let item = self.serializer.serialize_element(value).map_err(|e| {
+            let e = e.at_index(index);
+            match self.kind {
+                SeqKind::Variant(variant) => e.at_field(variant),
+                _ => e,
+            }
+        })?;
+        self.items.push(item);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<PyObject> {
+        let py = self.serializer.py;
+        match self.kind {
+            SeqKind::List => Ok(PyList::new(py, self.items).into()),
+            SeqKind::Tuple => Ok(PyTuple::new(py, self.items).into()),
+            SeqKind::Variant(variant) => {
+                let tuple = PyTuple::new(py, self.items).into();
+                self.serializer.variant(variant, tuple)
+            }
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+/// Collects the entries of a map, struct or struct variant into a `dict`.
+pub struct SerializeMap<'py> {
+    serializer: Serializer<'py>,
+    dict: &'py PyDict,
+    key: Option<PyObject>,
+    /// The name of the variant of a struct variant, stored as `{variant: dict}`.
+    variant: Option<&'static str>,
+}
+
+impl<'py> SerializeMap<'py> {
+    fn new(serializer: Serializer<'py>, variant: Option<&'static str>) -> Self {
+        SerializeMap {
+            serializer,
+            dict: PyDict::new(serializer.py),
+            key: None,
+            variant,
+        }
+    }
+
+    fn set_field<T: Serialize + ?Sized>(&mut self, key: &'static str, value: &T) -> Result<()> {
+        let variant = self.variant;
+        let value = self.serializer.serialize_element(value).map_err(|e| {
+            let e = e.at_field(key);
+            match variant {
+                Some(variant) => e.at_field(variant),
+                None => e,
+            }
+        })?;
+        self.dict.set_item(key, value)?;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<PyObject> {
+        match self.variant {
+            Some(variant) => self.serializer.variant(variant, self.dict.into()),
+            None => Ok(self.dict.into()),
+        }
+    }
+}
+
+impl ser::SerializeMap for SerializeMap<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        self.key = Some(self.serializer.serialize_element(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        let key = key.as_ref(self.serializer.py);
+        let value = self
+            .serializer
+            .serialize_element(value)
+            .map_err(|e| e.at_key(key))?;
+        self.dict
+            .set_item(key, value)
+            .map_err(|e| Error::from(e).at_key(key))
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStruct for SerializeMap<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.set_field(key, value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeMap<'_> {
+    type Ok = PyObject;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        self.set_field(key, value)
+    }
+
+    fn end(self) -> Result<PyObject> {
+        self.finish()
+    }
+}
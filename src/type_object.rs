@@ -6,6 +6,8 @@ use crate::once_cell::GILOnceCell;
 use crate::pyclass::{create_type_object, PyClass};
 use crate::pyclass_init::PyObjectInit;
 use crate::types::{PyAny, PyType};
+#[cfg(not(any(Py_LIMITED_API, PyPy)))]
+use crate::types::{PyDict, PyModule, PyTuple};
 use crate::{conversion::IntoPyPointer, PyMethodDefType};
 use crate::{ffi, AsPyPointer, PyErr, PyNativeType, PyObject, PyResult, Python};
 use parking_lot::{const_mutex, Mutex};
@@ -46,6 +48,14 @@ pub trait PySizedLayout<T: PyTypeInfo>: PyLayout<T> + Sized {}
 /// Otherwise, implementing this trait is undefined behavior.
 pub unsafe trait PyBorrowFlagLayout<T: PyTypeInfo>: PyLayout<T> + Sized {}
 
+/// Marker type indicates that `Self` can be a base layout of a `#[pyclass(frozen)]`.
+///
+/// # Safety
+///
+/// Self should either be laid out as `ffi::PyObject` followed by the instance data of the
+/// native base type, or be the layout of another frozen class, so that no borrow flag is used.
+pub unsafe trait PyFrozenLayout<T: PyTypeInfo>: PyLayout<T> + Sized {}
+
 /// Our custom type flags
 #[doc(hidden)]
 pub mod type_flags {
@@ -247,3 +257,35 @@ fn initialize_tp_dict(
 
 // This is necessary for making static `LazyStaticType`s
 unsafe impl Sync for LazyStaticType {}
+
+/// Creates a type object for `T` which belongs to `module` only, for `#[pymodule(multi_phase)]`
+/// modules.
+///
+/// It is a subclass of the type object of `T` which is shared by the whole process, without
+/// attributes or instance data of its own, so that its instances are accepted wherever instances
+/// of `T` are. It is owned by the module, and freed with it once no instances are left.
+#[cfg(not(any(Py_LIMITED_API, PyPy)))]
+pub(crate) fn create_module_type_object<T: PyClass>(module: &PyModule) -> PyResult<&PyType> {
+    let py = module.py();
+    let shared = T::type_object(py);
+    let dict = PyDict::new(py);
+    dict.set_item("__module__", T::MODULE.map_or_else(|| module.name(), Ok)?)?;
+    dict.set_item("__qualname__", shared.getattr("__qualname__")?)?;
+    dict.set_item("__doc__", shared.getattr("__doc__")?)?;
+    dict.set_item("__slots__", PyTuple::empty(py))?;
+    let shared_ptr = shared.as_type_ptr();
+    let type_object = unsafe {
+        // `type` only subclasses types with `Py_TPFLAGS_BASETYPE`, so it is set while the module
+        // type object is created, and then cleared on both unless `T` allows subclasses.
+        let flags = (*shared_ptr).tp_flags;
+        (*shared_ptr).tp_flags |= ffi::Py_TPFLAGS_BASETYPE;
+        let type_object = py.get_type::<PyType>().call1((T::NAME, (shared,), dict));
+        (*shared_ptr).tp_flags = flags;
+        let type_object: &PyType = type_object?.downcast()?;
+        if T::FLAGS & type_flags::BASETYPE == 0 {
+            (*type_object.as_type_ptr()).tp_flags &= !ffi::Py_TPFLAGS_BASETYPE;
+        }
+        type_object
+    };
+    Ok(type_object)
+}
@@ -1,3 +1,4 @@
+use crate::inspect::TypeInfo;
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, Py, PyAny, PyObject, PyResult, PyTryFrom, Python,
     ToPyObject,
@@ -106,12 +107,20 @@ impl<'a> IntoPy<PyObject> for &'a [u8] {
     fn into_py(self, py: Python) -> PyObject {
         PyBytes::new(py, self).to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("bytes")
+    }
 }
 
 impl<'a> FromPyObject<'a> for &'a [u8] {
     fn extract(obj: &'a PyAny) -> PyResult<Self> {
         Ok(<PyBytes as PyTryFrom>::try_from(obj)?.as_bytes())
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("bytes")
+    }
 }
 #[cfg(test)]
 mod test {
@@ -0,0 +1,357 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+use crate::err::{PyErr, PyResult};
+use crate::ffi;
+use crate::instance::PyNativeType;
+use crate::panic::PanicException;
+use crate::{AsPyPointer, PyAny, Python};
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_int, c_void};
+use std::panic::{self, AssertUnwindSafe};
+
+/// Represents a Python `PyCapsule`.
+///
+/// A capsule is an opaque Python object which wraps a pointer. It is the standard way for
+/// extension modules to share C-level APIs (such as tables of function pointers) with each
+/// other. The name of the capsule is used as a simple type check when reading the pointer back.
+///
+/// See the [Python documentation](https://docs.python.org/3/c-api/capsule.html) for more.
+///
+/// # Example
+/// ```
+/// use pyo3::{prelude::*, types::PyCapsule};
+///
+/// #[repr(C)]
+/// struct Foo {
+///     pub val: u32,
+/// }
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let foo = Foo { val: 123 };
+/// let capsule = PyCapsule::new(py, foo, "builtins.capsule").unwrap();
+///
+/// let rust_foo: &Foo = unsafe { capsule.reference::<Foo>() }.unwrap();
+/// assert_eq!(rust_foo.val, 123);
+/// ```
+#[repr(transparent)]
+pub struct PyCapsule(PyAny);
+
+pyobject_native_var_type!(PyCapsule, ffi::PyCapsule_Type, ffi::PyCapsule_CheckExact);
+
+impl PyCapsule {
+    /// Constructs a new capsule whose contents are `value`, associated with `name`.
+    ///
+    /// `name` should be the full dotted path to the attribute under which the capsule is
+    /// made available, so that [PyCapsule::import] can later find it, e.g. `"pkg.mod._C_API"`.
+    ///
+    /// `value` is moved into the capsule and dropped when Python frees the capsule.
+    pub fn new<'p, T: 'static + Send>(
+        py: Python<'p>,
+        value: T,
+        name: &str,
+    ) -> PyResult<&'p PyCapsule> {
+        Self::new_with_destructor(py, value, name, |_, _| {})
+    }
+
+    /// Constructs a new capsule like [PyCapsule::new], additionally running `destructor` when
+    /// Python frees the capsule.
+    ///
+    /// The destructor receives ownership of the value together with the capsule's context
+    /// pointer (see [PyCapsule::set_context]). `value` is dropped after the destructor returns,
+    /// unless the destructor consumes it.
+    pub fn new_with_destructor<'p, T, F>(
+        py: Python<'p>,
+        value: T,
+        name: &str,
+        destructor: F,
+    ) -> PyResult<&'p PyCapsule>
+    where
+        T: 'static + Send,
+        F: 'static + FnOnce(T, *mut c_void) + Send,
+    {
+        // The capsule only stores a pointer to its name, so the name must live as long as
+        // the capsule itself: keep it together with the contents.
+        let name = CString::new(name)?;
+        let name_ptr = name.as_ptr();
+        let contents = Box::new(CapsuleContents {
+            value,
+            destructor,
+            name,
+        });
+
+        unsafe {
+            let ptr = ffi::PyCapsule_New(
+                Box::into_raw(contents) as *mut c_void,
+                name_ptr,
+                Some(capsule_destructor::<T, F>),
+            );
+            py.from_owned_ptr_or_err(ptr)
+        }
+    }
+
+    /// Imports an existing capsule.
+    ///
+    /// `name` is the full dotted path to the capsule, e.g. `"pkg.mod._C_API"`: the module
+    /// `pkg.mod` is imported and its `_C_API` attribute is looked up. The capsule's name must
+    /// match `name` exactly.
+    ///
+    /// # Safety
+    ///
+    /// The capsule's pointer must point to a valid value of type `T`.
+    pub unsafe fn import<'p, T>(py: Python<'p>, name: &str) -> PyResult<&'p T> {
+        let name = CString::new(name)?;
+        let ptr = ffi::PyCapsule_Import(name.as_ptr(), false as c_int);
+        if ptr.is_null() {
+            Err(PyErr::fetch(py))
+        } else {
+            Ok(&*(ptr as *const T))
+        }
+    }
+
+    /// Gets a reference to the value stored in this capsule.
+    ///
+    /// # Safety
+    ///
+    /// The capsule's pointer must point to a valid value of type `T`. This holds for capsules
+    /// created by [PyCapsule::new] with the same `T`.
+    pub unsafe fn reference<T>(&self) -> PyResult<&T> {
+        self.pointer().map(|ptr| &*(ptr as *const T))
+    }
+
+    /// Gets the raw pointer stored in this capsule.
+    ///
+    /// This fails if the capsule is not valid, see [PyCapsule::is_valid].
+    pub fn pointer(&self) -> PyResult<*mut c_void> {
+        let ptr = unsafe { ffi::PyCapsule_GetPointer(self.as_ptr(), self.name_ptr()) };
+        if ptr.is_null() {
+            Err(PyErr::fetch(self.py()))
+        } else {
+            Ok(ptr)
+        }
+    }
+
+    /// Sets the context pointer of this capsule.
+    ///
+    /// The context pointer is passed to the destructor given to
+    /// [PyCapsule::new_with_destructor].
+    ///
+    /// # Safety
+    ///
+    /// `context` must be valid for whatever the destructor of this capsule does with it, and
+    /// for any other reader of the context pointer.
+    pub unsafe fn set_context(&self, context: *mut c_void) -> PyResult<()> {
+        let result = ffi::PyCapsule_SetContext(self.as_ptr(), context);
+        if result != 0 {
+            Err(PyErr::fetch(self.py()))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Gets the context pointer of this capsule.
+    ///
+    /// This is a null pointer unless it has been set with [PyCapsule::set_context].
+    pub fn get_context(&self) -> PyResult<*mut c_void> {
+        let ctx = unsafe { ffi::PyCapsule_GetContext(self.as_ptr()) };
+        if ctx.is_null() && PyErr::occurred(self.py()) {
+            Err(PyErr::fetch(self.py()))
+        } else {
+            Ok(ctx)
+        }
+    }
+
+    /// Checks whether this is a valid capsule: its pointer is not null and its name matches
+    /// the name it was created with.
+    pub fn is_valid(&self) -> bool {
+        let r = unsafe { ffi::PyCapsule_IsValid(self.as_ptr(), self.name_ptr()) };
+        r != 0
+    }
+
+    /// Gets the name of this capsule, if it has one.
+    pub fn name(&self) -> Option<&CStr> {
+        let ptr = self.name_ptr();
+        if ptr.is_null() {
+            None
+        } else {
+            Some(unsafe { CStr::from_ptr(ptr) })
+        }
+    }
+
+    fn name_ptr(&self) -> *const std::os::raw::c_char {
+        unsafe { ffi::PyCapsule_GetName(self.as_ptr()) }
+    }
+}
+
+// `value` must stay the first field so that the capsule pointer can be read as `*const T`.
+#[repr(C)]
+struct CapsuleContents<T: 'static + Send, F: 'static + FnOnce(T, *mut c_void) + Send> {
+    value: T,
+    destructor: F,
+    name: CString,
+}
+
+unsafe extern "C" fn capsule_destructor<
+    T: 'static + Send,
+    F: 'static + FnOnce(T, *mut c_void) + Send,
+>(
+    capsule: *mut ffi::PyObject,
+) {
+    let pool = crate::GILPool::new();
+    let ptr = ffi::PyCapsule_GetPointer(capsule, ffi::PyCapsule_GetName(capsule));
+    let ctx = ffi::PyCapsule_GetContext(capsule);
+    let CapsuleContents {
+        value, destructor, ..
+    } = *Box::from_raw(ptr as *mut CapsuleContents<T, F>);
+    // Panics must not unwind into Python, and a destructor cannot raise: report them instead.
+    // The capsule itself is not passed, as it is being deallocated.
+    let result = panic::catch_unwind(AssertUnwindSafe(move || destructor(value, ctx)));
+    if let Err(payload) = result {
+        PanicException::from_panic_payload(payload).restore(pool.python());
+        ffi::PyErr_WriteUnraisable(std::ptr::null_mut());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{PyCapsule, PyModule};
+    use crate::{PyResult, Python};
+    use std::os::raw::c_void;
+    use std::sync::mpsc::{channel, Sender};
+
+    #[test]
+    fn test_pycapsule_struct() -> PyResult<()> {
+        #[repr(C)]
+        struct Foo {
+            pub val: u32,
+        }
+
+        impl Foo {
+            fn get_val(&self) -> u32 {
+                self.val
+            }
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let foo = Foo { val: 123 };
+
+        let cap = PyCapsule::new(py, foo, "foo")?;
+        assert!(cap.is_valid());
+        assert_eq!(cap.name().unwrap().to_str().unwrap(), "foo");
+
+        let foo_capi = unsafe { cap.reference::<Foo>()? };
+        assert_eq!(foo_capi.val, 123);
+        assert_eq!(foo_capi.get_val(), 123);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pycapsule_func() {
+        fn foo(x: u32) -> u32 {
+            x
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cap = PyCapsule::new(py, foo as fn(u32) -> u32, "foo").unwrap();
+
+        let f = unsafe { cap.reference::<fn(u32) -> u32>() }.unwrap();
+        assert_eq!(f(123), 123);
+    }
+
+    #[test]
+    fn test_pycapsule_context() -> PyResult<()> {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let cap = PyCapsule::new(py, 0usize, "foo")?;
+
+        assert!(cap.get_context()?.is_null());
+
+        let ctx = Box::new(123u32);
+        unsafe { cap.set_context(Box::into_raw(ctx) as _)? };
+
+        let ctx_ptr = cap.get_context()?;
+        let ctx = unsafe { Box::from_raw(ctx_ptr as *mut u32) };
+        assert_eq!(*ctx, 123);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pycapsule_import() -> PyResult<()> {
+        #[repr(C)]
+        struct Foo {
+            pub val: u32,
+        }
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let foo = Foo { val: 123 };
+
+        let cap = PyCapsule::new(py, foo, "builtins.capsule")?;
+
+        let module = PyModule::import(py, "builtins")?;
+        module.add("capsule", cap)?;
+
+        let result: PyResult<&Foo> = unsafe { PyCapsule::import(py, "builtins.non_existant") };
+        assert!(result.is_err());
+
+        let cap: &Foo = unsafe { PyCapsule::import(py, "builtins.capsule")? };
+        assert_eq!(cap.val, 123);
+        Ok(())
+    }
+
+    #[test]
+    fn test_pycapsule_destructor() {
+        let (tx, rx) = channel::<bool>();
+
+        fn destructor(_val: u32, ctx: *mut c_void) {
+            assert!(!ctx.is_null());
+            let context = unsafe { *Box::from_raw(ctx as *mut Sender<bool>) };
+            context.send(true).unwrap();
+        }
+
+        {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            let cap = PyCapsule::new_with_destructor(py, 0u32, "foo", destructor).unwrap();
+            unsafe { cap.set_context(Box::into_raw(Box::new(tx)) as _).unwrap() };
+        }
+
+        // the capsule is freed once the GIL pool above is dropped
+        assert_eq!(rx.recv(), Ok(true));
+    }
+
+    #[test]
+    fn test_pycapsule_drops_value() {
+        struct DropFlag(Sender<()>);
+
+        impl Drop for DropFlag {
+            fn drop(&mut self) {
+                self.0.send(()).unwrap();
+            }
+        }
+
+        let (tx, rx) = channel();
+        {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            PyCapsule::new(py, DropFlag(tx), "foo").unwrap();
+        }
+        assert_eq!(rx.recv(), Ok(()));
+    }
+
+    #[test]
+    fn test_pycapsule_destructor_panic() {
+        {
+            let gil = Python::acquire_gil();
+            let py = gil.python();
+            PyCapsule::new_with_destructor(py, 0u32, "foo", |_, _| panic!("destructor panicked"))
+                .unwrap();
+        }
+
+        // the panic is reported as unraisable instead of unwinding into Python
+        let gil = Python::acquire_gil();
+        assert!(!crate::PyErr::occurred(gil.python()));
+    }
+}
@@ -149,6 +149,10 @@ mod complex_conversion {
                         PyObject::from_owned_ptr(py, raw_obj)
                     }
                 }
+
+                fn type_output() -> crate::inspect::TypeInfo {
+                    crate::inspect::TypeInfo::Builtin("complex")
+                }
             }
             #[cfg(not(any(Py_LIMITED_API, PyPy)))]
             #[allow(clippy::float_cmp)] // The comparison is for an error value
@@ -163,6 +167,10 @@ mod complex_conversion {
                         }
                     }
                 }
+
+                fn type_input() -> crate::inspect::TypeInfo {
+                    crate::inspect::TypeInfo::Builtin("complex")
+                }
             }
             #[cfg(any(Py_LIMITED_API, PyPy))]
             #[allow(clippy::float_cmp)] // The comparison is for an error value
@@ -178,6 +186,10 @@ mod complex_conversion {
                         Ok(Complex::new(real as $float, imag as $float))
                     }
                 }
+
+                fn type_input() -> crate::inspect::TypeInfo {
+                    crate::inspect::TypeInfo::Builtin("complex")
+                }
             }
         };
     }
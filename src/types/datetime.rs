@@ -411,6 +411,23 @@ fn opt_to_pyobj(py: Python, opt: Option<&PyObject>) -> *mut ffi::PyObject {
     }
 }
 
+/// Creates a `datetime.timezone` with the given UTC offset.
+///
+/// An offset of zero returns `datetime.timezone.utc`.
+pub(crate) fn timezone_from_offset<'p>(py: Python<'p>, offset: &PyDelta) -> PyResult<&'p PyTzInfo> {
+    #[cfg(all(Py_3_7, not(PyPy)))]
+    unsafe {
+        let ptr = (PyDateTimeAPI.TimeZone_FromTimeZone)(offset.as_ptr(), ptr::null_mut());
+        py.from_owned_ptr_or_err(ptr)
+    }
+    #[cfg(not(all(Py_3_7, not(PyPy))))]
+    {
+        py.import("datetime")?
+            .call1("timezone", (offset,))?
+            .extract()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[cfg(not(PyPy))]
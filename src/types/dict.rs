@@ -1,7 +1,8 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::err::{self, PyErr, PyResult};
-use crate::types::{PyAny, PyList};
+use crate::inspect::TypeInfo;
+use crate::types::{PyAny, PyList, PyMapping};
 #[cfg(not(PyPy))]
 use crate::IntoPyPointer;
 use crate::{
@@ -247,6 +248,10 @@ where
             .map(|(k, v)| (k.into_py(py), v.into_py(py)));
         IntoPyDict::into_py_dict(iter, py).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::dict_of(K::type_output(), V::type_output())
+    }
 }
 
 impl<K, V> IntoPy<PyObject> for collections::BTreeMap<K, V>
@@ -260,6 +265,10 @@ where
             .map(|(k, v)| (k.into_py(py), v.into_py(py)));
         IntoPyDict::into_py_dict(iter, py).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::dict_of(K::type_output(), V::type_output())
+    }
 }
 
 /// Conversion trait that allows a sequence of tuples to be converted into `PyDict`
@@ -330,13 +339,17 @@ where
     S: hash::BuildHasher + Default,
 {
     fn extract(ob: &'source PyAny) -> Result<Self, PyErr> {
-        let dict = <PyDict as PyTryFrom>::try_from(ob)?;
-        let mut ret = HashMap::with_capacity_and_hasher(dict.len(), S::default());
-        for (k, v) in dict.iter() {
-            ret.insert(K::extract(k)?, V::extract(v)?);
-        }
+        let mapping = <PyMapping as PyTryFrom>::try_from(ob)?;
+        let mut ret = HashMap::with_capacity_and_hasher(mapping.len().unwrap_or(0), S::default());
+        extract_mapping_items(mapping, |k, v| {
+            ret.insert(k, v);
+        })?;
         Ok(ret)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::mapping_of(K::type_input(), V::type_input())
+    }
 }
 
 impl<'source, K, V> FromPyObject<'source> for BTreeMap<K, V>
@@ -345,13 +358,41 @@ where
     V: FromPyObject<'source>,
 {
     fn extract(ob: &'source PyAny) -> Result<Self, PyErr> {
-        let dict = <PyDict as PyTryFrom>::try_from(ob)?;
+        let mapping = <PyMapping as PyTryFrom>::try_from(ob)?;
         let mut ret = BTreeMap::new();
+        extract_mapping_items(mapping, |k, v| {
+            ret.insert(k, v);
+        })?;
+        Ok(ret)
+    }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::mapping_of(K::type_input(), V::type_input())
+    }
+}
+
+/// Extracts all (key, value) pairs of `mapping`, passing each to `insert`.
+///
+/// Dicts are iterated directly; other mappings go through `mapping.items()`.
+fn extract_mapping_items<'source, K, V>(
+    mapping: &'source PyMapping,
+    mut insert: impl FnMut(K, V),
+) -> PyResult<()>
+where
+    K: FromPyObject<'source>,
+    V: FromPyObject<'source>,
+{
+    if let Ok(dict) = <PyDict as PyTryFrom>::try_from(mapping) {
         for (k, v) in dict.iter() {
-            ret.insert(K::extract(k)?, V::extract(v)?);
+            insert(K::extract(k)?, V::extract(v)?);
+        }
+    } else {
+        for item in mapping.items()?.iter()? {
+            let (k, v) = item?.extract()?;
+            insert(k, v);
         }
-        Ok(ret)
     }
+    Ok(())
 }
 
 #[cfg(feature = "hashbrown")]
@@ -382,6 +423,10 @@ mod hashbrown_hashmap_conversion {
                 .map(|(k, v)| (k.into_py(py), v.into_py(py)));
             IntoPyDict::into_py_dict(iter, py).into()
         }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::dict_of(K::type_output(), V::type_output())
+        }
     }
 
     impl<'source, K, V, S> FromPyObject<'source> for hashbrown::HashMap<K, V, S>
@@ -391,13 +436,20 @@ mod hashbrown_hashmap_conversion {
         S: hash::BuildHasher + Default,
     {
         fn extract(ob: &'source PyAny) -> Result<Self, PyErr> {
-            let dict = <PyDict as PyTryFrom>::try_from(ob)?;
-            let mut ret = hashbrown::HashMap::with_capacity_and_hasher(dict.len(), S::default());
-            for (k, v) in dict.iter() {
-                ret.insert(K::extract(k)?, V::extract(v)?);
-            }
+            let mapping = <PyMapping as PyTryFrom>::try_from(ob)?;
+            let mut ret = hashbrown::HashMap::with_capacity_and_hasher(
+                mapping.len().unwrap_or(0),
+                S::default(),
+            );
+            extract_mapping_items(mapping, |k, v| {
+                ret.insert(k, v);
+            })?;
             Ok(ret)
         }
+
+        fn type_input() -> TypeInfo {
+            TypeInfo::mapping_of(K::type_input(), V::type_input())
+        }
     }
 
     #[test]
@@ -830,4 +882,33 @@ mod test {
         assert_eq!(py_map.len(), 3);
         assert_eq!(py_map.get_item("b").unwrap().extract::<i32>().unwrap(), 2);
     }
+
+    #[test]
+    fn test_extract_maps_from_mappings() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+
+        for expr in &[
+            "{'a': 1, 'b': 2}",
+            "__import__('collections').OrderedDict([('a', 1), ('b', 2)])",
+            "__import__('types').MappingProxyType({'a': 1, 'b': 2})",
+        ] {
+            let obj = py.eval(expr, None, None).unwrap();
+            let hash_map: HashMap<String, i32> = obj.extract().unwrap();
+            assert_eq!(hash_map.len(), 2);
+            assert_eq!(hash_map["b"], 2);
+            let btree_map: BTreeMap<String, i32> = obj.extract().unwrap();
+            assert_eq!(
+                btree_map.into_iter().collect::<Vec<_>>(),
+                [("a".to_string(), 1), ("b".to_string(), 2)]
+            );
+        }
+
+        let list = py.eval("[('a', 1)]", None, None).unwrap();
+        let err = list.extract::<HashMap<String, i32>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TypeError: 'list' object cannot be converted to 'Mapping'"
+        );
+    }
 }
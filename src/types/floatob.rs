@@ -1,6 +1,7 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 //
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
+use crate::inspect::TypeInfo;
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, PyAny, PyErr, PyNativeType, PyObject, PyResult, Python,
     ToPyObject,
@@ -45,6 +46,10 @@ impl IntoPy<PyObject> for f64 {
     fn into_py(self, py: Python) -> PyObject {
         PyFloat::new(py, self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("float")
+    }
 }
 
 impl<'source> FromPyObject<'source> for f64 {
@@ -59,6 +64,10 @@ impl<'source> FromPyObject<'source> for f64 {
             Ok(v)
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("float")
+    }
 }
 
 impl ToPyObject for f32 {
@@ -71,12 +80,20 @@ impl IntoPy<PyObject> for f32 {
     fn into_py(self, py: Python) -> PyObject {
         PyFloat::new(py, f64::from(self)).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("float")
+    }
 }
 
 impl<'source> FromPyObject<'source> for f32 {
     fn extract(obj: &'source PyAny) -> PyResult<Self> {
         Ok(obj.extract::<f64>()? as f32)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("float")
+    }
 }
 
 #[cfg(test)]
@@ -1,6 +1,8 @@
+use crate::callback::IntoPyCallbackOutput;
 use crate::derive_utils::PyFunctionArguments;
 use crate::exceptions::PyValueError;
 use crate::prelude::*;
+use crate::types::{PyCapsule, PyDict, PyTuple};
 use crate::{
     class::methods::{self, PyMethodDef},
     ffi, AsPyPointer,
@@ -41,6 +43,58 @@ impl PyCFunction {
         )
     }
 
+    /// Create a new built-in function which calls `closure`.
+    ///
+    /// The closure receives the positional and keyword arguments of the call, and is dropped
+    /// together with the function object.
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    /// use pyo3::py_run;
+    /// use pyo3::types::{PyCFunction, PyDict, PyTuple};
+    ///
+    /// Python::with_gil(|py| {
+    ///     let offset = 10;
+    ///     let add = PyCFunction::new_closure(py, move |args: &PyTuple, _kwargs: Option<&PyDict>| {
+    ///         args.get_item(0).extract::<i32>().map(|x| x + offset)
+    ///     })
+    ///     .unwrap();
+    ///     py_run!(py, add, "assert add(1) == 11");
+    /// });
+    /// ```
+    pub fn new_closure<F, R>(py: Python, closure: F) -> PyResult<&PyCFunction>
+    where
+        F: Fn(&PyTuple, Option<&PyDict>) -> R + Send + 'static,
+        R: IntoPyCallbackOutput<*mut ffi::PyObject>,
+    {
+        let method_def = PyMethodDef::cfunction_with_keywords(
+            "pyo3-closure\0",
+            methods::PyCFunctionWithKeywords(run_closure::<F, R>),
+            "\0",
+        )
+        .as_method_def()
+        .map_err(|err| PyValueError::new_err(err.0))?;
+        // The method definition must outlive the function object, so it is stored in the
+        // capsule which the function receives as `self`.
+        let capsule = PyCapsule::new(
+            py,
+            ClosureContents {
+                closure,
+                method_def,
+            },
+            CLOSURE_CAPSULE_NAME,
+        )?;
+        unsafe {
+            let contents = capsule.reference::<ClosureContents<F>>()?;
+            py.from_owned_ptr_or_err::<PyCFunction>(ffi::PyCFunction_NewEx(
+                &contents.method_def as *const ffi::PyMethodDef as *mut ffi::PyMethodDef,
+                capsule.as_ptr(),
+                std::ptr::null_mut(),
+            ))
+        }
+    }
+
     #[doc(hidden)]
     pub fn internal_new(
         method_def: PyMethodDef,
@@ -68,6 +122,35 @@ impl PyCFunction {
     }
 }
 
+const CLOSURE_CAPSULE_NAME: &str = "pyo3-closure";
+
+struct ClosureContents<F> {
+    closure: F,
+    method_def: ffi::PyMethodDef,
+}
+
+// `ffi::PyMethodDef` only holds pointers to static strings and to `run_closure`.
+unsafe impl<F: Send> Send for ClosureContents<F> {}
+
+unsafe extern "C" fn run_closure<F, R>(
+    capsule_ptr: *mut ffi::PyObject,
+    args: *mut ffi::PyObject,
+    kwargs: *mut ffi::PyObject,
+) -> *mut ffi::PyObject
+where
+    F: Fn(&PyTuple, Option<&PyDict>) -> R + Send + 'static,
+    R: IntoPyCallbackOutput<*mut ffi::PyObject>,
+{
+    crate::callback_body!(py, {
+        let capsule: &PyCapsule = py.from_borrowed_ptr(capsule_ptr);
+        let contents = capsule.reference::<ClosureContents<F>>()?;
+        (contents.closure)(
+            py.from_borrowed_ptr(args),
+            py.from_borrowed_ptr_or_opt(kwargs),
+        )
+    })
+}
+
 /// Represents a Python function object.
 #[repr(transparent)]
 pub struct PyFunction(PyAny);
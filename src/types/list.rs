@@ -4,6 +4,7 @@
 
 use crate::err::{self, PyResult};
 use crate::ffi::{self, Py_ssize_t};
+use crate::inspect::TypeInfo;
 use crate::{
     AsPyPointer, IntoPy, IntoPyPointer, PyAny, PyNativeType, PyObject, Python, ToBorrowedObject,
     ToPyObject,
@@ -188,6 +189,10 @@ macro_rules! array_impls {
                 fn into_py(self, py: Python) -> PyObject {
                     self.as_ref().to_object(py)
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::list_of(TypeInfo::Any)
+                }
             }
         )+
     }
@@ -221,6 +226,10 @@ where
             PyObject::from_owned_ptr(py, ptr)
         }
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::list_of(T::type_output())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,356 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+use crate::err::{self, PyDowncastError, PyErr, PyResult};
+use crate::ffi;
+use crate::instance::PyNativeType;
+use crate::once_cell::GILOnceCell;
+use crate::types::{PyAny, PyDict, PySequence, PyType};
+use crate::{AsPyPointer, Py, PyTryFrom, Python, ToBorrowedObject};
+
+/// Represents a reference to a Python object supporting the mapping protocol.
+///
+/// Any instance of `collections.abc.Mapping` (including `dict`, `OrderedDict`,
+/// `types.MappingProxyType` and classes registered with the ABC) can be downcast to `PyMapping`.
+#[repr(transparent)]
+pub struct PyMapping(PyAny);
+pyobject_native_type_named!(PyMapping);
+pyobject_native_type_extract!(PyMapping);
+
+impl PyMapping {
+    /// Returns the number of objects in the mapping.
+    ///
+    /// This is equivalent to the Python expression `len(self)`.
+    #[inline]
+    pub fn len(&self) -> PyResult<usize> {
+        let v = unsafe { ffi::PyMapping_Size(self.as_ptr()) };
+        if v == -1 {
+            Err(PyErr::fetch(self.py()))
+        } else {
+            Ok(v as usize)
+        }
+    }
+
+    /// Returns whether the mapping is empty.
+    #[inline]
+    pub fn is_empty(&self) -> PyResult<bool> {
+        self.len().map(|l| l == 0)
+    }
+
+    /// Determines if the mapping contains the specified key.
+    ///
+    /// This is equivalent to the Python expression `key in self`.
+    #[inline]
+    pub fn contains<K>(&self, key: K) -> PyResult<bool>
+    where
+        K: ToBorrowedObject,
+    {
+        // PyMapping_HasKey swallows exceptions, so go through the `in` operator instead.
+        let r = key.with_borrowed_ptr(self.py(), |ptr| unsafe {
+            ffi::PySequence_Contains(self.as_ptr(), ptr)
+        });
+        match r {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(PyErr::fetch(self.py())),
+        }
+    }
+
+    /// Gets the item in self with key `key`.
+    ///
+    /// Returns a `KeyError` if the item is not found.
+    ///
+    /// This is equivalent to the Python expression `self[key]`.
+    #[inline]
+    pub fn get_item<K>(&self, key: K) -> PyResult<&PyAny>
+    where
+        K: ToBorrowedObject,
+    {
+        key.with_borrowed_ptr(self.py(), |ptr| unsafe {
+            self.py()
+                .from_owned_ptr_or_err(ffi::PyObject_GetItem(self.as_ptr(), ptr))
+        })
+    }
+
+    /// Sets the item in self with key `key`.
+    ///
+    /// This is equivalent to the Python statement `self[key] = value`.
+    #[inline]
+    pub fn set_item<K, V>(&self, key: K, value: V) -> PyResult<()>
+    where
+        K: ToBorrowedObject,
+        V: ToBorrowedObject,
+    {
+        key.with_borrowed_ptr(self.py(), move |key| {
+            value.with_borrowed_ptr(self.py(), |value| unsafe {
+                err::error_on_minusone(self.py(), ffi::PyObject_SetItem(self.as_ptr(), key, value))
+            })
+        })
+    }
+
+    /// Deletes the item with key `key`.
+    ///
+    /// This is equivalent to the Python statement `del self[key]`.
+    #[inline]
+    pub fn del_item<K>(&self, key: K) -> PyResult<()>
+    where
+        K: ToBorrowedObject,
+    {
+        key.with_borrowed_ptr(self.py(), |key| unsafe {
+            err::error_on_minusone(self.py(), ffi::PyMapping_DelItem(self.as_ptr(), key))
+        })
+    }
+
+    /// Returns a sequence containing all keys in the mapping.
+    ///
+    /// This is equivalent to the Python expression `list(self.keys())`.
+    #[inline]
+    pub fn keys(&self) -> PyResult<&PySequence> {
+        unsafe {
+            self.py()
+                .from_owned_ptr_or_err(ffi::PyMapping_Keys(self.as_ptr()))
+        }
+    }
+
+    /// Returns a sequence containing all values in the mapping.
+    ///
+    /// This is equivalent to the Python expression `list(self.values())`.
+    #[inline]
+    pub fn values(&self) -> PyResult<&PySequence> {
+        unsafe {
+            self.py()
+                .from_owned_ptr_or_err(ffi::PyMapping_Values(self.as_ptr()))
+        }
+    }
+
+    /// Returns a sequence of tuples of all (key, value) pairs in the mapping.
+    ///
+    /// This is equivalent to the Python expression `list(self.items())`.
+    #[inline]
+    pub fn items(&self) -> PyResult<&PySequence> {
+        unsafe {
+            self.py()
+                .from_owned_ptr_or_err(ffi::PyMapping_Items(self.as_ptr()))
+        }
+    }
+}
+
+/// Returns `collections.abc.Mapping`, importing it on first use.
+fn get_mapping_abc(py: Python) -> PyResult<&PyType> {
+    static MAPPING_ABC: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+    if let Some(abc) = MAPPING_ABC.get(py) {
+        return Ok(abc.as_ref(py));
+    }
+    let abc = py.import("collections.abc")?.getattr("Mapping")?;
+    let abc: Py<PyType> = <PyType as PyTryFrom>::try_from(abc)?.into();
+    let _ = MAPPING_ABC.set(py, abc);
+    Ok(MAPPING_ABC.get(py).unwrap().as_ref(py))
+}
+
+impl<'v> PyTryFrom<'v> for PyMapping {
+    /// Downcasting to `PyMapping` requires the concrete class to be a subclass (or registered
+    /// subclass) of `collections.abc.Mapping` (from the Python standard library) - i.e.
+    /// `isinstance(<class>, collections.abc.Mapping) == True`.
+    fn try_from<V: Into<&'v PyAny>>(value: V) -> Result<&'v PyMapping, PyDowncastError<'v>> {
+        let value = value.into();
+
+        // `isinstance(value, collections.abc.Mapping)` is comparatively slow, so check for the
+        // common case of a dict first
+        let is_mapping = <PyDict as PyTryFrom>::try_from(value).is_ok()
+            || get_mapping_abc(value.py())
+                .and_then(|abc| abc.is_instance(value))
+                .unwrap_or(false);
+
+        if is_mapping {
+            unsafe { Ok(<PyMapping as PyTryFrom>::try_from_unchecked(value)) }
+        } else {
+            Err(PyDowncastError::new(value, "Mapping"))
+        }
+    }
+
+    #[inline]
+    fn try_from_exact<V: Into<&'v PyAny>>(value: V) -> Result<&'v PyMapping, PyDowncastError<'v>> {
+        <PyMapping as PyTryFrom>::try_from(value)
+    }
+
+    #[inline]
+    unsafe fn try_from_unchecked<V: Into<&'v PyAny>>(value: V) -> &'v PyMapping {
+        let ptr = value.into() as *const _ as *const PyMapping;
+        &*ptr
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exceptions::PyKeyError;
+    use crate::types::{PyDict, PyMapping, PyTuple};
+    use crate::{PyTryFrom, Python, ToPyObject};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_numbers_and_sequences_are_not_mappings() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        assert!(<PyMapping as PyTryFrom>::try_from(5i32.to_object(py).as_ref(py)).is_err());
+        assert!(<PyMapping as PyTryFrom>::try_from(vec![1, 2].to_object(py).as_ref(py)).is_err());
+        assert!(<PyMapping as PyTryFrom>::try_from("abc".to_object(py).as_ref(py)).is_err());
+    }
+
+    #[test]
+    fn test_abc_mappings_are_mappings() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        for expr in &[
+            "{}",
+            "__import__('collections').OrderedDict()",
+            "__import__('types').MappingProxyType({})",
+        ] {
+            let obj = py.eval(expr, None, None).unwrap();
+            assert!(<PyMapping as PyTryFrom>::try_from(obj).is_ok(), "{}", expr);
+        }
+
+        // a class registered with the ABC, which does not inherit from it
+        let registered = py
+            .eval(
+                "__import__('collections').abc.Mapping.register(type('M', (), {}))()",
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(<PyMapping as PyTryFrom>::try_from(registered).is_ok());
+    }
+
+    #[test]
+    fn test_len() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+        assert_eq!(0, mapping.len().unwrap());
+        assert!(mapping.is_empty().unwrap());
+
+        v.insert(7, 32);
+        let ob = v.to_object(py);
+        let mapping2 = ob.cast_as::<PyMapping>(py).unwrap();
+        assert_eq!(1, mapping2.len().unwrap());
+        assert!(!mapping2.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_contains() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert("key0", 1234);
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+        mapping.set_item("key1", "foo").unwrap();
+
+        assert!(mapping.contains("key0").unwrap());
+        assert!(mapping.contains("key1").unwrap());
+        assert!(!mapping.contains("key2").unwrap());
+
+        // unhashable keys raise, rather than returning false
+        let unhashable = PyDict::new(py);
+        assert!(mapping.contains(unhashable).is_err());
+    }
+
+    #[test]
+    fn test_get_item() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+        assert_eq!(
+            32,
+            mapping.get_item(7i32).unwrap().extract::<i32>().unwrap()
+        );
+        let err = mapping.get_item(8i32).unwrap_err();
+        assert!(err.is_instance::<PyKeyError>(py));
+    }
+
+    #[test]
+    fn test_set_item() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+        assert!(mapping.set_item(7i32, 42i32).is_ok()); // change
+        assert!(mapping.set_item(8i32, 123i32).is_ok()); // insert
+        assert_eq!(
+            42i32,
+            mapping.get_item(7i32).unwrap().extract::<i32>().unwrap()
+        );
+        assert_eq!(
+            123i32,
+            mapping.get_item(8i32).unwrap().extract::<i32>().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_del_item() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+        assert!(mapping.del_item(7i32).is_ok());
+        assert_eq!(0, mapping.len().unwrap());
+        assert!(mapping.get_item(7i32).is_err());
+        assert!(mapping.del_item(7i32).is_err());
+    }
+
+    #[test]
+    fn test_keys_values_items() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let mut v = HashMap::new();
+        v.insert(7, 32);
+        v.insert(8, 42);
+        v.insert(9, 123);
+        let ob = v.to_object(py);
+        let mapping = ob.cast_as::<PyMapping>(py).unwrap();
+
+        let mut key_sum = 0;
+        for el in mapping.keys().unwrap().iter().unwrap() {
+            key_sum += el.unwrap().extract::<i32>().unwrap();
+        }
+        assert_eq!(7 + 8 + 9, key_sum);
+
+        let mut value_sum = 0;
+        for el in mapping.values().unwrap().iter().unwrap() {
+            value_sum += el.unwrap().extract::<i32>().unwrap();
+        }
+        assert_eq!(32 + 42 + 123, value_sum);
+
+        let mut key_sum = 0;
+        let mut value_sum = 0;
+        for el in mapping.items().unwrap().iter().unwrap() {
+            let tuple = el.unwrap().cast_as::<PyTuple>().unwrap();
+            key_sum += tuple.get_item(0).extract::<i32>().unwrap();
+            value_sum += tuple.get_item(1).extract::<i32>().unwrap();
+        }
+        assert_eq!(7 + 8 + 9, key_sum);
+        assert_eq!(32 + 42 + 123, value_sum);
+    }
+
+    #[test]
+    fn test_mapping_proxy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let proxy = py
+            .eval("__import__('types').MappingProxyType({'a': 1})", None, None)
+            .unwrap();
+        let mapping = <PyMapping as PyTryFrom>::try_from(proxy).unwrap();
+        assert_eq!(1, mapping.len().unwrap());
+        assert_eq!(1, mapping.get_item("a").unwrap().extract::<i32>().unwrap());
+        // mapping proxies are read-only
+        assert!(mapping.set_item("b", 2).is_err());
+    }
+}
@@ -0,0 +1,478 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+use crate::err::PyResult;
+use crate::ffi;
+use crate::instance::PyNativeType;
+use crate::{AsPyPointer, PyAny, Python};
+#[cfg(not(Py_LIMITED_API))]
+use {
+    crate::buffer::{Element, PyBuffer},
+    crate::PyCell,
+};
+
+/// Represents a Python `memoryview`.
+#[repr(transparent)]
+pub struct PyMemoryView(PyAny);
+
+pyobject_native_var_type!(
+    PyMemoryView,
+    ffi::PyMemoryView_Type,
+    ffi::PyMemoryView_Check
+);
+
+impl PyMemoryView {
+    /// Creates a new memoryview of the object `src`, which must support the buffer protocol.
+    ///
+    /// This is equivalent to the Python expression `memoryview(src)`.
+    pub fn from_object(src: &PyAny) -> PyResult<&PyMemoryView> {
+        unsafe {
+            src.py()
+                .from_owned_ptr_or_err(ffi::PyMemoryView_FromObject(src.as_ptr()))
+        }
+    }
+
+    /// Creates a new memoryview of `buffer`, with the same layout, format and writability.
+    ///
+    /// The memoryview takes over `buffer`, which is released once the memoryview, and all views
+    /// derived from it, have been released.
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn from_buffer<T>(py: Python, buffer: PyBuffer<T>) -> PyResult<&PyMemoryView>
+    where
+        T: Element + 'static,
+    {
+        let owner = PyCell::new(py, RustBuffer::from_buffer(buffer))?;
+        Self::from_object(owner)
+    }
+
+    /// Creates a new read-only memoryview of `data` without copying it.
+    ///
+    /// `data` (e.g. a `Vec<T>` or a `Box<[T]>`) is moved to the Python heap and freed once the
+    /// memoryview, and all views derived from it, have been released. The format of the
+    /// memoryview is derived from [Element::element_type].
+    ///
+    /// # Example
+    /// ```
+    /// use pyo3::prelude::*;
+    /// use pyo3::types::PyMemoryView;
+    /// use pyo3::py_run;
+    ///
+    /// let gil = Python::acquire_gil();
+    /// let py = gil.python();
+    /// let view = PyMemoryView::new(py, vec![1.0f64, 2.0, 3.0]).unwrap();
+    /// py_run!(py, view, r#"
+    /// assert view.format == "d"
+    /// assert view.readonly
+    /// assert view.tolist() == [1.0, 2.0, 3.0]
+    /// "#);
+    /// ```
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn new<T>(py: Python, data: impl Into<Box<[T]>>) -> PyResult<&PyMemoryView>
+    where
+        T: Element + Send + 'static,
+    {
+        Self::from_owned_slice(py, data.into(), true)
+    }
+
+    /// Creates a new writable memoryview of `data` without copying it.
+    ///
+    /// Like [PyMemoryView::new], except that Python code may write to the memory.
+    #[cfg(not(Py_LIMITED_API))]
+    pub fn new_writable<T>(py: Python, data: impl Into<Box<[T]>>) -> PyResult<&PyMemoryView>
+    where
+        T: Element + Send + 'static,
+    {
+        Self::from_owned_slice(py, data.into(), false)
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    fn from_owned_slice<T>(py: Python, data: Box<[T]>, readonly: bool) -> PyResult<&PyMemoryView>
+    where
+        T: Element + Send + 'static,
+    {
+        let owner = PyCell::new(py, RustBuffer::new(data, readonly)?)?;
+        Self::from_object(owner)
+    }
+}
+
+#[cfg(not(Py_LIMITED_API))]
+use self::rust_buffer::RustBuffer;
+
+/// An internal class exporting memory owned by Rust, or a buffer held by Rust, through the buffer
+/// protocol.
+///
+/// This is written out by hand, since the `#[pyclass]` and `#[pyproto]` macros cannot be used
+/// inside PyO3 itself.
+#[cfg(not(Py_LIMITED_API))]
+mod rust_buffer {
+    use crate::buffer::{Element, PyBuffer};
+    use crate::class::buffer::{
+        PyBufferGetBufferProtocol, PyBufferProtocol, PyBufferReleaseBufferProtocol,
+    };
+    use crate::class::impl_::{PyBufferProcs, PyClassImpl, ThreadCheckerStub};
+    use crate::exceptions::PyBufferError;
+    use crate::pycell::PyCellBase;
+    use crate::pyclass::{boolean_struct::False, PyClassAlloc};
+    use crate::pyclass_slots::PyClassDummySlot;
+    use crate::type_object::{LazyStaticType, PyTypeInfo};
+    use crate::{ffi, AsPyPointer, PyAny, PyCell, PyClass, PyClassInitializer};
+    use crate::{PyRefMut, PyResult, Python};
+    use std::os::raw::{c_char, c_int, c_void};
+    use std::{mem, ptr};
+
+    pub(super) struct RustBuffer {
+        // Keeps the memory, and the arrays describing it, alive.
+        _owner: Box<dyn Send>,
+        buf: *mut c_void,
+        len: ffi::Py_ssize_t,
+        readonly: bool,
+        format: *const c_char,
+        itemsize: ffi::Py_ssize_t,
+        ndim: c_int,
+        shape: *mut ffi::Py_ssize_t,
+        strides: *mut ffi::Py_ssize_t,
+        suboffsets: *mut ffi::Py_ssize_t,
+        c_contiguous: bool,
+        f_contiguous: bool,
+    }
+
+    // The raw pointers point into `_owner`, which is `Send`.
+    unsafe impl Send for RustBuffer {}
+
+    /// A slice together with the shape and strides of its buffer.
+    struct OwnedSlice<T> {
+        data: Box<[T]>,
+        shape: [ffi::Py_ssize_t; 1],
+        strides: [ffi::Py_ssize_t; 1],
+    }
+
+    impl RustBuffer {
+        pub(super) fn new<T>(data: Box<[T]>, readonly: bool) -> PyResult<Self>
+        where
+            T: Element + Send + 'static,
+        {
+            let format = T::element_type().to_format().ok_or_else(|| {
+                PyBufferError::new_err("cannot export a buffer of an unknown element type")
+            })?;
+            let itemsize = mem::size_of::<T>() as ffi::Py_ssize_t;
+            let mut owner = Box::new(OwnedSlice {
+                shape: [data.len() as ffi::Py_ssize_t],
+                strides: [itemsize],
+                data,
+            });
+            Ok(RustBuffer {
+                buf: owner.data.as_mut_ptr() as *mut c_void,
+                len: owner.shape[0] * itemsize,
+                readonly,
+                format: format.as_ptr(),
+                itemsize,
+                ndim: 1,
+                shape: owner.shape.as_mut_ptr(),
+                strides: owner.strides.as_mut_ptr(),
+                suboffsets: ptr::null_mut(),
+                c_contiguous: true,
+                f_contiguous: true,
+                _owner: owner,
+            })
+        }
+
+        /// Exports the buffer described by `buffer`, which stays acquired until `self` is dropped.
+        pub(super) fn from_buffer<T: Element + 'static>(buffer: PyBuffer<T>) -> Self {
+            let raw = buffer.raw();
+            RustBuffer {
+                buf: raw.buf,
+                len: raw.len,
+                readonly: buffer.readonly(),
+                format: buffer.format().as_ptr(),
+                itemsize: raw.itemsize,
+                ndim: raw.ndim,
+                shape: raw.shape,
+                strides: raw.strides,
+                suboffsets: raw.suboffsets,
+                c_contiguous: buffer.is_c_contiguous(),
+                f_contiguous: buffer.is_fortran_contiguous(),
+                _owner: Box::new(buffer),
+            }
+        }
+
+        /// Checks that a consumer which requested `flags` can use the buffer.
+        fn check_flags(&self, flags: c_int) -> PyResult<()> {
+            let requested = |flag| (flags & flag) == flag;
+            let error = if self.readonly && requested(ffi::PyBUF_WRITABLE) {
+                "Object is not writable"
+            } else if !self.suboffsets.is_null() && !requested(ffi::PyBUF_INDIRECT) {
+                "buffer requires suboffsets"
+            } else if !self.c_contiguous
+                && (!requested(ffi::PyBUF_STRIDES) || requested(ffi::PyBUF_C_CONTIGUOUS))
+            {
+                "buffer is not C-contiguous"
+            } else if !self.f_contiguous && requested(ffi::PyBUF_F_CONTIGUOUS) {
+                "buffer is not Fortran-contiguous"
+            } else if !self.c_contiguous
+                && !self.f_contiguous
+                && requested(ffi::PyBUF_ANY_CONTIGUOUS)
+            {
+                "buffer is not contiguous"
+            } else {
+                return Ok(());
+            };
+            Err(PyBufferError::new_err(error))
+        }
+    }
+
+    impl<'p> PyBufferProtocol<'p> for RustBuffer {
+        fn bf_getbuffer(
+            slf: PyRefMut<Self>,
+            view: *mut ffi::Py_buffer,
+            flags: c_int,
+        ) -> <Self as PyBufferGetBufferProtocol<'p>>::Result {
+            if view.is_null() {
+                return Err(PyBufferError::new_err("View is null"));
+            }
+            slf.check_flags(flags)?;
+            let requested = |flag| (flags & flag) == flag;
+
+            unsafe {
+                (*view).obj = slf.as_ptr();
+                ffi::Py_INCREF((*view).obj);
+
+                (*view).buf = slf.buf;
+                (*view).len = slf.len;
+                (*view).readonly = slf.readonly as c_int;
+                (*view).itemsize = slf.itemsize;
+
+                (*view).format = ptr::null_mut();
+                if requested(ffi::PyBUF_FORMAT) {
+                    (*view).format = slf.format as *mut _;
+                }
+
+                (*view).ndim = 1;
+                (*view).shape = ptr::null_mut();
+                if requested(ffi::PyBUF_ND) {
+                    (*view).ndim = slf.ndim;
+                    (*view).shape = slf.shape;
+                }
+
+                (*view).strides = ptr::null_mut();
+                if requested(ffi::PyBUF_STRIDES) {
+                    (*view).strides = slf.strides;
+                }
+
+                (*view).suboffsets = ptr::null_mut();
+                if requested(ffi::PyBUF_INDIRECT) {
+                    (*view).suboffsets = slf.suboffsets;
+                }
+                (*view).internal = ptr::null_mut();
+            }
+
+            Ok(())
+        }
+
+        fn bf_releasebuffer(
+            _slf: PyRefMut<Self>,
+            _view: *mut ffi::Py_buffer,
+        ) -> <Self as PyBufferReleaseBufferProtocol<'p>>::Result {
+        }
+    }
+
+    impl<'p> PyBufferGetBufferProtocol<'p> for RustBuffer {
+        type Result = PyResult<()>;
+    }
+
+    impl<'p> PyBufferReleaseBufferProtocol<'p> for RustBuffer {
+        type Result = ();
+    }
+
+    unsafe impl PyTypeInfo for RustBuffer {
+        type Type = RustBuffer;
+        type BaseType = PyAny;
+        type Layout = PyCell<Self>;
+        type BaseLayout = PyCellBase<PyAny>;
+        type Initializer = PyClassInitializer<Self>;
+        type AsRefTarget = PyCell<Self>;
+
+        const NAME: &'static str = "RustBuffer";
+        const MODULE: Option<&'static str> = Some("pyo3_runtime");
+
+        #[inline]
+        fn type_object_raw(py: Python) -> *mut ffi::PyTypeObject {
+            static TYPE_OBJECT: LazyStaticType = LazyStaticType::new();
+            TYPE_OBJECT.get_or_init::<Self>(py)
+        }
+    }
+
+    impl PyClass for RustBuffer {
+        type Dict = PyClassDummySlot;
+        type WeakRef = PyClassDummySlot;
+        type BaseNativeType = PyAny;
+        type Frozen = False;
+    }
+
+    impl PyClassAlloc for RustBuffer {}
+
+    static PROCS: PyBufferProcs = PyBufferProcs {
+        bf_getbuffer: Some(crate::class::buffer::getbuffer::<RustBuffer>),
+        bf_releasebuffer: Some(crate::class::buffer::releasebuffer::<RustBuffer>),
+    };
+
+    impl PyClassImpl for RustBuffer {
+        type ThreadChecker = ThreadCheckerStub<RustBuffer>;
+
+        fn for_each_proto_slot(mut visitor: impl FnMut(&ffi::PyType_Slot)) {
+            visitor(&ffi::PyType_Slot {
+                slot: ffi::Py_bf_getbuffer,
+                pfunc: crate::class::buffer::getbuffer::<RustBuffer> as _,
+            });
+            visitor(&ffi::PyType_Slot {
+                slot: ffi::Py_bf_releasebuffer,
+                pfunc: crate::class::buffer::releasebuffer::<RustBuffer> as _,
+            });
+        }
+
+        fn get_buffer() -> Option<&'static PyBufferProcs> {
+            Some(&PROCS)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::types::{PyBytes, PyMemoryView};
+    use crate::{py_run, Python, ToPyObject};
+
+    #[test]
+    fn test_from_object() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let bytes = PyBytes::new(py, b"abc");
+        let view = PyMemoryView::from_object(bytes).unwrap();
+        py_run!(py, view, "assert view.tobytes() == b'abc'");
+
+        let obj = 1.to_object(py);
+        assert!(PyMemoryView::from_object(obj.as_ref(py)).is_err());
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    #[test]
+    fn test_from_buffer() {
+        use crate::buffer::PyBuffer;
+
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let array = py
+            .import("array")
+            .unwrap()
+            .call_method1("array", ("i", vec![1, 2, 3]))
+            .unwrap();
+        let view = PyMemoryView::from_buffer(py, PyBuffer::<i32>::get(array).unwrap()).unwrap();
+        py_run!(py, view array, r#"
+            assert view.format == 'i'
+            assert view.tolist() == [1, 2, 3]
+            assert not view.readonly
+            view[0] = 5
+            assert array[0] == 5
+            try:
+                array.append(4)
+            except BufferError:
+                pass
+            else:
+                assert False
+        "#);
+
+        let strided = py.eval("memoryview(b'abcdef')[::2]", None, None).unwrap();
+        let view = PyMemoryView::from_buffer(py, PyBuffer::<u8>::get(strided).unwrap()).unwrap();
+        py_run!(
+            py,
+            view,
+            r#"
+            assert view.readonly
+            assert view.strides == (2,)
+            assert not view.c_contiguous
+            assert view.tobytes() == b'ace'
+        "#
+        );
+
+        let matrix = py
+            .eval(
+                "memoryview(bytearray(range(6))).cast('B', (2, 3))",
+                None,
+                None,
+            )
+            .unwrap();
+        let view = PyMemoryView::from_buffer(py, PyBuffer::<u8>::get(matrix).unwrap()).unwrap();
+        py_run!(
+            py,
+            view,
+            r#"
+            assert view.shape == (2, 3)
+            assert view.tolist() == [[0, 1, 2], [3, 4, 5]]
+            assert not view.readonly
+        "#
+        );
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    #[test]
+    fn test_new_readonly() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = PyMemoryView::new(py, vec![1u16, 2, 3]).unwrap();
+        py_run!(
+            py,
+            view,
+            r#"
+            assert view.readonly
+            assert view.format == 'H'
+            assert view.itemsize == 2
+            assert view.shape == (3,)
+            assert view.tolist() == [1, 2, 3]
+            assert view[1:].tolist() == [2, 3]
+            try:
+                view[0] = 5
+            except TypeError:
+                pass
+            else:
+                assert False, "expected TypeError"
+        "#
+        );
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    #[test]
+    fn test_new_writable() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let data: Box<[f64]> = vec![0.5, 1.5].into_boxed_slice();
+        let view = PyMemoryView::new_writable(py, data).unwrap();
+        py_run!(
+            py,
+            view,
+            r#"
+            assert not view.readonly
+            view[0] = 2.5
+            assert view.tolist() == [2.5, 1.5]
+            assert bytes(view.cast('B')) == view.tobytes()
+        "#
+        );
+    }
+
+    #[cfg(not(Py_LIMITED_API))]
+    #[test]
+    fn test_new_keeps_owner_alive() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let view = PyMemoryView::new(py, vec![1u8, 2, 3]).unwrap();
+
+        // derived views share the owner, so it must outlive the original view
+        py_run!(
+            py,
+            view,
+            r#"
+            sliced = view[1:]
+            view.release()
+            del view
+            import gc; gc.collect()
+            assert sliced.tolist() == [2, 3]
+        "#
+        );
+    }
+}
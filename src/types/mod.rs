@@ -6,8 +6,11 @@ pub use self::any::PyAny;
 pub use self::boolobject::PyBool;
 pub use self::bytearray::PyByteArray;
 pub use self::bytes::PyBytes;
+pub use self::capsule::PyCapsule;
 pub use self::complex::PyComplex;
 #[cfg(not(Py_LIMITED_API))]
+pub(crate) use self::datetime::timezone_from_offset;
+#[cfg(not(Py_LIMITED_API))]
 pub use self::datetime::{
     PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PyTime, PyTimeAccess, PyTzInfo,
 };
@@ -16,6 +19,8 @@ pub use self::floatob::PyFloat;
 pub use self::function::{PyCFunction, PyFunction};
 pub use self::iterator::PyIterator;
 pub use self::list::PyList;
+pub use self::mapping::PyMapping;
+pub use self::memoryview::PyMemoryView;
 pub use self::module::PyModule;
 pub use self::num::PyLong;
 pub use self::num::PyLong as PyInt;
@@ -25,6 +30,7 @@ pub use self::slice::{PySlice, PySliceIndices};
 pub use self::string::{PyString, PyString as PyUnicode};
 pub use self::tuple::PyTuple;
 pub use self::typeobject::PyType;
+pub use self::weakref::{PyWeakref, PyWeakrefProxy};
 
 // Implementations core to all native types
 #[macro_export]
@@ -146,6 +152,7 @@ macro_rules! pyobject_native_type_sized {
             type Dict = $crate::pyclass_slots::PyClassDummySlot;
             type WeakRef = $crate::pyclass_slots::PyClassDummySlot;
             type LayoutAsBase = $crate::pycell::PyCellBase<$name>;
+            type FrozenLayoutAsBase = $crate::pycell::PyFrozenCellBase<$name>;
             type BaseNativeType = $name;
             type ThreadChecker = $crate::class::impl_::ThreadCheckerStub<$crate::PyObject>;
         }
@@ -229,6 +236,7 @@ mod any;
 mod boolobject;
 mod bytearray;
 mod bytes;
+mod capsule;
 mod complex;
 #[cfg(not(Py_LIMITED_API))]
 mod datetime;
@@ -237,6 +245,8 @@ mod floatob;
 mod function;
 mod iterator;
 mod list;
+mod mapping;
+mod memoryview;
 mod module;
 mod num;
 mod sequence;
@@ -245,3 +255,4 @@ mod slice;
 mod string;
 mod tuple;
 mod typeobject;
+mod weakref;
@@ -3,15 +3,19 @@
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
 
 use crate::callback::IntoPyCallbackOutput;
+use crate::derive_utils::ModuleState;
 use crate::err::{PyErr, PyResult};
 use crate::exceptions;
 use crate::ffi;
 use crate::instance::PyNativeType;
 use crate::pyclass::PyClass;
+#[cfg(not(any(Py_LIMITED_API, PyPy)))]
+use crate::type_object::create_module_type_object;
 use crate::type_object::PyTypeObject;
 use crate::types::{PyAny, PyDict, PyList};
 use crate::types::{PyCFunction, PyTuple};
 use crate::{AsPyPointer, IntoPy, Py, PyObject, Python};
+use std::any::{type_name, Any};
 use std::ffi::{CStr, CString};
 use std::str;
 
@@ -139,13 +143,65 @@ impl PyModule {
     /// This is a convenience function that initializes the `class`,
     /// sets `new_type.__module__` to this module's name,
     /// and adds the type to this module.
+    ///
+    /// A `#[pymodule(multi_phase)]` module gets a type object of its own, which is a subclass of
+    /// the one shared by the whole process.
     pub fn add_class<T>(&self) -> PyResult<()>
     where
         T: PyClass,
     {
+        #[cfg(not(any(Py_LIMITED_API, PyPy)))]
+        {
+            if ModuleState::of(self).is_some() {
+                return self.add(T::NAME, create_module_type_object::<T>(self)?);
+            }
+        }
         self.add(T::NAME, <T as PyTypeObject>::type_object(self.py()))
     }
 
+    /// Returns the state of a `#[pymodule(multi_phase, state = T)]` module.
+    ///
+    /// The state is created with `T::default()` when the module is executed, before its
+    /// initialization function runs, and dropped when the module is freed. Every module object
+    /// created from the definition, e.g. by reloading it or importing it in a subinterpreter, has
+    /// its own state. Functions can access it with `#[pyfunction(pass_module)]`:
+    ///
+    /// ```rust
+    /// use pyo3::prelude::*;
+    /// use pyo3::wrap_pyfunction;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// #[derive(Default)]
+    /// struct Counter {
+    ///     count: AtomicUsize,
+    /// }
+    ///
+    /// #[pyfunction(pass_module)]
+    /// fn increment(module: &PyModule) -> PyResult<usize> {
+    ///     let counter = module.state::<Counter>()?;
+    ///     Ok(counter.count.fetch_add(1, Ordering::SeqCst) + 1)
+    /// }
+    ///
+    /// #[pymodule(multi_phase, state = Counter)]
+    /// fn counter(_py: Python, module: &PyModule) -> PyResult<()> {
+    ///     module.add_function(wrap_pyfunction!(increment, module)?)
+    /// }
+    /// ```
+    ///
+    /// This fails with `TypeError` if the module has no state of type `T`.
+    pub fn state<T: Any + Send>(&self) -> PyResult<&T> {
+        ModuleState::of(self)
+            .and_then(|state| state.state.as_ref())
+            .and_then(|state| state.downcast_ref::<T>())
+            .ok_or_else(|| {
+                exceptions::PyTypeError::new_err(format!(
+                    "module {} has no state of type {}",
+                    self.name().unwrap_or("<unnamed>"),
+                    type_name::<T>()
+                ))
+            })
+    }
+
     /// Adds a function or a (sub)module to a module, using the functions __name__ as name.
     ///
     /// Use this together with the`#[pyfunction]` and [wrap_pyfunction!] or `#[pymodule]` and
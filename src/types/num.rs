@@ -2,6 +2,7 @@
 //
 // based on Daniel Grunwald's https://github.com/dgrunwald/rust-cpython
 
+use crate::inspect::TypeInfo;
 use crate::{
     exceptions, ffi, AsPyPointer, FromPyObject, IntoPy, PyAny, PyErr, PyNativeType, PyObject,
     PyResult, Python, ToPyObject,
@@ -34,6 +35,10 @@ macro_rules! int_fits_larger_int {
             fn into_py(self, py: Python) -> PyObject {
                 (self as $larger_type).into_py(py)
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
 
         impl<'source> FromPyObject<'source> for $rust_type {
@@ -42,6 +47,10 @@ macro_rules! int_fits_larger_int {
                 <$rust_type>::try_from(val)
                     .map_err(|e| exceptions::PyOverflowError::new_err(e.to_string()))
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
     };
 }
@@ -70,6 +79,10 @@ macro_rules! int_fits_c_long {
             fn into_py(self, py: Python) -> PyObject {
                 unsafe { PyObject::from_owned_ptr(py, ffi::PyLong_FromLong(self as c_long)) }
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
 
         impl<'source> FromPyObject<'source> for $rust_type {
@@ -88,6 +101,10 @@ macro_rules! int_fits_c_long {
                 <$rust_type>::try_from(val)
                     .map_err(|e| exceptions::PyOverflowError::new_err(e.to_string()))
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
     };
 }
@@ -105,6 +122,10 @@ macro_rules! int_convert_u64_or_i64 {
             fn into_py(self, py: Python) -> PyObject {
                 unsafe { PyObject::from_owned_ptr(py, $pylong_from_ll_or_ull(self)) }
             }
+
+            fn type_output() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
         impl<'source> FromPyObject<'source> for $rust_type {
             fn extract(ob: &'source PyAny) -> PyResult<$rust_type> {
@@ -120,6 +141,10 @@ macro_rules! int_convert_u64_or_i64 {
                     }
                 }
             }
+
+            fn type_input() -> TypeInfo {
+                TypeInfo::Builtin("int")
+            }
         }
     };
 }
@@ -184,6 +209,10 @@ mod fast_128bit_int_conversion {
                         PyObject::from_owned_ptr(py, obj)
                     }
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
 
             impl<'source> FromPyObject<'source> for $rust_type {
@@ -208,6 +237,10 @@ mod fast_128bit_int_conversion {
                         }
                     }
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
         };
     }
@@ -250,6 +283,10 @@ mod slow_128bit_int_conversion {
                         )
                     }
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
 
             impl<'source> FromPyObject<'source> for $rust_type {
@@ -269,6 +306,10 @@ mod slow_128bit_int_conversion {
                         Ok((<$rust_type>::from(upper) << SHIFT) | lower)
                     }
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
         };
     }
@@ -399,6 +440,10 @@ mod bigint_conversion {
                 fn into_py(self, py: Python) -> PyObject {
                     self.to_object(py)
                 }
+
+                fn type_output() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
             impl<'source> FromPyObject<'source> for $rust_ty {
                 fn extract(ob: &'source PyAny) -> PyResult<$rust_ty> {
@@ -428,6 +473,10 @@ mod bigint_conversion {
                         }
                     }
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::Builtin("int")
+                }
             }
         };
     }
@@ -3,6 +3,7 @@
 use crate::err::{self, PyDowncastError, PyErr, PyResult};
 use crate::exceptions;
 use crate::ffi::{self, Py_ssize_t};
+use crate::inspect::TypeInfo;
 use crate::instance::PyNativeType;
 use crate::types::{PyAny, PyList, PyTuple};
 use crate::AsPyPointer;
@@ -277,6 +278,10 @@ macro_rules! array_impls {
                     extract_sequence_into_slice(obj, &mut array)?;
                     Ok(array)
                 }
+
+                fn type_input() -> TypeInfo {
+                    TypeInfo::sequence_of(T::type_input())
+                }
             }
 
             #[cfg(feature = "nightly")]
@@ -320,6 +325,10 @@ where
     default fn extract(obj: &'a PyAny) -> PyResult<Self> {
         extract_sequence(obj)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::sequence_of(T::type_input())
+    }
 }
 
 #[cfg(feature = "nightly")]
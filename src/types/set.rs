@@ -2,6 +2,7 @@
 //
 
 use crate::err::{self, PyErr, PyResult};
+use crate::inspect::TypeInfo;
 #[cfg(Py_LIMITED_API)]
 use crate::types::PyIterator;
 use crate::{
@@ -227,6 +228,10 @@ where
         }
         set.into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::set_of(K::type_output())
+    }
 }
 
 impl<'source, K, S> FromPyObject<'source> for HashSet<K, S>
@@ -238,6 +243,10 @@ where
         let set: &PySet = ob.downcast()?;
         set.iter().map(K::extract).collect()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::set_of(K::type_input())
+    }
 }
 
 impl<K> IntoPy<PyObject> for BTreeSet<K>
@@ -253,6 +262,10 @@ where
         }
         set.into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::set_of(K::type_output())
+    }
 }
 
 impl<'source, K> FromPyObject<'source> for BTreeSet<K>
@@ -263,6 +276,10 @@ where
         let set: &PySet = ob.downcast()?;
         set.iter().map(K::extract).collect()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::set_of(K::type_input())
+    }
 }
 
 impl PyFrozenSet {
@@ -357,6 +374,10 @@ mod hashbrown_hashset_conversion {
             }
             set.into()
         }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::set_of(K::type_output())
+        }
     }
 
     impl<'source, K, S> FromPyObject<'source> for hashbrown::HashSet<K, S>
@@ -368,6 +389,10 @@ mod hashbrown_hashset_conversion {
             let set: &PySet = ob.downcast()?;
             set.iter().map(K::extract).collect()
         }
+
+        fn type_input() -> TypeInfo {
+            TypeInfo::set_of(K::type_input())
+        }
     }
 
     #[test]
@@ -1,5 +1,6 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
+use crate::inspect::TypeInfo;
 use crate::types::PyBytes;
 use crate::{
     ffi, AsPyPointer, FromPyObject, IntoPy, PyAny, PyNativeType, PyObject, PyResult, PyTryFrom,
@@ -102,6 +103,10 @@ impl<'a> IntoPy<PyObject> for &'a str {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 /// Converts a Rust `Cow<str>` to a Python object.
@@ -118,6 +123,10 @@ impl IntoPy<PyObject> for Cow<'_, str> {
     fn into_py(self, py: Python) -> PyObject {
         self.to_object(py)
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 /// Converts a Rust `String` to a Python object.
@@ -140,12 +149,20 @@ impl IntoPy<PyObject> for char {
         let mut bytes = [0u8; 4];
         PyString::new(py, self.encode_utf8(&mut bytes)).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl IntoPy<PyObject> for String {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, &self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl<'a> IntoPy<PyObject> for &'a String {
@@ -153,6 +170,10 @@ impl<'a> IntoPy<PyObject> for &'a String {
     fn into_py(self, py: Python) -> PyObject {
         PyString::new(py, self).into()
     }
+
+    fn type_output() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 /// Allows extracting strings from Python objects.
@@ -161,6 +182,10 @@ impl<'source> FromPyObject<'source> for &'source str {
     fn extract(ob: &'source PyAny) -> PyResult<Self> {
         <PyString as PyTryFrom>::try_from(ob)?.to_str()
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 /// Allows extracting strings from Python objects.
@@ -171,6 +196,10 @@ impl FromPyObject<'_> for String {
             .to_str()
             .map(ToOwned::to_owned)
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 impl FromPyObject<'_> for char {
@@ -185,6 +214,10 @@ impl FromPyObject<'_> for char {
             ))
         }
     }
+
+    fn type_input() -> TypeInfo {
+        TypeInfo::Builtin("str")
+    }
 }
 
 #[cfg(test)]
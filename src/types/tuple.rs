@@ -1,6 +1,7 @@
 // Copyright (c) 2017-present PyO3 Project and Contributors
 
 use crate::ffi::{self, Py_ssize_t};
+use crate::inspect::TypeInfo;
 use crate::{
     exceptions, AsPyPointer, FromPyObject, IntoPy, IntoPyPointer, Py, PyAny, PyErr, PyNativeType,
     PyObject, PyResult, PyTryFrom, Python, ToPyObject,
@@ -159,6 +160,10 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
                 PyObject::from_owned_ptr(py, ptr)
             }
         }
+
+        fn type_output() -> TypeInfo {
+            TypeInfo::Tuple(vec![$($T::type_output()),+])
+        }
     }
 
     impl <$($T: IntoPy<PyObject>),+> IntoPy<Py<PyTuple>> for ($($T,)+) {
@@ -183,6 +188,10 @@ macro_rules! tuple_conversion ({$length:expr,$(($refN:ident, $n:tt, $T:ident)),+
                 Err(wrong_tuple_length(t, $length))
             }
         }
+
+        fn type_input() -> TypeInfo {
+            TypeInfo::Tuple(vec![$($T::type_input()),+])
+        }
     }
 });
 
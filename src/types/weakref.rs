@@ -0,0 +1,276 @@
+// Copyright (c) 2017-present PyO3 Project and Contributors
+
+use crate::err::{self, PyResult};
+use crate::ffi;
+use crate::instance::PyNativeType;
+use crate::pycell::PyRef;
+use crate::pyclass::PyClass;
+use crate::{AsPyPointer, PyAny, PyCell, PyTryFrom};
+
+/// Represents a Python `weakref.ReferenceType`.
+///
+/// A weak reference does not keep its referent alive. Use [PyWeakref::upgrade] to get back
+/// a (strong) reference to the object, if it is still alive.
+///
+/// # Example
+/// ```
+/// use pyo3::prelude::*;
+/// use pyo3::types::PyWeakref;
+///
+/// #[pyclass(weakref)]
+/// struct Foo {}
+///
+/// let gil = Python::acquire_gil();
+/// let py = gil.python();
+/// let foo = Py::new(py, Foo {}).unwrap();
+/// let weak: Py<PyWeakref> = foo.downgrade(py).unwrap().into();
+///
+/// assert!(weak.as_ref(py).upgrade().is_some());
+/// drop(foo);
+/// assert!(weak.as_ref(py).upgrade().is_none());
+/// ```
+#[repr(transparent)]
+pub struct PyWeakref(PyAny);
+
+pyobject_native_var_type!(PyWeakref, ffi::_PyWeakref_RefType, ffi::PyWeakref_CheckRef);
+
+impl PyWeakref {
+    /// Creates a new weak reference to `object`.
+    ///
+    /// If `callback` is given, it will be called with the weak reference object as its only
+    /// argument when `object` is about to be finalized.
+    ///
+    /// Returns a `TypeError` if `object` does not support weak references.
+    ///
+    /// This is equivalent to the Python expression `weakref.ref(object, callback)`.
+    pub fn new<'p>(object: &'p PyAny, callback: Option<&PyAny>) -> PyResult<&'p PyWeakref> {
+        unsafe {
+            object.py().from_owned_ptr_or_err(ffi::PyWeakref_NewRef(
+                object.as_ptr(),
+                callback.map_or(std::ptr::null_mut(), |c| c.as_ptr()),
+            ))
+        }
+    }
+
+    /// Returns the referenced object, or `None` if it has already been garbage collected.
+    ///
+    /// This is equivalent to the Python expression `self()`.
+    pub fn upgrade(&self) -> Option<&PyAny> {
+        upgrade(self)
+    }
+
+    /// Returns the referenced object downcast to `T`, or `None` if it has already been
+    /// garbage collected.
+    ///
+    /// Returns a `TypeError` if the referenced object is not of type `T`.
+    pub fn upgrade_as<'p, T>(&'p self) -> PyResult<Option<&'p T>>
+    where
+        T: PyTryFrom<'p>,
+    {
+        upgrade_as(self)
+    }
+
+    /// Immutably borrows the referenced `#[pyclass]` instance, or returns `None` if it has
+    /// already been garbage collected.
+    ///
+    /// Returns a `TypeError` if the referenced object is not an instance of `T`, and a
+    /// `PyBorrowError` if it is currently mutably borrowed.
+    pub fn upgrade_borrow<T: PyClass>(&self) -> PyResult<Option<PyRef<T>>> {
+        upgrade_borrow(self)
+    }
+}
+
+/// Represents a Python `weakref.ProxyType` or `weakref.CallableProxyType`.
+///
+/// A proxy forwards attribute access and calls to its referent for as long as the referent is
+/// alive. Using the proxy after the referent has been collected raises a `ReferenceError`.
+#[repr(transparent)]
+pub struct PyWeakrefProxy(PyAny);
+
+pyobject_native_var_type!(
+    PyWeakrefProxy,
+    ffi::_PyWeakref_ProxyType,
+    ffi::PyWeakref_CheckProxy
+);
+
+impl PyWeakrefProxy {
+    /// Creates a new weak reference proxy to `object`.
+    ///
+    /// If `object` is callable, the proxy will be a `weakref.CallableProxyType`. The meaning of
+    /// `callback` is the same as for [PyWeakref::new].
+    ///
+    /// This is equivalent to the Python expression `weakref.proxy(object, callback)`.
+    pub fn new<'p>(object: &'p PyAny, callback: Option<&PyAny>) -> PyResult<&'p PyWeakrefProxy> {
+        unsafe {
+            object.py().from_owned_ptr_or_err(ffi::PyWeakref_NewProxy(
+                object.as_ptr(),
+                callback.map_or(std::ptr::null_mut(), |c| c.as_ptr()),
+            ))
+        }
+    }
+
+    /// Returns the referenced object, or `None` if it has already been garbage collected.
+    pub fn upgrade(&self) -> Option<&PyAny> {
+        upgrade(self)
+    }
+
+    /// Returns the referenced object downcast to `T`, or `None` if it has already been
+    /// garbage collected.
+    ///
+    /// Returns a `TypeError` if the referenced object is not of type `T`.
+    pub fn upgrade_as<'p, T>(&'p self) -> PyResult<Option<&'p T>>
+    where
+        T: PyTryFrom<'p>,
+    {
+        upgrade_as(self)
+    }
+
+    /// Immutably borrows the referenced `#[pyclass]` instance, or returns `None` if it has
+    /// already been garbage collected.
+    ///
+    /// Returns a `TypeError` if the referenced object is not an instance of `T`, and a
+    /// `PyBorrowError` if it is currently mutably borrowed.
+    pub fn upgrade_borrow<T: PyClass>(&self) -> PyResult<Option<PyRef<T>>> {
+        upgrade_borrow(self)
+    }
+}
+
+fn upgrade<R: PyNativeType + AsPyPointer>(reference: &R) -> Option<&PyAny> {
+    let py = reference.py();
+    unsafe {
+        // PyWeakref_GetObject returns a borrowed reference, or Py_None if the referent is dead
+        let ptr = ffi::PyWeakref_GetObject(reference.as_ptr());
+        if ptr.is_null() {
+            // Only possible if `reference` is not a weak reference, which the type rules out.
+            err::panic_after_error(py)
+        } else if ptr == ffi::Py_None() {
+            None
+        } else {
+            Some(py.from_borrowed_ptr(ptr))
+        }
+    }
+}
+
+fn upgrade_as<'p, R, T>(reference: &'p R) -> PyResult<Option<&'p T>>
+where
+    R: PyNativeType + AsPyPointer,
+    T: PyTryFrom<'p>,
+{
+    match upgrade(reference) {
+        Some(object) => Ok(Some(T::try_from(object)?)),
+        None => Ok(None),
+    }
+}
+
+fn upgrade_borrow<R, T>(reference: &R) -> PyResult<Option<PyRef<T>>>
+where
+    R: PyNativeType + AsPyPointer,
+    T: PyClass,
+{
+    match upgrade_as::<R, PyCell<T>>(reference)? {
+        Some(cell) => Ok(Some(cell.try_borrow()?)),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::exceptions::{PyReferenceError, PyTypeError};
+    use crate::types::{PyList, PySet, PyWeakref, PyWeakrefProxy};
+    use crate::{py_run, AsPyPointer, Py, PyTryFrom, Python, ToPyObject};
+
+    fn new_set(py: Python) -> Py<PySet> {
+        // create the set in a fresh pool, so that dropping the returned `Py` frees it
+        let pool = unsafe { py.new_pool() };
+        PySet::empty(pool.python()).unwrap().into()
+    }
+
+    #[test]
+    fn test_weakref_upgrade() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = new_set(py);
+        let weak: Py<PyWeakref> = PyWeakref::new(set.as_ref(py), None).unwrap().into();
+
+        {
+            // upgraded references are owned by this pool, so must not outlive it
+            let pool = unsafe { py.new_pool() };
+            let py = pool.python();
+            let object = weak.as_ref(py).upgrade().unwrap();
+            assert_eq!(object.as_ptr(), set.as_ptr());
+            let typed = weak.as_ref(py).upgrade_as::<PySet>().unwrap().unwrap();
+            assert_eq!(typed.as_ptr(), set.as_ptr());
+        }
+
+        drop(set);
+        assert!(weak.as_ref(py).upgrade().is_none());
+        assert!(weak.as_ref(py).upgrade_as::<PySet>().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_weakref_upgrade_wrong_type() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = PySet::empty(py).unwrap();
+        let weak = PyWeakref::new(set, None).unwrap();
+
+        let err = weak.upgrade_as::<PyList>().unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+
+    #[test]
+    fn test_weakref_not_supported() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let obj = 1.to_object(py);
+        let err = PyWeakref::new(obj.as_ref(py), None).unwrap_err();
+        assert!(err.is_instance::<PyTypeError>(py));
+    }
+
+    #[test]
+    fn test_weakref_callback() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let called = PyList::empty(py);
+        let callback = called.getattr("append").unwrap();
+
+        let set = new_set(py);
+        let weak: Py<PyWeakref> = PyWeakref::new(set.as_ref(py), Some(callback))
+            .unwrap()
+            .into();
+        drop(set);
+
+        py_run!(py, called weak, "assert called == [weak]");
+    }
+
+    #[test]
+    fn test_weakref_proxy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let set = new_set(py);
+        let proxy: Py<PyWeakrefProxy> = PyWeakrefProxy::new(set.as_ref(py), None).unwrap().into();
+
+        assert!(<PyWeakref as PyTryFrom>::try_from(proxy.as_ref(py)).is_err());
+        assert_eq!(proxy.as_ref(py).len().unwrap(), 0);
+        {
+            let pool = unsafe { py.new_pool() };
+            let py = pool.python();
+            assert!(proxy.as_ref(py).upgrade_as::<PySet>().unwrap().is_some());
+        }
+
+        drop(set);
+        assert!(proxy.as_ref(py).upgrade().is_none());
+        let err = proxy.as_ref(py).len().unwrap_err();
+        assert!(err.is_instance::<PyReferenceError>(py));
+    }
+
+    #[test]
+    fn test_weakref_callable_proxy() {
+        let gil = Python::acquire_gil();
+        let py = gil.python();
+        let func = py.eval("lambda: 42", None, None).unwrap();
+        let proxy = PyWeakrefProxy::new(func, None).unwrap();
+        assert_eq!(proxy.call0().unwrap().extract::<i32>().unwrap(), 42);
+        assert_eq!(proxy.upgrade().unwrap().as_ptr(), func.as_ptr());
+    }
+}
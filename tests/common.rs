@@ -35,3 +35,43 @@ macro_rules! py_expect_exception {
         err
     }};
 }
+
+/// Runs `$code` with `$globals` as its globals, printing the Python error if it fails.
+#[macro_export]
+macro_rules! py_run_with_globals {
+    ($py:expr, $globals:expr, $code:expr $(,)?) => {
+        $py.run($code, Some($globals), None)
+            .map_err(|e| e.print($py))
+            .unwrap()
+    };
+}
+
+/// Creates the globals of an asyncio test: the given `(name, object)` pairs, the builtins, and a
+/// `run` function which runs a coroutine on a new event loop.
+#[macro_export]
+macro_rules! asyncio_globals {
+    ($py:expr, [$(($name:expr, $value:expr)),* $(,)?]) => {{
+        use pyo3::types::IntoPyDict;
+        use pyo3::ToPyObject;
+        let globals = [
+            $(($name, $value.to_object($py)),)*
+            ("__builtins__", $py.import("builtins").unwrap().to_object($py)),
+        ]
+        .into_py_dict($py);
+        py_run_with_globals!(
+            $py,
+            globals,
+            r#"
+import asyncio
+
+def run(coroutine):
+    loop = asyncio.new_event_loop()
+    try:
+        return loop.run_until_complete(coroutine)
+    finally:
+        loop.close()
+"#
+        );
+        globals
+    }};
+}
@@ -0,0 +1,274 @@
+use pyo3::asyncio::{self, Awaitable};
+use pyo3::prelude::*;
+use pyo3::types::{PyAny, PyCFunction, PyDict};
+use pyo3::{wrap_pyfunction, PyNativeType};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+mod common;
+
+/// The globals of the tests: the functions of this file, and a `run` function which runs a
+/// coroutine on a new event loop.
+fn globals(py: Python) -> &PyDict {
+    asyncio_globals!(
+        py,
+        [
+            ("double", wrap_pyfunction!(double)(py).unwrap()),
+            ("fail", wrap_pyfunction!(fail)(py).unwrap()),
+            ("panic", wrap_pyfunction!(panic)(py).unwrap()),
+            ("rust_await", wrap_pyfunction!(rust_await)(py).unwrap()),
+        ]
+    )
+}
+
+fn run_async(py: Python, code: &str) {
+    py_run_with_globals!(py, globals(py), code);
+}
+
+#[pyfunction]
+fn double(x: i32) -> Awaitable {
+    Awaitable::new(async move { Ok(x * 2) })
+}
+
+#[pyfunction]
+fn fail() -> Awaitable {
+    Awaitable::new(async { Err::<(), _>(pyo3::exceptions::PyValueError::new_err("failed")) })
+}
+
+#[pyfunction]
+fn panic() -> Awaitable {
+    Awaitable::new(async { panic!("panicked in a future") as PyResult<()> })
+}
+
+/// Awaits `awaitable` in Rust.
+#[pyfunction]
+fn rust_await(awaitable: &PyAny) -> PyResult<Awaitable> {
+    let future = asyncio::into_future(awaitable)?;
+    Ok(Awaitable::new(future))
+}
+
+#[test]
+fn test_await_rust_future() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def main():
+    assert await double(21) == 42
+    assert await asyncio.gather(double(1), double(2)) == [2, 4]
+    try:
+        await fail()
+    except ValueError as e:
+        assert str(e) == "failed"
+    else:
+        assert False
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_panic_in_future() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def main():
+    try:
+        await panic()
+    except BaseException as e:
+        assert type(e).__name__ == "PanicException"
+        assert str(e) == "panicked in a future"
+    else:
+        assert False
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_await_python_coroutine() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def value(x):
+    await asyncio.sleep(0)
+    return x
+
+async def raises():
+    await asyncio.sleep(0)
+    raise KeyError("key")
+
+async def main():
+    assert await rust_await(value(5)) == 5
+    assert await rust_await(double(4)) == 8
+    try:
+        await rust_await(raises())
+    except KeyError as e:
+        assert e.args == ("key",)
+    else:
+        assert False
+    try:
+        await rust_await(1)
+    except TypeError:
+        pass
+    else:
+        assert False
+run(main())
+"#,
+        );
+    });
+}
+
+/// A future which never completes, and records when it is dropped.
+struct Pending(Arc<AtomicBool>);
+
+impl Future for Pending {
+    type Output = PyResult<()>;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<Self::Output> {
+        Poll::Pending
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn test_cancel_python_future() {
+    Python::with_gil(|py| {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let pending = {
+            let dropped = dropped.clone();
+            PyCFunction::new_closure(py, move |args, _| {
+                Awaitable::new(Pending(dropped.clone()))
+                    .into_py_future(args.py())
+                    .map(PyObject::from)
+            })
+            .unwrap()
+        };
+        let globals = globals(py);
+        globals.set_item("pending", pending).unwrap();
+        py_run_with_globals!(
+            py,
+            globals,
+            r#"
+async def main():
+    future = pending()
+    await asyncio.sleep(0)
+    future.cancel()
+    for _ in range(10):
+        await asyncio.sleep(0)
+
+run(main())
+"#,
+        );
+        assert!(dropped.load(Ordering::SeqCst));
+    });
+}
+
+#[test]
+fn test_cancellation_reaches_python_task() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+cancelled = False
+
+async def forever():
+    global cancelled
+    try:
+        await asyncio.sleep(3600)
+    except asyncio.CancelledError:
+        cancelled = True
+        raise
+
+async def main():
+    future = asyncio.ensure_future(rust_await(forever()))
+    await asyncio.sleep(0.01)
+    future.cancel()
+    try:
+        await future
+    except asyncio.CancelledError:
+        pass
+    for _ in range(10):
+        await asyncio.sleep(0)
+    assert cancelled
+
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_no_running_loop() {
+    Python::with_gil(|py| {
+        let err = Awaitable::new(async { Ok(1) })
+            .into_py_future(py)
+            .unwrap_err();
+        assert!(err.is_instance::<pyo3::exceptions::PyRuntimeError>(py));
+    });
+}
+
+#[cfg(feature = "tokio")]
+mod tokio {
+    use super::*;
+
+    fn runtime() -> &'static ::tokio::runtime::Runtime {
+        static RUNTIME: pyo3::once_cell::GILOnceCell<::tokio::runtime::Runtime> =
+            pyo3::once_cell::GILOnceCell::new();
+        let py = unsafe { Python::assume_gil_acquired() };
+        RUNTIME.get_or_init(py, || {
+            ::tokio::runtime::Builder::new_multi_thread()
+                .worker_threads(1)
+                .build()
+                .unwrap()
+        })
+    }
+
+    /// Awaits `awaitable` on a Tokio thread, and returns its result together with whether it
+    /// was awaited on another thread than the caller.
+    #[pyfunction]
+    fn tokio_await(py: Python, awaitable: &PyAny) -> PyResult<PyObject> {
+        let future = asyncio::into_future(awaitable)?;
+        let caller = std::thread::current().id();
+        asyncio::tokio::future_into_py(py, runtime().handle(), async move {
+            let value = future.await?;
+            Ok((value, std::thread::current().id() != caller))
+        })
+        .map(PyObject::from)
+    }
+
+    #[test]
+    fn test_tokio() {
+        Python::with_gil(|py| {
+            let globals = globals(py);
+            let tokio_await = wrap_pyfunction!(tokio_await)(py).unwrap();
+            globals.set_item("tokio_await", tokio_await).unwrap();
+            py_run_with_globals!(
+                py,
+                globals,
+                r#"
+async def value(x):
+    await asyncio.sleep(0)
+    return x
+
+async def main():
+    assert await tokio_await(value(1)) == (1, True)
+
+run(main())
+"#,
+            );
+        });
+    }
+}
@@ -0,0 +1,97 @@
+use pyo3::prelude::*;
+use pyo3::py_run;
+
+mod common;
+
+#[pyclass(eq)]
+#[derive(PartialEq)]
+struct Point {
+    #[pyo3(get)]
+    x: i32,
+    #[pyo3(get)]
+    y: i32,
+}
+
+#[test]
+fn test_eq() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let a = Py::new(py, Point { x: 1, y: 2 }).unwrap();
+    let b = Py::new(py, Point { x: 1, y: 2 }).unwrap();
+    let c = Py::new(py, Point { x: 2, y: 1 }).unwrap();
+    py_run!(py, a b c, "assert a == b and not a != b");
+    py_run!(py, a b c, "assert a != c and not a == c");
+    py_assert!(py, a, "a == a");
+    // Other types are compared by identity
+    py_assert!(py, a, "a != (1, 2)");
+    py_expect_exception!(py, a, "a < a", PyTypeError);
+    // Defining `__eq__` without `__hash__` makes the class unhashable
+    py_expect_exception!(py, a, "hash(a)", PyTypeError);
+}
+
+#[pyclass(eq, ord)]
+#[derive(PartialEq, PartialOrd)]
+struct Version {
+    major: u32,
+    minor: f64,
+}
+
+#[test]
+fn test_ord() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let new = |major, minor| Py::new(py, Version { major, minor }).unwrap();
+    let (a, b, nan) = (new(1, 2.0), new(1, 3.0), new(1, f64::NAN));
+    py_run!(py, a b, "assert a < b and a <= b and b > a and b >= a");
+    py_run!(py, a b, "assert not a > b and not a >= b and a != b");
+    py_assert!(py, a, "a <= a and a >= a and a == a");
+    // `PartialOrd` may not order all values
+    py_run!(py, a nan, "assert not a < nan and not a >= nan and a != nan");
+    py_assert!(py, nan, "nan != nan");
+    py_expect_exception!(py, a, "a < 1", PyTypeError);
+}
+
+#[pyclass(eq, hash, frozen)]
+#[derive(PartialEq, Eq, Hash)]
+struct Key {
+    #[pyo3(get)]
+    name: String,
+}
+
+#[test]
+fn test_hash() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let a = Py::new(py, Key { name: "a".into() }).unwrap();
+    let a2 = Py::new(py, Key { name: "a".into() }).unwrap();
+    let b = Py::new(py, Key { name: "b".into() }).unwrap();
+    py_run!(py, a a2, "assert hash(a) == hash(a2)");
+    py_run!(py, a a2 b, "assert len({a, a2, b}) == 2");
+    py_run!(py, a a2, "assert {a: 1}[a2] == 1");
+}
+
+#[pyclass(eq, ord, hash, frozen)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Hash)]
+enum Level {
+    Low,
+    High,
+}
+
+#[test]
+fn test_enum_eq_ord_hash() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let level = py.get_type::<Level>();
+    py_run!(
+        py,
+        level,
+        r#"
+        assert level.Low < level.High
+        assert level.Low == level.Low
+        assert level.Low != level.High
+        assert len({level.Low, level.Low, level.High}) == 2
+        assert repr(level.High) == "Level.High"
+        assert int(level.High) == 1
+        "#
+    );
+}
@@ -0,0 +1,118 @@
+use pyo3::prelude::*;
+use std::fmt;
+
+mod common;
+
+#[pyclass(repr, str)]
+#[derive(Debug)]
+struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl fmt::Display for Point {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
+#[test]
+fn test_repr_and_str_from_traits() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let point = Py::new(py, Point { x: 1, y: -2 }).unwrap();
+    py_assert!(py, point, "repr(point) == 'Point { x: 1, y: -2 }'");
+    py_assert!(py, point, "str(point) == '(1, -2)'");
+    py_assert!(py, point, "f'{point}' == '(1, -2)'");
+}
+
+#[pyclass(repr)]
+#[derive(Debug)]
+struct OnlyRepr {
+    #[pyo3(get)]
+    value: Vec<u8>,
+}
+
+#[test]
+fn test_str_defaults_to_repr() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Py::new(py, OnlyRepr { value: vec![1, 2] }).unwrap();
+    py_assert!(
+        py,
+        obj,
+        "str(obj) == repr(obj) == 'OnlyRepr { value: [1, 2] }'"
+    );
+}
+
+#[pyclass(
+    get_all,
+    repr = "Person(name={name}, age={years})",
+    str = "{name} ({years})"
+)]
+struct Person {
+    name: String,
+    #[pyo3(name = "years")]
+    age: u32,
+}
+
+#[test]
+fn test_format_strings() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let person = Py::new(
+        py,
+        Person {
+            name: "Ada".to_string(),
+            age: 36,
+        },
+    )
+    .unwrap();
+    py_assert!(py, person, "repr(person) == \"Person(name='Ada', age=36)\"");
+    py_assert!(py, person, "str(person) == 'Ada (36)'");
+}
+
+#[pyclass(repr = "{{{value}}} / {value_plus_one}")]
+struct Braces {
+    #[pyo3(get)]
+    value: i32,
+    #[pyo3(get_with = "plus_one", name = "value_plus_one")]
+    other: i32,
+}
+
+fn plus_one(value: &i32) -> i32 {
+    value + 1
+}
+
+#[test]
+fn test_format_string_escapes_and_getters() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Py::new(py, Braces { value: 1, other: 2 }).unwrap();
+    py_assert!(py, obj, "repr(obj) == '{1} / 3'");
+}
+
+#[pyclass(repr, str)]
+#[derive(Debug, Clone)]
+enum Shape {
+    Circle,
+    Square,
+}
+
+impl fmt::Display for Shape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Shape::Circle => write!(f, "circle"),
+            Shape::Square => write!(f, "square"),
+        }
+    }
+}
+
+#[test]
+fn test_enum_repr_and_str() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<Shape>();
+    py_assert!(py, cls, "repr(cls.Circle) == 'Circle'");
+    py_assert!(py, cls, "str(cls.Square) == 'square'");
+}
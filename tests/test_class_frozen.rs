@@ -0,0 +1,143 @@
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::types::PyDict;
+use std::sync::Arc;
+use std::thread;
+
+mod common;
+
+#[pyclass(frozen)]
+struct Point {
+    #[pyo3(get)]
+    x: i32,
+    #[pyo3(get)]
+    y: i32,
+}
+
+#[pymethods]
+impl Point {
+    #[new]
+    fn new(x: i32, y: i32) -> Self {
+        Point { x, y }
+    }
+
+    fn norm2(&self) -> i32 {
+        self.x * self.x + self.y * self.y
+    }
+
+    #[getter]
+    fn coords(&self) -> (i32, i32) {
+        (self.x, self.y)
+    }
+}
+
+#[test]
+fn test_frozen_get() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let point = Py::new(py, Point { x: 3, y: 4 }).unwrap();
+    assert_eq!(point.get().x, 3);
+
+    let cell = point.as_ref(py);
+    assert_eq!(cell.get().y, 4);
+    // Shared borrows are still available, and never conflict
+    let borrowed = cell.borrow();
+    assert_eq!(cell.try_borrow().unwrap().x, borrowed.x);
+    assert!(unsafe { cell.try_borrow_unguarded() }.is_ok());
+}
+
+#[test]
+fn test_frozen_from_python() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<Point>();
+    py_run!(
+        py,
+        cls,
+        "p = cls(3, 4); assert p.norm2() == 25 and p.coords == (3, 4)"
+    );
+    py_expect_exception!(py, cls, "cls(3, 4).x = 5", PyAttributeError);
+
+    let point: Py<Point> = cls.call1((1, 2)).unwrap().extract().unwrap();
+    assert_eq!(point.get().norm2(), 5);
+    let point: PyRef<Point> = cls.call1((1, 2)).unwrap().extract().unwrap();
+    assert_eq!(point.y, 2);
+}
+
+#[test]
+fn test_frozen_get_from_other_thread() {
+    let point = {
+        let gil = Python::acquire_gil();
+        Arc::new(Py::new(gil.python(), Point { x: 1, y: 2 }).unwrap())
+    };
+    let other = Arc::clone(&point);
+    let handle = thread::spawn(move || other.get().norm2());
+    assert_eq!(handle.join().unwrap(), 5);
+    assert_eq!(point.get().x, 1);
+}
+
+#[pyclass(frozen, subclass)]
+struct Base {
+    #[pyo3(get)]
+    base: i32,
+}
+
+#[pyclass(frozen, extends=Base)]
+struct Child {
+    #[pyo3(get)]
+    child: i32,
+}
+
+#[pymethods]
+impl Child {
+    #[new]
+    fn new(base: i32, child: i32) -> (Self, Base) {
+        (Child { child }, Base { base })
+    }
+
+    fn sum(self_: PyRef<Self>) -> i32 {
+        self_.as_ref().base + self_.child
+    }
+}
+
+#[test]
+fn test_frozen_inheritance() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<Child>();
+    py_run!(
+        py,
+        cls,
+        "c = cls(1, 2); assert c.base == 1 and c.child == 2 and c.sum() == 3"
+    );
+
+    let child = Py::new(py, (Child { child: 2 }, Base { base: 1 })).unwrap();
+    assert_eq!(child.get().child, 2);
+    assert_eq!(child.as_ref(py).borrow().as_ref().base, 1);
+}
+
+#[pyclass(frozen, extends=PyDict)]
+struct FrozenDict {
+    #[pyo3(get)]
+    tag: &'static str,
+}
+
+#[pymethods]
+impl FrozenDict {
+    #[new]
+    fn new() -> Self {
+        FrozenDict { tag: "frozen" }
+    }
+}
+
+#[test]
+fn test_frozen_extends_native_type() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cls = py.get_type::<FrozenDict>();
+    py_run!(
+        py,
+        cls,
+        "d = cls(); d['a'] = 1; assert d == {'a': 1} and d.tag == 'frozen'"
+    );
+}
@@ -0,0 +1,221 @@
+mod common;
+
+#[cfg(feature = "serde")]
+mod test_class_pickle {
+    use crate::py_expect_exception;
+    use pyo3::prelude::*;
+    use pyo3::py_run;
+    use pyo3::types::{IntoPyDict, PyModule};
+    use serde::{Deserialize, Serialize};
+
+    #[pyclass(pickle, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    struct Point {
+        #[pyo3(get)]
+        x: i32,
+        #[pyo3(get)]
+        y: i32,
+        label: Option<String>,
+    }
+
+    #[pyclass(pickle, subclass, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    struct Base {
+        #[pyo3(get)]
+        name: String,
+    }
+
+    #[pymethods]
+    impl Base {
+        #[new]
+        fn new(name: String) -> Self {
+            Base { name }
+        }
+    }
+
+    #[pyclass(pickle, extends = Base, subclass, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    struct Sub {
+        #[pyo3(get)]
+        values: Vec<u64>,
+    }
+
+    #[pyclass(pickle, extends = Sub, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    struct SubSub {
+        #[pyo3(get)]
+        flag: bool,
+    }
+
+    #[pyclass(pickle, dict, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    struct WithDict {
+        #[pyo3(get)]
+        value: f64,
+    }
+
+    #[pyclass(pickle, module = "test_class_pickle")]
+    #[derive(Serialize, Deserialize)]
+    enum Color {
+        Red,
+        Green,
+    }
+
+    fn module(py: Python) -> &PyModule {
+        let module = PyModule::new(py, "test_class_pickle").unwrap();
+        module.add_class::<Point>().unwrap();
+        module.add_class::<Base>().unwrap();
+        module.add_class::<Sub>().unwrap();
+        module.add_class::<SubSub>().unwrap();
+        module.add_class::<WithDict>().unwrap();
+        module.add_class::<Color>().unwrap();
+        py.import("sys")
+            .unwrap()
+            .getattr("modules")
+            .unwrap()
+            .set_item("test_class_pickle", module)
+            .unwrap();
+        module
+    }
+
+    #[test]
+    fn test_pickle_roundtrip() {
+        Python::with_gil(|py| {
+            let _ = module(py);
+            let point = PyCell::new(
+                py,
+                Point {
+                    x: -1,
+                    y: 2,
+                    label: Some("origin".into()),
+                },
+            )
+            .unwrap();
+            py_run!(
+                py,
+                point,
+                r#"
+import pickle
+copy = pickle.loads(pickle.dumps(point))
+assert type(copy) is type(point)
+assert copy is not point
+assert (copy.x, copy.y) == (-1, 2)
+"#
+            );
+            let copy: &PyCell<Point> = py
+                .eval(
+                    "__import__('pickle').loads(__import__('pickle').dumps(point))",
+                    None,
+                    Some([("point", point)].into_py_dict(py)),
+                )
+                .unwrap()
+                .downcast()
+                .unwrap();
+            assert_eq!(copy.borrow().label.as_deref(), Some("origin"));
+        });
+    }
+
+    #[test]
+    fn test_pickle_subclass_hierarchy() {
+        Python::with_gil(|py| {
+            let _ = module(py);
+            let init = PyClassInitializer::from(Base {
+                name: "base".into(),
+            })
+            .add_subclass(Sub {
+                values: vec![1, 2, u64::max_value()],
+            })
+            .add_subclass(SubSub { flag: true });
+            let obj = PyCell::new(py, init).unwrap();
+            py_run!(
+                py,
+                obj,
+                r#"
+import copy, pickle
+for clone in [pickle.loads(pickle.dumps(obj)), copy.deepcopy(obj)]:
+    assert type(clone).__name__ == "SubSub"
+    assert clone.name == "base"
+    assert clone.values == [1, 2, 2 ** 64 - 1]
+    assert clone.flag is True
+"#
+            );
+        });
+    }
+
+    #[test]
+    fn test_pickle_dict_and_enum() {
+        Python::with_gil(|py| {
+            let _ = module(py);
+            let obj = PyCell::new(py, WithDict { value: 0.5 }).unwrap();
+            let color = Py::new(py, Color::Green).unwrap();
+            py_run!(
+                py,
+                obj color,
+                r#"
+import pickle
+obj.extra = [1, "two"]
+clone = pickle.loads(pickle.dumps(obj))
+assert clone.value == 0.5
+assert clone.extra == [1, "two"]
+assert pickle.loads(pickle.dumps(color)) == color
+"#
+            );
+        });
+    }
+
+    #[test]
+    fn test_pickle_errors() {
+        Python::with_gil(|py| {
+            let module = module(py);
+            py_expect_exception!(
+                py,
+                module,
+                "module.Point.__pyo3_unpickle__(b'')",
+                PyValueError
+            );
+            // Trailing data after the state
+            py_expect_exception!(
+                py,
+                module,
+                "module.Base.__pyo3_unpickle__(module.Base('x').__reduce__()[1][0] + b'\\x00')",
+                PyValueError
+            );
+            py_expect_exception!(
+                py,
+                module,
+                "module.Point.__pyo3_unpickle__('x')",
+                PyTypeError
+            );
+            // Subclasses defined in Python may have state unknown to the #[pyclass]
+            py_expect_exception!(
+                py,
+                module,
+                r#"
+import pickle
+class PySub(module.Base):
+    pass
+pickle.dumps(PySub("base"))
+"#,
+                PyTypeError
+            );
+            py_run!(
+                py,
+                module,
+                r#"
+state = module.Base("x").__reduce__()[1][0]
+# The header holds the class name and the format version
+for cls, state, message in [
+    (module.Color, state, "the state of 'test_class_pickle.Base' cannot be loaded as 'test_class_pickle.Color'"),
+    (module.Base, b"\x02" + state[1:], "unsupported state format version 2 (expected 1)"),
+]:
+    try:
+        cls.__pyo3_unpickle__(state)
+    except ValueError as e:
+        assert str(e) == "failed to unpickle: " + message, str(e)
+    else:
+        assert False
+"#
+            );
+        });
+    }
+}
@@ -6,10 +6,20 @@ fn test_compile_errors() {
     t.compile_fail("tests/ui/invalid_need_module_arg_position.rs");
     t.compile_fail("tests/ui/invalid_property_args.rs");
     t.compile_fail("tests/ui/invalid_pyclass_args.rs");
+    t.compile_fail("tests/ui/invalid_pyclass_compare.rs");
+    t.compile_fail("tests/ui/invalid_frozen_pyclass.rs");
+    t.compile_fail("tests/ui/invalid_pyclass_fields.rs");
+    t.compile_fail("tests/ui/invalid_pyclass_format.rs");
+    t.compile_fail("tests/ui/invalid_pyclass_enum.rs");
     t.compile_fail("tests/ui/invalid_pyfunctions.rs");
+    t.compile_fail("tests/ui/invalid_pyfunction_signatures.rs");
     t.compile_fail("tests/ui/invalid_pymethods.rs");
     t.compile_fail("tests/ui/invalid_pymethod_names.rs");
+    t.compile_fail("tests/ui/invalid_proto_pymethods.rs");
     t.compile_fail("tests/ui/invalid_argument_attributes.rs");
+    t.compile_fail("tests/ui/invalid_async.rs");
+    t.compile_fail("tests/ui/invalid_pymodule_args.rs");
+    t.compile_fail("tests/ui/invalid_pymodule_mod.rs");
     t.compile_fail("tests/ui/reject_generics.rs");
 
     tests_rust_1_45(&t);
@@ -25,6 +35,8 @@ fn test_compile_errors() {
 
     #[rustversion::since(1.48)]
     fn tests_rust_1_48(t: &trybuild::TestCases) {
+        // The metadata generated with `inspect` repeats the error
+        #[cfg(not(feature = "inspect"))]
         t.compile_fail("tests/ui/invalid_result_conversion.rs");
         t.compile_fail("tests/ui/missing_clone.rs");
         t.compile_fail("tests/ui/wrong_aspyref_lifetimes.rs");
@@ -35,6 +47,7 @@ fn test_compile_errors() {
     #[rustversion::since(1.49)]
     fn tests_rust_1_49(t: &trybuild::TestCases) {
         t.compile_fail("tests/ui/invalid_frompy_derive.rs");
+        t.compile_fail("tests/ui/invalid_intopy_derive.rs");
         t.compile_fail("tests/ui/invalid_pymethod_receiver.rs");
         t.compile_fail("tests/ui/pyclass_send.rs");
 
@@ -0,0 +1,386 @@
+use pyo3::prelude::*;
+use pyo3::types::PyDict;
+#[cfg(feature = "inspect")]
+use pyo3::{inspect, inspect_module};
+use pyo3::{py_run, wrap_pyfunction};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+mod common;
+
+/// A future which completes after `duration`, and is woken from another thread.
+struct Sleep {
+    state: Arc<Mutex<(bool, Option<Waker>)>>,
+}
+
+fn sleep(duration: Duration) -> Sleep {
+    let state = Arc::new(Mutex::new((false, None::<Waker>)));
+    let timer = state.clone();
+    std::thread::spawn(move || {
+        std::thread::sleep(duration);
+        // Wake outside of the lock, as waking the coroutine takes the GIL while it is polled.
+        let waker = {
+            let mut state = timer.lock().unwrap();
+            state.0 = true;
+            state.1.take()
+        };
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    });
+    Sleep { state }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<()> {
+        let mut state = self.state.lock().unwrap();
+        if state.0 {
+            Poll::Ready(())
+        } else {
+            state.1 = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// A future which never completes, and records when it is dropped.
+struct Pending(Arc<AtomicBool>);
+
+impl Future for Pending {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<()> {
+        Poll::Pending
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[pyfunction]
+async fn double(x: i32) -> i32 {
+    x * 2
+}
+
+#[pyfunction]
+async fn sleep_then(millis: u64, value: PyObject) -> PyObject {
+    sleep(Duration::from_millis(millis)).await;
+    value
+}
+
+#[pyfunction]
+async fn fail(message: String) -> PyResult<()> {
+    Err(pyo3::exceptions::PyValueError::new_err(message))
+}
+
+#[pyfunction]
+async fn panic() {
+    panic!("panicked in a coroutine")
+}
+
+#[pyfunction]
+async fn collect(n: usize) -> PyResult<Vec<usize>> {
+    let mut values = Vec::new();
+    for i in 0..n {
+        sleep(Duration::from_millis(1)).await;
+        values.push(i);
+    }
+    Ok(values)
+}
+
+/// Awaits a Python awaitable in the coroutine.
+#[pyfunction]
+async fn rust_await(awaitable: PyObject) -> PyResult<PyObject> {
+    let future = Python::with_gil(|py| pyo3::asyncio::into_future(awaitable.as_ref(py)))?;
+    future.await
+}
+
+#[pyclass(frozen)]
+struct Frozen {
+    value: i32,
+}
+
+#[pymethods]
+impl Frozen {
+    #[new]
+    fn new(value: i32) -> Self {
+        Frozen { value }
+    }
+
+    async fn get(&self) -> i32 {
+        sleep(Duration::from_millis(1)).await;
+        self.value
+    }
+
+    async fn add(slf: Py<Self>, other: i32) -> i32 {
+        sleep(Duration::from_millis(1)).await;
+        slf.get().value + other
+    }
+
+    #[staticmethod]
+    async fn answer() -> i32 {
+        42
+    }
+}
+
+#[pyclass]
+struct Counter {
+    count: usize,
+}
+
+#[pymethods]
+impl Counter {
+    #[new]
+    fn new() -> Self {
+        Counter { count: 0 }
+    }
+
+    async fn increment(slf: Py<Self>) -> usize {
+        sleep(Duration::from_millis(1)).await;
+        Python::with_gil(|py| {
+            let mut counter = slf.borrow_mut(py);
+            counter.count += 1;
+            counter.count
+        })
+    }
+}
+
+/// The globals of the tests: the functions and classes of this file, and a `run` function which
+/// runs a coroutine on a new event loop.
+fn globals(py: Python) -> &PyDict {
+    asyncio_globals!(
+        py,
+        [
+            ("double", wrap_pyfunction!(double)(py).unwrap()),
+            ("sleep_then", wrap_pyfunction!(sleep_then)(py).unwrap()),
+            ("fail", wrap_pyfunction!(fail)(py).unwrap()),
+            ("panic", wrap_pyfunction!(panic)(py).unwrap()),
+            ("collect", wrap_pyfunction!(collect)(py).unwrap()),
+            ("rust_await", wrap_pyfunction!(rust_await)(py).unwrap()),
+            ("Frozen", py.get_type::<Frozen>()),
+            ("Counter", py.get_type::<Counter>()),
+        ]
+    )
+}
+
+fn run_async(py: Python, code: &str) {
+    py_run_with_globals!(py, globals(py), code);
+}
+
+#[test]
+fn test_async_pyfunction() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def main():
+    assert await double(21) == 42
+    assert await sleep_then(10, "done") == "done"
+    assert await collect(3) == [0, 1, 2]
+    assert await asyncio.gather(sleep_then(5, 1), sleep_then(1, 2)) == [1, 2]
+    try:
+        await fail("failed")
+    except ValueError as e:
+        assert str(e) == "failed"
+    else:
+        assert False
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_coroutine_protocol() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+import collections.abc
+import inspect
+
+coroutine = double(21)
+assert isinstance(coroutine, collections.abc.Coroutine)
+assert inspect.isawaitable(coroutine)
+assert coroutine.__name__ == "double"
+assert coroutine.__qualname__ == "double"
+assert Frozen(1).get().__qualname__ == "Frozen.get"
+assert Frozen.answer().__qualname__ == "Frozen.answer"
+
+# Without an event loop, pending coroutines yield `None`.
+try:
+    coroutine.send(None)
+except StopIteration as e:
+    assert e.value == 42
+else:
+    assert False
+try:
+    coroutine.send(None)
+except RuntimeError as e:
+    assert str(e) == "cannot reuse already awaited coroutine"
+else:
+    assert False
+
+coroutine = sleep_then(1000, None)
+assert coroutine.send(None) is None
+try:
+    coroutine.throw(KeyError, "key")
+except KeyError as e:
+    assert e.args == ("key",)
+else:
+    assert False
+try:
+    next(coroutine)
+except RuntimeError:
+    pass
+else:
+    assert False
+
+coroutine = sleep_then(1000, None)
+coroutine.close()
+try:
+    coroutine.send(None)
+except RuntimeError:
+    pass
+else:
+    assert False
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_close_drops_future() {
+    Python::with_gil(|py| {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let coroutine = Py::new(
+            py,
+            pyo3::coroutine::Coroutine::new(None, None, Pending(dropped.clone())),
+        )
+        .unwrap();
+        py_run!(py, coroutine, "assert coroutine.send(None) is None");
+        assert!(!dropped.load(Ordering::SeqCst));
+        py_run!(py, coroutine, "coroutine.close()");
+        assert!(dropped.load(Ordering::SeqCst));
+        py_expect_exception!(py, coroutine, "coroutine.__name__", PyAttributeError);
+    });
+}
+
+#[test]
+fn test_cancel_task() {
+    Python::with_gil(|py| {
+        let dropped = Arc::new(AtomicBool::new(false));
+        let coroutine = Py::new(
+            py,
+            pyo3::coroutine::Coroutine::new(None, None, Pending(dropped.clone())),
+        )
+        .unwrap();
+        let globals = globals(py);
+        globals.set_item("coroutine", coroutine).unwrap();
+        py_run_with_globals!(
+            py,
+            globals,
+            r#"
+async def main():
+    task = asyncio.ensure_future(coroutine)
+    await asyncio.sleep(0)
+    task.cancel()
+    try:
+        await task
+    except asyncio.CancelledError:
+        pass
+    else:
+        assert False
+
+run(main())
+"#,
+        );
+        assert!(dropped.load(Ordering::SeqCst));
+    });
+}
+
+#[test]
+fn test_panic_in_coroutine() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def main():
+    try:
+        await panic()
+    except BaseException as e:
+        assert type(e).__name__ == "PanicException"
+        assert str(e) == "panicked in a coroutine"
+    else:
+        assert False
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_await_python_in_coroutine() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def value(x):
+    await asyncio.sleep(0.001)
+    return x
+
+async def main():
+    assert await rust_await(value(5)) == 5
+    assert await rust_await(double(4)) == 8
+run(main())
+"#,
+        );
+    });
+}
+
+#[test]
+fn test_async_methods() {
+    Python::with_gil(|py| {
+        run_async(
+            py,
+            r#"
+async def main():
+    frozen = Frozen(3)
+    assert await frozen.get() == 3
+    assert await frozen.add(4) == 7
+    assert await Frozen.answer() == 42
+
+    counter = Counter()
+    assert sorted(await asyncio.gather(counter.increment(), counter.increment())) == [1, 2]
+run(main())
+"#,
+        );
+    });
+}
+
+#[cfg(feature = "inspect")]
+#[pymodule]
+fn coroutines(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(double, m)?)?;
+    Ok(())
+}
+
+#[cfg(feature = "inspect")]
+#[test]
+fn test_async_stub() {
+    let module = inspect_module!(coroutines);
+    assert!(module.functions[0].is_async);
+    assert_eq!(
+        inspect::module_stub(&module),
+        "\nasync def double(x: int) -> int: ...\n"
+    );
+}
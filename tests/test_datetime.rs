@@ -197,3 +197,40 @@ fn test_pydatetime_out_of_bounds() {
         dt.unwrap_err();
     }
 }
+
+#[pyfunction]
+fn timeout_millis(timeout: std::time::Duration) -> u128 {
+    timeout.as_millis()
+}
+
+#[test]
+fn test_duration_argument() {
+    use pyo3::wrap_pyfunction;
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(timeout_millis)(py).unwrap();
+    pyo3::py_run!(
+        py,
+        f,
+        r#"
+        import datetime
+        assert f(datetime.timedelta(seconds=2, milliseconds=5)) == 2005
+        assert f(1.5) == 1500
+        assert f(3) == 3000
+        for value in [-1, datetime.timedelta(days=-1)]:
+            try:
+                f(value)
+            except ValueError:
+                pass
+            else:
+                assert False, "expected ValueError"
+        try:
+            f(1e30)
+        except OverflowError:
+            pass
+        else:
+            assert False, "expected OverflowError"
+        "#
+    );
+}
@@ -0,0 +1,157 @@
+#[cfg(feature = "inspect")]
+use pyo3::inspect_module;
+use pyo3::prelude::*;
+use pyo3::{py_run, wrap_pymodule};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod common;
+
+#[pyclass]
+struct External {}
+
+#[pyfunction]
+fn external_function() -> &'static str {
+    "external"
+}
+
+// Functions may share their names with modules
+#[pyfunction]
+fn helpers() -> &'static str {
+    helpers::NAME
+}
+
+mod helpers {
+    pub const NAME: &str = "helpers";
+}
+
+#[pymodule]
+fn legacy(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add("legacy", true)
+}
+
+/// A declarative module.
+#[pymodule]
+mod declarative_module {
+    use super::*;
+
+    #[pymodule_export]
+    use super::{external_function, helpers as renamed_helpers, External};
+
+    #[pyclass]
+    struct Point {
+        #[pyo3(get)]
+        x: i32,
+    }
+
+    #[pymethods]
+    impl Point {
+        #[new]
+        fn new(x: i32) -> Self {
+            Point { x }
+        }
+    }
+
+    #[pyfunction]
+    fn double(x: i32) -> i32 {
+        x * 2
+    }
+
+    /// A nested module.
+    #[pymodule]
+    mod nested {
+        use pyo3::prelude::*;
+
+        #[pyfunction]
+        fn triple(x: i32) -> i32 {
+            x * 3
+        }
+    }
+
+    #[pymodule(renamed)]
+    mod original_name {}
+
+    #[pymodule]
+    fn legacy_nested(_py: Python, m: &PyModule) -> PyResult<()> {
+        m.add("legacy", true)
+    }
+
+    #[pymodule_export]
+    use super::legacy;
+
+    #[pymodule_init]
+    fn init(_py: Python, m: &PyModule) -> PyResult<()> {
+        m.add("answer", 42)
+    }
+}
+
+#[test]
+fn test_declarative_module() {
+    Python::with_gil(|py| {
+        let m = wrap_pymodule!(declarative_module)(py);
+        py_run!(
+            py,
+            m,
+            r#"
+assert m.__name__ == "declarative_module"
+assert m.__doc__ == "A declarative module."
+assert m.double(2) == 4
+assert m.Point(1).x == 1
+assert m.Point.__module__ == "builtins"
+assert m.external_function() == "external"
+assert m.helpers() == "helpers"
+assert m.External.__name__ == "External"
+assert m.nested.__name__ == "nested"
+assert m.nested.__doc__ == "A nested module."
+assert m.nested.triple(3) == 9
+assert m.renamed.__name__ == "renamed"
+assert m.legacy_nested.legacy
+assert m.answer == 42
+"#
+        );
+    });
+}
+
+#[cfg(feature = "inspect")]
+#[test]
+fn test_declarative_module_info() {
+    let info = inspect_module!(declarative_module);
+    assert_eq!(info.name, "declarative_module");
+    assert_eq!(info.doc, "A declarative module.");
+    let functions: Vec<_> = info.functions.iter().map(|f| f.name).collect();
+    assert_eq!(functions, ["external_function", "helpers", "double"]);
+    let classes: Vec<_> = info.classes.iter().map(|c| c.name).collect();
+    assert_eq!(classes, ["External", "Point"]);
+    let submodules: Vec<_> = info.submodules.iter().map(|m| m.name).collect();
+    assert_eq!(submodules, ["nested", "renamed", "legacy_nested", "legacy"]);
+    assert_eq!(info.submodules[0].functions[0].name, "triple");
+}
+
+#[derive(Default)]
+struct Counter(AtomicUsize);
+
+#[pymodule(multi_phase, state = Counter)]
+mod declarative_multi_phase {
+    use super::*;
+
+    #[pyfunction(pass_module)]
+    fn count(module: &PyModule) -> PyResult<usize> {
+        Ok(module.state::<Counter>()?.0.fetch_add(1, Ordering::SeqCst) + 1)
+    }
+}
+
+#[test]
+fn test_declarative_multi_phase_module() {
+    Python::with_gil(|py| {
+        let first = wrap_pymodule!(declarative_multi_phase)(py);
+        let second = wrap_pymodule!(declarative_multi_phase)(py);
+        py_run!(
+            py,
+            first second,
+            r#"
+assert first.count() == 1
+assert first.count() == 2
+assert second.count() == 1
+"#
+        );
+    });
+}
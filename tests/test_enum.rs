@@ -0,0 +1,163 @@
+use pyo3::prelude::*;
+use pyo3::{py_run, wrap_pyfunction};
+
+mod common;
+
+#[pyclass]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum MyEnum {
+    Variant,
+    OtherVariant,
+}
+
+#[test]
+fn test_enum_class_attr() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let my_enum = py.get_type::<MyEnum>();
+    py_assert!(py, my_enum, "getattr(my_enum, 'Variant', None) is not None");
+    py_assert!(py, my_enum, "getattr(my_enum, 'foobar', None) is None");
+}
+
+#[pyfunction]
+fn return_enum() -> MyEnum {
+    MyEnum::Variant
+}
+
+#[test]
+fn test_return_enum() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(return_enum)(py).unwrap();
+    let mynum = py.get_type::<MyEnum>();
+
+    py_run!(py, f mynum, "assert f() == mynum.Variant")
+}
+
+#[pyfunction]
+fn enum_arg(e: MyEnum) -> MyEnum {
+    match e {
+        MyEnum::Variant => MyEnum::OtherVariant,
+        MyEnum::OtherVariant => MyEnum::Variant,
+    }
+}
+
+#[test]
+fn test_enum_arg() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(enum_arg)(py).unwrap();
+    let mynum = py.get_type::<MyEnum>();
+
+    py_run!(py, f mynum, "assert f(mynum.OtherVariant) == mynum.Variant");
+    py_run!(py, f mynum, "assert f(mynum.Variant) == mynum.OtherVariant");
+    py_expect_exception!(py, f, "f(0)", PyTypeError);
+}
+
+#[test]
+fn test_extract_enum() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let variant = py.get_type::<MyEnum>().getattr("OtherVariant").unwrap();
+    assert_eq!(variant.extract::<MyEnum>().unwrap(), MyEnum::OtherVariant);
+    assert_eq!(
+        MyEnum::Variant.into_py(py).extract::<MyEnum>(py).unwrap(),
+        MyEnum::Variant
+    );
+}
+
+#[test]
+fn test_default_repr_correct() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let var1 = Py::new(py, MyEnum::Variant).unwrap();
+    let var2 = Py::new(py, MyEnum::OtherVariant).unwrap();
+    py_assert!(py, var1, "repr(var1) == 'MyEnum.Variant'");
+    py_assert!(py, var2, "repr(var2) == 'MyEnum.OtherVariant'");
+}
+
+#[test]
+fn test_eq() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let my_enum = py.get_type::<MyEnum>();
+    let var1 = Py::new(py, MyEnum::Variant).unwrap();
+    py_run!(
+        py,
+        my_enum var1,
+        r#"
+        assert var1 == my_enum.Variant
+        assert not (var1 != my_enum.Variant)
+        assert var1 != my_enum.OtherVariant
+        assert var1 != 0
+        assert var1 != "Variant"
+        "#
+    );
+    py_expect_exception!(py, var1, "var1 < var1", PyTypeError);
+}
+
+#[test]
+fn test_hash() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let my_enum = py.get_type::<MyEnum>();
+    let var1 = Py::new(py, MyEnum::Variant).unwrap();
+    py_run!(
+        py,
+        my_enum var1,
+        r#"
+        assert hash(var1) == hash(my_enum.Variant)
+        assert hash(my_enum.Variant) != hash(my_enum.OtherVariant)
+        variants = {my_enum.Variant: 1, my_enum.OtherVariant: 2}
+        assert variants[var1] == 1
+        assert len({var1, my_enum.Variant, my_enum.OtherVariant}) == 2
+        "#
+    );
+}
+
+#[pyclass]
+enum CustomDiscriminant {
+    One = 1,
+    Two = 2,
+    Ten = 10,
+}
+
+#[test]
+fn test_custom_discriminant() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let cd = py.get_type::<CustomDiscriminant>();
+    py_run!(
+        py,
+        cd,
+        r#"
+        assert int(cd.One) == 1
+        assert int(cd.Two) == 2
+        assert int(cd.Ten) == 10
+        assert cd.One != cd.Two
+        "#
+    );
+}
+
+#[test]
+fn test_enum_no_constructor() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let my_enum = py.get_type::<MyEnum>();
+    py_expect_exception!(py, my_enum, "my_enum()", PyTypeError);
+}
+
+#[pyclass(name = "Renamed")]
+enum RenamedEnum {
+    #[allow(dead_code)]
+    Variant,
+}
+
+#[test]
+fn test_renamed_enum_repr() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let renamed = py.get_type::<RenamedEnum>();
+    py_assert!(py, renamed, "renamed.__name__ == 'Renamed'");
+    py_assert!(py, renamed, "repr(renamed.Variant) == 'Renamed.Variant'");
+}
@@ -296,7 +296,10 @@ fn test_err_rename() {
     assert!(f.is_err());
     assert_eq!(
         f.unwrap_err().to_string(),
-        "TypeError: 'dict' object cannot be converted to 'Union[str, uint, int]'"
+        "TypeError: 'dict' object cannot be converted to 'Union[str, uint, int]'\n\
+         - variant Bar::A (str): 'dict' object cannot be converted to 'PyString'\n\
+         - variant Bar::B (uint): 'dict' object cannot be interpreted as an integer\n\
+         - variant Bar::C (int): 'dict' object cannot be interpreted as an integer"
     );
 }
 
@@ -326,3 +329,151 @@ fn test_from_py_with() {
         assert_eq!(zap.some_object_length, 3usize);
     });
 }
+
+#[derive(Debug, FromPyObject)]
+pub struct WithDefaults {
+    #[pyo3(item)]
+    name: String,
+    #[pyo3(item, default)]
+    tags: Vec<String>,
+    #[pyo3(item("max-size"), default = "usize::max_value()")]
+    max_size: usize,
+    #[pyo3(attribute("real"), default = 1.5)]
+    ratio: f64,
+}
+
+#[test]
+fn test_field_defaults() {
+    Python::with_gil(|py| {
+        let obj = py
+            .eval(r#"{"name": "a", "tags": ["b"]}"#, None, None)
+            .unwrap();
+        let with_defaults = WithDefaults::extract(obj).unwrap();
+        assert_eq!(with_defaults.name, "a");
+        assert_eq!(with_defaults.tags, vec!["b".to_string()]);
+        assert_eq!(with_defaults.max_size, usize::max_value());
+        assert_eq!(with_defaults.ratio, 1.5);
+
+        let obj = py
+            .eval(r#"{"name": "a", "max-size": 3}"#, None, None)
+            .unwrap();
+        let with_defaults = WithDefaults::extract(obj).unwrap();
+        assert!(with_defaults.tags.is_empty());
+        assert_eq!(with_defaults.max_size, 3);
+
+        // Present values must still be valid, and fields without a default are still required
+        let obj = py.eval(r#"{"name": "a", "tags": 1}"#, None, None).unwrap();
+        assert!(WithDefaults::extract(obj).is_err());
+        let err = WithDefaults::extract(PyDict::new(py)).unwrap_err();
+        assert_eq!(err.to_string(), "TypeError: field 'name': KeyError: 'name'");
+        assert!(err
+            .cause(py)
+            .unwrap()
+            .is_instance::<pyo3::exceptions::PyKeyError>(py));
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(from_item_all)]
+pub struct FromItemAll {
+    name: String,
+    #[pyo3(item("user-id"))]
+    id: u64,
+    #[pyo3(default)]
+    admin: bool,
+}
+
+#[derive(Debug, FromPyObject)]
+pub enum FromItemAllEnum {
+    #[pyo3(from_item_all)]
+    Named {
+        name: String,
+    },
+    Value(u64),
+}
+
+#[test]
+fn test_from_item_all() {
+    Python::with_gil(|py| {
+        let obj = py
+            .eval(r#"{"name": "a", "user-id": 1}"#, None, None)
+            .unwrap();
+        let from_item_all = FromItemAll::extract(obj).unwrap();
+        assert_eq!(from_item_all.name, "a");
+        assert_eq!(from_item_all.id, 1);
+        assert!(!from_item_all.admin);
+
+        let obj = py.eval(r#"{"name": "b"}"#, None, None).unwrap();
+        match FromItemAllEnum::extract(obj).unwrap() {
+            FromItemAllEnum::Named { name } => assert_eq!(name, "b"),
+            other => panic!("expected `Named`, got {:?}", other),
+        }
+    });
+}
+
+#[derive(Debug, FromPyObject)]
+pub struct Mode(u8, u8);
+
+#[derive(Debug, FromPyObject)]
+pub enum Config {
+    #[pyo3(from_item_all)]
+    File { path: String, mode: Mode },
+    #[pyo3(annotation = "str")]
+    Inline(String),
+}
+
+#[derive(Debug, FromPyObject)]
+#[pyo3(from_item_all)]
+pub struct Settings {
+    config: Config,
+}
+
+#[test]
+fn test_enum_error_paths() {
+    Python::with_gil(|py| {
+        let obj = py
+            .eval("{'config': {'path': 'a', 'mode': (1, 2)}}", None, None)
+            .unwrap();
+        match Settings::extract(obj).unwrap().config {
+            Config::File { path, mode } => {
+                assert_eq!(path, "a");
+                assert_eq!((mode.0, mode.1), (1, 2));
+            }
+            other => panic!("expected `File`, got {:?}", other),
+        }
+
+        let obj = py
+            .eval("{'config': {'path': 1, 'mode': (1, 2)}}", None, None)
+            .unwrap();
+        let err = Settings::extract(obj).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "TypeError: field 'config': 'dict' object cannot be converted to 'Union[File, str]'\n\
+             - variant Config::File: field 'path': 'int' object cannot be converted to 'PyString'\n\
+             - variant Config::Inline (str): 'dict' object cannot be converted to 'PyString'"
+        );
+
+        let obj = py
+            .eval("{'path': 'a', 'mode': (1, None)}", None, None)
+            .unwrap();
+        let err = Config::extract(obj).unwrap_err();
+        assert!(err.to_string().contains(
+            "- variant Config::File: field 'mode': field '1': \
+             'NoneType' object cannot be interpreted as an integer\n"
+        ));
+
+        // The errors are chained down to the original error through `__cause__`
+        let mode_err = err.cause(py).unwrap();
+        assert_eq!(
+            mode_err.to_string(),
+            "TypeError: field 'mode': field '1': 'NoneType' object cannot be interpreted as an integer"
+        );
+        let index_err = mode_err.cause(py).unwrap();
+        let original = index_err.cause(py).unwrap();
+        assert_eq!(
+            original.to_string(),
+            "TypeError: 'NoneType' object cannot be interpreted as an integer"
+        );
+        assert!(original.cause(py).is_none());
+    });
+}
@@ -0,0 +1,243 @@
+use pyo3::class::iter::IterNextOutput;
+use pyo3::exceptions::{PyGeneratorExit, PyValueError};
+use pyo3::generator::{Generator, PyGenerator};
+use pyo3::prelude::*;
+use pyo3::py_run;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+mod common;
+
+/// Yields the values from `start` down to 1, and returns "liftoff".
+struct Countdown {
+    remaining: u32,
+}
+
+impl Generator for Countdown {
+    type Yield = u32;
+    type Return = &'static str;
+
+    fn resume(
+        &mut self,
+        _py: Python,
+        _value: &PyAny,
+    ) -> PyResult<IterNextOutput<u32, &'static str>> {
+        if self.remaining == 0 {
+            return Ok(IterNextOutput::Return("liftoff"));
+        }
+        self.remaining -= 1;
+        Ok(IterNextOutput::Yield(self.remaining + 1))
+    }
+}
+
+/// Echoes the values sent to it, yields "caught" for a `ValueError` thrown into it, and may
+/// refuse to be closed.
+struct Echo {
+    ignore_exit: bool,
+    closed: Arc<AtomicBool>,
+}
+
+impl Generator for Echo {
+    type Yield = PyObject;
+    type Return = ();
+
+    fn resume(&mut self, _py: Python, value: &PyAny) -> PyResult<IterNextOutput<PyObject, ()>> {
+        Ok(IterNextOutput::Yield(value.into()))
+    }
+
+    fn throw(&mut self, py: Python, err: PyErr) -> PyResult<IterNextOutput<PyObject, ()>> {
+        if err.is_instance::<PyValueError>(py) {
+            return Ok(IterNextOutput::Yield("caught".into_py(py)));
+        }
+        if err.is_instance::<PyGeneratorExit>(py) {
+            self.closed.store(true, Ordering::SeqCst);
+            if self.ignore_exit {
+                return Ok(IterNextOutput::Yield(py.None()));
+            }
+        }
+        Err(err)
+    }
+}
+
+fn new_echo(py: Python, ignore_exit: bool) -> (Py<PyGenerator>, Arc<AtomicBool>) {
+    let closed = Arc::new(AtomicBool::new(false));
+    let generator = PyGenerator::new(Echo {
+        ignore_exit,
+        closed: closed.clone(),
+    });
+    (Py::new(py, generator).unwrap(), closed)
+}
+
+#[pyclass]
+struct Launch {
+    from: u32,
+}
+
+#[pymethods]
+impl Launch {
+    fn __await__(&self) -> PyGenerator {
+        PyGenerator::new(Countdown {
+            remaining: self.from,
+        })
+    }
+}
+
+#[test]
+fn test_iterate() {
+    Python::with_gil(|py| {
+        let countdown = Py::new(py, PyGenerator::new(Countdown { remaining: 3 })).unwrap();
+        py_run!(
+            py,
+            countdown,
+            r#"
+import collections.abc
+assert isinstance(countdown, collections.abc.Generator)
+assert iter(countdown) is countdown
+assert list(countdown) == [3, 2, 1]
+assert list(countdown) == []
+"#
+        );
+    });
+}
+
+#[test]
+fn test_return_value() {
+    Python::with_gil(|py| {
+        let countdown = Py::new(py, PyGenerator::new(Countdown { remaining: 1 })).unwrap();
+        py_run!(
+            py,
+            countdown,
+            r#"
+def delegate(generator):
+    result = yield from generator
+    yield result
+
+assert list(delegate(countdown)) == [1, "liftoff"]
+"#
+        );
+
+        let countdown = Py::new(py, PyGenerator::new(Countdown { remaining: 0 })).unwrap();
+        py_run!(
+            py,
+            countdown,
+            r#"
+try:
+    next(countdown)
+except StopIteration as e:
+    assert e.value == "liftoff"
+else:
+    assert False
+"#
+        );
+    });
+}
+
+#[test]
+fn test_send() {
+    Python::with_gil(|py| {
+        let (echo, _) = new_echo(py, false);
+        py_run!(
+            py,
+            echo,
+            r#"
+try:
+    echo.send(1)
+except TypeError as e:
+    assert str(e) == "can't send non-None value to a just-started generator"
+else:
+    assert False
+assert echo.send(None) is None
+assert echo.send(1) == 1
+assert next(echo) is None
+"#
+        );
+    });
+}
+
+#[test]
+fn test_throw() {
+    Python::with_gil(|py| {
+        let (echo, _) = new_echo(py, false);
+        py_run!(
+            py,
+            echo,
+            r#"
+next(echo)
+assert echo.throw(ValueError) == "caught"
+assert echo.throw(ValueError("value")) == "caught"
+assert echo.throw(ValueError, "value") == "caught"
+try:
+    echo.throw(KeyError, "key")
+except KeyError as e:
+    assert e.args == ("key",)
+else:
+    assert False
+# The generator finished with the exception.
+try:
+    next(echo)
+except StopIteration:
+    pass
+else:
+    assert False
+try:
+    echo.throw(KeyError)
+except KeyError:
+    pass
+else:
+    assert False
+"#
+        );
+    });
+}
+
+#[test]
+fn test_close() {
+    Python::with_gil(|py| {
+        let (echo, closed) = new_echo(py, false);
+        py_run!(py, echo, "next(echo); echo.close(); echo.close()");
+        assert!(closed.load(Ordering::SeqCst));
+        py_expect_exception!(py, echo, "next(echo)", PyStopIteration);
+
+        // A generator which was never started is closed without resuming it.
+        let (echo, closed) = new_echo(py, false);
+        py_run!(py, echo, "echo.close()");
+        assert!(!closed.load(Ordering::SeqCst));
+
+        let (echo, closed) = new_echo(py, true);
+        py_run!(py, echo, "next(echo)");
+        py_expect_exception!(
+            py,
+            echo,
+            "echo.close()",
+            PyRuntimeError,
+            "generator ignored GeneratorExit"
+        );
+        assert!(closed.load(Ordering::SeqCst));
+    });
+}
+
+#[test]
+fn test_await() {
+    Python::with_gil(|py| {
+        let launch = Py::new(py, Launch { from: 2 }).unwrap();
+        py_run!(
+            py,
+            launch,
+            r#"
+async def main(launch):
+    return await launch
+
+# Without an event loop, the values yielded by `__await__` reach whoever drives the coroutine.
+coroutine = main(launch)
+assert coroutine.send(None) == 2
+assert coroutine.send(None) == 1
+try:
+    coroutine.send(None)
+except StopIteration as e:
+    assert e.value == "liftoff"
+else:
+    assert False
+"#
+        );
+    });
+}
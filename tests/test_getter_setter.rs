@@ -135,3 +135,84 @@ fn ref_getter_setter() {
     py_run!(py, inst, "assert inst.num == 10");
     py_run!(py, inst, "inst.num = 20; assert inst.num == 20");
 }
+
+#[pyclass(get_all, set_all)]
+struct AllFields {
+    num: i32,
+    #[pyo3(name = "label")]
+    text: String,
+    #[pyo3(get_with = "percent", set_with = "from_percent")]
+    ratio: f64,
+}
+
+fn percent(ratio: &f64) -> String {
+    format!("{}%", ratio * 100.0)
+}
+
+fn from_percent(value: &PyAny) -> PyResult<f64> {
+    let percent: f64 = value.extract()?;
+    Ok(percent / 100.0)
+}
+
+#[test]
+fn get_all_set_all() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let inst = Py::new(
+        py,
+        AllFields {
+            num: 10,
+            text: "Hello".to_string(),
+            ratio: 0.5,
+        },
+    )
+    .unwrap();
+
+    py_run!(py, inst, "inst.num = 20; assert inst.num == 20");
+    py_run!(
+        py,
+        inst,
+        "inst.label = 'There'; assert inst.label == 'There'"
+    );
+    py_run!(py, inst, "assert not hasattr(inst, 'text')");
+    py_run!(py, inst, "assert inst.ratio == '50%'; inst.ratio = 25");
+    assert_eq!(inst.borrow(py).ratio, 0.25);
+    py_expect_exception!(py, inst, "inst.ratio = 'a'", PyTypeError);
+}
+
+#[pyclass]
+struct Renamed {
+    #[pyo3(get, name = "value")]
+    inner: i32,
+    #[pyo3(get_with = "Clone::clone")]
+    items: Vec<i32>,
+    #[pyo3(get, set, name = "type")]
+    kind: String,
+    hidden: i32,
+}
+
+#[test]
+fn field_options() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+
+    let inst = Py::new(
+        py,
+        Renamed {
+            inner: 1,
+            items: vec![1, 2],
+            kind: "a".to_string(),
+            hidden: 3,
+        },
+    )
+    .unwrap();
+
+    py_run!(py, inst, "assert inst.value == 1 and inst.items == [1, 2]");
+    py_run!(py, inst, "assert not hasattr(inst, 'inner')");
+    py_run!(py, inst, "assert inst.type == 'a'; inst.type = 'b'");
+    assert_eq!(inst.borrow(py).kind, "b");
+    assert_eq!(inst.borrow(py).hidden, 3);
+    py_expect_exception!(py, inst, "inst.value = 2", PyAttributeError);
+    py_expect_exception!(py, inst, "inst.hidden", PyAttributeError);
+}
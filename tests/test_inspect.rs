@@ -0,0 +1,289 @@
+#![cfg(feature = "inspect")]
+
+use pyo3::inspect::{self, FunctionKind, ParameterKind, TypeInfo};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyTuple};
+use pyo3::{inspect_module, wrap_pyfunction, wrap_pymodule};
+use std::collections::HashMap;
+
+/// A point.
+#[pyclass(module = "shapes")]
+#[derive(Clone)]
+struct Point {
+    #[pyo3(get, set)]
+    x: f64,
+    #[pyo3(get)]
+    y: f64,
+}
+
+#[pymethods]
+impl Point {
+    #[new]
+    fn new(x: f64, y: f64) -> Self {
+        Point { x, y }
+    }
+
+    #[classattr]
+    const DIMENSIONS: usize = 2;
+
+    #[classattr]
+    fn origin() -> Point {
+        Point { x: 0.0, y: 0.0 }
+    }
+
+    /// Returns the distance to `other`.
+    fn distance(&self, other: &Point) -> f64 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+
+    #[getter]
+    fn norm(&self) -> f64 {
+        self.distance(&Point { x: 0.0, y: 0.0 })
+    }
+
+    #[setter]
+    fn set_y(&mut self, y: f64) {
+        self.y = y;
+    }
+
+    #[classmethod]
+    fn from_tuple(_cls: &pyo3::types::PyType, xy: (f64, f64)) -> Self {
+        Point { x: xy.0, y: xy.1 }
+    }
+
+    #[staticmethod]
+    fn parse(text: &str) -> Option<Point> {
+        let mut parts = text.split(',').map(|part| part.trim().parse().ok());
+        Some(Point {
+            x: parts.next()??,
+            y: parts.next()??,
+        })
+    }
+}
+
+#[pyclass(module = "shapes")]
+enum Color {
+    Red,
+    Green,
+}
+
+#[derive(FromPyObject)]
+enum Size {
+    #[pyo3(annotation = "int")]
+    Int(i64),
+    Named(String),
+}
+
+#[pyfunction]
+fn scale(point: &Point, factor: Option<f64>) -> Point {
+    let factor = factor.unwrap_or(1.0);
+    Point {
+        x: point.x * factor,
+        y: point.y * factor,
+    }
+}
+
+#[pyfunction]
+#[pyo3(signature = (points, /, *args, sort = true, **kwargs))]
+fn collect(
+    points: Vec<Point>,
+    args: &PyTuple,
+    sort: bool,
+    kwargs: Option<&PyDict>,
+) -> HashMap<String, Vec<Point>> {
+    let _ = (points, args, sort, kwargs);
+    HashMap::new()
+}
+
+#[pyfunction]
+fn sized(size: Size) -> usize {
+    match size {
+        Size::Int(size) => size as usize,
+        Size::Named(name) => name.len(),
+    }
+}
+
+#[pymodule]
+fn colors(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Color>()?;
+    Ok(())
+}
+
+/// Shapes and colors.
+#[pymodule]
+fn shapes(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Point>()?;
+    m.add_function(wrap_pyfunction!(scale, m)?)?;
+    m.add_function(wrap_pyfunction!(collect, m)?)?;
+    m.add_function(wrap_pyfunction!(sized, m)?)?;
+    m.add_wrapped(wrap_pymodule!(colors))?;
+
+    #[pyfn(m, "double")]
+    fn double(x: i32) -> i32 {
+        x * 2
+    }
+
+    Ok(())
+}
+
+mod helpers {
+    use pyo3::prelude::*;
+
+    #[pyfunction]
+    pub fn helper() {}
+}
+
+#[cfg(any())]
+#[pyfunction]
+fn disabled() {}
+
+#[pymodule]
+fn configured(_py: Python, m: &PyModule) -> PyResult<()> {
+    use helpers::__pyo3_get_function_helper;
+
+    m.add_function(wrap_pyfunction!(helper, m)?)?;
+    #[cfg(any())]
+    m.add_function(wrap_pyfunction!(disabled, m)?)?;
+    #[cfg(any())]
+    {
+        m.add_class::<Point>()?;
+    }
+
+    #[pyfn(m, "also_disabled")]
+    #[cfg(any())]
+    fn also_disabled() {}
+
+    Ok(())
+}
+
+#[test]
+fn test_type_info() {
+    assert_eq!(
+        <i32 as FromPyObject>::type_input(),
+        TypeInfo::Builtin("int")
+    );
+    assert_eq!(
+        <Vec<Option<String>> as IntoPy<PyObject>>::type_output(),
+        TypeInfo::list_of(TypeInfo::optional_of(TypeInfo::Builtin("str")))
+    );
+    assert_eq!(
+        <Size as FromPyObject>::type_input().to_string(),
+        "typing.Union[int, str]"
+    );
+    assert_eq!(
+        <Point as IntoPy<PyObject>>::type_output().to_string(),
+        "shapes.Point"
+    );
+}
+
+#[test]
+fn test_module_info() {
+    let module = inspect_module!(shapes);
+    assert_eq!(module.name, "shapes");
+    assert_eq!(module.doc.trim_end_matches('\0'), "Shapes and colors.");
+
+    let names: Vec<_> = module.functions.iter().map(|f| f.name).collect();
+    assert_eq!(names, ["double", "scale", "collect", "sized"]);
+    assert!(module
+        .functions
+        .iter()
+        .all(|f| f.kind == FunctionKind::Function));
+
+    let collect = &module.functions[2];
+    let kinds: Vec<_> = collect.parameters.iter().map(|p| p.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            ParameterKind::PositionalOnly,
+            ParameterKind::VarPositional,
+            ParameterKind::KeywordOnly,
+            ParameterKind::VarKeyword,
+        ]
+    );
+    assert_eq!(collect.parameters[2].default, Some("True"));
+
+    assert_eq!(module.classes.len(), 1);
+    assert_eq!(module.classes[0].name, "Point");
+    assert_eq!(module.submodules.len(), 1);
+    assert_eq!(module.submodules[0].classes[0].name, "Color");
+}
+
+#[test]
+fn test_module_info_cfg() {
+    let module = inspect_module!(configured);
+    let names: Vec<_> = module.functions.iter().map(|f| f.name).collect();
+    assert_eq!(names, ["helper"]);
+    assert!(module.classes.is_empty());
+}
+
+#[test]
+fn test_class_info() {
+    let class = inspect::class_info::<Point>();
+    assert_eq!(class.module, Some("shapes"));
+    assert_eq!(class.attributes.len(), 2);
+    let methods: Vec<_> = class.methods.iter().map(|m| (m.name, m.kind)).collect();
+    assert_eq!(
+        methods,
+        [
+            ("__new__", FunctionKind::New),
+            ("DIMENSIONS", FunctionKind::ClassAttribute),
+            ("origin", FunctionKind::ClassAttribute),
+            ("distance", FunctionKind::Method),
+            ("norm", FunctionKind::Getter),
+            ("y", FunctionKind::Setter),
+            ("from_tuple", FunctionKind::ClassMethod),
+            ("parse", FunctionKind::StaticMethod),
+        ]
+    );
+}
+
+#[test]
+fn test_module_stub() {
+    let stub = inspect::module_stub(&inspect_module!(shapes));
+    assert_eq!(
+        stub,
+        r#""""Shapes and colors."""
+
+import typing
+from . import colors as colors
+
+class Point:
+    """A point."""
+    x: float
+    @property
+    def y(self) -> float: ...
+    def __new__(cls, x: float, y: float) -> Point: ...
+    DIMENSIONS: typing.ClassVar[int]
+    origin: typing.ClassVar[Point]
+    def distance(self, other: Point) -> float:
+        """Returns the distance to `other`."""
+    @property
+    def norm(self) -> float: ...
+    @classmethod
+    def from_tuple(cls, xy: typing.Tuple[float, float]) -> Point: ...
+    @staticmethod
+    def parse(text: str) -> typing.Optional[Point]: ...
+    @y.setter
+    def y(self, y: float) -> None: ...
+
+def double(x: int) -> int: ...
+
+def scale(point: Point, factor: typing.Optional[float] = None) -> Point: ...
+
+def collect(points: typing.Sequence[Point], /, *args, sort: bool = True, **kwargs) -> typing.Dict[str, typing.List[Point]]: ...
+
+def sized(size: typing.Union[int, str]) -> int: ...
+"#
+    );
+
+    let stub = inspect::module_stub(&inspect_module!(colors));
+    assert_eq!(
+        stub,
+        "import shapes\n\
+         import typing\n\
+         \n\
+         class Color:\n    \
+             Red: typing.ClassVar[shapes.Color]\n    \
+             Green: typing.ClassVar[shapes.Color]\n"
+    );
+}
@@ -0,0 +1,207 @@
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::types::PyDict;
+
+#[derive(IntoPyObject)]
+pub struct Named {
+    a: i32,
+    #[pyo3(item("bee"))]
+    b: String,
+    #[pyo3(item(1))]
+    c: Option<f64>,
+}
+
+#[test]
+fn test_named_fields_struct() {
+    Python::with_gil(|py| {
+        let obj = Named {
+            a: 1,
+            b: "b".into(),
+            c: None,
+        }
+        .into_py(py);
+        let dict: &PyDict = obj.extract(py).unwrap();
+        assert_eq!(dict.len(), 3);
+        py_run!(py, obj, "assert obj == {'a': 1, 'bee': 'b', 1: None}");
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Point {
+    #[pyo3(attribute)]
+    x: i32,
+    #[pyo3(attribute("why"))]
+    y: i32,
+}
+
+#[test]
+fn test_namedtuple_struct() {
+    Python::with_gil(|py| {
+        let a = Point { x: 1, y: 2 }.into_py(py);
+        let b = Point { x: 3, y: 4 }.into_py(py);
+        py_run!(
+            py,
+            a b,
+            r#"
+assert type(a).__name__ == "Point"
+assert type(a) is type(b)
+assert type(a)._fields == ("x", "why")
+assert (a.x, a.why) == (1, 2)
+assert tuple(b) == (3, 4)
+"#
+        );
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Tuple(String, usize);
+
+#[derive(IntoPyObject)]
+pub struct Newtype(Vec<u8>);
+
+#[derive(IntoPyObject)]
+#[pyo3(transparent)]
+pub struct Transparent {
+    inner: bool,
+}
+
+#[test]
+fn test_tuple_and_newtype_structs() {
+    Python::with_gil(|py| {
+        let tuple = Tuple("a".into(), 2).into_py(py);
+        let newtype = Newtype(vec![1, 2]).into_py(py);
+        let transparent = Transparent { inner: true }.into_py(py);
+        py_run!(
+            py,
+            tuple newtype transparent,
+            r#"
+assert tuple == ("a", 2)
+assert newtype == [1, 2]
+assert transparent is True
+"#
+        );
+    });
+}
+
+#[derive(IntoPyObject)]
+pub struct Generic<T> {
+    value: T,
+}
+
+#[test]
+fn test_generic_struct() {
+    Python::with_gil(|py| {
+        let obj = Generic { value: (1, "one") }.into_py(py);
+        py_run!(py, obj, "assert obj == {'value': (1, 'one')}");
+    });
+}
+
+fn twice(value: i32, py: Python) -> PyObject {
+    (value * 2).into_py(py)
+}
+
+#[derive(IntoPyObject)]
+pub struct WithConversion {
+    #[pyo3(into_py_with = "twice")]
+    value: i32,
+}
+
+#[test]
+fn test_into_py_with() {
+    Python::with_gil(|py| {
+        let obj = WithConversion { value: 21 }.into_py(py);
+        py_run!(py, obj, "assert obj == {'value': 42}");
+    });
+}
+
+#[derive(IntoPyObject)]
+pub enum Shape {
+    Circle {
+        center: Point,
+        radius: f64,
+    },
+    Segment(Point, Point),
+    #[pyo3(annotation = "str")]
+    Named(String),
+    #[pyo3(transparent)]
+    Square {
+        side: f64,
+    },
+}
+
+#[test]
+fn test_enum() {
+    Python::with_gil(|py| {
+        let circle = Shape::Circle {
+            center: Point { x: 0, y: 1 },
+            radius: 2.0,
+        }
+        .into_py(py);
+        let segment = Shape::Segment(Point { x: 0, y: 0 }, Point { x: 1, y: 1 }).into_py(py);
+        let named = Shape::Named("unit".into()).into_py(py);
+        let square = Shape::Square { side: 0.5 }.into_py(py);
+        py_run!(
+            py,
+            circle segment named square,
+            r#"
+assert circle["center"].why == 1
+assert circle["radius"] == 2.0
+assert segment == ((0, 0), (1, 1))
+assert named == "unit"
+assert square == 0.5
+"#
+        );
+    });
+}
+
+#[cfg(feature = "inspect")]
+#[test]
+fn test_type_output() {
+    use pyo3::inspect::TypeInfo;
+
+    let int = TypeInfo::Builtin("int");
+    assert_eq!(
+        <Named as IntoPy<PyObject>>::type_output(),
+        TypeInfo::Dict(
+            Box::new(TypeInfo::union_of(vec![
+                TypeInfo::Builtin("str"),
+                int.clone()
+            ])),
+            Box::new(TypeInfo::union_of(vec![
+                int.clone(),
+                TypeInfo::Builtin("str"),
+                TypeInfo::optional_of(TypeInfo::Builtin("float")),
+            ]))
+        )
+    );
+    assert_eq!(
+        <Tuple as IntoPy<PyObject>>::type_output(),
+        TypeInfo::Tuple(vec![TypeInfo::Builtin("str"), int.clone()])
+    );
+    assert_eq!(
+        <Transparent as IntoPy<PyObject>>::type_output(),
+        TypeInfo::Builtin("bool")
+    );
+    assert_eq!(
+        <WithConversion as IntoPy<PyObject>>::type_output(),
+        TypeInfo::Dict(Box::new(TypeInfo::Builtin("str")), Box::new(TypeInfo::Any))
+    );
+    assert_eq!(
+        <Shape as IntoPy<PyObject>>::type_output(),
+        TypeInfo::union_of(vec![
+            TypeInfo::Dict(
+                Box::new(TypeInfo::Builtin("str")),
+                Box::new(TypeInfo::union_of(vec![
+                    TypeInfo::Tuple(vec![int.clone(), int.clone()]),
+                    TypeInfo::Builtin("float"),
+                ]))
+            ),
+            TypeInfo::Tuple(vec![
+                TypeInfo::Tuple(vec![int.clone(), int.clone()]),
+                TypeInfo::Tuple(vec![int.clone(), int]),
+            ]),
+            TypeInfo::Builtin("str"),
+            TypeInfo::Builtin("float"),
+        ])
+    );
+}
@@ -424,6 +424,70 @@ fn meth_args() {
     py_run!(py, inst, "assert inst.args_as_vec(1,2,3) == 6");
 }
 
+#[pyclass]
+struct MethSignature {}
+
+#[pymethods]
+impl MethSignature {
+    #[new]
+    #[pyo3(signature = (*args))]
+    fn new(args: &PyTuple) -> Self {
+        let _ = args;
+        MethSignature {}
+    }
+
+    #[pyo3(signature = (a, /, b = 2, *, c = Some(3)))]
+    fn get_pos_only(&self, a: i32, b: i32, c: Option<i32>) -> (i32, i32, Option<i32>) {
+        (a, b, c)
+    }
+
+    #[classmethod]
+    #[pyo3(signature = (a, b = "default".to_string()))]
+    fn get_cls_defaults(_cls: &PyType, a: i32, b: String) -> (i32, String) {
+        (a, b)
+    }
+
+    #[staticmethod]
+    #[pyo3(signature = (*args, **kwargs))]
+    fn get_static_args(py: Python, args: &PyTuple, kwargs: Option<&PyDict>) -> PyObject {
+        [args.into(), kwargs.to_object(py)].to_object(py)
+    }
+}
+
+#[test]
+fn meth_signature() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let inst = Py::new(py, MethSignature {}).unwrap();
+    let cls = py.get_type::<MethSignature>();
+
+    py_run!(py, cls, "assert isinstance(cls(1, 2, 3), cls)");
+
+    py_run!(py, inst, "assert inst.get_pos_only(1) == (1, 2, 3)");
+    py_run!(py, inst, "assert inst.get_pos_only(1, 5) == (1, 5, 3)");
+    py_run!(
+        py,
+        inst,
+        "assert inst.get_pos_only(1, b=5, c=None) == (1, 5, None)"
+    );
+    py_expect_exception!(py, inst, "inst.get_pos_only(a=1)", PyTypeError);
+    py_expect_exception!(py, inst, "inst.get_pos_only(1, 2, 3)", PyTypeError);
+
+    py_run!(py, cls, "assert cls.get_cls_defaults(1) == (1, 'default')");
+    py_run!(
+        py,
+        cls,
+        "assert cls.get_cls_defaults(a=1, b='x') == (1, 'x')"
+    );
+
+    py_run!(py, cls, "assert cls.get_static_args() == [(), None]");
+    py_run!(
+        py,
+        cls,
+        "assert cls.get_static_args(1, a=2) == [(1,), {'a': 2}]"
+    );
+}
+
 #[pyclass]
 /// A class with "documentation".
 struct MethDocs {
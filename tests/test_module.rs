@@ -409,3 +409,159 @@ fn test_module_functions_with_module() {
                         == ('module_with_functions_with_module', 1, 2)"
     );
 }
+
+#[derive(Default)]
+struct CounterState {
+    count: std::sync::atomic::AtomicUsize,
+}
+
+#[pyclass]
+struct Item {
+    value: usize,
+}
+
+#[pymethods]
+impl Item {
+    #[new]
+    fn new(value: usize) -> Self {
+        Item { value }
+    }
+
+    #[classattr]
+    fn kind() -> &'static str {
+        "item"
+    }
+}
+
+#[pyfunction(pass_module)]
+fn increment(module: &PyModule) -> PyResult<usize> {
+    let state = module.state::<CounterState>()?;
+    Ok(state
+        .count
+        .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+        + 1)
+}
+
+#[pyfunction]
+fn item_value(item: PyRef<Item>) -> usize {
+    item.value
+}
+
+/// A multi-phase module with state.
+#[pymodule(multi_phase, state = CounterState)]
+fn multi_phase_module(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(pyo3::wrap_pyfunction!(increment, m)?)?;
+    m.add_function(pyo3::wrap_pyfunction!(item_value, m)?)?;
+    m.add_class::<Item>()?;
+    // The state exists before the initialization function runs.
+    m.state::<CounterState>()?;
+    Ok(())
+}
+
+#[pymodule(multi_phase)]
+fn stateless_module(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(pyo3::wrap_pyfunction!(increment, m)?)
+}
+
+#[test]
+fn test_multi_phase_module_state() {
+    Python::with_gil(|py| {
+        let first = pyo3::wrap_pymodule!(multi_phase_module)(py);
+        let second = pyo3::wrap_pymodule!(multi_phase_module)(py);
+        pyo3::py_run!(
+            py,
+            first second,
+            r#"
+import types
+assert isinstance(first, types.ModuleType)
+assert first is not second
+assert first.__name__ == "multi_phase_module"
+assert first.__doc__ == "A multi-phase module with state."
+assert first.increment() == 1
+assert first.increment() == 2
+assert second.increment() == 1
+"#
+        );
+
+        let stateless = pyo3::wrap_pymodule!(stateless_module)(py);
+        py_expect_exception!(py, stateless, "stateless.increment()", PyTypeError);
+        let single_phase = pyo3::wrap_pymodule!(module_with_functions)(py);
+        assert!(single_phase
+            .as_ref(py)
+            .downcast::<PyModule>()
+            .unwrap()
+            .state::<CounterState>()
+            .is_err());
+    });
+}
+
+#[test]
+#[cfg(not(any(Py_LIMITED_API, PyPy)))]
+fn test_multi_phase_module_types() {
+    Python::with_gil(|py| {
+        let first = pyo3::wrap_pymodule!(multi_phase_module)(py);
+        let second = pyo3::wrap_pymodule!(multi_phase_module)(py);
+        let shared = py.get_type::<Item>();
+        let rust_item = Py::new(py, Item::new(3)).unwrap();
+        pyo3::py_run!(
+            py,
+            first second shared rust_item,
+            r#"
+assert first.Item is not second.Item
+assert first.Item is not shared
+assert issubclass(first.Item, shared)
+assert first.Item.__name__ == "Item"
+assert first.Item.__module__ == "multi_phase_module"
+assert first.Item.kind == "item"
+
+item = first.Item(3)
+assert isinstance(item, first.Item)
+assert not isinstance(item, second.Item)
+assert first.item_value(item) == 3
+assert second.item_value(item) == 3
+assert not isinstance(rust_item, first.Item)
+assert first.item_value(rust_item) == 3
+
+for base in [first.Item, shared]:
+    try:
+        class Sub(base): pass
+    except TypeError:
+        pass
+    else:
+        assert False
+"#
+        );
+        py_expect_exception!(py, first, "first.item_value(object())", PyTypeError);
+    });
+}
+
+#[test]
+#[cfg(not(any(Py_LIMITED_API, PyPy)))]
+fn test_multi_phase_module_types_are_freed() {
+    use pyo3::PyNativeType;
+
+    Python::with_gil(|py| {
+        let make = pyo3::types::PyCFunction::new_closure(py, |args, _| {
+            pyo3::wrap_pymodule!(multi_phase_module)(args.py())
+        })
+        .unwrap();
+        pyo3::py_run!(
+            py,
+            make,
+            r#"
+import gc
+import weakref
+
+module = make()
+item_type = weakref.ref(module.Item)
+item = module.Item(1)
+del module
+gc.collect()
+assert item_type() is not None
+del item
+gc.collect()
+assert item_type() is None
+"#
+        );
+    });
+}
@@ -63,6 +63,24 @@ impl PyClassWithMultiplePyMethods {
     const CLASS_ATTRIBUTE: &'static str = "CLASS_ATTRIBUTE";
 }
 
+#[pymethods]
+impl PyClassWithMultiplePyMethods {
+    fn __repr__(&self) -> &'static str {
+        "repr"
+    }
+
+    fn __add__(&self, other: &str) -> String {
+        format!("add {}", other)
+    }
+}
+
+#[pymethods]
+impl PyClassWithMultiplePyMethods {
+    fn __radd__(&self, other: &str) -> String {
+        format!("radd {}", other)
+    }
+}
+
 #[test]
 fn test_class_with_multiple_pymethods() {
     Python::with_gil(|py| {
@@ -73,5 +91,8 @@ fn test_class_with_multiple_pymethods() {
         py_assert!(py, cls, "cls.staticmethod() == 'staticmethod'");
         py_assert!(py, cls, "cls.class_attribute == 'class_attribute'");
         py_assert!(py, cls, "cls.CLASS_ATTRIBUTE == 'CLASS_ATTRIBUTE'");
+        py_assert!(py, cls, "repr(cls()) == 'repr'");
+        py_assert!(py, cls, "cls() + 'a' == 'add a'");
+        py_assert!(py, cls, "'a' + cls() == 'radd a'");
     })
 }
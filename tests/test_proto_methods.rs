@@ -0,0 +1,452 @@
+use pyo3::class::basic::CompareOp;
+use pyo3::class::iter::IterNextOutput;
+use pyo3::exceptions::{PyAttributeError, PyIndexError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::py_run;
+use pyo3::types::{PySlice, PyType};
+
+mod common;
+
+#[pyclass]
+struct ExampleClass {
+    #[pyo3(get, set)]
+    value: i32,
+    custom_attr: Option<i32>,
+}
+
+#[pymethods]
+impl ExampleClass {
+    fn __getattr__(&self, py: Python, attr: &str) -> PyResult<PyObject> {
+        if attr == "special_custom_attr" {
+            Ok(self.custom_attr.into_py(py))
+        } else {
+            Err(PyAttributeError::new_err(attr.to_string()))
+        }
+    }
+
+    fn __setattr__(&mut self, attr: &str, value: &PyAny) -> PyResult<()> {
+        if attr == "special_custom_attr" {
+            self.custom_attr = Some(value.extract()?);
+            Ok(())
+        } else {
+            Err(PyAttributeError::new_err(attr.to_string()))
+        }
+    }
+
+    fn __delattr__(&mut self, attr: &str) -> PyResult<()> {
+        if attr == "special_custom_attr" {
+            self.custom_attr = None;
+            Ok(())
+        } else {
+            Err(PyAttributeError::new_err(attr.to_string()))
+        }
+    }
+
+    fn __str__(&self) -> String {
+        self.value.to_string()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("ExampleClass(value={})", self.value)
+    }
+
+    fn __hash__(&self) -> u64 {
+        self.value as u64
+    }
+
+    fn __richcmp__(&self, other: PyRef<Self>, op: CompareOp) -> bool {
+        match op {
+            CompareOp::Lt => self.value < other.value,
+            CompareOp::Le => self.value <= other.value,
+            CompareOp::Eq => self.value == other.value,
+            CompareOp::Ne => self.value != other.value,
+            CompareOp::Gt => self.value > other.value,
+            CompareOp::Ge => self.value >= other.value,
+        }
+    }
+
+    fn __bool__(&self) -> bool {
+        self.value != 0
+    }
+}
+
+fn make_example(py: Python) -> &PyCell<ExampleClass> {
+    Py::new(
+        py,
+        ExampleClass {
+            value: 5,
+            custom_attr: Some(20),
+        },
+    )
+    .unwrap()
+    .into_ref(py)
+}
+
+#[test]
+fn test_getattr() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        assert_eq!(
+            example_py
+                .getattr("value")
+                .unwrap()
+                .extract::<i32>()
+                .unwrap(),
+            5,
+        );
+        assert_eq!(
+            example_py
+                .getattr("special_custom_attr")
+                .unwrap()
+                .extract::<i32>()
+                .unwrap(),
+            20,
+        );
+        assert!(example_py
+            .getattr("other_attr")
+            .unwrap_err()
+            .is_instance::<PyAttributeError>(py));
+    })
+}
+
+#[test]
+fn test_setattr() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        example_py.setattr("special_custom_attr", 15).unwrap();
+        assert_eq!(
+            example_py
+                .getattr("special_custom_attr")
+                .unwrap()
+                .extract::<i32>()
+                .unwrap(),
+            15,
+        );
+        assert!(example_py
+            .setattr("other_attr", 15)
+            .unwrap_err()
+            .is_instance::<PyAttributeError>(py));
+    })
+}
+
+#[test]
+fn test_delattr() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        example_py.delattr("special_custom_attr").unwrap();
+        assert!(example_py.getattr("special_custom_attr").unwrap().is_none());
+    })
+}
+
+#[test]
+fn test_str_repr_hash() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        assert_eq!(example_py.str().unwrap().to_str().unwrap(), "5");
+        assert_eq!(
+            example_py.repr().unwrap().to_str().unwrap(),
+            "ExampleClass(value=5)"
+        );
+        assert_eq!(example_py.hash().unwrap(), 5);
+    })
+}
+
+#[test]
+fn test_richcmp() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        let other = Py::new(
+            py,
+            ExampleClass {
+                value: 6,
+                custom_attr: None,
+            },
+        )
+        .unwrap();
+        py_run!(py, example_py other, "assert example_py < other");
+        py_run!(py, example_py other, "assert example_py != other");
+        py_run!(py, example_py other, "assert not example_py >= other");
+        py_assert!(py, example_py, "example_py == example_py");
+        // Comparison with other types is left to Python
+        py_assert!(py, example_py, "example_py != 'a string'");
+        py_expect_exception!(py, example_py, "example_py < 1", PyTypeError);
+    })
+}
+
+#[test]
+fn test_bool() {
+    Python::with_gil(|py| {
+        let example_py = make_example(py);
+        assert!(example_py.is_true().unwrap());
+        example_py.borrow_mut().value = 0;
+        assert!(!example_py.is_true().unwrap());
+    })
+}
+
+#[pyclass]
+struct Sequence {
+    values: Vec<String>,
+}
+
+#[pymethods]
+impl Sequence {
+    fn __len__(&self) -> usize {
+        self.values.len()
+    }
+
+    fn __getitem__(&self, index: isize) -> PyResult<String> {
+        self.values
+            .get(index as usize)
+            .cloned()
+            .ok_or_else(|| PyIndexError::new_err(index))
+    }
+
+    fn __setitem__(&mut self, index: isize, value: String) -> PyResult<()> {
+        match self.values.get_mut(index as usize) {
+            Some(item) => {
+                *item = value;
+                Ok(())
+            }
+            None => Err(PyIndexError::new_err(index)),
+        }
+    }
+
+    fn __contains__(&self, value: &str) -> bool {
+        self.values.iter().any(|item| item == value)
+    }
+}
+
+#[test]
+fn test_sequence() {
+    Python::with_gil(|py| {
+        let sequence = Py::new(
+            py,
+            Sequence {
+                values: vec!["a".to_string(), "b".to_string()],
+            },
+        )
+        .unwrap();
+        py_assert!(py, sequence, "len(sequence) == 2");
+        py_assert!(py, sequence, "sequence[1] == 'b'");
+        py_assert!(py, sequence, "'a' in sequence");
+        py_assert!(py, sequence, "'c' not in sequence");
+        py_run!(py, sequence, "sequence[0] = 'c'; assert sequence[0] == 'c'");
+        py_expect_exception!(py, sequence, "sequence[2]", PyIndexError);
+        py_expect_exception!(py, sequence, "sequence['x']", PyTypeError);
+        // Only `__setitem__` is implemented
+        py_expect_exception!(py, sequence, "del sequence[0]", PyNotImplementedError);
+    })
+}
+
+#[pyclass]
+struct Counter {
+    count: usize,
+}
+
+#[pymethods]
+impl Counter {
+    fn __iter__(slf: PyRef<Self>) -> PyRef<Self> {
+        slf
+    }
+
+    fn __next__(mut slf: PyRefMut<Self>) -> IterNextOutput<usize, &'static str> {
+        if slf.count < 3 {
+            slf.count += 1;
+            IterNextOutput::Yield(slf.count)
+        } else {
+            IterNextOutput::Return("done")
+        }
+    }
+}
+
+#[test]
+fn test_iterator() {
+    Python::with_gil(|py| {
+        let counter = Py::new(py, Counter { count: 0 }).unwrap();
+        py_assert!(py, counter, "list(counter) == [1, 2, 3]");
+        py_run!(
+            py,
+            counter,
+            r#"
+try:
+    next(counter)
+except StopIteration as e:
+    assert e.value == "done"
+else:
+    assert False
+"#
+        );
+    })
+}
+
+#[pyclass]
+#[derive(Clone)]
+struct Number {
+    value: i64,
+}
+
+#[pymethods]
+impl Number {
+    fn __repr__(&self) -> String {
+        format!("Number({})", self.value)
+    }
+
+    fn __add__(&self, other: i64) -> Number {
+        Number {
+            value: self.value + other,
+        }
+    }
+
+    fn __radd__(&self, other: i64) -> Number {
+        Number {
+            value: other + self.value,
+        }
+    }
+
+    fn __sub__(&self, other: &Number) -> Number {
+        Number {
+            value: self.value - other.value,
+        }
+    }
+
+    fn __rsub__(&self, other: i64) -> Number {
+        Number {
+            value: other - self.value,
+        }
+    }
+
+    fn __pow__(&self, other: u32, modulo: Option<i64>) -> Number {
+        let value = self.value.pow(other);
+        Number {
+            value: modulo.map_or(value, |modulo| value % modulo),
+        }
+    }
+
+    fn __iadd__(&mut self, other: i64) {
+        self.value += other;
+    }
+
+    fn __neg__(&self) -> Number {
+        Number { value: -self.value }
+    }
+
+    fn __int__(&self) -> i64 {
+        self.value
+    }
+
+    fn __index__(&self) -> i64 {
+        self.value
+    }
+}
+
+#[test]
+fn test_binary_operators() {
+    Python::with_gil(|py| {
+        let n = Py::new(py, Number { value: 3 }).unwrap();
+        py_assert!(py, n, "repr(n + 1) == 'Number(4)'");
+        py_assert!(py, n, "repr(1 + n) == 'Number(4)'");
+        py_assert!(py, n, "repr(n - n) == 'Number(0)'");
+        py_assert!(py, n, "repr(10 - n) == 'Number(7)'");
+        py_assert!(py, n, "repr(n ** 2) == 'Number(9)'");
+        py_assert!(py, n, "repr(pow(n, 2, 5)) == 'Number(4)'");
+        // Unsupported operands make Python raise `TypeError`
+        py_expect_exception!(py, n, "n + 'a'", PyTypeError);
+        py_expect_exception!(py, n, "n - 1", PyTypeError);
+        py_expect_exception!(py, n, "2 ** n", PyTypeError);
+    })
+}
+
+#[test]
+fn test_inplace_and_unary_operators() {
+    Python::with_gil(|py| {
+        let n = Py::new(py, Number { value: 3 }).unwrap();
+        py_run!(
+            py,
+            n,
+            "m = n; m += 2; assert m is n and repr(n) == 'Number(5)'"
+        );
+        py_assert!(py, n, "repr(-n) == 'Number(-5)'");
+        py_assert!(py, n, "int(n) == 5");
+        py_assert!(py, n, "[0, 1, 2, 3, 4, 5][n] == 5");
+        // Without `__isub__`, `-=` falls back to `__sub__`
+        py_run!(
+            py,
+            n,
+            "m = n; m -= n; assert m is not n and repr(m) == 'Number(0)'"
+        );
+    })
+}
+
+#[pyclass]
+struct Descriptor {
+    value: Option<i32>,
+}
+
+#[pymethods]
+impl Descriptor {
+    fn __get__(&self, py: Python, instance: Option<&PyAny>, _owner: Option<&PyType>) -> PyObject {
+        match instance {
+            Some(_) => self.value.into_py(py),
+            None => "class access".into_py(py),
+        }
+    }
+
+    fn __set__(&mut self, _instance: &PyAny, value: i32) -> PyResult<()> {
+        if value < 0 {
+            return Err(PyValueError::new_err("negative value"));
+        }
+        self.value = Some(value);
+        Ok(())
+    }
+}
+
+#[test]
+fn test_descriptor() {
+    Python::with_gil(|py| {
+        let descriptor = Py::new(py, Descriptor { value: None }).unwrap();
+        py_run!(
+            py,
+            descriptor,
+            r#"
+Owner = type("Owner", (), {"attr": descriptor})
+owner = Owner()
+assert Owner.attr == "class access"
+assert owner.attr is None
+owner.attr = 5
+assert owner.attr == 5
+try:
+    owner.attr = -1
+except ValueError:
+    pass
+else:
+    assert False
+try:
+    del owner.attr
+except NotImplementedError:
+    pass
+else:
+    assert False
+"#
+        );
+    })
+}
+
+#[pyclass]
+struct Indexable {}
+
+#[pymethods]
+impl Indexable {
+    fn __getitem__(&self, py: Python, key: &PySlice) -> PyResult<PyObject> {
+        let indices = key.indices(10)?;
+        Ok((indices.start, indices.stop, indices.step).into_py(py))
+    }
+}
+
+#[test]
+fn test_python_argument() {
+    Python::with_gil(|py| {
+        let indexable = Py::new(py, Indexable {}).unwrap();
+        py_assert!(py, indexable, "indexable[1:5] == (1, 5, 1)");
+        py_expect_exception!(py, indexable, "indexable[1]", PyTypeError);
+    })
+}
@@ -1,7 +1,7 @@
 #[cfg(not(Py_LIMITED_API))]
 use pyo3::buffer::PyBuffer;
 use pyo3::prelude::*;
-use pyo3::types::PyCFunction;
+use pyo3::types::{PyCFunction, PyDict, PyTuple};
 #[cfg(not(Py_LIMITED_API))]
 use pyo3::types::{PyDateTime, PyFunction};
 use pyo3::{raw_pycfunction, wrap_pyfunction};
@@ -189,6 +189,29 @@ fn test_raw_function() {
     assert_eq!(res, "Some(true)");
 }
 
+#[test]
+fn test_closure() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let counter = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+    let count = {
+        let counter = counter.clone();
+        PyCFunction::new_closure(py, move |args: &PyTuple, kwargs: Option<&PyDict>| {
+            assert!(args.is_empty());
+            let step: usize = match kwargs.and_then(|kwargs| kwargs.get_item("step")) {
+                Some(step) => step.extract()?,
+                None => 1,
+            };
+            Ok::<_, PyErr>(counter.fetch_add(step, std::sync::atomic::Ordering::SeqCst) + step)
+        })
+        .unwrap()
+    };
+    py_assert!(py, count, "count() == 1");
+    py_assert!(py, count, "count(step=2) == 3");
+    py_expect_exception!(py, count, "count(step='a')", PyTypeError);
+    assert_eq!(counter.load(std::sync::atomic::Ordering::SeqCst), 3);
+}
+
 #[pyfunction]
 fn conversion_error(str_arg: &str, int_arg: i64, tuple_arg: (&str, f64), option_arg: Option<i64>) {
     println!(
@@ -239,3 +262,56 @@ fn test_conversion_error() {
         "argument 'option_arg': 'str' object cannot be interpreted as an integer"
     );
 }
+
+#[pyfunction]
+#[pyo3(signature = (a, /, b = 1, *args, c, d = vec![5], e = None, **kwargs))]
+fn function_with_signature(
+    py: Python,
+    a: i32,
+    b: i32,
+    args: &PyTuple,
+    c: i32,
+    d: Vec<i32>,
+    e: Option<i32>,
+    kwargs: Option<&PyDict>,
+) -> PyObject {
+    (a, b, args, c, d, e, kwargs).to_object(py)
+}
+
+#[test]
+fn test_function_with_signature() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(function_with_signature)(py).unwrap();
+
+    py_assert!(py, f, "f(1, c=3) == (1, 1, (), 3, [5], None, None)");
+    py_assert!(
+        py,
+        f,
+        "f(1, 2, 7, 8, c=3) == (1, 2, (7, 8), 3, [5], None, None)"
+    );
+    py_assert!(
+        py,
+        f,
+        "f(1, b=2, c=3, d=[4], e=5, x=6) == (1, 2, (), 3, [4], 5, {'x': 6})"
+    );
+    py_expect_exception!(
+        py,
+        f,
+        "f(a=1, c=3)",
+        PyTypeError,
+        "function_with_signature() got some positional-only arguments passed as keyword arguments: 'a'"
+    );
+    py_expect_exception!(
+        py,
+        f,
+        "f(1)",
+        PyTypeError,
+        "function_with_signature() missing 1 required keyword argument: 'c'"
+    );
+    py_assert!(
+        py,
+        f,
+        "f.__text_signature__ == '(a, /, b=1, *args, c, d=..., e=None, **kwargs)'"
+    );
+}
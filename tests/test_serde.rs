@@ -2,7 +2,12 @@
 mod test_serde {
     use pyo3::prelude::*;
 
+    use pyo3::class::basic::CompareOp;
+    use pyo3::exceptions::{PyTypeError, PyValueError, PyZeroDivisionError};
+    use pyo3::serde::{from_object, to_object};
+    use serde::de::DeserializeOwned;
     use serde::{Deserialize, Serialize};
+    use std::collections::{BTreeMap, BTreeSet, HashMap};
 
     #[pyclass]
     #[derive(Debug, Serialize, Deserialize)]
@@ -76,4 +81,195 @@ mod test_serde {
             )
         });
     }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Record {
+        id: u32,
+        score: f64,
+        tags: Vec<String>,
+        pair: (i8, char),
+        parent: Option<Box<Record>>,
+        attributes: BTreeMap<String, i64>,
+        unit: (),
+        big: u128,
+    }
+
+    fn eval<'py>(py: Python<'py>, code: &str) -> &'py PyAny {
+        py.eval(code, None, None).unwrap()
+    }
+
+    #[test]
+    fn test_to_object_and_back() {
+        Python::with_gil(|py| {
+            let record = Record {
+                id: 1,
+                score: 0.5,
+                tags: vec!["a".into(), "b".into()],
+                pair: (-1, 'x'),
+                parent: Some(Box::new(Record {
+                    id: 0,
+                    score: -2.0,
+                    tags: vec![],
+                    pair: (0, 'y'),
+                    parent: None,
+                    attributes: BTreeMap::new(),
+                    unit: (),
+                    big: 0,
+                })),
+                attributes: vec![("size".to_string(), 3)].into_iter().collect(),
+                unit: (),
+                big: u128::max_value(),
+            };
+            let obj = to_object(py, &record).unwrap();
+            let expected = eval(
+                py,
+                "{'id': 1, 'score': 0.5, 'tags': ['a', 'b'], 'pair': (-1, 'x'), \
+                 'parent': {'id': 0, 'score': -2.0, 'tags': [], 'pair': (0, 'y'), 'parent': None, \
+                 'attributes': {}, 'unit': None, 'big': 0}, \
+                 'attributes': {'size': 3}, 'unit': None, 'big': 2 ** 128 - 1}",
+            );
+            assert!(obj
+                .as_ref(py)
+                .rich_compare(expected, CompareOp::Eq)
+                .and_then(PyAny::is_true)
+                .unwrap());
+            assert_eq!(from_object::<Record>(obj.as_ref(py)).unwrap(), record);
+        });
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    enum External {
+        Unit,
+        Newtype(i32),
+        Tuple(i32, String),
+        Struct { x: i32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "type")]
+    enum Internal {
+        Unit,
+        Struct { x: i32 },
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(tag = "t", content = "c")]
+    enum Adjacent {
+        Unit,
+        Newtype(String),
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    #[serde(untagged)]
+    enum Untagged {
+        Int(i64),
+        Text(String),
+        Pair(bool, bool),
+    }
+
+    fn assert_roundtrip<T>(py: Python, value: T, expected: &str)
+    where
+        T: Serialize + DeserializeOwned + PartialEq + std::fmt::Debug,
+    {
+        let obj = to_object(py, &value).unwrap();
+        let expected = eval(py, expected);
+        assert!(
+            obj.as_ref(py)
+                .rich_compare(expected, CompareOp::Eq)
+                .and_then(PyAny::is_true)
+                .unwrap(),
+            "{:?} != {:?}",
+            obj,
+            expected
+        );
+        assert_eq!(from_object::<T>(expected).unwrap(), value);
+    }
+
+    #[test]
+    fn test_enum_representations() {
+        Python::with_gil(|py| {
+            assert_roundtrip(py, External::Unit, "'Unit'");
+            assert_roundtrip(py, External::Newtype(1), "{'Newtype': 1}");
+            assert_roundtrip(py, External::Tuple(1, "a".into()), "{'Tuple': (1, 'a')}");
+            assert_roundtrip(py, External::Struct { x: 1 }, "{'Struct': {'x': 1}}");
+            assert_roundtrip(py, Internal::Unit, "{'type': 'Unit'}");
+            assert_roundtrip(py, Internal::Struct { x: 1 }, "{'type': 'Struct', 'x': 1}");
+            assert_roundtrip(py, Adjacent::Unit, "{'t': 'Unit'}");
+            assert_roundtrip(
+                py,
+                Adjacent::Newtype("a".into()),
+                "{'t': 'Newtype', 'c': 'a'}",
+            );
+            assert_roundtrip(py, Untagged::Int(-5), "-5");
+            assert_roundtrip(py, Untagged::Text("a".into()), "'a'");
+            assert_roundtrip(py, Untagged::Pair(true, false), "(True, False)");
+        });
+    }
+
+    #[test]
+    fn test_from_other_objects() {
+        Python::with_gil(|py| {
+            let obj = eval(
+                py,
+                "__import__('types').MappingProxyType({'a': range(3), 'b': {1, 2}})",
+            );
+            let map: BTreeMap<String, BTreeSet<u8>> = from_object(obj).unwrap();
+            assert_eq!(map["a"], [0, 1, 2].iter().copied().collect());
+            assert_eq!(map["b"], [1, 2].iter().copied().collect());
+
+            let bytes: Vec<Vec<u8>> = from_object(eval(py, "[b'ab', bytearray(b'c')]")).unwrap();
+            assert_eq!(bytes, [b"ab".to_vec(), b"c".to_vec()]);
+
+            let borrowed: Vec<&str> = from_object(eval(py, "['x', 'y']")).unwrap();
+            assert_eq!(borrowed, ["x", "y"]);
+
+            let keys: BTreeMap<(i32, i32), bool> = from_object(eval(py, "{(1, 2): True}")).unwrap();
+            assert!(keys[&(1, 2)]);
+        });
+    }
+
+    #[test]
+    fn test_error_paths() {
+        Python::with_gil(|py| {
+            let err = from_object::<Vec<Record>>(eval(py, "[{'id': 'x'}]")).unwrap_err();
+            assert!(err.is_instance::<PyTypeError>(py));
+            assert_eq!(
+                err.to_string(),
+                "TypeError: invalid type: string \"x\", expected u32 at `[0].id`"
+            );
+
+            let err = from_object::<BTreeMap<i32, External>>(eval(py, "{7: {'Tuple': (1, 2)}}"))
+                .unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "TypeError: invalid type: integer `2`, expected a string at `[7].Tuple[1]`"
+            );
+
+            let err = from_object::<Vec<u8>>(eval(py, "[1, 256]")).unwrap_err();
+            assert!(err.is_instance::<PyValueError>(py));
+            assert_eq!(
+                err.to_string(),
+                "ValueError: invalid value: integer `256`, expected u8 at `[1]`"
+            );
+
+            let err = from_object::<Internal>(eval(py, "{'x': 1}")).unwrap_err();
+            assert_eq!(err.to_string(), "ValueError: missing field `type`");
+
+            let err = from_object::<Vec<i32>>(eval(py, "[object()]")).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "TypeError: invalid type: object of type 'object', expected i32 at `[0]`"
+            );
+
+            // Python exceptions keep their type
+            let err = from_object::<Vec<i32>>(eval(py, "(1 // x for x in [1, 0])")).unwrap_err();
+            assert!(err.is_instance::<PyZeroDivisionError>(py));
+
+            let mut map = HashMap::new();
+            map.insert(vec![1], 1);
+            let err = to_object(py, &map).unwrap_err();
+            assert!(err.is_instance::<PyTypeError>(py));
+            assert!(err.to_string().ends_with(" at `[[1]]`"), "{}", err);
+        });
+    }
 }
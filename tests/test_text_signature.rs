@@ -1,5 +1,6 @@
 use pyo3::prelude::*;
-use pyo3::{types::PyType, wrap_pyfunction, wrap_pymodule, PyCell};
+use pyo3::types::{PyDict, PyTuple, PyType};
+use pyo3::{py_run, wrap_pyfunction, wrap_pymodule, PyCell};
 
 mod common;
 
@@ -220,3 +221,258 @@ fn test_raw_identifiers() {
         "typeobj.method.__text_signature__ == '($self)'"
     );
 }
+
+#[test]
+fn test_signature_function() {
+    #[pyfunction]
+    #[pyo3(signature = (a, /, b = -1, *, c = "c", d = true, e = 1.5, f = vec![1]))]
+    fn my_function(a: i32, b: i32, c: &str, d: bool, e: f64, f: Vec<i32>) {
+        let _ = (a, b, c, d, e, f);
+    }
+
+    #[pyfunction]
+    #[pyo3(signature = (a, b = None))]
+    #[text_signature = "(a, b)"]
+    fn my_function_with_text_signature(a: i32, b: Option<i32>) {
+        let _ = (a, b);
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+    let g = wrap_pyfunction!(my_function_with_text_signature)(py).unwrap();
+
+    py_assert!(
+        py,
+        f,
+        "f.__text_signature__ == '(a, /, b=-1, *, c=\"c\", d=True, e=1.5, f=...)'"
+    );
+    py_assert!(py, g, "g.__text_signature__ == '(a, b)'");
+}
+
+#[test]
+fn test_signature_methods() {
+    #[pyclass]
+    struct MyClass {}
+
+    #[pymethods]
+    impl MyClass {
+        #[pyo3(signature = (a, *args))]
+        fn method(&self, a: i32, args: &PyTuple) {
+            let _ = (a, args);
+        }
+        #[classmethod]
+        #[pyo3(signature = (b = None))]
+        fn class_method(_cls: &PyType, b: Option<i32>) {
+            let _ = b;
+        }
+        #[staticmethod]
+        #[pyo3(signature = (*, c, **kwargs))]
+        fn static_method(c: i32, kwargs: Option<&PyDict>) {
+            let _ = (c, kwargs);
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let typeobj = py.get_type::<MyClass>();
+
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.method.__text_signature__ == '($self, a, *args)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.class_method.__text_signature__ == '($cls, b=None)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.static_method.__text_signature__ == '(*, c, **kwargs)'"
+    );
+}
+
+#[test]
+fn test_auto_function() {
+    #[pyfunction(b = "None", "*", c = 42, d = "vec![]")]
+    fn my_function(py: Python, a: i32, b: Option<i32>, c: i32, d: Vec<i32>) {
+        let _ = (py, a, b, c, d);
+    }
+
+    #[pyfunction(args = "*", kwargs = "**")]
+    fn my_varargs_function(a: i32, args: &PyTuple, kwargs: Option<&PyDict>) {
+        let _ = (a, args, kwargs);
+    }
+
+    #[pyfunction]
+    #[pyo3(text_signature = None)]
+    fn my_function_without_signature(a: i32) {
+        let _ = a;
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+    let g = wrap_pyfunction!(my_varargs_function)(py).unwrap();
+    let h = wrap_pyfunction!(my_function_without_signature)(py).unwrap();
+
+    py_assert!(
+        py,
+        f,
+        "f.__text_signature__ == '(a, b=None, *, c=42, d=...)'"
+    );
+    py_assert!(py, g, "g.__text_signature__ == '(a, *args, **kwargs)'");
+    py_assert!(py, h, "h.__text_signature__ is None");
+    py_run!(
+        py,
+        f,
+        "import inspect; assert str(inspect.signature(f)) == '(a, b=None, *, c=42, d=Ellipsis)'"
+    );
+}
+
+#[test]
+fn test_auto_pyfn() {
+    #[pymodule]
+    fn my_auto_module(_py: Python, m: &PyModule) -> PyResult<()> {
+        #[pyfn(m, "my_function")]
+        fn my_function(a: i32, b: Option<i32>) {
+            let _ = (a, b);
+        }
+        Ok(())
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let m = wrap_pymodule!(my_auto_module)(py);
+
+    py_assert!(py, m, "m.my_function.__text_signature__ == '(a, b=None)'");
+}
+
+#[test]
+#[cfg_attr(all(Py_LIMITED_API, not(Py_3_10)), ignore)]
+fn test_auto_methods() {
+    /// docs
+    #[pyclass]
+    struct MyClass {}
+
+    #[pymethods]
+    impl MyClass {
+        #[new]
+        #[args(a, b = "None")]
+        fn new(a: i32, b: Option<i32>) -> Self {
+            let _ = (a, b);
+            Self {}
+        }
+        fn method(&self, a: i32) {
+            let _ = a;
+        }
+        fn pyself_method(_this: &PyCell<Self>, b: i32) {
+            let _ = b;
+        }
+        #[classmethod]
+        #[args(c = 5)]
+        fn class_method(_cls: &PyType, c: i32) {
+            let _ = c;
+        }
+        #[staticmethod]
+        fn static_method() {}
+        #[pyo3(text_signature = None)]
+        fn method_without_signature(&self) {}
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let typeobj = py.get_type::<MyClass>();
+
+    py_assert!(py, typeobj, "typeobj.__text_signature__ == '(a, b=None)'");
+    py_assert!(py, typeobj, "typeobj.__doc__ == 'docs'");
+    py_assert!(py, typeobj, "typeobj.method.__doc__ is None");
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.method.__text_signature__ == '($self, a)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.pyself_method.__text_signature__ == '($self, b)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.class_method.__text_signature__ == '($cls, c=5)'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.static_method.__text_signature__ == '()'"
+    );
+    py_assert!(
+        py,
+        typeobj,
+        "typeobj.method_without_signature.__text_signature__ is None"
+    );
+}
+
+#[test]
+#[cfg_attr(all(Py_LIMITED_API, not(Py_3_10)), ignore)]
+fn test_auto_new_opt_out() {
+    #[pyclass]
+    struct MyClass {}
+
+    #[pymethods]
+    impl MyClass {
+        #[new]
+        #[pyo3(text_signature = None)]
+        fn new(a: i32) -> Self {
+            let _ = a;
+            Self {}
+        }
+    }
+
+    #[pyclass]
+    #[text_signature = "(x)"]
+    struct MyOtherClass {}
+
+    #[pymethods]
+    impl MyOtherClass {
+        #[new]
+        fn new(a: i32) -> Self {
+            let _ = a;
+            Self {}
+        }
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let typeobj = py.get_type::<MyClass>();
+    let other_typeobj = py.get_type::<MyOtherClass>();
+
+    py_assert!(py, typeobj, "typeobj.__text_signature__ is None");
+    py_assert!(
+        py,
+        other_typeobj,
+        "other_typeobj.__text_signature__ == '(x)'"
+    );
+}
+
+#[test]
+fn test_auto_keeps_docstring_signature() {
+    /// my_function(a, /)
+    /// --
+    ///
+    /// docs
+    #[pyfunction]
+    fn my_function(a: i32) {
+        let _ = a;
+    }
+
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let f = wrap_pyfunction!(my_function)(py).unwrap();
+
+    py_assert!(py, f, "f.__text_signature__ == '(a, /)'");
+    py_assert!(py, f, "f.__doc__ == 'docs'");
+}
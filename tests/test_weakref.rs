@@ -0,0 +1,88 @@
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyWeakref, PyWeakrefProxy};
+
+mod common;
+
+#[pyclass(weakref)]
+struct WeakrefSupport {
+    value: i32,
+}
+
+#[pyclass]
+struct NoWeakrefSupport {}
+
+#[test]
+fn test_downgrade() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Py::new(py, WeakrefSupport { value: 5 }).unwrap();
+    let weak: Py<PyWeakref> = obj.downgrade(py).unwrap().into();
+
+    {
+        let pool = unsafe { py.new_pool() };
+        let py = pool.python();
+        let obj_ref = weak.as_ref(py).upgrade_borrow::<WeakrefSupport>().unwrap();
+        assert_eq!(obj_ref.unwrap().value, 5);
+    }
+
+    drop(obj);
+    assert!(weak.as_ref(py).upgrade().is_none());
+    assert!(weak
+        .as_ref(py)
+        .upgrade_borrow::<WeakrefSupport>()
+        .unwrap()
+        .is_none());
+}
+
+#[test]
+fn test_downgrade_unsupported() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Py::new(py, NoWeakrefSupport {}).unwrap();
+    let err = obj.downgrade(py).unwrap_err();
+    assert!(err.is_instance::<PyTypeError>(py));
+}
+
+#[test]
+fn test_upgrade_borrow() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = PyCell::new(py, WeakrefSupport { value: 5 }).unwrap();
+    let weak = PyWeakref::new(obj, None).unwrap();
+
+    {
+        let _guard = obj.borrow_mut();
+        assert!(weak.upgrade_borrow::<WeakrefSupport>().is_err());
+    }
+    assert_eq!(
+        weak.upgrade_borrow::<WeakrefSupport>()
+            .unwrap()
+            .unwrap()
+            .value,
+        5
+    );
+
+    let err = weak.upgrade_borrow::<NoWeakrefSupport>().err().unwrap();
+    assert!(err.is_instance::<PyTypeError>(py));
+}
+
+#[test]
+fn test_proxy() {
+    let gil = Python::acquire_gil();
+    let py = gil.python();
+    let obj = Py::new(py, WeakrefSupport { value: 5 }).unwrap();
+    let proxy: Py<PyWeakrefProxy> = PyWeakrefProxy::new(obj.as_ref(py), None).unwrap().into();
+
+    py_assert!(py, proxy, "repr(proxy).startswith('<weakproxy')");
+    {
+        let pool = unsafe { py.new_pool() };
+        let py = pool.python();
+        let obj_ref = proxy.as_ref(py).upgrade_borrow::<WeakrefSupport>().unwrap();
+        assert_eq!(obj_ref.unwrap().value, 5);
+    }
+
+    drop(obj);
+    assert!(proxy.as_ref(py).upgrade().is_none());
+    py_expect_exception!(py, proxy, "proxy.value", PyReferenceError);
+}
@@ -0,0 +1,34 @@
+use pyo3::prelude::*;
+
+#[pyclass(frozen)]
+struct Frozen {}
+
+#[pymethods]
+impl Frozen {
+    async fn mutable(&mut self) {}
+}
+
+#[pymethods]
+impl Frozen {
+    async fn python(&self, _py: Python<'_>) {}
+}
+
+#[pymethods]
+impl Frozen {
+    #[getter]
+    async fn value(&self) -> i32 {
+        1
+    }
+}
+
+#[pymethods]
+impl Frozen {
+    async fn __repr__(&self) -> String {
+        String::new()
+    }
+}
+
+#[pyfunction(pass_module)]
+async fn with_module(_module: &PyModule) {}
+
+fn main() {}
@@ -165,4 +165,27 @@ struct InvalidFromPyWithLiteral {
     field: String,
 }
 
+#[derive(FromPyObject)]
+struct InvalidDefault {
+    #[pyo3(default(1))]
+    field: usize,
+}
+
+#[derive(FromPyObject)]
+#[pyo3(from_item_all)]
+struct FromItemAllWithAttribute {
+    #[pyo3(attribute)]
+    field: String,
+}
+
+#[derive(FromPyObject)]
+#[pyo3(from_item_all)]
+struct FromItemAllTuple(String, String);
+
+#[derive(FromPyObject)]
+#[pyo3(from_item_all, transparent)]
+struct FromItemAllTransparent {
+    field: String,
+}
+
 fn main() {}
@@ -0,0 +1,12 @@
+use pyo3::prelude::*;
+
+#[pyclass(frozen)]
+struct SettableField {
+    #[pyo3(get, set)]
+    value: i32,
+}
+
+#[pyclass(frozen, unsendable)]
+struct FrozenUnsendable {}
+
+fn main() {}
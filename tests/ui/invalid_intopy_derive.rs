@@ -0,0 +1,51 @@
+use pyo3::prelude::IntoPyObject;
+
+#[derive(IntoPyObject)]
+struct Foo();
+
+#[derive(IntoPyObject)]
+struct Unit;
+
+#[derive(IntoPyObject)]
+enum EmptyEnum {}
+
+#[derive(IntoPyObject)]
+enum EnumWithUnitVar {
+    Unit,
+    Valid(String),
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(transparent)]
+struct TransparentTwoFields {
+    a: String,
+    b: String,
+}
+
+#[derive(IntoPyObject)]
+#[pyo3(annotation = "int")]
+struct StructWithAnnotation(String);
+
+#[derive(IntoPyObject)]
+struct ItemOnTuple(#[pyo3(item)] String, String);
+
+#[derive(IntoPyObject)]
+struct ItemAndAttribute {
+    #[pyo3(attribute)]
+    a: String,
+    #[pyo3(item)]
+    b: String,
+}
+
+#[derive(IntoPyObject)]
+struct InvalidIntoPyWith {
+    #[pyo3(into_py_with = 1)]
+    a: String,
+}
+
+#[derive(IntoPyObject)]
+union Union {
+    a: usize,
+}
+
+fn main() {}
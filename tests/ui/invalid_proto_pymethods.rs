@@ -0,0 +1,31 @@
+use pyo3::prelude::*;
+
+#[pyclass]
+struct MyClass {}
+
+#[pymethods]
+impl MyClass {
+    fn __add__(&self, a: i32, b: i32) -> i32 {
+        a + b
+    }
+}
+
+#[pymethods]
+impl MyClass {
+    #[args(other = "1")]
+    fn __radd__(&self, other: i32) -> i32 {
+        other
+    }
+}
+
+#[pymethods]
+impl MyClass {
+    fn __traverse__(&self) {}
+}
+
+#[pymethods]
+impl MyClass {
+    fn __getbuffer__(&self) {}
+}
+
+fn main() {}
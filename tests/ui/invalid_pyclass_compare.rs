@@ -0,0 +1,15 @@
+use pyo3::prelude::*;
+
+#[pyclass(ord)]
+#[derive(PartialEq, PartialOrd)]
+struct OrdWithoutEq {}
+
+#[pyclass(hash)]
+#[derive(Hash)]
+struct HashWithoutEq {}
+
+#[pyclass(eq, hash)]
+#[derive(PartialEq, Hash)]
+struct HashWithoutFrozen {}
+
+fn main() {}
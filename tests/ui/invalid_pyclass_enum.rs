@@ -0,0 +1,27 @@
+use pyo3::prelude::*;
+
+#[pyclass]
+enum NotFieldless {
+    Unit,
+    Tuple(i32),
+}
+
+#[pyclass]
+enum StructVariant {
+    Struct { x: i32 },
+}
+
+#[pyclass]
+enum NoVariants {}
+
+#[pyclass(extends = pyo3::types::PyAny)]
+enum Extends {
+    Variant,
+}
+
+#[pyclass]
+union NotStructOrEnum {
+    x: i32,
+}
+
+fn main() {}
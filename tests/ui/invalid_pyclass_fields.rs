@@ -0,0 +1,49 @@
+use pyo3::prelude::*;
+
+#[pyclass(get_all)]
+struct RedundantGet {
+    #[pyo3(get)]
+    value: i32,
+}
+
+#[pyclass]
+struct NameWithoutGet {
+    #[pyo3(name = "other")]
+    value: i32,
+}
+
+#[pyclass]
+struct InvalidName {
+    #[pyo3(get, name = "not an ident")]
+    value: i32,
+}
+
+#[pyclass]
+struct DuplicateName {
+    #[pyo3(get, name = "a", name = "b")]
+    value: i32,
+}
+
+#[pyclass]
+struct GetWithNotAString {
+    #[pyo3(get_with = clone)]
+    value: i32,
+}
+
+#[pyclass]
+struct UnknownOption {
+    #[pyo3(get, sett)]
+    value: i32,
+}
+
+#[pyclass(frozen, set_all)]
+struct FrozenSetAll {
+    value: i32,
+}
+
+#[pyclass(get_all)]
+enum EnumGetAll {
+    A,
+}
+
+fn main() {}
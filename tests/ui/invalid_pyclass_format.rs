@@ -0,0 +1,20 @@
+use pyo3::prelude::*;
+
+#[pyclass(repr = "Point({x}, {y})")]
+struct MissingGet {
+    #[pyo3(get)]
+    x: i32,
+    y: i32,
+}
+
+#[pyclass(get_all, str = "Point({x")]
+struct Unmatched {
+    x: i32,
+}
+
+#[pyclass(repr = "Color")]
+enum Color {
+    Red,
+}
+
+fn main() {}
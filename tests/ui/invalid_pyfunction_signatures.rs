@@ -0,0 +1,70 @@
+use pyo3::prelude::*;
+
+#[pyfunction]
+#[pyo3(signature = (b))]
+fn wrong_name(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction]
+#[pyo3(signature = ())]
+fn missing_entry(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction]
+#[pyo3(signature = (a, b))]
+fn extra_entry(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction]
+#[pyo3(signature = (/, a))]
+fn leading_slash(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction(a)]
+#[pyo3(signature = (a))]
+fn signature_and_args(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction]
+#[pyo3(text_signature = None)]
+#[text_signature = "(a)"]
+fn text_signature_disabled_and_given(a: i32) {
+    let _ = a;
+}
+
+#[pyfunction]
+#[pyo3(text_signature = "(a)")]
+fn text_signature_option_with_value(a: i32) {
+    let _ = a;
+}
+
+#[pyclass]
+struct MyClass {}
+
+#[pymethods]
+impl MyClass {
+    #[args(a)]
+    #[pyo3(signature = (a))]
+    fn method_with_args(&self, a: i32) {
+        let _ = a;
+    }
+}
+
+#[pyclass]
+struct MyOtherClass {}
+
+#[pymethods]
+impl MyOtherClass {
+    #[getter]
+    #[pyo3(signature = ())]
+    fn getter_with_signature(&self) -> i32 {
+        0
+    }
+}
+
+fn main() {}
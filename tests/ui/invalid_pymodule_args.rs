@@ -0,0 +1,18 @@
+use pyo3::prelude::*;
+
+#[pymodule(state = String)]
+fn state_without_multi_phase(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Ok(())
+}
+
+#[pymodule(multi_phase, sate = String)]
+fn unknown_option(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Ok(())
+}
+
+#[pymodule(name, other_name)]
+fn two_names(_py: Python, _m: &PyModule) -> PyResult<()> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,45 @@
+use pyo3::prelude::*;
+
+#[pymodule]
+mod glob_export {
+    #[pymodule_export]
+    use super::*;
+}
+
+#[pymodule]
+mod init_on_struct {
+    #[pymodule_init]
+    struct Init;
+}
+
+#[pymodule]
+mod export_on_fn {
+    #[pymodule_export]
+    fn export() {}
+}
+
+#[pymodule]
+mod two_inits {
+    use pyo3::prelude::*;
+
+    #[pymodule_init]
+    fn first(_py: Python, _m: &PyModule) -> PyResult<()> {
+        Ok(())
+    }
+
+    #[pymodule_init]
+    fn second(_py: Python, _m: &PyModule) -> PyResult<()> {
+        Ok(())
+    }
+}
+
+#[pymodule]
+mod nested_multi_phase {
+    #[pymodule(multi_phase)]
+    mod nested {}
+}
+
+#[pymodule]
+struct NotAModule;
+
+fn main() {}